@@ -0,0 +1,438 @@
+// crates/oxid68k/src/disasm.rs
+//! Desensamblador del 68000 para trazas y listados (`oxide_core::write_listing`).
+//! Sintaxis Motorola: `MOVE.W (A0)+,$1234(A1,D2.W)`. Lo que no decodifica sale como
+//! `DC.W $xxxx` de 2 bytes, así la traza nunca pierde el paso.
+//!
+//! `disassemble_with` usa tablas de símbolos (`oxide_core::symbols`) para los
+//! destinos absolutos, relativos al PC y de saltos (`JSR InitVideo`) y para los
+//! traps de Line A del Mac (`_LoadSeg`).
+
+use oxide_core::{MemoryBus, SymbolTable};
+
+const CONDITIONS: [&str; 16] = [
+    "T", "F", "HI", "LS", "CC", "CS", "NE", "EQ", "VC", "VS", "PL", "MI", "GE", "LT", "GT", "LE",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Sz {
+    B,
+    W,
+    L,
+}
+
+impl Sz {
+    /// Tamaño en los bits 6-7 (00 = B, 01 = W, 10 = L).
+    fn from_bits(bits: u16) -> Option<Self> {
+        match bits & 3 {
+            0 => Some(Sz::B),
+            1 => Some(Sz::W),
+            2 => Some(Sz::L),
+            _ => None,
+        }
+    }
+    fn suffix(self) -> &'static str {
+        match self {
+            Sz::B => ".B",
+            Sz::W => ".W",
+            Sz::L => ".L",
+        }
+    }
+}
+
+/// Nombres que consulta `disassemble_with`. Los traps van en su propia tabla: sus
+/// números ($A000-$AFFF) chocan con direcciones de la RAM baja.
+#[derive(Debug, Clone, Default)]
+pub struct Symbols {
+    /// Dirección -> etiqueta
+    pub addresses: SymbolTable,
+    /// Palabra de Line A -> nombre del trap (Toolbox/OS del Mac)
+    pub traps: SymbolTable,
+}
+
+impl Symbols {
+    /// Nombre del trap: la palabra exacta o, si no está, sin los bits de
+    /// modificador (auto-pop en Toolbox; SYS/CLEAR/ASYNC en OS).
+    pub fn trap(&self, op: u16) -> Option<&str> {
+        let plain = if op & 0x0800 != 0 { op & !0x0400 } else { op & !0x0600 };
+        self.traps.get(op as u32).or_else(|| self.traps.get(plain as u32))
+    }
+}
+
+/// Devuelve (mnemónico, longitud en bytes) de la instrucción en `pc`.
+pub fn disassemble(pc: u32, bus: &dyn MemoryBus) -> (String, u32) {
+    disassemble_with(pc, bus, &Symbols::default())
+}
+
+/// Como `disassemble`, con los nombres de `symbols` donde los haya.
+pub fn disassemble_with(pc: u32, bus: &dyn MemoryBus, symbols: &Symbols) -> (String, u32) {
+    let mut d = Decoder { bus, pos: pc.wrapping_add(2), symbols };
+    let op = bus.read_u16_be(pc);
+    match d.decode(op) {
+        Some(text) => (text, d.pos.wrapping_sub(pc)),
+        None => (format!("DC.W ${:04X}", op), 2),
+    }
+}
+
+struct Decoder<'a> {
+    bus: &'a dyn MemoryBus,
+    /// Siguiente palabra de extensión
+    pos: u32,
+    symbols: &'a Symbols,
+}
+
+/// `$12` / `-$12`
+fn signed(v: i32) -> String {
+    if v < 0 {
+        format!("-${:X}", v.unsigned_abs())
+    } else {
+        format!("${:X}", v)
+    }
+}
+
+/// Lista de MOVEM: `D0-D2/A0/A6`. En -(An) la máscara va invertida (bit 0 = A7).
+fn reg_list(mask: u16, predec: bool) -> String {
+    let mask = if predec { mask.reverse_bits() } else { mask };
+    let name = |i: u16| format!("{}{}", if i < 8 { 'D' } else { 'A' }, i % 8);
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < 16 {
+        if mask & (1 << i) == 0 {
+            i += 1;
+            continue;
+        }
+        // Los rangos no cruzan de D a A
+        let mut end = i;
+        while end + 1 < 16 && (end + 1) % 8 != 0 && mask & (1 << (end + 1)) != 0 {
+            end += 1;
+        }
+        parts.push(if end == i { name(i) } else { format!("{}-{}", name(i), name(end)) });
+        i = end + 1;
+    }
+    parts.join("/")
+}
+
+impl Decoder<'_> {
+    fn word(&mut self) -> u16 {
+        let w = self.bus.read_u16_be(self.pos);
+        self.pos = self.pos.wrapping_add(2);
+        w
+    }
+    fn long(&mut self) -> u32 {
+        ((self.word() as u32) << 16) | self.word() as u32
+    }
+    /// Etiqueta de `addr`, o `$addr` con al menos `digits` cifras.
+    fn label(&self, addr: u32, digits: usize) -> String {
+        self.symbols.addresses.name_or_hex(addr, digits)
+    }
+
+    /// Extensión breve `d8(base,Xn.S)`.
+    fn indexed(&mut self, base: &str) -> String {
+        let ext = self.word();
+        let kind = if ext & 0x8000 != 0 { 'A' } else { 'D' };
+        let size = if ext & 0x0800 != 0 { 'L' } else { 'W' };
+        format!("{}({},{}{}.{})", signed(ext as u8 as i8 as i32), base, kind, (ext >> 12) & 7, size)
+    }
+
+    /// Modo de direccionamiento (modo, registro) en texto. None si no existe.
+    fn ea(&mut self, mode: u16, reg: u16, size: Sz) -> Option<String> {
+        Some(match mode {
+            0 => format!("D{}", reg),
+            1 => format!("A{}", reg),
+            2 => format!("(A{})", reg),
+            3 => format!("(A{})+", reg),
+            4 => format!("-(A{})", reg),
+            5 => format!("{}(A{})", signed(self.word() as i16 as i32), reg),
+            6 => self.indexed(&format!("A{}", reg)),
+            _ => match reg {
+                0 => {
+                    let w = self.word();
+                    match self.symbols.addresses.get(w as i16 as u32) {
+                        Some(name) => format!("{}.W", name),
+                        None => format!("${:04X}.W", w),
+                    }
+                }
+                1 => {
+                    let l = self.long();
+                    match self.symbols.addresses.get(l) {
+                        Some(name) => name.to_string(),
+                        None => format!("${:08X}.L", l),
+                    }
+                }
+                2 => {
+                    let base = self.pos;
+                    let target = base.wrapping_add(self.word() as i16 as u32);
+                    format!("{}(PC)", self.label(target, 0))
+                }
+                3 => self.indexed("PC"),
+                4 => match size {
+                    Sz::B => format!("#${:02X}", self.word() & 0xFF),
+                    Sz::W => format!("#${:04X}", self.word()),
+                    Sz::L => format!("#${:08X}", self.long()),
+                },
+                _ => return None,
+            },
+        })
+    }
+    /// EA de los bits 0-5 del opcode.
+    fn src(&mut self, op: u16, size: Sz) -> Option<String> {
+        self.ea((op >> 3) & 7, op & 7, size)
+    }
+
+    fn decode(&mut self, op: u16) -> Option<String> {
+        let reg9 = (op >> 9) & 7;
+        let mode = (op >> 3) & 7;
+        match op >> 12 {
+            0x0 => self.group0(op),
+            0x1..=0x3 => {
+                let size = match op >> 12 {
+                    1 => Sz::B,
+                    3 => Sz::W,
+                    _ => Sz::L,
+                };
+                let src = self.src(op, size)?;
+                let dst_mode = (op >> 6) & 7;
+                if dst_mode == 1 {
+                    if size == Sz::B {
+                        return None;
+                    }
+                    return Some(format!("MOVEA{} {},A{}", size.suffix(), src, reg9));
+                }
+                let dst = self.ea(dst_mode, reg9, size)?;
+                Some(format!("MOVE{} {},{}", size.suffix(), src, dst))
+            }
+            0x4 => self.group4(op),
+            0x5 => {
+                let cc = CONDITIONS[((op >> 8) & 0xF) as usize];
+                match Sz::from_bits(op >> 6) {
+                    Some(size) => {
+                        let data = if reg9 == 0 { 8 } else { reg9 };
+                        let name = if op & 0x0100 != 0 { "SUBQ" } else { "ADDQ" };
+                        Some(format!("{}{} #{},{}", name, size.suffix(), data, self.src(op, size)?))
+                    }
+                    None if mode == 1 => {
+                        let target = self.pos.wrapping_add(self.word() as i16 as u32);
+                        Some(format!("DB{} D{},{}", cc, op & 7, self.label(target, 0)))
+                    }
+                    None => Some(format!("S{} {}", cc, self.src(op, Sz::B)?)),
+                }
+            }
+            0x6 => {
+                let name = match (op >> 8) & 0xF {
+                    0 => "BRA",
+                    1 => "BSR",
+                    c => return Some(self.branch(&format!("B{}", CONDITIONS[c as usize]), op)),
+                };
+                Some(self.branch(name, op))
+            }
+            0x7 if op & 0x0100 == 0 => Some(format!("MOVEQ #{},D{}", signed(op as u8 as i8 as i32), reg9)),
+            0x8 => match (op >> 6) & 7 {
+                3 => Some(format!("DIVU.W {},D{}", self.src(op, Sz::W)?, reg9)),
+                7 => Some(format!("DIVS.W {},D{}", self.src(op, Sz::W)?, reg9)),
+                4 if mode <= 1 => Some(Self::bcd_x("SBCD", op)),
+                _ => self.alu("OR", op),
+            },
+            0x9 | 0xD => {
+                let name = if op >> 12 == 0x9 { "SUB" } else { "ADD" };
+                let opmode = (op >> 6) & 7;
+                if opmode == 3 || opmode == 7 {
+                    let size = if opmode == 3 { Sz::W } else { Sz::L };
+                    return Some(format!("{}A{} {},A{}", name, size.suffix(), self.src(op, size)?, reg9));
+                }
+                if op & 0x0100 != 0 && mode <= 1 {
+                    let size = Sz::from_bits(op >> 6)?;
+                    return Some(Self::bcd_x(&format!("{}X{}", name, size.suffix()), op));
+                }
+                self.alu(name, op)
+            }
+            0xB => {
+                let opmode = (op >> 6) & 7;
+                match opmode {
+                    3 | 7 => {
+                        let size = if opmode == 3 { Sz::W } else { Sz::L };
+                        Some(format!("CMPA{} {},A{}", size.suffix(), self.src(op, size)?, reg9))
+                    }
+                    0..=2 => {
+                        let size = Sz::from_bits(opmode)?;
+                        Some(format!("CMP{} {},D{}", size.suffix(), self.src(op, size)?, reg9))
+                    }
+                    _ => {
+                        let size = Sz::from_bits(opmode)?;
+                        if mode == 1 {
+                            Some(format!("CMPM{} (A{})+,(A{})+", size.suffix(), op & 7, reg9))
+                        } else {
+                            Some(format!("EOR{} D{},{}", size.suffix(), reg9, self.src(op, size)?))
+                        }
+                    }
+                }
+            }
+            0xC => match (op >> 3) & 0x3F {
+                _ if (op >> 6) & 7 == 3 => Some(format!("MULU.W {},D{}", self.src(op, Sz::W)?, reg9)),
+                _ if (op >> 6) & 7 == 7 => Some(format!("MULS.W {},D{}", self.src(op, Sz::W)?, reg9)),
+                0x20 | 0x21 => Some(Self::bcd_x("ABCD", op)),
+                0x28 => Some(format!("EXG D{},D{}", reg9, op & 7)),
+                0x29 => Some(format!("EXG A{},A{}", reg9, op & 7)),
+                0x31 => Some(format!("EXG D{},A{}", reg9, op & 7)),
+                _ => self.alu("AND", op),
+            },
+            0xE => self.shift(op),
+            0xA => self.symbols.trap(op).map(String::from),
+            _ => None, // Line F
+        }
+    }
+
+    fn branch(&mut self, name: &str, op: u16) -> String {
+        let base = self.pos;
+        let (disp, suffix) = match op as u8 {
+            0 => (self.word() as i16 as i32, ".W"),
+            d => (d as i8 as i32, ".S"),
+        };
+        format!("{}{} {}", name, suffix, self.label(base.wrapping_add(disp as u32), 0))
+    }
+
+    /// ABCD/SBCD/ADDX/SUBX: `Dy,Dx` o `-(Ay),-(Ax)`.
+    fn bcd_x(name: &str, op: u16) -> String {
+        let (rx, ry) = ((op >> 9) & 7, op & 7);
+        if op & 0x0008 != 0 {
+            format!("{} -(A{}),-(A{})", name, ry, rx)
+        } else {
+            format!("{} D{},D{}", name, ry, rx)
+        }
+    }
+
+    /// OR/AND/ADD/SUB: `<ea>,Dn` o `Dn,<ea>` según el bit 8.
+    fn alu(&mut self, name: &str, op: u16) -> Option<String> {
+        let size = Sz::from_bits(op >> 6)?;
+        let reg = (op >> 9) & 7;
+        let ea = self.src(op, size)?;
+        if op & 0x0100 != 0 {
+            Some(format!("{}{} D{},{}", name, size.suffix(), reg, ea))
+        } else {
+            Some(format!("{}{} {},D{}", name, size.suffix(), ea, reg))
+        }
+    }
+
+    fn group0(&mut self, op: u16) -> Option<String> {
+        const BIT_OPS: [&str; 4] = ["BTST", "BCHG", "BCLR", "BSET"];
+        let bit_op = BIT_OPS[((op >> 6) & 3) as usize];
+        // Dinámicos: Dn,<ea> (modo 1 sería MOVEP)
+        if op & 0x0100 != 0 {
+            if (op >> 3) & 7 == 1 {
+                return None;
+            }
+            return Some(format!("{} D{},{}", bit_op, (op >> 9) & 7, self.src(op, Sz::B)?));
+        }
+        if op & 0x0F00 == 0x0800 {
+            let bit = self.word() & 0xFF;
+            return Some(format!("{} #{},{}", bit_op, bit, self.src(op, Sz::B)?));
+        }
+        let name = match (op >> 9) & 7 {
+            0 => "ORI",
+            1 => "ANDI",
+            2 => "SUBI",
+            3 => "ADDI",
+            5 => "EORI",
+            6 => "CMPI",
+            _ => return None,
+        };
+        // ORI/ANDI/EORI a CCR y SR
+        match op & 0xFF {
+            0x3C if matches!(name, "ORI" | "ANDI" | "EORI") => {
+                return Some(format!("{} #${:02X},CCR", name, self.word() & 0xFF));
+            }
+            0x7C if matches!(name, "ORI" | "ANDI" | "EORI") => {
+                return Some(format!("{} #${:04X},SR", name, self.word()));
+            }
+            _ => {}
+        }
+        let size = Sz::from_bits(op >> 6)?;
+        let imm = self.ea(7, 4, size)?;
+        Some(format!("{}{} {},{}", name, size.suffix(), imm, self.src(op, size)?))
+    }
+
+    fn group4(&mut self, op: u16) -> Option<String> {
+        let reg9 = (op >> 9) & 7;
+        match op {
+            0x4AFC => return Some("ILLEGAL".into()),
+            0x4E70 => return Some("RESET".into()),
+            0x4E71 => return Some("NOP".into()),
+            0x4E72 => return Some(format!("STOP #${:04X}", self.word())),
+            0x4E73 => return Some("RTE".into()),
+            0x4E75 => return Some("RTS".into()),
+            0x4E76 => return Some("TRAPV".into()),
+            0x4E77 => return Some("RTR".into()),
+            _ => {}
+        }
+        match op & 0xFFF8 {
+            0x4840 => return Some(format!("SWAP D{}", op & 7)),
+            0x4880 => return Some(format!("EXT.W D{}", op & 7)),
+            0x48C0 => return Some(format!("EXT.L D{}", op & 7)),
+            0x4E50 => return Some(format!("LINK A{},#{}", op & 7, signed(self.word() as i16 as i32))),
+            0x4E58 => return Some(format!("UNLK A{}", op & 7)),
+            0x4E60 => return Some(format!("MOVE A{},USP", op & 7)),
+            0x4E68 => return Some(format!("MOVE USP,A{}", op & 7)),
+            _ => {}
+        }
+        if op & 0xFFF0 == 0x4E40 {
+            return Some(format!("TRAP #{}", op & 0xF));
+        }
+        match (op >> 6) & 7 {
+            7 => return Some(format!("LEA {},A{}", self.src(op, Sz::L)?, reg9)),
+            6 => return Some(format!("CHK.W {},D{}", self.src(op, Sz::W)?, reg9)),
+            _ => {}
+        }
+        match op & 0xFFC0 {
+            0x40C0 => return Some(format!("MOVE SR,{}", self.src(op, Sz::W)?)),
+            0x44C0 => return Some(format!("MOVE {},CCR", self.src(op, Sz::W)?)),
+            0x46C0 => return Some(format!("MOVE {},SR", self.src(op, Sz::W)?)),
+            0x4800 => return Some(format!("NBCD {}", self.src(op, Sz::B)?)),
+            0x4840 => return Some(format!("PEA {}", self.src(op, Sz::L)?)),
+            0x4AC0 => return Some(format!("TAS {}", self.src(op, Sz::B)?)),
+            0x4E80 => return Some(format!("JSR {}", self.src(op, Sz::L)?)),
+            0x4EC0 => return Some(format!("JMP {}", self.src(op, Sz::L)?)),
+            0x4880 | 0x48C0 | 0x4C80 | 0x4CC0 => {
+                let size = if op & 0x0040 != 0 { Sz::L } else { Sz::W };
+                let mask = self.word();
+                let mode = (op >> 3) & 7;
+                let ea = self.src(op, size)?;
+                return Some(if op & 0x0400 != 0 {
+                    format!("MOVEM{} {},{}", size.suffix(), ea, reg_list(mask, false))
+                } else {
+                    format!("MOVEM{} {},{}", size.suffix(), reg_list(mask, mode == 4), ea)
+                });
+            }
+            _ => {}
+        }
+        let name = match op & 0xFF00 {
+            0x4000 => "NEGX",
+            0x4200 => "CLR",
+            0x4400 => "NEG",
+            0x4600 => "NOT",
+            0x4A00 => "TST",
+            _ => return None,
+        };
+        let size = Sz::from_bits(op >> 6)?;
+        Some(format!("{}{} {}", name, size.suffix(), self.src(op, size)?))
+    }
+
+    fn shift(&mut self, op: u16) -> Option<String> {
+        const KINDS: [&str; 4] = ["AS", "LS", "ROX", "RO"];
+        let dir = if op & 0x0100 != 0 { 'L' } else { 'R' };
+        match Sz::from_bits(op >> 6) {
+            // Memoria: un bit, tamaño word
+            None => {
+                let kind = KINDS.get(((op >> 9) & 7) as usize)?;
+                Some(format!("{}{}.W {}", kind, dir, self.src(op, Sz::W)?))
+            }
+            Some(size) => {
+                let kind = KINDS[((op >> 3) & 3) as usize];
+                let count = (op >> 9) & 7;
+                let count = if op & 0x0020 != 0 {
+                    format!("D{}", count)
+                } else {
+                    format!("#{}", if count == 0 { 8 } else { count })
+                };
+                Some(format!("{}{}{} {},D{}", kind, dir, size.suffix(), count, op & 7))
+            }
+        }
+    }
+}
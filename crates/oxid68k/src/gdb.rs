@@ -0,0 +1,346 @@
+// crates/oxid68k/src/gdb.rs
+//! Stub GDB RSP (Remote Serial Protocol) para el 68000 (feature `gdb-stub`).
+//!
+//! `m68k-elf-gdb` -> `target remote localhost:<puerto>`. Soporta `?`, `g`/`G`,
+//! `p`/`P`, `m`/`M`, `s`, `c` (interrumpible con Ctrl-C), `Z0`/`z0`, `k` y `D`.
+//! `monitor vectors` (qRcmd) vuelca la tabla de vectores de excepción.
+//! Registros en el orden de GDB: D0-D7, A0-A7, SR (PS), PC, 32 bits big-endian.
+//! La máquina se ve a través de `DebugTarget` (memoria y paso con el timing del
+//! sistema) más acceso directo a la CPU para los registros.
+
+use crate::vectors::dump_vector_table;
+use crate::Oxid68k;
+use oxide_core::debug_server::DebugTarget;
+use oxide_core::MemoryBus;
+use std::collections::BTreeSet;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// D0-D7, A0-A7, SR, PC
+const NUM_REGS: usize = 18;
+/// Instrucciones entre comprobaciones de Ctrl-C durante `c`
+const CONTINUE_CHUNK: u32 = 10_000;
+/// Tamaño máximo de paquete anunciado en `qSupported`
+const PACKET_SIZE: u32 = 0x1000;
+const SIGINT: u8 = 2;
+const SIGTRAP: u8 = 5;
+
+/// Máquina con 68000 depurable por GDB.
+pub trait GdbTarget: DebugTarget {
+    fn cpu(&self) -> &Oxid68k;
+    fn cpu_mut(&mut self) -> &mut Oxid68k;
+}
+
+// ============================================================================
+// FRAMING
+// ============================================================================
+
+/// Suma módulo 256 del contenido del paquete.
+pub fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |acc, &b| acc.wrapping_add(b))
+}
+
+/// `$<payload>#<checksum>`
+pub fn frame(payload: &str) -> String {
+    format!("${}#{:02x}", payload, checksum(payload.as_bytes()))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Incoming {
+    Packet(String),
+    /// Paquete recibido con checksum erróneo (hay que responder `-`)
+    BadChecksum,
+    Ack,
+    Nack,
+    /// Ctrl-C (0x03) fuera de paquete
+    Interrupt,
+}
+
+/// Extrae el siguiente elemento de `buf`. Devuelve también los bytes consumidos;
+/// `None` si falta el resto del paquete. La basura antes de `$` se descarta.
+pub fn parse_incoming(buf: &[u8]) -> Option<(Incoming, usize)> {
+    for (i, &b) in buf.iter().enumerate() {
+        match b {
+            b'+' => return Some((Incoming::Ack, i + 1)),
+            b'-' => return Some((Incoming::Nack, i + 1)),
+            0x03 => return Some((Incoming::Interrupt, i + 1)),
+            b'$' => {
+                let hash = i + 1 + buf[i + 1..].iter().position(|&c| c == b'#')?;
+                let sum = buf.get(hash + 1..hash + 3)?;
+                let payload = &buf[i + 1..hash];
+                let expected = std::str::from_utf8(sum).ok().and_then(|s| u8::from_str_radix(s, 16).ok());
+                let item = if expected == Some(checksum(payload)) {
+                    Incoming::Packet(String::from_utf8_lossy(payload).into_owned())
+                } else {
+                    Incoming::BadChecksum
+                };
+                return Some((item, hash + 3));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn hex_u32(s: &str) -> Option<u32> {
+    u32::from_str_radix(s, 16).ok()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// `addr,len`
+fn addr_len(s: &str) -> Option<(u32, u32)> {
+    let (a, l) = s.split_once(',')?;
+    Some((hex_u32(a)?, hex_u32(l)?))
+}
+
+// ============================================================================
+// REGISTROS
+// ============================================================================
+
+fn read_reg(cpu: &Oxid68k, n: usize) -> Option<u32> {
+    Some(match n {
+        0..=7 => cpu.d[n],
+        8..=15 => cpu.a[n - 8],
+        16 => cpu.sr.to_u16() as u32,
+        17 => cpu.pc,
+        _ => return None,
+    })
+}
+
+fn write_reg(cpu: &mut Oxid68k, n: usize, val: u32) -> bool {
+    match n {
+        0..=7 => cpu.d[n] = val,
+        8..=15 => cpu.a[n - 8] = val,
+        // Cambiar S intercambia A7 con USP/SSP como haría la CPU
+        16 => cpu.set_sr(val as u16),
+        17 => cpu.pc = val,
+        _ => return false,
+    }
+    true
+}
+
+/// Memoria del `DebugTarget` vista como bus de solo lectura.
+struct DebugBus<'a>(&'a dyn GdbTarget);
+
+impl MemoryBus for DebugBus<'_> {
+    fn read(&self, addr: u32) -> u8 {
+        self.0.read_mem(addr)
+    }
+    fn write(&mut self, _addr: u32, _val: u8) {}
+}
+
+// ============================================================================
+// STUB
+// ============================================================================
+
+/// Qué hacer tras responder a un paquete.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    Reply(String),
+    /// Responder y cerrar la sesión (`k`, `D`)
+    ReplyAndClose(String),
+}
+
+#[derive(Default)]
+pub struct GdbStub {
+    breakpoints: BTreeSet<u32>,
+}
+
+impl GdbStub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = u32> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Atiende un paquete ya validado. `interrupted` se consulta durante `c`.
+    pub fn handle(&mut self, target: &mut dyn GdbTarget, packet: &str, interrupted: &mut dyn FnMut() -> bool) -> Action {
+        let error = || Action::Reply("E01".into());
+        let (cmd, args) = packet.split_at(packet.len().min(1));
+        match cmd {
+            "?" => Action::Reply(format!("S{:02x}", SIGTRAP)),
+            "g" => Action::Reply(
+                (0..NUM_REGS).map(|n| format!("{:08x}", read_reg(target.cpu(), n).unwrap_or(0))).collect(),
+            ),
+            "G" => match decode_hex(args) {
+                Some(bytes) if bytes.len() >= NUM_REGS * 4 => {
+                    for (n, chunk) in bytes.chunks(4).take(NUM_REGS).enumerate() {
+                        let val = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                        write_reg(target.cpu_mut(), n, val);
+                    }
+                    Action::Reply("OK".into())
+                }
+                _ => error(),
+            },
+            "p" => match hex_u32(args).and_then(|n| read_reg(target.cpu(), n as usize)) {
+                Some(v) => Action::Reply(format!("{:08x}", v)),
+                None => error(),
+            },
+            "P" => {
+                let parsed = args.split_once('=').and_then(|(n, v)| {
+                    let bytes = decode_hex(v).filter(|b| b.len() == 4)?;
+                    Some((hex_u32(n)? as usize, u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])))
+                });
+                match parsed {
+                    Some((n, v)) if write_reg(target.cpu_mut(), n, v) => Action::Reply("OK".into()),
+                    _ => error(),
+                }
+            }
+            "m" => match addr_len(args) {
+                // Cada byte son dos dígitos hex: más no cabe en la respuesta
+                Some((addr, len)) if len <= PACKET_SIZE / 2 => Action::Reply(
+                    (0..len).map(|i| format!("{:02x}", target.read_mem(addr.wrapping_add(i)))).collect(),
+                ),
+                _ => error(),
+            },
+            "M" => {
+                let parsed = args.split_once(':').and_then(|(al, data)| Some((addr_len(al)?, decode_hex(data)?)));
+                match parsed {
+                    Some(((addr, len), data)) if data.len() == len as usize => {
+                        for (i, b) in data.iter().enumerate() {
+                            target.write_mem(addr.wrapping_add(i as u32), *b);
+                        }
+                        Action::Reply("OK".into())
+                    }
+                    _ => error(),
+                }
+            }
+            "s" | "c" => {
+                // Argumento opcional: dirección desde la que reanudar
+                if let Some(addr) = hex_u32(args) {
+                    target.cpu_mut().pc = addr;
+                }
+                let signal = if cmd == "s" {
+                    target.step();
+                    SIGTRAP
+                } else {
+                    self.run(target, interrupted)
+                };
+                Action::Reply(format!("S{:02x}", signal))
+            }
+            "Z" | "z" => {
+                // Solo breakpoints software: Z0,addr,kind
+                let mut parts = args.split(',');
+                match (parts.next(), parts.next().and_then(hex_u32)) {
+                    (Some("0"), Some(addr)) => {
+                        if cmd == "Z" {
+                            self.breakpoints.insert(addr);
+                        } else {
+                            self.breakpoints.remove(&addr);
+                        }
+                        Action::Reply("OK".into())
+                    }
+                    _ => Action::Reply(String::new()),
+                }
+            }
+            "q" if args.starts_with("Supported") => Action::Reply(format!("PacketSize={:x}", PACKET_SIZE)),
+            "q" if args == "Attached" => Action::Reply("1".into()),
+            "q" if args.starts_with("Rcmd,") => {
+                let command = decode_hex(&args[5..]).map(|b| String::from_utf8_lossy(&b).trim().to_string());
+                match command.as_deref() {
+                    Some("vectors") => {
+                        let base = target.cpu().vector_base();
+                        let text = dump_vector_table(&DebugBus(target), base);
+                        Action::Reply(text.bytes().map(|b| format!("{:02x}", b)).collect())
+                    }
+                    _ => error(),
+                }
+            }
+            "H" => Action::Reply("OK".into()),
+            "k" => Action::ReplyAndClose(String::new()),
+            "D" => Action::ReplyAndClose("OK".into()),
+            // Paquete no soportado: respuesta vacía
+            _ => Action::Reply(String::new()),
+        }
+    }
+
+    /// Ejecuta hasta breakpoint (SIGTRAP) o Ctrl-C (SIGINT). Al menos una instrucción.
+    fn run(&self, target: &mut dyn GdbTarget, interrupted: &mut dyn FnMut() -> bool) -> u8 {
+        loop {
+            for _ in 0..CONTINUE_CHUNK {
+                target.step();
+                if self.breakpoints.contains(&target.pc()) {
+                    return SIGTRAP;
+                }
+            }
+            if interrupted() {
+                return SIGINT;
+            }
+        }
+    }
+
+    /// Sesión RSP sobre un socket: acks, reenvío con `-` y Ctrl-C durante `c`.
+    pub fn serve(&mut self, target: &mut dyn GdbTarget, mut stream: TcpStream) -> io::Result<()> {
+        let mut buf = Vec::new();
+        let mut last_reply = String::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            while let Some((item, used)) = parse_incoming(&buf) {
+                buf.drain(..used);
+                let action = match item {
+                    Incoming::Packet(p) => {
+                        stream.write_all(b"+")?;
+                        let mut probe = stream.try_clone()?;
+                        self.handle(target, &p, &mut || ctrl_c_pending(&mut probe, &mut buf))
+                    }
+                    Incoming::BadChecksum => {
+                        stream.write_all(b"-")?;
+                        continue;
+                    }
+                    Incoming::Nack => Action::Reply(std::mem::take(&mut last_reply)),
+                    Incoming::Ack | Incoming::Interrupt => continue,
+                };
+                let (reply, close) = match action {
+                    Action::Reply(r) => (r, false),
+                    Action::ReplyAndClose(r) => (r, true),
+                };
+                stream.write_all(frame(&reply).as_bytes())?;
+                last_reply = reply;
+                if close {
+                    return Ok(());
+                }
+            }
+            let n = stream.read(&mut chunk)?;
+            if n == 0 {
+                return Ok(());
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// Escucha en `addr` y atiende a un cliente GDB a la vez.
+    pub fn listen(&mut self, target: &mut dyn GdbTarget, addr: &str) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        for stream in listener.incoming() {
+            self.serve(target, stream?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Mira sin bloquear si GDB mandó Ctrl-C (0x03). Cualquier otro byte se deja
+/// en `pending` para que la sesión lo procese después.
+pub fn ctrl_c_pending(stream: &mut TcpStream, pending: &mut Vec<u8>) -> bool {
+    let mut byte = [0u8; 1];
+    if stream.set_nonblocking(true).is_err() {
+        return false;
+    }
+    let got = match stream.read(&mut byte) {
+        Ok(1) if byte[0] == 0x03 => true,
+        Ok(1) => {
+            pending.push(byte[0]);
+            false
+        }
+        _ => false,
+    };
+    let _ = stream.set_nonblocking(false);
+    got
+}
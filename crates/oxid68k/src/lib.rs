@@ -1,5 +1,21 @@
 // crates/oxid68k/src/lib.rs - Motorola 68000 Complete Implementation
-use oxide_core::{Cpu, MemoryBus};
+use oxide_core::state::{StateReader, StateWriter};
+use oxide_core::{Cpu, MemoryBus, StateError};
+use std::cell::Cell;
+
+#[cfg(test)]
+mod tests;
+#[cfg(feature = "gdb-stub")]
+pub mod gdb;
+#[cfg(feature = "cpu_020")]
+pub mod icache;
+pub mod disasm;
+#[cfg(feature = "prefetch")]
+pub mod prefetch;
+pub mod vectors;
+
+#[cfg(feature = "cpu_020")]
+use icache::{InstructionCache, CACR_CLEAR, CACR_CLEAR_ENTRY, CACR_ENABLE, CACR_FREEZE};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Size {
@@ -42,7 +58,84 @@ impl Size {
     }
 }
 
+// ============================================================================
+//  TIMING
+// ============================================================================
+
+/// Ciclos de cálculo de dirección efectiva (tabla del manual del 68000).
+/// `s` solo distingue byte/word de long (un acceso de bus extra).
+pub fn ea_cycles(m: u8, r: u8, s: Size) -> u32 {
+    let base = match (m, r) {
+        (0, _) | (1, _) => return 0,
+        (2, _) | (3, _) => 4,
+        (4, _) => 6,
+        (5, _) => 8,
+        (6, _) => 10,
+        (7, 0) => 8,  // abs.W
+        (7, 1) => 12, // abs.L
+        (7, 2) => 8,  // d16(PC)
+        (7, 3) => 10, // d8(PC,Xn)
+        (7, 4) => 4,  // #imm
+        _ => 0,
+    };
+    if s == Size::Long {
+        base + 4
+    } else {
+        base
+    }
+}
+
+/// `<ea>,Dn` (ADD/SUB/AND/OR y ADDA/SUBA.L): .L cuesta 8 con Dn/An/#imm y 6 con memoria.
+fn alu_dn_cycles(m: u8, r: u8, s: Size) -> u32 {
+    let base = match s {
+        Size::Long if m <= 1 || (m == 7 && r == 4) => 8,
+        Size::Long => 6,
+        _ => 4,
+    };
+    base + ea_cycles(m, r, s)
+}
+
+/// `Dn,<ea>` a memoria (read-modify-write).
+fn alu_mem_cycles(m: u8, r: u8, s: Size) -> u32 {
+    (if s == Size::Long { 12 } else { 8 }) + ea_cycles(m, r, s)
+}
+
+/// ORI/ANDI/SUBI/ADDI/EORI `#imm,<ea>` (la lectura del inmediato va en la base).
+fn imm_cycles(m: u8, r: u8, s: Size) -> u32 {
+    match (m, s) {
+        (0, Size::Long) => 16,
+        (0, _) => 8,
+        (_, Size::Long) => 20 + ea_cycles(m, r, s),
+        _ => 12 + ea_cycles(m, r, s),
+    }
+}
+
+/// CMPI `#imm,<ea>`: no escribe, así que sale más barato que el resto.
+fn cmpi_cycles(m: u8, r: u8, s: Size) -> u32 {
+    match (m, s) {
+        (0, Size::Long) => 14,
+        (0, _) => 8,
+        (_, Size::Long) => 12 + ea_cycles(m, r, s),
+        _ => 8 + ea_cycles(m, r, s),
+    }
+}
+
+/// Variante de la familia 68k: decide qué codificaciones son legales.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Model {
+    #[default]
+    M68000,
+    /// 68010: MOVEC (VBR, SFC, DFC, USP)
+    #[cfg(feature = "cpu_010")]
+    M68010,
+    /// 68020+: TST acepta An (.W/.L), PC-relativo e inmediato; caché de instrucciones
+    #[cfg(feature = "cpu_020")]
+    M68020,
+}
+
 #[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct StatusRegister {
     pub trace: bool,
     pub supervisor: bool,
@@ -94,6 +187,7 @@ impl StatusRegister {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Oxid68k {
     pub d: [u32; 8],
     pub a: [u32; 8],
@@ -105,6 +199,41 @@ pub struct Oxid68k {
     pub stopped: bool,
     pub cycles: u32,
     pub pending_int: Option<u8>,
+    pub model: Model,
+    /// Registros de control del 68010 (MOVEC): base de vectores y function codes
+    #[cfg(feature = "cpu_010")]
+    pub vbr: u32,
+    #[cfg(feature = "cpu_010")]
+    pub sfc: u8,
+    #[cfg(feature = "cpu_010")]
+    pub dfc: u8,
+    /// Control de la caché de instrucciones del 68020 (solo E y F se conservan) y su dirección
+    #[cfg(feature = "cpu_020")]
+    pub cacr: u32,
+    #[cfg(feature = "cpu_020")]
+    pub caar: u32,
+    #[cfg(feature = "cpu_020")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    icache: InstructionCache,
+    #[cfg(feature = "prefetch")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    prefetch: prefetch::PrefetchQueue,
+    /// Acceso word/long a dirección impar pendiente de Address Error: (dirección, lectura)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    addr_fault: Cell<Option<(u32, bool)>>,
+    /// Vector de la última excepción lanzada por la instrucción en curso (decide el trace)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    raised: Option<u8>,
+    /// Contador por palabra de opcode (65536 entradas)
+    #[cfg(feature = "opcode-stats")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stats: Box<[u64]>,
+}
+
+impl Default for Oxid68k {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Oxid68k {
@@ -120,14 +249,68 @@ impl Oxid68k {
             stopped: false,
             cycles: 0,
             pending_int: None,
+            model: Model::M68000,
+            #[cfg(feature = "cpu_010")]
+            vbr: 0,
+            #[cfg(feature = "cpu_010")]
+            sfc: 0,
+            #[cfg(feature = "cpu_010")]
+            dfc: 0,
+            #[cfg(feature = "cpu_020")]
+            cacr: 0,
+            #[cfg(feature = "cpu_020")]
+            caar: 0,
+            #[cfg(feature = "cpu_020")]
+            icache: InstructionCache::new(),
+            #[cfg(feature = "prefetch")]
+            prefetch: prefetch::PrefetchQueue::new(),
+            addr_fault: Cell::new(None),
+            raised: None,
+            #[cfg(feature = "opcode-stats")]
+            stats: vec![0; 0x10000].into_boxed_slice(),
         }
     }
+
+    /// Histograma indexado por palabra de opcode, desde la creación o el último `reset_opcode_stats`.
+    #[cfg(feature = "opcode-stats")]
+    pub fn opcode_stats(&self) -> &[u64] {
+        &self.stats
+    }
+
+    #[cfg(feature = "opcode-stats")]
+    pub fn reset_opcode_stats(&mut self) {
+        self.stats.fill(0);
+    }
+    /// Dirección de retorno si la instrucción en el PC es JSR o BSR (la siguiente
+    /// instrucción); para el "step over" del depurador.
+    pub fn call_return_address(&self, bus: &dyn MemoryBus) -> Option<u32> {
+        let op = bus.read_u16_be(self.pc);
+        if op & 0xFFC0 != 0x4E80 && op & 0xFF00 != 0x6100 {
+            return None;
+        }
+        let (_, len) = disasm::disassemble(self.pc, bus);
+        Some(self.pc.wrapping_add(len))
+    }
     #[inline]
     fn fetch(&mut self, bus: &dyn MemoryBus) -> u16 {
-        let v = bus.read_u16(self.pc);
+        #[cfg(feature = "cpu_020")]
+        if self.icache_enabled() {
+            self.icache.fetch(self.pc, self.cacr & CACR_FREEZE == 0);
+        }
+        #[cfg(feature = "prefetch")]
+        let v = self.prefetch.next(self.pc, bus);
+        #[cfg(not(feature = "prefetch"))]
+        let v = bus.read_u16_be(self.pc);
         self.pc = self.pc.wrapping_add(2);
         v
     }
+    /// Tras un salto, excepción o interrupción: la cola se rellena dentro de sus ciclos.
+    #[cfg(feature = "prefetch")]
+    fn refill_prefetch(&mut self, bus: &dyn MemoryBus) {
+        if !self.prefetch.holds(self.pc) {
+            self.prefetch.fill(self.pc, bus);
+        }
+    }
     #[inline]
     fn fetch_long(&mut self, bus: &dyn MemoryBus) -> u32 {
         let h = self.fetch(bus) as u32;
@@ -154,7 +337,44 @@ impl Oxid68k {
         self.sr.negative = v & 8 != 0;
         self.sr.extend = v & 16 != 0;
     }
+    /// ¿Está activa la caché de instrucciones? (68020 con CACR.E)
+    #[cfg(feature = "cpu_020")]
+    #[inline]
+    fn icache_enabled(&self) -> bool {
+        self.model == Model::M68020 && self.cacr & CACR_ENABLE != 0
+    }
+    #[cfg(feature = "cpu_020")]
+    fn write_cacr(&mut self, v: u32) {
+        if v & CACR_CLEAR != 0 {
+            self.icache.clear();
+        }
+        if v & CACR_CLEAR_ENTRY != 0 {
+            self.icache.clear_entry(self.caar);
+        }
+        self.cacr = v & (CACR_ENABLE | CACR_FREEZE);
+    }
+    /// Modos de direccionamiento del 68020; siempre falso sin `cpu_020`.
+    #[inline]
+    fn is_020(&self) -> bool {
+        #[cfg(feature = "cpu_020")]
+        return self.model == Model::M68020;
+        #[cfg(not(feature = "cpu_020"))]
+        return false;
+    }
+    /// Base de la tabla de vectores: VBR en el 68010, siempre 0 en el 68000.
+    #[inline]
+    pub fn vector_base(&self) -> u32 {
+        #[cfg(feature = "cpu_010")]
+        return self.vbr;
+        #[cfg(not(feature = "cpu_010"))]
+        return 0;
+    }
+    #[inline]
+    fn vector_address(&self, vec: u8) -> u32 {
+        self.vector_base().wrapping_add(vec as u32 * 4)
+    }
     fn exception(&mut self, vec: u8, bus: &mut dyn MemoryBus) {
+        self.raised = Some(vec);
         let old_sr = self.sr.to_u16();
         if !self.sr.supervisor {
             self.usp = self.a[7];
@@ -166,7 +386,7 @@ impl Oxid68k {
         self.write_long(bus, self.a[7], self.pc);
         self.a[7] = self.a[7].wrapping_sub(2);
         self.write_word(bus, self.a[7], old_sr);
-        self.pc = self.read_long(bus, (vec as u32) * 4);
+        self.pc = self.read_long(bus, self.vector_address(vec));
         self.cycles += 34;
     }
 
@@ -175,6 +395,29 @@ impl Oxid68k {
             "[Oxid68k] Bus Error at PC={:08X} Access={:08X} IR={:04X}",
             self.pc, fault_addr, ir
         );
+        // Function Code - Placeholder 0x5
+        self.exception_group0(bus, 2, fault_addr, ir, 0x0005);
+        self.cycles += 50;
+    }
+
+    fn exception_address_error(&mut self, bus: &mut dyn MemoryBus, fault_addr: u32, read: bool, ir: u16, program: bool) {
+        // R/W (bit 4) + FC2-0: datos (5/1) o programa (6/2), de supervisor o de usuario
+        let fc = match (self.sr.supervisor, program) {
+            (true, false) => 5,
+            (false, false) => 1,
+            (true, true) => 6,
+            (false, true) => 2,
+        };
+        let info = fc | if read { 0x10 } else { 0 };
+        self.exception_group0(bus, 3, fault_addr, ir, info);
+        self.cycles += 50;
+    }
+
+    /// Trama de grupo 0 (Bus/Address Error, 14 bytes): PC, SR, IR, dirección de acceso y palabra de estado.
+    fn exception_group0(&mut self, bus: &mut dyn MemoryBus, vec: u8, fault_addr: u32, ir: u16, info: u16) {
+        // El acceso impar de la propia instrucción ya se atiende con esta trama (o lo
+        // sustituye el error de bus): solo cuenta como doble fallo uno al apilar
+        self.addr_fault.set(None);
         let old_sr = self.sr.to_u16();
         if !self.sr.supervisor {
             self.usp = self.a[7];
@@ -183,7 +426,6 @@ impl Oxid68k {
         }
         self.sr.trace = false;
 
-        // Group 0 Exception (14 bytes)
         // PC (4)
         self.a[7] = self.a[7].wrapping_sub(4);
         self.write_long(bus, self.a[7], self.pc);
@@ -196,12 +438,16 @@ impl Oxid68k {
         // Access Address (4)
         self.a[7] = self.a[7].wrapping_sub(4);
         self.write_long(bus, self.a[7], fault_addr);
-        // Function Code (2) - Placeholder 0x5
+        // Function Code / R/W (2)
         self.a[7] = self.a[7].wrapping_sub(2);
-        self.write_word(bus, self.a[7], 0x0005);
+        self.write_word(bus, self.a[7], info);
 
-        self.pc = self.read_long(bus, 8); // Vector 2 (Address 8)
-        self.cycles += 50;
+        // Doble fallo al apilar (SSP impar): el 68000 se detiene
+        if self.addr_fault.take().is_some() {
+            self.halted = true;
+            return;
+        }
+        self.pc = self.read_long(bus, self.vector_address(vec));
     }
     pub fn trigger_interrupt(&mut self, lv: u8) {
         if lv > self.sr.int_mask {
@@ -224,38 +470,85 @@ impl Oxid68k {
                 self.write_long(bus, self.a[7], self.pc);
                 self.a[7] = self.a[7].wrapping_sub(2);
                 self.write_word(bus, self.a[7], old_sr);
-                self.pc = self.read_long(bus, ((24 + lv) as u32) * 4);
+                self.pc = self.read_long(bus, self.vector_address(24 + lv));
                 self.cycles += 44;
             }
         }
     }
+    /// Hay un Address Error pendiente: la instrucción está abortada y ningún
+    /// acceso más llega al bus (`exec` deshace después sus registros).
+    #[inline]
+    fn aborted(&self) -> bool {
+        cfg!(feature = "address-error") && self.addr_fault.get().is_some()
+    }
     #[inline]
     fn read_byte(&self, bus: &dyn MemoryBus, a: u32) -> u8 {
+        if self.aborted() {
+            return 0;
+        }
         bus.read(a)
     }
+    /// Word/long a dirección impar: registra el Address Error (se procesa al final de `step`).
+    /// Devuelve `true` también si la instrucción ya estaba abortada: el acceso no se hace.
+    /// Sin la feature `address-error` no se comprueba nada y el acceso llega al bus tal cual.
+    #[inline]
+    fn misaligned(&self, a: u32, read: bool) -> bool {
+        if !cfg!(feature = "address-error") {
+            return false;
+        }
+        if self.addr_fault.get().is_some() {
+            return true;
+        }
+        if a & 1 == 0 {
+            return false;
+        }
+        self.addr_fault.set(Some((a, read)));
+        true
+    }
     #[inline]
     fn read_word(&self, bus: &dyn MemoryBus, a: u32) -> u16 {
-        bus.read_u16(a)
+        if self.misaligned(a, true) {
+            return 0;
+        }
+        bus.read_u16_be(a)
     }
     #[inline]
     fn read_long(&self, bus: &dyn MemoryBus, a: u32) -> u32 {
-        ((bus.read_u16(a) as u32) << 16) | bus.read_u16(a.wrapping_add(2)) as u32
+        if self.misaligned(a, true) {
+            return 0;
+        }
+        ((bus.read_u16_be(a) as u32) << 16) | bus.read_u16_be(a.wrapping_add(2)) as u32
     }
     #[inline]
     fn write_byte(&self, bus: &mut dyn MemoryBus, a: u32, v: u8) {
+        if self.aborted() {
+            return;
+        }
+        #[cfg(feature = "cpu_020")]
+        self.icache.invalidate(a);
         bus.write(a, v);
     }
     #[inline]
     fn write_word(&self, bus: &mut dyn MemoryBus, a: u32, v: u16) {
-        bus.write(a, (v >> 8) as u8);
-        bus.write(a.wrapping_add(1), v as u8);
+        if self.misaligned(a, false) {
+            return;
+        }
+        #[cfg(feature = "cpu_020")]
+        self.icache.invalidate(a);
+        bus.write_u16_be(a, v);
     }
     #[inline]
     fn write_long(&self, bus: &mut dyn MemoryBus, a: u32, v: u32) {
-        bus.write(a, (v >> 24) as u8);
-        bus.write(a.wrapping_add(1), (v >> 16) as u8);
-        bus.write(a.wrapping_add(2), (v >> 8) as u8);
-        bus.write(a.wrapping_add(3), v as u8);
+        if self.misaligned(a, false) {
+            return;
+        }
+        #[cfg(feature = "cpu_020")]
+        {
+            self.icache.invalidate(a);
+            self.icache.invalidate(a.wrapping_add(2));
+        }
+        bus.write_u16_be(a, (v >> 16) as u16);
+        bus.write_u16_be(a.wrapping_add(2), v as u16);
     }
     fn read_sz(&self, bus: &dyn MemoryBus, a: u32, s: Size) -> u32 {
         match s {
@@ -400,9 +693,47 @@ impl Oxid68k {
             _ => {}
         }
     }
+    /// Dirección efectiva para read-modify-write: aplica (An)+ / -(An) una sola vez.
+    fn rmw_ea(&mut self, bus: &dyn MemoryBus, m: u8, r: u8, s: Size) -> u32 {
+        let step = if r == 7 && s == Size::Byte {
+            2
+        } else {
+            s.bytes()
+        };
+        match m {
+            3 => {
+                let a = self.a[r as usize];
+                self.a[r as usize] = a.wrapping_add(step);
+                a
+            }
+            4 => {
+                self.a[r as usize] = self.a[r as usize].wrapping_sub(step);
+                self.a[r as usize]
+            }
+            _ => self.calc_ea(bus, m, r),
+        }
+    }
+    /// Lectura de un read-modify-write con el EA calculado una sola vez.
+    /// Devuelve (dirección, valor); la dirección es la que recibe `rmw_write` (ignorada para Dn/An).
+    fn rmw_read(&mut self, bus: &dyn MemoryBus, m: u8, r: u8, s: Size) -> (u32, u32) {
+        match m {
+            0 => (0, self.d[r as usize] & s.mask()),
+            1 => (0, self.a[r as usize]),
+            _ => {
+                let a = self.rmw_ea(bus, m, r, s);
+                (a, self.read_sz(bus, a, s))
+            }
+        }
+    }
+    fn rmw_write(&mut self, bus: &mut dyn MemoryBus, m: u8, r: u8, addr: u32, s: Size, v: u32) {
+        match m {
+            0 | 1 => self.write_ea(bus, m, r, s, v),
+            _ => self.write_sz(bus, addr, v, s),
+        }
+    }
     fn calc_ea(&mut self, bus: &dyn MemoryBus, m: u8, r: u8) -> u32 {
         match m {
-            2 | 3 | 4 => self.a[r as usize],
+            2..=4 => self.a[r as usize],
             5 => {
                 let d = self.fetch(bus) as i16 as i32;
                 (self.a[r as usize] as i32).wrapping_add(d) as u32
@@ -516,18 +847,32 @@ impl Oxid68k {
     }
 }
 
+/// Cabecera de `save_state`; subir la versión al cambiar el layout
+const STATE_MAGIC: &[u8; 4] = b"O68K";
+const STATE_VERSION: u8 = 1;
+
 impl Cpu for Oxid68k {
     fn reset(&mut self) {
         *self = Self::new();
     }
     fn reset_with_bus(&mut self, bus: &mut dyn MemoryBus) {
-        let raw = self.read_long(bus, 0);
+        // El RESET del 68010 pone VBR a 0: los vectores 0/1 salen siempre de la tabla original
+        #[cfg(feature = "cpu_010")]
+        {
+            self.vbr = 0;
+        }
+        let raw = self.read_long(bus, self.vector_address(0));
         self.ssp = if raw > 0x100000 { 0x80000 } else { raw };
         self.a[7] = self.ssp;
-        self.pc = self.read_long(bus, 4);
+        self.pc = self.read_long(bus, self.vector_address(1));
         self.sr = StatusRegister::new();
         self.halted = false;
         self.stopped = false;
+        #[cfg(feature = "prefetch")]
+        {
+            self.prefetch.flush();
+            self.prefetch.fill(self.pc, bus);
+        }
         println!(
             "[Oxid68k] Reset: SSP={:08X} (raw={:08X}) PC={:08X}",
             self.a[7], raw, self.pc
@@ -536,23 +881,145 @@ impl Cpu for Oxid68k {
     fn pc(&self) -> u32 {
         self.pc
     }
+    fn save_state(&self) -> Vec<u8> {
+        let mut w = StateWriter::new(STATE_MAGIC, STATE_VERSION);
+        for v in self.d.iter().chain(&self.a) {
+            w.u32(*v);
+        }
+        w.u32(self.pc).u16(self.sr.to_u16()).u32(self.usp).u32(self.ssp);
+        w.bool(self.halted).bool(self.stopped).u32(self.cycles);
+        w.u8(self.pending_int.unwrap_or(0));
+        let model = match self.model {
+            Model::M68000 => 0,
+            #[cfg(feature = "cpu_010")]
+            Model::M68010 => 1,
+            #[cfg(feature = "cpu_020")]
+            Model::M68020 => 2,
+        };
+        w.u8(model);
+        // Registros de control: siempre presentes (a 0 sin la feature) para que el
+        // layout no dependa de cómo se compiló el núcleo
+        #[cfg(feature = "cpu_010")]
+        w.u32(self.vbr).u8(self.sfc).u8(self.dfc);
+        #[cfg(not(feature = "cpu_010"))]
+        w.u32(0).u8(0).u8(0);
+        #[cfg(feature = "cpu_020")]
+        w.u32(self.cacr).u32(self.caar);
+        #[cfg(not(feature = "cpu_020"))]
+        w.u32(0).u32(0);
+        w.finish()
+    }
+    fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        // Todo se lee antes de tocar la CPU: una instantánea inválida no la deja a medias
+        let mut r = StateReader::new(data, STATE_MAGIC, STATE_VERSION, "68000")?;
+        let mut regs = [0u32; 16];
+        for v in &mut regs {
+            *v = r.u32()?;
+        }
+        let (pc, sr, usp, ssp) = (r.u32()?, r.u16()?, r.u32()?, r.u32()?);
+        let (halted, stopped, cycles) = (r.bool()?, r.bool()?, r.u32()?);
+        let pending_int = match r.u8()? {
+            0 => None,
+            level @ 1..=7 => Some(level),
+            _ => return Err(StateError::InvalidField("pending interrupt level")),
+        };
+        let model = match r.u8()? {
+            0 => Model::M68000,
+            #[cfg(feature = "cpu_010")]
+            1 => Model::M68010,
+            #[cfg(feature = "cpu_020")]
+            2 => Model::M68020,
+            _ => return Err(StateError::InvalidField("model")),
+        };
+        let (_vbr, _sfc, _dfc) = (r.u32()?, r.u8()?, r.u8()?);
+        let (_cacr, _caar) = (r.u32()?, r.u32()?);
+        r.finish()?;
+
+        self.d.copy_from_slice(&regs[..8]);
+        self.a.copy_from_slice(&regs[8..]);
+        self.pc = pc;
+        // Sin `set_sr`: A7 ya es el puntero de pila activo, USP/SSP se restauran tal cual
+        self.sr.from_u16(sr);
+        (self.usp, self.ssp) = (usp, ssp);
+        (self.halted, self.stopped, self.cycles) = (halted, stopped, cycles);
+        self.pending_int = pending_int;
+        self.model = model;
+        #[cfg(feature = "cpu_010")]
+        {
+            (self.vbr, self.sfc, self.dfc) = (_vbr, _sfc, _dfc);
+        }
+        #[cfg(feature = "cpu_020")]
+        {
+            (self.cacr, self.caar) = (_cacr, _caar);
+            self.icache.clear();
+        }
+        #[cfg(feature = "prefetch")]
+        self.prefetch.flush();
+        self.addr_fault.set(None);
+        Ok(())
+    }
+    fn request_interrupt(&mut self, level: u8) {
+        if (1..=7).contains(&level) {
+            self.trigger_interrupt(level);
+        }
+    }
     fn step(&mut self, bus: &mut dyn MemoryBus) -> u32 {
         if self.halted {
             return 0;
         }
         if self.pending_int.is_some() {
             self.process_int(bus);
+            #[cfg(feature = "prefetch")]
+            self.refill_prefetch(bus);
         }
         if self.stopped {
             return 4;
         }
+        // PC impar (JMP/JSR/RTS a dirección impar): error de dirección al buscar
+        // la instrucción, sin ejecutar nada
+        if cfg!(feature = "address-error") && self.pc & 1 != 0 {
+            self.cycles = 0;
+            self.exception_address_error(bus, self.pc, true, 0, true);
+            return self.cycles;
+        }
+        // T se muestrea antes de ejecutar: decide si hay trace al terminar
+        let traced = self.sr.trace;
+        self.raised = None;
         let op = self.fetch(bus);
         self.cycles = 4;
+        #[cfg(feature = "opcode-stats")]
+        {
+            self.stats[op as usize] += 1;
+        }
         self.exec(op, bus);
+        #[cfg(feature = "cpu_020")]
+        {
+            let saved = self.icache.take_hits() * icache::HIT_SAVING;
+            self.cycles = self.cycles.saturating_sub(saved);
+        }
 
+        let mut faulted = false;
         if let Some(fault_addr) = bus.bus_error() {
             bus.ack_bus_error();
             self.exception_bus_error(bus, fault_addr, op);
+            faulted = true;
+        }
+        if let Some((fault_addr, read)) = self.addr_fault.take() {
+            self.exception_address_error(bus, fault_addr, read, op, false);
+            faulted = true;
+        }
+        // Trace (vector 9) tras la instrucción; no tras RTE, ni esperando en STOP,
+        // ni si la instrucción abortó por error de bus/dirección. Tampoco si no llegó
+        // a ejecutarse (ilegal, privilegio, Line A/F): solo TRAP, TRAPV, CHK y la
+        // división por cero llevan trace encima de su propia excepción
+        let not_executed = matches!(self.raised, Some(4 | 8 | 10 | 11));
+        if traced && !faulted && !not_executed && op != 0x4E73 && !self.stopped && !self.halted {
+            self.exception(9, bus);
+        }
+        #[cfg(feature = "prefetch")]
+        {
+            self.refill_prefetch(bus);
+            self.cycles += self.prefetch.take_penalty();
         }
 
         self.cycles
@@ -561,6 +1028,19 @@ impl Cpu for Oxid68k {
 
 impl Oxid68k {
     fn exec(&mut self, op: u16, bus: &mut dyn MemoryBus) {
+        // Un acceso impar aborta la instrucción: los registros, SR y PC vuelven a
+        // como estaban tras leer el opcode, sin destino ni flags escritos
+        let saved = cfg!(feature = "address-error")
+            .then(|| (self.d, self.a, self.sr.to_u16(), self.usp, self.ssp, self.pc));
+        self.dispatch(op, bus);
+        if let Some((d, a, sr, usp, ssp, pc)) = saved {
+            if self.addr_fault.get().is_some() {
+                (self.d, self.a, self.usp, self.ssp, self.pc) = (d, a, usp, ssp, pc);
+                self.sr.from_u16(sr);
+            }
+        }
+    }
+    fn dispatch(&mut self, op: u16, bus: &mut dyn MemoryBus) {
         match (op >> 12) & 0xF {
             0x0 => self.g0(op, bus),
             0x1 => self.mov(op, bus, Size::Byte),
@@ -666,53 +1146,110 @@ impl Oxid68k {
         match (op >> 9) & 7 {
             0 => {
                 let i = self.imm(bus, sz);
-                let d = self.read_ea(bus, m, r, sz);
+                let (addr, d) = self.rmw_read(bus, m, r, sz);
                 let res = d | i;
                 self.sr.set_logic(res, sz);
-                self.write_ea(bus, m, r, sz, res);
-                self.cycles = 8;
+                self.rmw_write(bus, m, r, addr, sz, res);
+                self.cycles = imm_cycles(m, r, sz);
             }
             1 => {
                 let i = self.imm(bus, sz);
-                let d = self.read_ea(bus, m, r, sz);
+                let (addr, d) = self.rmw_read(bus, m, r, sz);
                 let res = d & i;
                 self.sr.set_logic(res, sz);
-                self.write_ea(bus, m, r, sz, res);
-                self.cycles = 8;
+                self.rmw_write(bus, m, r, addr, sz, res);
+                // ANDI.L #,Dn va en 14, no en 16 como ORI/EORI/ADDI/SUBI.L
+                self.cycles = if m == 0 && sz == Size::Long { 14 } else { imm_cycles(m, r, sz) };
             }
             2 => {
                 let i = self.imm(bus, sz);
-                let d = self.read_ea(bus, m, r, sz);
+                let (addr, d) = self.rmw_read(bus, m, r, sz);
                 let res = self.sub_flags(d, i, sz);
                 self.sr.extend = self.sr.carry;
-                self.write_ea(bus, m, r, sz, res);
-                self.cycles = 8;
+                self.rmw_write(bus, m, r, addr, sz, res);
+                self.cycles = imm_cycles(m, r, sz);
             }
             3 => {
                 let i = self.imm(bus, sz);
-                let d = self.read_ea(bus, m, r, sz);
+                let (addr, d) = self.rmw_read(bus, m, r, sz);
                 let res = self.add_flags(d, i, sz);
                 self.sr.extend = self.sr.carry;
-                self.write_ea(bus, m, r, sz, res);
-                self.cycles = 8;
+                self.rmw_write(bus, m, r, addr, sz, res);
+                self.cycles = imm_cycles(m, r, sz);
             }
             5 => {
                 let i = self.imm(bus, sz);
-                let d = self.read_ea(bus, m, r, sz);
+                let (addr, d) = self.rmw_read(bus, m, r, sz);
                 let res = d ^ i;
                 self.sr.set_logic(res, sz);
-                self.write_ea(bus, m, r, sz, res);
-                self.cycles = 8;
+                self.rmw_write(bus, m, r, addr, sz, res);
+                self.cycles = imm_cycles(m, r, sz);
             }
             6 => {
                 let i = self.imm(bus, sz);
                 let d = self.read_ea(bus, m, r, sz);
                 self.sub_flags(d, i, sz);
-                self.cycles = 8;
+                self.cycles = cmpi_cycles(m, r, sz);
             }
+            // 7 = MOVES (68010+): ilegal; no se emula ni con `cpu_010` (el bus no ve function codes)
             _ => self.exception(4, bus),
         }
     }
+    /// MOVEC Rc,Rn (4E7A) / Rn,Rc (4E7B), 68010+. Privilegiada; registro de
+    /// control desconocido = ilegal. CACR/CAAR solo en el 68020 (`cpu_020`). En el 68000 (o sin `cpu_010`) es ilegal.
+    #[cfg(not(feature = "cpu_010"))]
+    fn movec(&mut self, _op: u16, bus: &mut dyn MemoryBus) {
+        self.exception(4, bus);
+    }
+    #[cfg(feature = "cpu_010")]
+    fn movec(&mut self, op: u16, bus: &mut dyn MemoryBus) {
+        if self.model == Model::M68000 {
+            self.exception(4, bus);
+            return;
+        }
+        if !self.sr.supervisor {
+            self.exception(8, bus);
+            return;
+        }
+        let ext = self.fetch(bus);
+        let rn = ((ext >> 12) & 7) as usize;
+        let is_addr = ext & 0x8000 != 0;
+        if op & 1 == 0 {
+            let v = match ext & 0x0FFF {
+                0x000 => self.sfc as u32,
+                0x001 => self.dfc as u32,
+                #[cfg(feature = "cpu_020")]
+                0x002 if self.model == Model::M68020 => self.cacr,
+                0x800 => self.usp,
+                0x801 => self.vbr,
+                #[cfg(feature = "cpu_020")]
+                0x802 if self.model == Model::M68020 => self.caar,
+                _ => {
+                    self.exception(4, bus);
+                    return;
+                }
+            };
+            if is_addr { self.a[rn] = v } else { self.d[rn] = v }
+            self.cycles = 12;
+        } else {
+            let v = if is_addr { self.a[rn] } else { self.d[rn] };
+            match ext & 0x0FFF {
+                0x000 => self.sfc = (v & 7) as u8,
+                0x001 => self.dfc = (v & 7) as u8,
+                #[cfg(feature = "cpu_020")]
+                0x002 if self.model == Model::M68020 => self.write_cacr(v),
+                0x800 => self.usp = v,
+                0x801 => self.vbr = v,
+                #[cfg(feature = "cpu_020")]
+                0x802 if self.model == Model::M68020 => self.caar = v,
+                _ => {
+                    self.exception(4, bus);
+                    return;
+                }
+            }
+            self.cycles = 10;
+        }
+    }
     fn imm(&mut self, bus: &dyn MemoryBus, s: Size) -> u32 {
         match s {
             Size::Byte => (self.fetch(bus) & 0xFF) as u32,
@@ -754,7 +1291,7 @@ impl Oxid68k {
             }
         } else {
             // Memory - calculate address ONCE, then read-modify-write
-            let addr = self.calc_ea(bus, m, r);
+            let addr = self.rmw_ea(bus, m, r, Size::Byte);
             let v = self.read_byte(bus, addr) as u32;
             self.sr.zero = (v & mk) == 0;
             match (op >> 6) & 3 {
@@ -808,7 +1345,7 @@ impl Oxid68k {
                 _ => {}
             }
         } else {
-            let addr = self.calc_ea(bus, m, r);
+            let addr = self.rmw_ea(bus, m, r, Size::Byte);
             let v = self.read_byte(bus, addr) as u32;
             self.sr.zero = (v & mk) == 0;
             match (op >> 6) & 3 {
@@ -878,7 +1415,9 @@ impl Oxid68k {
             self.sr.set_logic(v, sz);
         }
         self.write_ea(bus, dm, dr, sz, v);
-        self.cycles = 4;
+        // -(An) como destino no paga los 2 ciclos extra del predecremento
+        let dst = if dm == 4 { 2 } else { dm };
+        self.cycles = 4 + ea_cycles(sm, sr, sz) + ea_cycles(dst, dr, sz);
     }
     fn moveq(&mut self, op: u16) {
         let r = ((op >> 9) & 7) as usize;
@@ -946,6 +1485,10 @@ impl Oxid68k {
                 self.cycles = 20;
                 return;
             }
+            0x4E7A | 0x4E7B => {
+                self.movec(op, bus);
+                return;
+            }
             _ => {}
         }
         if (op & 0xFFF0) == 0x4E60 {
@@ -1007,7 +1550,7 @@ impl Oxid68k {
         if (op & 0xFFF8) == 0x4840 && m == 0 {
             let rg = (op & 7) as usize;
             let v = self.d[rg];
-            self.d[rg] = (v >> 16) | (v << 16);
+            self.d[rg] = v.rotate_left(16);
             self.sr.set_logic(self.d[rg], Size::Long);
             self.cycles = 4;
             return;
@@ -1068,9 +1611,9 @@ impl Oxid68k {
                     return;
                 }
                 0xA => {
-                    let v = self.read_ea(bus, m, r, Size::Byte);
+                    let (addr, v) = self.rmw_read(bus, m, r, Size::Byte);
                     self.sr.set_logic(v, Size::Byte);
-                    self.write_ea(bus, m, r, Size::Byte, v | 0x80);
+                    self.rmw_write(bus, m, r, addr, Size::Byte, v | 0x80);
                     self.cycles = 4;
                     return;
                 }
@@ -1106,7 +1649,7 @@ impl Oxid68k {
                     self.sr.extend = self.sr.carry;
                     self.set_d(r as usize, res, sz);
                 } else {
-                    let addr = self.calc_ea(bus, m, r);
+                    let addr = self.rmw_ea(bus, m, r, sz);
                     let d = self.read_sz(bus, addr, sz);
                     let x = if self.sr.extend { 1 } else { 0 };
                     let res = self.sub_flags(0, d.wrapping_add(x), sz);
@@ -1125,7 +1668,7 @@ impl Oxid68k {
                     self.set_d(r as usize, 0, sz);
                 } else {
                     // Memory - calculate address ONCE, then read (dummy) and write
-                    let addr = self.calc_ea(bus, m, r);
+                    let addr = self.rmw_ea(bus, m, r, sz);
                     let _ = self.read_sz(bus, addr, sz); // Dummy read (68k behavior)
                     self.write_sz(bus, addr, 0, sz);
                 }
@@ -1143,7 +1686,7 @@ impl Oxid68k {
                     self.sr.extend = self.sr.carry;
                     self.set_d(r as usize, res, sz);
                 } else {
-                    let addr = self.calc_ea(bus, m, r);
+                    let addr = self.rmw_ea(bus, m, r, sz);
                     let d = self.read_sz(bus, addr, sz);
                     let res = self.sub_flags(0, d, sz);
                     self.sr.extend = self.sr.carry;
@@ -1159,7 +1702,7 @@ impl Oxid68k {
                     self.sr.set_logic(res, sz);
                     self.set_d(r as usize, res, sz);
                 } else {
-                    let addr = self.calc_ea(bus, m, r);
+                    let addr = self.rmw_ea(bus, m, r, sz);
                     let d = self.read_sz(bus, addr, sz);
                     let res = !d & sz.mask();
                     self.sr.set_logic(res, sz);
@@ -1175,7 +1718,7 @@ impl Oxid68k {
                     let res = self.sbcd(0, d, x);
                     self.d[r as usize] = (self.d[r as usize] & 0xFFFFFF00) | res as u32;
                 } else {
-                    let addr = self.calc_ea(bus, m, r);
+                    let addr = self.rmw_ea(bus, m, r, Size::Byte);
                     let d = self.read_byte(bus, addr);
                     let x = if self.sr.extend { 1 } else { 0 };
                     let res = self.sbcd(0, d, x);
@@ -1184,9 +1727,21 @@ impl Oxid68k {
                 self.cycles = 8;
             }
             0xA => {
+                // TST: el 68000 solo admite EA de datos alterables; el 68020
+                // añade An (nunca en .B), PC-relativo e inmediato
+                let legal = match (m, r) {
+                    (1, _) => self.is_020() && sz != Size::Byte,
+                    (7, 2..=4) => self.is_020(),
+                    (7, 5..) => false,
+                    _ => true,
+                };
+                if !legal {
+                    self.exception(4, bus);
+                    return;
+                }
                 let v = self.read_ea(bus, m, r, sz);
                 self.sr.set_logic(v, sz);
-                self.cycles = 4;
+                self.cycles = 4 + ea_cycles(m, r, sz);
             }
             _ => self.exception(4, bus),
         }
@@ -1200,13 +1755,12 @@ impl Oxid68k {
         };
         let m = ((op >> 3) & 7) as u8;
         let r = (op & 7) as u8;
-        let (base, mask) = if m == 4 {
-            let mk = self.fetch(bus);
-            (self.a[r as usize], mk)
+        // La máscara de registros va antes que las extensiones del EA
+        let mask = self.fetch(bus);
+        let base = if m == 4 {
+            self.a[r as usize]
         } else {
-            let a = self.calc_ea(bus, m, r);
-            let mk = self.fetch(bus);
-            (a, mk)
+            self.calc_ea(bus, m, r)
         };
         if dir {
             let mut a = base;
@@ -1230,12 +1784,14 @@ impl Oxid68k {
             }
         } else {
             if m == 4 {
+                // 68000: si An está en la lista se guarda su valor ORIGINAL
+                // (An solo se actualiza al final). El 68020+ guarda el decrementado.
+                // Máscara invertida: bit 0 = A7 ... bit 15 = D0; se guarda de A7 hacia D0.
                 let mut a = base;
-                for i in 0..16 {
-                    let b = 15 - i;
+                for b in 0..16 {
                     if mask & (1 << b) != 0 {
                         a = a.wrapping_sub(sz.bytes());
-                        let v = if i < 8 { self.a[7 - i] } else { self.d[15 - i] };
+                        let v = if b < 8 { self.a[7 - b] } else { self.d[15 - b] };
                         self.write_sz(bus, a, v, sz);
                     }
                 }
@@ -1264,7 +1820,7 @@ impl Oxid68k {
             if m == 1 {
                 let disp = self.fetch(bus) as i16 as i32;
                 if !self.test_cc(cc) {
-                    let v = ((self.d[r as usize] as u16).wrapping_sub(1)) as u16;
+                    let v = (self.d[r as usize] as u16).wrapping_sub(1);
                     self.d[r as usize] = (self.d[r as usize] & 0xFFFF0000) | v as u32;
                     if v != 0xFFFF {
                         self.pc = (self.pc.wrapping_sub(2) as i32).wrapping_add(disp) as u32;
@@ -1276,9 +1832,15 @@ impl Oxid68k {
                     self.cycles = 12;
                 }
             } else {
-                let v = if self.test_cc(cc) { 0xFF } else { 0x00 };
+                // Scc: Dn tarda 6 si la condición se cumple y 4 si no; memoria 8 + EA
+                let t = self.test_cc(cc);
+                let v = if t { 0xFF } else { 0x00 };
                 self.write_ea(bus, m, r, Size::Byte, v);
-                self.cycles = 8;
+                self.cycles = match (m, t) {
+                    (0, true) => 6,
+                    (0, false) => 4,
+                    _ => 8 + ea_cycles(m, r, Size::Byte),
+                };
             }
         } else {
             let sz = Size::from_bits((op >> 6) & 3).unwrap();
@@ -1288,19 +1850,19 @@ impl Oxid68k {
                 if m == 1 {
                     self.a[r as usize] = self.a[r as usize].wrapping_sub(d);
                 } else {
-                    let dst = self.read_ea(bus, m, r, sz);
+                    let (addr, dst) = self.rmw_read(bus, m, r, sz);
                     let res = self.sub_flags(dst, d, sz);
                     self.sr.extend = self.sr.carry;
-                    self.write_ea(bus, m, r, sz, res);
+                    self.rmw_write(bus, m, r, addr, sz, res);
                 }
             } else {
                 if m == 1 {
                     self.a[r as usize] = self.a[r as usize].wrapping_add(d);
                 } else {
-                    let dst = self.read_ea(bus, m, r, sz);
+                    let (addr, dst) = self.rmw_read(bus, m, r, sz);
                     let res = self.add_flags(dst, d, sz);
                     self.sr.extend = self.sr.carry;
-                    self.write_ea(bus, m, r, sz, res);
+                    self.rmw_write(bus, m, r, addr, sz, res);
                 }
             }
             self.cycles = 4;
@@ -1345,16 +1907,16 @@ impl Oxid68k {
         let m = ((op >> 3) & 7) as u8;
         let r = (op & 7) as u8;
         match (op >> 6) & 7 {
-            0 | 1 | 2 => {
+            0..=2 => {
                 let sz = Size::from_bits((op >> 6) & 3).unwrap();
                 let s = self.read_ea(bus, m, r, sz);
                 let res = self.d[dr] | s;
                 self.set_d(dr, res, sz);
                 self.sr.set_logic(res, sz);
-                self.cycles = 4;
+                self.cycles = alu_dn_cycles(m, r, sz);
             }
             3 => {
-                let div = self.read_ea(bus, m, r, Size::Word) as u32;
+                let div = self.read_ea(bus, m, r, Size::Word);
                 if div == 0 {
                     self.exception(5, bus);
                     return;
@@ -1402,11 +1964,11 @@ impl Oxid68k {
                     _ => Size::Word,
                 };
                 let s = self.d[dr];
-                let d = self.read_ea(bus, m, r, sz);
+                let (addr, d) = self.rmw_read(bus, m, r, sz);
                 let res = s | d;
-                self.write_ea(bus, m, r, sz, res);
+                self.rmw_write(bus, m, r, addr, sz, res);
                 self.sr.set_logic(res, sz);
-                self.cycles = 8;
+                self.cycles = alu_mem_cycles(m, r, sz);
             }
             7 => {
                 let div = self.read_ea(bus, m, r, Size::Word) as i16 as i32;
@@ -1418,7 +1980,7 @@ impl Oxid68k {
                 let q = dvd / div;
                 let rm = dvd % div;
                 self.sr.carry = false;
-                if q > 32767 || q < -32768 {
+                if !(-32768..=32767).contains(&q) {
                     self.sr.overflow = true;
                 } else {
                     self.sr.overflow = false;
@@ -1436,21 +1998,21 @@ impl Oxid68k {
         let m = ((op >> 3) & 7) as u8;
         let r = (op & 7) as u8;
         match (op >> 6) & 7 {
-            0 | 1 | 2 => {
+            0..=2 => {
                 let sz = Size::from_bits((op >> 6) & 3).unwrap();
                 let s = self.read_ea(bus, m, r, sz);
                 let d = self.d[dr];
                 let res = self.sub_flags(d, s, sz);
                 self.sr.extend = self.sr.carry;
                 self.set_d(dr, res, sz);
-                self.cycles = 4;
+                self.cycles = alu_dn_cycles(m, r, sz);
             }
             3 => {
                 let s = self.read_ea(bus, m, r, Size::Word) as i16 as i32 as u32;
                 self.a[dr] = self.a[dr].wrapping_sub(s);
-                self.cycles = 8;
+                self.cycles = 8 + ea_cycles(m, r, Size::Word);
             }
-            4 | 5 | 6 => {
+            4..=6 => {
                 if m == 0 || m == 1 {
                     self.subx(op, bus);
                 } else {
@@ -1461,17 +2023,17 @@ impl Oxid68k {
                         _ => Size::Word,
                     };
                     let s = self.d[dr];
-                    let d = self.read_ea(bus, m, r, sz);
+                    let (addr, d) = self.rmw_read(bus, m, r, sz);
                     let res = self.sub_flags(d, s, sz);
                     self.sr.extend = self.sr.carry;
-                    self.write_ea(bus, m, r, sz, res);
-                    self.cycles = 8;
+                    self.rmw_write(bus, m, r, addr, sz, res);
+                    self.cycles = alu_mem_cycles(m, r, sz);
                 }
             }
             7 => {
                 let s = self.read_ea(bus, m, r, Size::Long);
                 self.a[dr] = self.a[dr].wrapping_sub(s);
-                self.cycles = 8;
+                self.cycles = alu_dn_cycles(m, r, Size::Long);
             }
             _ => {}
         }
@@ -1518,30 +2080,29 @@ impl Oxid68k {
         let m = ((op >> 3) & 7) as u8;
         let r = (op & 7) as u8;
         match (op >> 6) & 7 {
-            0 | 1 | 2 => {
+            0..=2 => {
                 let sz = Size::from_bits((op >> 6) & 3).unwrap();
                 let s = self.read_ea(bus, m, r, sz);
                 let d = self.d[dr];
                 self.sub_flags(d, s, sz);
-                self.cycles = 4;
+                self.cycles = (if sz == Size::Long { 6 } else { 4 }) + ea_cycles(m, r, sz);
             }
             3 => {
                 let s = self.read_ea(bus, m, r, Size::Word) as i16 as i32 as u32;
                 let d = self.a[dr];
                 self.sub_flags(d, s, Size::Long);
-                self.cycles = 6;
+                self.cycles = 6 + ea_cycles(m, r, Size::Word);
             }
-            4 | 5 | 6 => {
+            4..=6 => {
                 if m == 1 {
-                    let ax = dr;
-                    let ay = r as usize;
+                    // CMPM (Ay)+,(Ax)+ : flags como CMP (X intacto)
                     let sz = Size::from_bits((op >> 6) & 3).unwrap();
-                    let s = self.read_sz(bus, self.a[ay], sz);
-                    self.a[ay] = self.a[ay].wrapping_add(sz.bytes());
-                    let d = self.read_sz(bus, self.a[ax], sz);
-                    self.a[ax] = self.a[ax].wrapping_add(sz.bytes());
+                    let ay = self.rmw_ea(bus, 3, r, sz);
+                    let s = self.read_sz(bus, ay, sz);
+                    let ax = self.rmw_ea(bus, 3, dr as u8, sz);
+                    let d = self.read_sz(bus, ax, sz);
                     self.sub_flags(d, s, sz);
-                    self.cycles = 12;
+                    self.cycles = if sz == Size::Long { 20 } else { 12 };
                 } else {
                     let sz = match (op >> 6) & 7 {
                         4 => Size::Byte,
@@ -1550,18 +2111,22 @@ impl Oxid68k {
                         _ => Size::Word,
                     };
                     let s = self.d[dr];
-                    let d = self.read_ea(bus, m, r, sz);
+                    let (addr, d) = self.rmw_read(bus, m, r, sz);
                     let res = s ^ d;
                     self.sr.set_logic(res, sz);
-                    self.write_ea(bus, m, r, sz, res);
-                    self.cycles = 8;
+                    self.rmw_write(bus, m, r, addr, sz, res);
+                    self.cycles = match (m, sz) {
+                        (0, Size::Long) => 8,
+                        (0, _) => 4,
+                        _ => alu_mem_cycles(m, r, sz),
+                    };
                 }
             }
             7 => {
                 let s = self.read_ea(bus, m, r, Size::Long);
                 let d = self.a[dr];
                 self.sub_flags(d, s, Size::Long);
-                self.cycles = 6;
+                self.cycles = 6 + ea_cycles(m, r, Size::Long);
             }
             _ => {}
         }
@@ -1571,16 +2136,16 @@ impl Oxid68k {
         let m = ((op >> 3) & 7) as u8;
         let r = (op & 7) as u8;
         match (op >> 6) & 7 {
-            0 | 1 | 2 => {
+            0..=2 => {
                 let sz = Size::from_bits((op >> 6) & 3).unwrap();
                 let s = self.read_ea(bus, m, r, sz);
                 let res = self.d[dr] & s;
                 self.set_d(dr, res, sz);
                 self.sr.set_logic(res, sz);
-                self.cycles = 4;
+                self.cycles = alu_dn_cycles(m, r, sz);
             }
             3 => {
-                let s = self.read_ea(bus, m, r, Size::Word) as u32;
+                let s = self.read_ea(bus, m, r, Size::Word);
                 let d = self.d[dr] as u16 as u32;
                 let res = s * d;
                 self.d[dr] = res;
@@ -1613,49 +2178,43 @@ impl Oxid68k {
                     }
                 } else {
                     let s = self.d[dr];
-                    let d = self.read_ea(bus, m, r, Size::Byte);
+                    let (addr, d) = self.rmw_read(bus, m, r, Size::Byte);
                     let res = s & d;
-                    self.write_ea(bus, m, r, Size::Byte, res);
+                    self.rmw_write(bus, m, r, addr, Size::Byte, res);
                     self.sr.set_logic(res, Size::Byte);
-                    self.cycles = 8;
+                    self.cycles = alu_mem_cycles(m, r, Size::Byte);
                 }
             }
             5 => {
                 if m == 0 {
                     let ry = r as usize;
-                    let t = self.d[dr];
-                    self.d[dr] = self.d[ry];
-                    self.d[ry] = t;
+                    self.d.swap(dr, ry);
                     self.cycles = 6;
                 } else if m == 1 {
                     let ry = r as usize;
-                    let t = self.a[dr];
-                    self.a[dr] = self.a[ry];
-                    self.a[ry] = t;
+                    self.a.swap(dr, ry);
                     self.cycles = 6;
                 } else {
                     let s = self.d[dr];
-                    let d = self.read_ea(bus, m, r, Size::Word);
+                    let (addr, d) = self.rmw_read(bus, m, r, Size::Word);
                     let res = s & d;
-                    self.write_ea(bus, m, r, Size::Word, res);
+                    self.rmw_write(bus, m, r, addr, Size::Word, res);
                     self.sr.set_logic(res, Size::Word);
-                    self.cycles = 8;
+                    self.cycles = alu_mem_cycles(m, r, Size::Word);
                 }
             }
             6 => {
                 if m == 1 {
                     let ry = r as usize;
-                    let t = self.d[dr];
-                    self.d[dr] = self.a[ry];
-                    self.a[ry] = t;
+                    std::mem::swap(&mut self.d[dr], &mut self.a[ry]);
                     self.cycles = 6;
                 } else {
                     let s = self.d[dr];
-                    let d = self.read_ea(bus, m, r, Size::Long);
+                    let (addr, d) = self.rmw_read(bus, m, r, Size::Long);
                     let res = s & d;
-                    self.write_ea(bus, m, r, Size::Long, res);
+                    self.rmw_write(bus, m, r, addr, Size::Long, res);
                     self.sr.set_logic(res, Size::Long);
-                    self.cycles = 12;
+                    self.cycles = alu_mem_cycles(m, r, Size::Long);
                 }
             }
             7 => {
@@ -1677,21 +2236,21 @@ impl Oxid68k {
         let m = ((op >> 3) & 7) as u8;
         let r = (op & 7) as u8;
         match (op >> 6) & 7 {
-            0 | 1 | 2 => {
+            0..=2 => {
                 let sz = Size::from_bits((op >> 6) & 3).unwrap();
                 let s = self.read_ea(bus, m, r, sz);
                 let d = self.d[dr];
                 let res = self.add_flags(d, s, sz);
                 self.sr.extend = self.sr.carry;
                 self.set_d(dr, res, sz);
-                self.cycles = 4;
+                self.cycles = alu_dn_cycles(m, r, sz);
             }
             3 => {
                 let s = self.read_ea(bus, m, r, Size::Word) as i16 as i32 as u32;
                 self.a[dr] = self.a[dr].wrapping_add(s);
-                self.cycles = 8;
+                self.cycles = 8 + ea_cycles(m, r, Size::Word);
             }
-            4 | 5 | 6 => {
+            4..=6 => {
                 if m == 0 || m == 1 {
                     self.addx(op, bus);
                 } else {
@@ -1702,17 +2261,17 @@ impl Oxid68k {
                         _ => Size::Word,
                     };
                     let s = self.d[dr];
-                    let d = self.read_ea(bus, m, r, sz);
+                    let (addr, d) = self.rmw_read(bus, m, r, sz);
                     let res = self.add_flags(d, s, sz);
                     self.sr.extend = self.sr.carry;
-                    self.write_ea(bus, m, r, sz, res);
-                    self.cycles = 8;
+                    self.rmw_write(bus, m, r, addr, sz, res);
+                    self.cycles = alu_mem_cycles(m, r, sz);
                 }
             }
             7 => {
                 let s = self.read_ea(bus, m, r, Size::Long);
                 self.a[dr] = self.a[dr].wrapping_add(s);
-                self.cycles = 8;
+                self.cycles = alu_dn_cycles(m, r, Size::Long);
             }
             _ => {}
         }
@@ -1760,7 +2319,7 @@ impl Oxid68k {
             let r = (op & 7) as u8;
             let dr = (op & 0x0100) != 0;
             let ty = (op >> 9) & 3;
-            let v = self.read_ea(bus, m, r, Size::Word);
+            let (addr, v) = self.rmw_read(bus, m, r, Size::Word);
             let res = match ty {
                 0 => self.asx(v, 1, dr, Size::Word),
                 1 => self.lsx(v, 1, dr, Size::Word),
@@ -1768,7 +2327,7 @@ impl Oxid68k {
                 3 => self.rox(v, 1, dr, Size::Word),
                 _ => v,
             };
-            self.write_ea(bus, m, r, Size::Word, res);
+            self.rmw_write(bus, m, r, addr, Size::Word, res);
             self.cycles = 8;
         } else {
             let sz = Size::from_bits((op >> 6) & 3).unwrap();
@@ -1778,7 +2337,7 @@ impl Oxid68k {
             let rg = (op & 7) as usize;
             let cnt = if ir {
                 let cr = ((op >> 9) & 7) as usize;
-                (self.d[cr] % 64) as u32
+                self.d[cr] % 64
             } else {
                 let c = (op >> 9) & 7;
                 if c == 0 {
@@ -0,0 +1,65 @@
+// crates/oxid68k/src/prefetch.rs
+//! Cola de prefetch de 2 palabras del 68000 (feature `prefetch`).
+//!
+//! El 68000 lleva siempre dos palabras leídas por delante del PC (IRD/IRC): cada
+//! palabra consumida se repone con un ciclo de bus, y eso ya está contado en las
+//! tablas de ciclos. Lo que el modelo añade:
+//! - Las escrituras no actualizan la cola: el código automodificable que pisa la
+//!   instrucción siguiente ejecuta la palabra vieja, como en el chip real.
+//! - Saltos y excepciones rellenan la cola dentro de sus propios ciclos, pero si el
+//!   PC cambia desde fuera (depurador, trampas del frontend) la siguiente
+//!   instrucción paga el rellenado: `REFILL_CYCLES` por palabra.
+
+use oxide_core::MemoryBus;
+
+/// Ciclos de bus por palabra leída para rellenar la cola
+pub const REFILL_CYCLES: u32 = 4;
+
+#[derive(Default)]
+pub struct PrefetchQueue {
+    /// Dirección de `words[0]`; `None` = cola vacía
+    addr: Option<u32>,
+    words: [u16; 2],
+    /// Ciclos de rellenado no contados en las tablas desde el último `take_penalty`
+    penalty: u32,
+}
+
+impl PrefetchQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indica si la cola tiene ya las palabras de `pc` y `pc + 2`.
+    pub fn holds(&self, pc: u32) -> bool {
+        self.addr == Some(pc)
+    }
+
+    /// Carga `pc` y `pc + 2` (rellenado por un salto: sin penalización).
+    pub fn fill(&mut self, pc: u32, bus: &dyn MemoryBus) {
+        self.words = [bus.read_u16_be(pc), bus.read_u16_be(pc.wrapping_add(2))];
+        self.addr = Some(pc);
+    }
+
+    /// Vacía la cola (reset, cambio externo del PC).
+    pub fn flush(&mut self) {
+        self.addr = None;
+    }
+
+    /// Consume la palabra en `pc` y repone la siguiente. Si la cola no la tenía,
+    /// la rellena entera y apunta la penalización.
+    pub fn next(&mut self, pc: u32, bus: &dyn MemoryBus) -> u16 {
+        if !self.holds(pc) {
+            self.fill(pc, bus);
+            self.penalty += 2 * REFILL_CYCLES;
+        }
+        let word = self.words[0];
+        self.words = [self.words[1], bus.read_u16_be(pc.wrapping_add(4))];
+        self.addr = Some(pc.wrapping_add(2));
+        word
+    }
+
+    /// Ciclos de rellenado pendientes de sumar al paso actual (y los pone a 0).
+    pub fn take_penalty(&mut self) -> u32 {
+        std::mem::take(&mut self.penalty)
+    }
+}
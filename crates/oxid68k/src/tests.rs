@@ -0,0 +1,1518 @@
+// crates/oxid68k/src/tests.rs
+use crate::*;
+use oxide_core::MemoryBus;
+
+const CODE: u32 = 0x1000;
+
+struct TestBus {
+    ram: Vec<u8>,
+    /// Leer esta dirección señala un error de bus
+    bus_error_at: Option<u32>,
+    bus_error: std::cell::Cell<Option<u32>>,
+}
+impl TestBus {
+    fn new() -> Self { Self { ram: vec![0; 0x10000], bus_error_at: None, bus_error: Default::default() } }
+}
+impl MemoryBus for TestBus {
+    fn read(&self, addr: u32) -> u8 {
+        if self.bus_error_at == Some(addr) {
+            self.bus_error.set(Some(addr));
+        }
+        self.ram[(addr & 0xFFFF) as usize]
+    }
+    fn write(&mut self, addr: u32, val: u8) { self.ram[(addr & 0xFFFF) as usize] = val; }
+    fn bus_error(&self) -> Option<u32> { self.bus_error.get() }
+    fn ack_bus_error(&mut self) { self.bus_error.set(None); }
+}
+
+/// Carga las palabras en CODE y deja la CPU apuntando ahí.
+fn setup(words: &[u16]) -> (Oxid68k, TestBus) {
+    let mut bus = TestBus::new();
+    for (i, w) in words.iter().enumerate() {
+        bus.write_u16_be(CODE + i as u32 * 2, *w);
+    }
+    let mut cpu = Oxid68k::new();
+    cpu.pc = CODE;
+    cpu.a[7] = 0x8000;
+    // Como si hubiera saltado ahí: la cola ya tiene las dos primeras palabras
+    #[cfg(feature = "prefetch")]
+    cpu.prefetch.fill(CODE, &bus);
+    (cpu, bus)
+}
+
+// ============================================================================
+// MOVEQ
+// ============================================================================
+
+#[test]
+fn test_moveq_negative() {
+    let (mut cpu, mut bus) = setup(&[0x70FF]); // MOVEQ #-1,D0
+    cpu.sr.carry = true;
+    cpu.sr.overflow = true;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[0], 0xFFFF_FFFF);
+    assert!(cpu.sr.negative);
+    assert!(!cpu.sr.zero);
+    assert!(!cpu.sr.carry && !cpu.sr.overflow);
+}
+
+#[test]
+fn test_moveq_zero() {
+    let (mut cpu, mut bus) = setup(&[0x7200]); // MOVEQ #0,D1
+    cpu.d[1] = 0x1234_5678;
+    cpu.sr.carry = true;
+    cpu.sr.overflow = true;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[1], 0);
+    assert!(cpu.sr.zero);
+    assert!(!cpu.sr.negative);
+    assert!(!cpu.sr.carry && !cpu.sr.overflow);
+}
+
+#[test]
+fn test_moveq_positive() {
+    let (mut cpu, mut bus) = setup(&[0x7E7F]); // MOVEQ #127,D7
+    cpu.d[7] = 0xFFFF_FFFF;
+    cpu.sr.extend = true;
+    let cycles = cpu.step(&mut bus);
+    assert_eq!(cpu.d[7], 0x0000_007F);
+    assert!(!cpu.sr.zero && !cpu.sr.negative);
+    assert!(cpu.sr.extend); // X no se toca
+    assert_eq!(cycles, 4);
+}
+
+// ============================================================================
+// MOVE / MOVEA
+// ============================================================================
+
+#[test]
+fn test_move_b_reg_to_reg() {
+    let (mut cpu, mut bus) = setup(&[0x1200]); // MOVE.B D0,D1
+    cpu.d[0] = 0x0000_0080;
+    cpu.d[1] = 0x1234_5678;
+    cpu.sr.carry = true;
+    cpu.sr.overflow = true;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[1], 0x1234_5680); // solo el byte bajo
+    assert!(cpu.sr.negative && !cpu.sr.zero);
+    assert!(!cpu.sr.carry && !cpu.sr.overflow);
+}
+
+#[test]
+fn test_move_w_reg_to_mem() {
+    let (mut cpu, mut bus) = setup(&[0x3080]); // MOVE.W D0,(A0)
+    cpu.d[0] = 0xAAAA_0000;
+    cpu.a[0] = 0x2000;
+    bus.write_u16_be(0x2000, 0xFFFF);
+    cpu.step(&mut bus);
+    assert_eq!(bus.read_u16_be(0x2000), 0x0000);
+    assert!(cpu.sr.zero && !cpu.sr.negative);
+}
+
+#[test]
+fn test_move_l_mem_to_reg() {
+    let (mut cpu, mut bus) = setup(&[0x2418]); // MOVE.L (A0)+,D2
+    cpu.a[0] = 0x2000;
+    bus.write_u32_be(0x2000, 0x8000_0001);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[2], 0x8000_0001);
+    assert_eq!(cpu.a[0], 0x2004);
+    assert!(cpu.sr.negative && !cpu.sr.zero);
+}
+
+#[test]
+fn test_move_l_immediate() {
+    let (mut cpu, mut bus) = setup(&[0x263C, 0x1234, 0x5678]); // MOVE.L #$12345678,D3
+    cpu.sr.overflow = true;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[3], 0x1234_5678);
+    assert_eq!(cpu.pc, CODE + 6);
+    assert!(!cpu.sr.negative && !cpu.sr.zero && !cpu.sr.overflow);
+}
+
+#[test]
+fn test_move_w_immediate_to_mem() {
+    let (mut cpu, mut bus) = setup(&[0x31FC, 0x8001, 0x3000]); // MOVE.W #$8001,$3000.W
+    cpu.step(&mut bus);
+    assert_eq!(bus.read_u16_be(0x3000), 0x8001);
+    assert!(cpu.sr.negative);
+}
+
+#[test]
+fn test_movea_w_sign_extends_without_flags() {
+    let (mut cpu, mut bus) = setup(&[0x3240]); // MOVEA.W D0,A1
+    cpu.d[0] = 0x0000_8000;
+    cpu.sr.zero = true;
+    cpu.sr.carry = true;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[1], 0xFFFF_8000);
+    // MOVEA no altera los códigos de condición
+    assert!(cpu.sr.zero && cpu.sr.carry && !cpu.sr.negative);
+}
+
+#[test]
+fn test_movea_l() {
+    let (mut cpu, mut bus) = setup(&[0x2C7C, 0x00FF, 0x0000]); // MOVEA.L #$00FF0000,A6
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[6], 0x00FF_0000);
+    assert!(!cpu.sr.zero && !cpu.sr.negative);
+}
+
+// ============================================================================
+// BTST / BCHG / BCLR / BSET
+// ============================================================================
+
+#[test]
+fn test_bset_dynamic_memory_wraps_to_byte() {
+    let (mut cpu, mut bus) = setup(&[0x03D0]); // BSET D1,(A0)
+    cpu.d[1] = 9; // 9 mod 8 = bit 1
+    cpu.a[0] = 0x2000;
+    bus.write(0x2000, 0x00);
+    cpu.step(&mut bus);
+    assert_eq!(bus.read(0x2000), 0x02);
+    assert!(cpu.sr.zero); // Z refleja el bit ANTES de modificarlo
+    assert_eq!(bus.read(0x2001), 0x00);
+}
+
+#[test]
+fn test_btst_static_register_wraps_to_long() {
+    let (mut cpu, mut bus) = setup(&[0x0800, 0x0021]); // BTST #33,D0
+    cpu.d[0] = 0x0000_0002; // 33 mod 32 = bit 1
+    cpu.step(&mut bus);
+    assert!(!cpu.sr.zero);
+    assert_eq!(cpu.d[0], 0x0000_0002);
+    assert_eq!(cpu.pc, CODE + 4);
+}
+
+#[test]
+fn test_bclr_bchg_register() {
+    let (mut cpu, mut bus) = setup(&[0x0380, 0x0840, 0x001F]); // BCLR D1,D0 ; BCHG #31,D0
+    cpu.d[0] = 0x8000_0001;
+    cpu.d[1] = 32; // 32 mod 32 = bit 0
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[0], 0x8000_0000);
+    assert!(!cpu.sr.zero);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[0], 0x0000_0000);
+    assert!(!cpu.sr.zero);
+}
+
+#[test]
+fn test_bset_postincrement_advances_once() {
+    let (mut cpu, mut bus) = setup(&[0x08D8, 0x0007]); // BSET #7,(A0)+
+    cpu.a[0] = 0x2000;
+    cpu.step(&mut bus);
+    assert_eq!(bus.read(0x2000), 0x80);
+    assert_eq!(cpu.a[0], 0x2001);
+}
+
+// ============================================================================
+// MOVEM
+// ============================================================================
+
+#[test]
+fn test_movem_predec_stores_original_base_register() {
+    let (mut cpu, mut bus) = setup(&[0x48E0, 0x4080]); // MOVEM.L D1/A0,-(A0)
+    cpu.a[0] = 0x2010;
+    cpu.d[1] = 0x1111_2222;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[0], 0x2008);
+    assert_eq!(bus.read_u32_be(0x2008), 0x1111_2222);
+    assert_eq!(bus.read_u32_be(0x200C), 0x2010); // valor previo al decremento
+}
+
+#[test]
+fn test_movem_displacement_reads_mask_first() {
+    // MOVEM.W $10(A1),D0/D2 : máscara y luego desplazamiento
+    let (mut cpu, mut bus) = setup(&[0x4CA9, 0x0005, 0x0010]);
+    cpu.a[1] = 0x2000;
+    bus.write_u16_be(0x2010, 0x8000);
+    bus.write_u16_be(0x2012, 0x0042);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[0], 0xFFFF_8000); // extensión de signo a 32 bits
+    assert_eq!(cpu.d[2], 0x0000_0042);
+    assert_eq!(cpu.pc, CODE + 6);
+}
+
+#[test]
+fn test_movem_postinc_load_writes_back() {
+    let (mut cpu, mut bus) = setup(&[0x4CD8, 0x0003]); // MOVEM.L (A0)+,D0/D1
+    cpu.a[0] = 0x2000;
+    bus.write_u32_be(0x2000, 1);
+    bus.write_u32_be(0x2004, 2);
+    cpu.step(&mut bus);
+    assert_eq!((cpu.d[0], cpu.d[1]), (1, 2));
+    assert_eq!(cpu.a[0], 0x2008);
+}
+
+#[test]
+fn test_movem_predec_partial_mask_order() {
+    let (mut cpu, mut bus) = setup(&[0x48A7, 0x8001]); // MOVEM.W D0/A7,-(A7): D0 = bit 15, A7 = bit 0
+    cpu.a[7] = 0x3000;
+    cpu.d[0] = 0x0000_AAAA;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[7], 0x2FFC);
+    assert_eq!(bus.read_u16_be(0x2FFC), 0xAAAA); // D0 en la dirección más baja
+    assert_eq!(bus.read_u16_be(0x2FFE), 0x3000); // A7 original arriba
+}
+
+// ============================================================================
+// Address Error (vector 3)
+// ============================================================================
+
+#[test]
+#[cfg(feature = "address-error")]
+fn test_move_w_to_odd_address_raises_address_error() {
+    let (mut cpu, mut bus) = setup(&[0x3080]); // MOVE.W D0,(A0)
+    bus.write_u32_be(0x0C, 0x4000); // vector 3
+    cpu.a[0] = 0x2001;
+    cpu.d[0] = 0x1234;
+    let sr = cpu.sr.to_u16();
+    cpu.step(&mut bus);
+
+    assert_eq!(cpu.pc, 0x4000);
+    assert_eq!(cpu.a[7], 0x8000 - 14);
+    let sp = cpu.a[7];
+    assert_eq!(bus.read_u16_be(sp), 0x0005); // escritura, datos de supervisor
+    assert_eq!(bus.read_u32_be(sp + 2), 0x2001); // dirección de acceso
+    assert_eq!(bus.read_u16_be(sp + 6), 0x3080); // IR
+    assert_eq!(bus.read_u16_be(sp + 8), sr);
+    assert_eq!(bus.read_u16_be(0x2000), 0x0000); // la escritura no llegó al bus
+}
+
+#[test]
+#[cfg(feature = "address-error")]
+fn test_odd_read_aborts_instruction_without_writing_destination() {
+    let (mut cpu, mut bus) = setup(&[0x3010]); // MOVE.W (A0),D0
+    bus.write_u32_be(0x0C, 0x4000);
+    cpu.a[0] = 0x2001;
+    cpu.d[0] = 0x1234_5678;
+    let sr = cpu.sr.to_u16();
+    cpu.step(&mut bus);
+
+    assert_eq!(cpu.pc, 0x4000);
+    assert_eq!(cpu.d[0], 0x1234_5678); // ni destino
+    assert_eq!(bus.read_u16_be(cpu.a[7] + 8), sr); // ni flags: SR apilado intacto
+    assert!(!cpu.sr.zero && !cpu.sr.negative);
+}
+
+#[test]
+#[cfg(feature = "address-error")]
+fn test_odd_postincrement_is_rolled_back() {
+    let (mut cpu, mut bus) = setup(&[0x2218]); // MOVE.L (A0)+,D1
+    bus.write_u32_be(0x0C, 0x4000);
+    cpu.a[0] = 0x2001;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[0], 0x2001);
+    assert_eq!(bus.read_u32_be(cpu.a[7] + 10), CODE + 2); // PC tras el opcode
+}
+
+#[test]
+#[cfg(feature = "address-error")]
+fn test_bus_error_with_odd_access_is_not_a_double_fault() {
+    let (mut cpu, mut bus) = setup(&[0x3290]); // MOVE.W (A0),(A1)
+    bus.write_u32_be(0x08, 0x4000); // vector 2
+    bus.bus_error_at = Some(0x2000);
+    cpu.a[0] = 0x2000;
+    cpu.a[1] = 0x3001;
+    cpu.step(&mut bus);
+
+    // Una sola trama (la del error de bus); la CPU sigue viva
+    assert!(!cpu.halted);
+    assert_eq!(cpu.pc, 0x4000);
+    assert_eq!(cpu.a[7], 0x8000 - 14);
+}
+
+#[test]
+#[cfg(feature = "address-error")]
+fn test_odd_ssp_while_stacking_is_a_double_fault() {
+    let (mut cpu, mut bus) = setup(&[0x3080]); // MOVE.W D0,(A0)
+    bus.write_u32_be(0x0C, 0x4000);
+    cpu.a[0] = 0x2001;
+    cpu.a[7] = 0x8001;
+    cpu.step(&mut bus);
+    assert!(cpu.halted);
+}
+
+#[test]
+fn test_byte_access_to_odd_address_is_fine() {
+    let (mut cpu, mut bus) = setup(&[0x1080]); // MOVE.B D0,(A0)
+    cpu.a[0] = 0x2001;
+    cpu.d[0] = 0x42;
+    cpu.step(&mut bus);
+    assert_eq!(bus.read(0x2001), 0x42);
+    assert_eq!(cpu.pc, CODE + 2);
+}
+
+#[test]
+#[cfg(feature = "address-error")]
+fn test_odd_long_read_flags_read_access() {
+    let (mut cpu, mut bus) = setup(&[0x2011]); // MOVE.L (A1),D0
+    bus.write_u32_be(0x0C, 0x4000);
+    cpu.a[1] = 0x2003;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x4000);
+    assert_eq!(bus.read_u16_be(cpu.a[7]), 0x0015); // bit R/W = lectura
+}
+
+#[test]
+#[cfg(feature = "address-error")]
+fn test_jump_to_odd_address_faults_on_instruction_fetch() {
+    let (mut cpu, mut bus) = setup(&[0x4ED0]); // JMP (A0)
+    bus.write_u32_be(0x0C, 0x4000);
+    cpu.a[0] = 0x2001;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x2001);
+    cpu.step(&mut bus);
+
+    assert_eq!(cpu.pc, 0x4000);
+    let sp = cpu.a[7];
+    assert_eq!(sp, 0x8000 - 14);
+    assert_eq!(bus.read_u16_be(sp), 0x0016); // lectura, programa de supervisor
+    assert_eq!(bus.read_u32_be(sp + 2), 0x2001);
+    assert_eq!(bus.read_u32_be(sp + 10), 0x2001); // PC apilado
+}
+
+#[test]
+#[cfg(not(feature = "address-error"))]
+fn test_odd_access_does_not_trap_without_feature() {
+    let (mut cpu, mut bus) = setup(&[0x3080]); // MOVE.W D0,(A0)
+    bus.write_u32_be(0x0C, 0x4000);
+    cpu.a[0] = 0x2001;
+    cpu.d[0] = 0x1234;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, CODE + 2);
+    assert_eq!(cpu.a[7], 0x8000);
+    assert_eq!(bus.read_u16_be(0x2001), 0x1234);
+}
+
+// ============================================================================
+// Volcado JSON del estado (feature `serde`)
+// ============================================================================
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_state_serializes_to_json() {
+    let (mut cpu, _bus) = setup(&[]);
+    cpu.d[3] = 0xDEAD;
+    let json = serde_json::to_string(&cpu).unwrap();
+    assert!(json.contains(&format!("\"pc\":{}", CODE)));
+    assert!(json.contains("\"d\":[0,0,0,57005,0,0,0,0]"));
+    assert!(json.contains("\"supervisor\":true"));
+    assert!(!json.contains("addr_fault"));
+}
+
+// ============================================================================
+// Histograma de opcodes (feature `opcode-stats`)
+// ============================================================================
+
+#[test]
+#[cfg(feature = "opcode-stats")]
+fn test_opcode_histogram_counts_executed_instructions() {
+    // MOVEQ #1,D0 ; MOVEQ #2,D1 ; MOVEQ #1,D0 ; NOP
+    let (mut cpu, mut bus) = setup(&[0x7001, 0x7202, 0x7001, 0x4E71]);
+    for _ in 0..4 {
+        cpu.step(&mut bus);
+    }
+    let stats = cpu.opcode_stats();
+    assert_eq!(stats[0x7001], 2);
+    assert_eq!(stats[0x7202], 1);
+    assert_eq!(stats[0x4E71], 1);
+    assert_eq!(stats.iter().sum::<u64>(), 4);
+
+    cpu.reset_opcode_stats();
+    assert_eq!(cpu.opcode_stats().iter().sum::<u64>(), 0);
+}
+
+// ============================================================================
+// CMPM
+// ============================================================================
+
+#[test]
+fn test_cmpm_b_compares_strings() {
+    // CMPM.B (A0)+,(A1)+ x3
+    let (mut cpu, mut bus) = setup(&[0xB308, 0xB308, 0xB308]);
+    bus.ram[0x2000..0x2003].copy_from_slice(b"ABC");
+    bus.ram[0x3000..0x3003].copy_from_slice(b"ABD");
+    cpu.a[0] = 0x2000;
+    cpu.a[1] = 0x3000;
+    cpu.sr.extend = true;
+
+    cpu.step(&mut bus);
+    assert!(cpu.sr.zero);
+    assert_eq!(cpu.cycles, 12);
+    cpu.step(&mut bus);
+    assert!(cpu.sr.zero);
+    cpu.step(&mut bus);
+    // 'D' - 'C' = 1
+    assert!(!cpu.sr.zero);
+    assert!(!cpu.sr.carry);
+    assert!(!cpu.sr.negative);
+    assert!(cpu.sr.extend); // CMPM no toca X
+    assert_eq!(cpu.a[0], 0x2003);
+    assert_eq!(cpu.a[1], 0x3003);
+}
+
+#[test]
+fn test_cmpm_w_and_l_sizes() {
+    // CMPM.W (A0)+,(A1)+ ; CMPM.L (A0)+,(A1)+
+    let (mut cpu, mut bus) = setup(&[0xB348, 0xB388]);
+    bus.write_u16_be(0x2000, 0x0002);
+    bus.write_u16_be(0x3000, 0x0001);
+    bus.write_u32_be(0x2002, 0x1234_5678);
+    bus.write_u32_be(0x3002, 0x1234_5678);
+    cpu.a[0] = 0x2000;
+    cpu.a[1] = 0x3000;
+
+    cpu.step(&mut bus);
+    assert!(cpu.sr.carry); // 1 - 2 pide préstamo
+    assert!(cpu.sr.negative);
+    assert_eq!((cpu.a[0], cpu.a[1]), (0x2002, 0x3002));
+
+    cpu.step(&mut bus);
+    assert!(cpu.sr.zero);
+    assert!(!cpu.sr.carry);
+    assert_eq!(cpu.cycles, 20);
+    assert_eq!((cpu.a[0], cpu.a[1]), (0x2006, 0x3006));
+}
+
+#[test]
+fn test_cmpm_b_a7_steps_by_two() {
+    // CMPM.B (A7)+,(A0)+
+    let (mut cpu, mut bus) = setup(&[0xB10F]);
+    cpu.a[0] = 0x2000;
+    cpu.a[7] = 0x3000;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[7], 0x3002);
+    assert_eq!(cpu.a[0], 0x2001);
+}
+
+// ============================================================================
+// Read-modify-write: el EA se calcula una sola vez
+// ============================================================================
+
+#[test]
+fn test_ori_w_postinc_advances_once() {
+    let (mut cpu, mut bus) = setup(&[0x0058, 0x00F0]); // ORI.W #$00F0,(A0)+
+    bus.write_u16_be(0x2000, 0x0F00);
+    bus.write_u16_be(0x2002, 0x1111);
+    cpu.a[0] = 0x2000;
+    cpu.step(&mut bus);
+    assert_eq!(bus.read_u16_be(0x2000), 0x0FF0);
+    assert_eq!(bus.read_u16_be(0x2002), 0x1111);
+    assert_eq!(cpu.a[0], 0x2002);
+    assert_eq!(cpu.pc, CODE + 4);
+}
+
+#[test]
+fn test_andi_b_predec_and_addi_l_postinc() {
+    // ANDI.B #$0F,-(A1) ; ADDI.L #1,(A2)+
+    let (mut cpu, mut bus) = setup(&[0x0221, 0x000F, 0x069A, 0x0000, 0x0001]);
+    bus.ram[0x2000] = 0xAB;
+    bus.write_u32_be(0x3000, 0xFFFF_FFFF);
+    cpu.a[1] = 0x2001;
+    cpu.a[2] = 0x3000;
+    cpu.step(&mut bus);
+    assert_eq!(bus.ram[0x2000], 0x0B);
+    assert_eq!(cpu.a[1], 0x2000);
+    cpu.step(&mut bus);
+    assert_eq!(bus.read_u32_be(0x3000), 0);
+    assert!(cpu.sr.zero && cpu.sr.carry && cpu.sr.extend);
+    assert_eq!(cpu.a[2], 0x3004);
+}
+
+#[test]
+fn test_clr_and_neg_apply_postinc_predec() {
+    // CLR.W (A0)+ ; NEG.L -(A1)
+    let (mut cpu, mut bus) = setup(&[0x4258, 0x44A1]);
+    bus.write_u16_be(0x2000, 0xFFFF);
+    bus.write_u32_be(0x3000, 1);
+    cpu.a[0] = 0x2000;
+    cpu.a[1] = 0x3004;
+    cpu.step(&mut bus);
+    assert_eq!(bus.read_u16_be(0x2000), 0);
+    assert_eq!(cpu.a[0], 0x2002);
+    cpu.step(&mut bus);
+    assert_eq!(bus.read_u32_be(0x3000), 0xFFFF_FFFF);
+    assert_eq!(cpu.a[1], 0x3000);
+}
+
+// ============================================================================
+// Timing: coste del cálculo de EA
+// ============================================================================
+
+#[test]
+fn test_ea_cycles_table() {
+    assert_eq!(ea_cycles(0, 0, Size::Long), 0);
+    assert_eq!(ea_cycles(2, 0, Size::Word), 4);
+    assert_eq!(ea_cycles(4, 0, Size::Word), 6);
+    assert_eq!(ea_cycles(6, 0, Size::Word), 10);
+    assert_eq!(ea_cycles(6, 0, Size::Long), 14);
+    assert_eq!(ea_cycles(7, 1, Size::Word), 12);
+    assert_eq!(ea_cycles(7, 4, Size::Long), 8);
+}
+
+#[test]
+fn test_add_cycles_depend_on_ea() {
+    // ADD.W (A0),D0 ; ADD.W (2,A0,D1.W),D0
+    let (mut cpu, mut bus) = setup(&[0xD050, 0xD070, 0x1002]);
+    cpu.a[0] = 0x2000;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 8);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 14);
+
+    // ADD.L D1,D0 = 8 ; ADD.L (A0),D0 = 14
+    let (mut cpu, mut bus) = setup(&[0xD081, 0xD090]);
+    cpu.a[0] = 0x2000;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 8);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 14);
+}
+
+#[test]
+fn test_move_and_immediate_cycles() {
+    // MOVE.W (A0)+,-(A1) = 4 + 4 + 4 ; MOVE.L D0,(A1) = 12 ; ORI.W #1,(A0) = 16
+    // ORI.L #1,D0 = 16 ; ANDI.L #$FF,D0 = 14
+    let (mut cpu, mut bus) = setup(&[
+        0x3318, 0x2280, 0x0050, 0x0001,
+        0x0080, 0x0000, 0x0001,
+        0x0280, 0x0000, 0x00FF,
+    ]);
+    cpu.a[0] = 0x2000;
+    cpu.a[1] = 0x3000;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 12);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 12);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 16);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 16);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.cycles, 14);
+}
+
+// ============================================================================
+// Interrupciones vía Cpu::request_interrupt
+// ============================================================================
+
+#[test]
+fn test_request_interrupt_through_trait_object() {
+    let (mut m68k, mut bus) = setup(&[0x4E71]);
+    bus.write_u32_be((24 + 3) * 4, 0x4000); // autovector nivel 3
+    bus.write_u16_be(0x4000, 0x4E71); // NOP
+    m68k.sr.int_mask = 2;
+
+    let cpu: &mut dyn Cpu = &mut m68k;
+    cpu.request_interrupt(0); // ignorado
+    cpu.request_interrupt(3);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc(), 0x4002);
+    assert_eq!(m68k.sr.int_mask, 3);
+}
+
+// ============================================================================
+// Scc
+// ============================================================================
+
+/// Ejecuta `op` (Scc) con el SR indicado y devuelve (byte bajo de D0, ciclos).
+fn run_scc(op: u16, sr: u16) -> (u8, u32) {
+    let (mut cpu, mut bus) = setup(&[op]);
+    cpu.d[0] = 0x1234_5655;
+    cpu.sr.from_u16(0x2700 | sr);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[0] & 0xFFFF_FF00, 0x1234_5600); // solo toca el byte bajo
+    (cpu.d[0] as u8, cpu.cycles)
+}
+
+#[test]
+fn test_scc_conditions_on_data_register() {
+    const Z: u16 = 0x04;
+    const N: u16 = 0x08;
+    const V: u16 = 0x02;
+    assert_eq!(run_scc(0x50C0, 0), (0xFF, 6)); // ST
+    assert_eq!(run_scc(0x51C0, 0), (0x00, 4)); // SF
+    assert_eq!(run_scc(0x57C0, Z), (0xFF, 6)); // SEQ
+    assert_eq!(run_scc(0x57C0, 0), (0x00, 4));
+    assert_eq!(run_scc(0x56C0, 0), (0xFF, 6)); // SNE
+    assert_eq!(run_scc(0x56C0, Z), (0x00, 4));
+    assert_eq!(run_scc(0x5CC0, N | V), (0xFF, 6)); // SGE: N == V
+    assert_eq!(run_scc(0x5CC0, N), (0x00, 4));
+    assert_eq!(run_scc(0x5DC0, N), (0xFF, 6)); // SLT: N != V
+    assert_eq!(run_scc(0x5DC0, 0), (0x00, 4));
+}
+
+#[test]
+fn test_scc_to_memory() {
+    // SEQ (A0) ; SNE (A0)+
+    let (mut cpu, mut bus) = setup(&[0x57D0, 0x56D8]);
+    bus.ram[0x2000] = 0x55;
+    cpu.a[0] = 0x2000;
+    cpu.sr.zero = true;
+    cpu.step(&mut bus);
+    assert_eq!(bus.ram[0x2000], 0xFF);
+    assert_eq!(cpu.cycles, 12);
+    cpu.step(&mut bus);
+    assert_eq!(bus.ram[0x2000], 0x00);
+    assert_eq!(cpu.a[0], 0x2001);
+    assert_eq!(cpu.cycles, 12);
+}
+
+// ============================================================================
+// TST
+// ============================================================================
+
+#[test]
+fn test_tst_data_register_sets_flags() {
+    let (mut cpu, mut bus) = setup(&[0x4A80]); // TST.L D0
+    cpu.d[0] = 0x8000_0000;
+    cpu.sr.overflow = true;
+    cpu.sr.carry = true;
+    cpu.step(&mut bus);
+    assert!(cpu.sr.negative);
+    assert!(!cpu.sr.zero && !cpu.sr.overflow && !cpu.sr.carry);
+    assert_eq!(cpu.cycles, 4);
+}
+
+#[test]
+fn test_tst_memory() {
+    let (mut cpu, mut bus) = setup(&[0x4A50]); // TST.W (A0)
+    cpu.a[0] = 0x2000;
+    bus.write_u16_be(0x2000, 0x0000);
+    cpu.step(&mut bus);
+    assert!(cpu.sr.zero);
+    assert_eq!(cpu.a[0], 0x2000);
+    assert_eq!(cpu.cycles, 8);
+}
+
+#[test]
+fn test_tst_address_register_is_illegal_on_68000() {
+    let (mut cpu, mut bus) = setup(&[0x4A48]); // TST.W A0
+    bus.write_u32_be(0x10, 0x4000); // vector 4
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x4000);
+}
+
+#[cfg(feature = "cpu_020")]
+#[test]
+fn test_tst_address_register_on_68020() {
+    let (mut cpu, mut bus) = setup(&[0x4A88, 0x4A08]); // TST.L A0 ; TST.B A0
+    bus.write_u32_be(0x10, 0x4000);
+    cpu.model = Model::M68020;
+    cpu.a[0] = 0xFFFF_0000;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, CODE + 2);
+    assert!(cpu.sr.negative && !cpu.sr.zero);
+
+    // .B sobre An no existe en ningún modelo
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x4000);
+}
+
+// ============================================================================
+// Pila: A7 par y USP/SSP
+// ============================================================================
+
+#[test]
+fn test_byte_push_pop_keeps_a7_even() {
+    // MOVE.B D0,-(A7) ; MOVE.B (A7)+,D1 ; MOVE.B D0,-(A0)
+    let (mut cpu, mut bus) = setup(&[0x1F00, 0x121F, 0x1100]);
+    cpu.d[0] = 0xAB;
+    cpu.a[0] = 0x3000;
+
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[7], 0x7FFE);
+    assert_eq!(bus.read(0x7FFE), 0xAB); // byte alto de la palabra
+
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[7], 0x8000);
+    assert_eq!(cpu.d[1] & 0xFF, 0xAB);
+
+    // El resto de registros de dirección sí avanzan de 1 en 1
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[0], 0x2FFF);
+}
+
+#[test]
+fn test_word_long_push_sizes() {
+    // MOVE.W D0,-(A7) ; MOVE.L D0,-(A7)
+    let (mut cpu, mut bus) = setup(&[0x3F00, 0x2F00]);
+    cpu.d[0] = 0x1122_3344;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[7], 0x7FFE);
+    assert_eq!(bus.read_u16_be(0x7FFE), 0x3344);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[7], 0x7FFA);
+    assert_eq!(bus.read_u32_be(0x7FFA), 0x1122_3344);
+}
+
+#[test]
+fn test_user_mode_pushes_to_usp_and_trap_uses_ssp() {
+    // MOVE #$0000,SR ; MOVE.W D0,-(A7) ; TRAP #0
+    let (mut cpu, mut bus) = setup(&[0x46FC, 0x0000, 0x3F00, 0x4E40]);
+    bus.write_u32_be(0x80, 0x4000); // vector 32
+    cpu.usp = 0x6000;
+    cpu.d[0] = 0xBEEF;
+
+    cpu.step(&mut bus);
+    assert!(!cpu.sr.supervisor);
+    assert_eq!((cpu.a[7], cpu.ssp), (0x6000, 0x8000));
+
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[7], 0x5FFE);
+    assert_eq!(bus.read_u16_be(0x5FFE), 0xBEEF);
+
+    cpu.step(&mut bus);
+    assert!(cpu.sr.supervisor);
+    assert_eq!(cpu.pc, 0x4000);
+    assert_eq!(cpu.usp, 0x5FFE);
+    assert_eq!(cpu.a[7], 0x8000 - 6); // SR + PC en la pila de supervisor
+    assert_eq!(bus.read_u16_be(0x7FFA) & 0x2000, 0); // SR apilado: modo usuario
+}
+
+#[test]
+fn test_move_usp() {
+    // MOVE A0,USP ; MOVE USP,A1 ; MOVE #$0000,SR ; MOVE USP,A2
+    let (mut cpu, mut bus) = setup(&[0x4E60, 0x4E69, 0x46FC, 0x0000, 0x4E6A]);
+    bus.write_u32_be(0x20, 0x4000); // vector 8: privilegio
+    cpu.a[0] = 0x1234_5670;
+
+    cpu.step(&mut bus);
+    assert_eq!(cpu.usp, 0x1234_5670);
+    assert_eq!(cpu.a[7], 0x8000); // A7 sigue siendo SSP
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[1], 0x1234_5670);
+
+    // En modo usuario MOVE USP es privilegiada
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[7], 0x1234_5670);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x4000);
+    assert_eq!(cpu.a[2], 0);
+    assert!(cpu.sr.supervisor);
+}
+
+// ============================================================================
+// Endianness: el 68000 es big-endian (opcodes, extensiones y datos)
+// ============================================================================
+
+#[test]
+fn test_68k_memory_words_are_big_endian() {
+    // MOVE.W $2000.W,D0 ; MOVE.L D0,$2010.W
+    let (mut cpu, mut bus) = setup(&[0x3038, 0x2000, 0x21C0, 0x2010]);
+    bus.write(0x2000, 0x12);
+    bus.write(0x2001, 0x34);
+    cpu.d[0] = 0xAAAA_0000;
+
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[0], 0xAAAA_1234);
+    cpu.step(&mut bus);
+    assert_eq!(
+        [bus.read(0x2010), bus.read(0x2011), bus.read(0x2012), bus.read(0x2013)],
+        [0xAA, 0xAA, 0x12, 0x34]
+    );
+}
+
+// ============================================================================
+// ADDQ / SUBQ
+// ============================================================================
+
+#[test]
+fn test_addq_w_address_register_is_long_without_flags() {
+    let (mut cpu, mut bus) = setup(&[0x5248]); // ADDQ.W #1,A0
+    cpu.a[0] = 0x0000_FFFF;
+    cpu.sr.zero = true;
+    cpu.sr.carry = true;
+    cpu.sr.extend = true;
+    cpu.step(&mut bus);
+    // El tamaño .W no corta el acarreo: opera sobre los 32 bits
+    assert_eq!(cpu.a[0], 0x0001_0000);
+    assert!(cpu.sr.zero && cpu.sr.carry && cpu.sr.extend);
+    assert!(!cpu.sr.negative && !cpu.sr.overflow);
+}
+
+#[test]
+fn test_addq_w_data_register_sets_flags() {
+    let (mut cpu, mut bus) = setup(&[0x5240]); // ADDQ.W #1,D0
+    cpu.d[0] = 0x1234_FFFF;
+    cpu.step(&mut bus);
+    // Solo cambia la palabra baja
+    assert_eq!(cpu.d[0], 0x1234_0000);
+    assert!(cpu.sr.zero && cpu.sr.carry && cpu.sr.extend);
+    assert!(!cpu.sr.negative && !cpu.sr.overflow);
+}
+
+#[test]
+fn test_subq_zero_means_eight() {
+    let (mut cpu, mut bus) = setup(&[0x5181, 0x5148]); // SUBQ.L #8,D1 ; SUBQ.W #8,A0
+    cpu.d[1] = 8;
+    cpu.a[0] = 0x0001_0004;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[1], 0);
+    assert!(cpu.sr.zero && !cpu.sr.carry);
+
+    cpu.sr.zero = false;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.a[0], 0x0000_FFFC); // préstamo hacia la palabra alta
+    assert!(!cpu.sr.zero && !cpu.sr.negative, "SUBQ a An no toca flags");
+}
+
+// ============================================================================
+// Tabla de vectores
+// ============================================================================
+
+#[test]
+fn test_dump_vector_table_labels_entries() {
+    let mut bus = TestBus::new();
+    bus.write_u32_be(3 * 4, 0x0040_1A2C); // Address Error
+    bus.write_u32_be(34 * 4, 0x0040_2000); // TRAP #2
+    let dump = vectors::dump_vector_table(&bus, 0);
+    let lines: Vec<&str> = dump.lines().collect();
+    assert_eq!(lines.len(), 256);
+    assert_eq!(lines[3], "  3 $00000C  00401A2C  Address Error");
+    assert!(lines[34].ends_with("00402000  TRAP #2"));
+    assert!(lines[27].ends_with("Level 3 Autovector"));
+    assert!(lines[200].ends_with("User Interrupt"));
+
+    // Con otra base se leen las entradas desde ahí
+    let dump = vectors::dump_vector_table(&bus, 0x0C);
+    assert!(dump.starts_with("  0 $00000C  00401A2C  Reset SSP"));
+}
+
+// ============================================================================
+// GDB stub (feature `gdb-stub`)
+// ============================================================================
+
+#[cfg(feature = "gdb-stub")]
+mod gdb_stub {
+    use super::{setup, TestBus, CODE};
+    use crate::gdb::*;
+    use crate::Oxid68k;
+    use oxide_core::debug_server::DebugTarget;
+    use oxide_core::{Cpu, MemoryBus};
+
+    struct Machine {
+        cpu: Oxid68k,
+        bus: TestBus,
+    }
+
+    impl DebugTarget for Machine {
+        fn registers(&self) -> Vec<(&'static str, u32)> {
+            vec![("PC", self.cpu.pc)]
+        }
+        fn pc(&self) -> u32 {
+            self.cpu.pc
+        }
+        fn read_mem(&self, addr: u32) -> u8 {
+            self.bus.read(addr)
+        }
+        fn write_mem(&mut self, addr: u32, val: u8) {
+            self.bus.write(addr, val);
+        }
+        fn step(&mut self) -> u32 {
+            self.cpu.step(&mut self.bus)
+        }
+    }
+
+    impl GdbTarget for Machine {
+        fn cpu(&self) -> &Oxid68k {
+            &self.cpu
+        }
+        fn cpu_mut(&mut self) -> &mut Oxid68k {
+            &mut self.cpu
+        }
+    }
+
+    /// MOVEQ #1,D0 ; ADDQ.L #1,D0 ; BRA.S -4
+    fn machine() -> Machine {
+        let (cpu, bus) = setup(&[0x7001, 0x5280, 0x60FC]);
+        Machine { cpu, bus }
+    }
+
+    fn reply(stub: &mut GdbStub, m: &mut Machine, packet: &str) -> String {
+        match stub.handle(m, packet, &mut || false) {
+            Action::Reply(r) | Action::ReplyAndClose(r) => r,
+        }
+    }
+
+    #[test]
+    fn test_packet_framing_and_checksum() {
+        assert_eq!(frame("OK"), "$OK#9a");
+        assert_eq!(checksum(b""), 0);
+
+        let (item, used) = parse_incoming(b"+$g#67$?").unwrap();
+        assert_eq!((item, used), (Incoming::Ack, 1));
+        assert_eq!(parse_incoming(b"$g#67$?"), Some((Incoming::Packet("g".into()), 5)));
+        // Paquete incompleto: esperar más bytes
+        assert_eq!(parse_incoming(b"$?"), None);
+        assert_eq!(parse_incoming(b"$g#6"), None);
+        assert_eq!(parse_incoming(b"$g#00"), Some((Incoming::BadChecksum, 5)));
+        assert_eq!(parse_incoming(b"junk\x03"), Some((Incoming::Interrupt, 5)));
+        assert_eq!(parse_incoming(b"-"), Some((Incoming::Nack, 1)));
+    }
+
+    #[test]
+    fn test_monitor_vectors_dumps_table() {
+        let mut stub = GdbStub::new();
+        let mut m = machine();
+        m.bus.write_u32_be(4 * 4, 0x0040_0100);
+        let cmd: String = b"vectors".iter().map(|b| format!("{:02x}", b)).collect();
+        let hex = reply(&mut stub, &mut m, &format!("qRcmd,{}", cmd));
+        let bytes: Vec<u8> = (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect();
+        let text = String::from_utf8(bytes).unwrap();
+        assert_eq!(text.lines().nth(4), Some("  4 $000010  00400100  Illegal Instruction"));
+        assert_eq!(reply(&mut stub, &mut m, "qRcmd,6a756e6b"), "E01"); // "junk"
+    }
+
+    #[test]
+    fn test_registers_read_and_write() {
+        let mut stub = GdbStub::new();
+        let mut m = machine();
+        m.cpu.d[0] = 0x1234_5678;
+        m.cpu.sr.from_u16(0x2700);
+        let regs = reply(&mut stub, &mut m, "g");
+        assert_eq!(regs.len(), 18 * 8);
+        assert_eq!(&regs[..8], "12345678");
+        assert_eq!(&regs[16 * 8..], format!("00002700{:08x}", CODE));
+
+        assert_eq!(reply(&mut stub, &mut m, "P9=cafebabe"), "OK");
+        assert_eq!(m.cpu.a[1], 0xCAFE_BABE);
+        assert_eq!(reply(&mut stub, &mut m, "p9"), "cafebabe");
+        assert_eq!(reply(&mut stub, &mut m, "p20"), "E01");
+
+        let mut g = String::from("G");
+        g.push_str(&"00000001".repeat(16));
+        g.push_str("0000001f00002000");
+        assert_eq!(reply(&mut stub, &mut m, &g), "OK");
+        assert_eq!(m.cpu.d[7], 1);
+        assert_eq!(m.cpu.pc, 0x2000);
+        assert!(!m.cpu.sr.supervisor);
+        assert_eq!(reply(&mut stub, &mut m, "G1234"), "E01");
+    }
+
+    #[test]
+    fn test_memory_read_and_write() {
+        let mut stub = GdbStub::new();
+        let mut m = machine();
+        assert_eq!(reply(&mut stub, &mut m, &format!("m{:x},4", CODE)), "70015280");
+        assert_eq!(reply(&mut stub, &mut m, "M3000,2:beef"), "OK");
+        assert_eq!(m.bus.read_u16_be(0x3000), 0xBEEF);
+        assert_eq!(reply(&mut stub, &mut m, "M3000,3:beef"), "E01");
+        // La respuesta no puede pasar de PacketSize (0x1000 dígitos hex)
+        assert_eq!(reply(&mut stub, &mut m, "m0,800").len(), 0x1000);
+        assert_eq!(reply(&mut stub, &mut m, "m0,801"), "E01");
+        assert_eq!(reply(&mut stub, &mut m, "m0,ffffffff"), "E01");
+    }
+
+    #[test]
+    fn test_ctrl_c_probe_keeps_other_bytes() {
+        use std::io::Write;
+        use std::net::{TcpListener, TcpStream};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        client.write_all(b"$?#3f\x03").unwrap();
+
+        let mut pending = Vec::new();
+        let mut interrupted = false;
+        for _ in 0..1000 {
+            if ctrl_c_pending(&mut server, &mut pending) {
+                interrupted = true;
+                break;
+            }
+            if pending.is_empty() {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+        }
+        assert!(interrupted);
+        assert_eq!(pending, b"$?#3f");
+    }
+
+    #[test]
+    fn test_step_continue_and_breakpoints() {
+        let mut stub = GdbStub::new();
+        let mut m = machine();
+        assert_eq!(reply(&mut stub, &mut m, "s"), "S05");
+        assert_eq!(m.cpu.d[0], 1);
+
+        assert_eq!(reply(&mut stub, &mut m, &format!("Z0,{:x},2", CODE + 4)), "OK");
+        assert_eq!(reply(&mut stub, &mut m, "c"), "S05");
+        assert_eq!(m.cpu.pc, CODE + 4);
+        assert_eq!(reply(&mut stub, &mut m, "c"), "S05"); // una vuelta del bucle
+        assert_eq!(m.cpu.d[0], 3);
+
+        assert_eq!(reply(&mut stub, &mut m, &format!("z0,{:x},2", CODE + 4)), "OK");
+        assert_eq!(stub.breakpoints().count(), 0);
+        // Sin breakpoints solo para Ctrl-C
+        let interrupted = match stub.handle(&mut m, "c", &mut || true) {
+            Action::Reply(r) => r,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(interrupted, "S02");
+
+        assert_eq!(reply(&mut stub, &mut m, "Z1,0,2"), "", "hardware breakpoints no soportados");
+        assert_eq!(stub.handle(&mut m, "D", &mut || false), Action::ReplyAndClose("OK".into()));
+    }
+}
+
+
+// ============================================================================
+// MOVEC / MOVES (68010+)
+// ============================================================================
+
+#[test]
+fn test_movec_and_moves_are_illegal_on_68000() {
+    // MOVEC VBR,D0 ; MOVES.W (A0),D1
+    let (mut cpu, mut bus) = setup(&[0x4E7A, 0x0801, 0x0E50, 0x1000]);
+    bus.write_u32_be(0x10, 0x4000); // vector 4
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x4000);
+
+    cpu.pc = CODE + 4;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x4000);
+}
+
+#[cfg(feature = "cpu_010")]
+#[test]
+fn test_movec_vbr_relocates_vectors_on_68010() {
+    // MOVEA.L #$3000,A1 ; MOVEC A1,VBR ; MOVEC VBR,D2 ; MOVEC SFC,D3 ; TRAP #0
+    let (mut cpu, mut bus) = setup(&[0x227C, 0x0000, 0x3000, 0x4E7B, 0x9801, 0x4E7A, 0x2801, 0x4E7A, 0x3000, 0x4E40]);
+    cpu.model = Model::M68010;
+    cpu.sfc = 5;
+    bus.write_u32_be(0x3000 + 32 * 4, 0x5000); // TRAP #0 en la tabla reubicada
+    bus.write_u32_be(32 * 4, 0x6000);
+    for _ in 0..4 {
+        cpu.step(&mut bus);
+    }
+    assert_eq!(cpu.vbr, 0x3000);
+    assert_eq!(cpu.d[2], 0x3000);
+    assert_eq!(cpu.d[3], 5);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x5000);
+
+    // En modo usuario MOVEC es privilegiada (vector 8, también vía VBR)
+    let (mut cpu, mut bus) = setup(&[0x4E7A, 0x0801]);
+    cpu.model = Model::M68010;
+    cpu.vbr = 0x3000;
+    cpu.set_sr(0x0000);
+    bus.write_u32_be(0x3000 + 8 * 4, 0x7000);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x7000);
+}
+
+#[cfg(feature = "cpu_010")]
+#[test]
+fn test_vbr_relocates_interrupts_and_reset_clears_it() {
+    let (mut cpu, mut bus) = setup(&[0x4E71]);
+    cpu.model = Model::M68010;
+    cpu.vbr = 0x2000;
+    bus.write_u32_be((24 + 3) * 4, 0x6000); // tabla original: no debe usarse
+    bus.write_u32_be(0x2000 + (24 + 3) * 4, 0x4000); // autovector nivel 3 reubicado
+    bus.write_u16_be(0x4000, 0x4E71);
+    cpu.sr.int_mask = 2;
+    cpu.pending_int = Some(3);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x4002);
+
+    // RESET: VBR = 0 y SSP/PC de $000000/$000004
+    bus.write_u32_be(0, 0x8000);
+    bus.write_u32_be(4, 0x1000);
+    bus.write_u32_be(0x2000, 0x7000);
+    bus.write_u32_be(0x2004, 0x5000);
+    cpu.reset_with_bus(&mut bus);
+    assert_eq!(cpu.vbr, 0);
+    assert_eq!((cpu.a[7], cpu.pc), (0x8000, 0x1000));
+}
+
+// ============================================================================
+// Caché de instrucciones del 68020 (feature `cpu_020`)
+// ============================================================================
+
+/// MOVEQ #cacr,D1 ; MOVEC D1,CACR ; MOVEQ #20,D0 ; bucle SUBQ.L #1,D0 / BNE.S.
+/// Devuelve los ciclos del bucle y la CPU al terminar.
+#[cfg(feature = "cpu_020")]
+fn run_cached_loop(cacr: u8) -> (u32, Oxid68k) {
+    let (mut cpu, mut bus) = setup(&[0x7200 | cacr as u16, 0x4E7B, 0x1002, 0x7014, 0x5380, 0x66FC, 0x4E71]);
+    cpu.model = Model::M68020;
+    for _ in 0..3 {
+        cpu.step(&mut bus);
+    }
+    let mut cycles = 0;
+    while cpu.pc != CODE + 12 {
+        cycles += cpu.step(&mut bus);
+    }
+    (cycles, cpu)
+}
+
+#[cfg(feature = "cpu_020")]
+#[test]
+fn test_icache_speeds_up_loop_and_cacr_controls_it() {
+    let (uncached, cpu) = run_cached_loop(0x00);
+    assert_eq!(cpu.cacr, 0);
+    let (cached, cpu) = run_cached_loop(0x09); // E + C (la orden C se lee a 0)
+    assert_eq!(cpu.cacr, 0x01);
+    assert!(cached < uncached, "{} >= {}", cached, uncached);
+    // SUBQ y BNE comparten línea: solo falla el primer SUBQ
+    assert_eq!(uncached - cached, (20 * 2 - 1) * icache::HIT_SAVING);
+}
+
+#[cfg(feature = "cpu_020")]
+#[test]
+fn test_icache_invalidated_by_writes_and_off_on_68000() {
+    // 4 x NOP ; MOVE.W D0,(A0) ; BRA.S -12 (vuelve al primer NOP)
+    let (mut cpu, mut bus) = setup(&[0x4E71, 0x4E71, 0x4E71, 0x4E71, 0x3080, 0x60F4]);
+    cpu.model = Model::M68020;
+    cpu.cacr = 0x01;
+    cpu.a[0] = CODE + 4;
+    cpu.d[0] = 0x4E71;
+    for _ in 0..6 {
+        cpu.step(&mut bus);
+    }
+    // Segunda vuelta: la primera línea sigue en caché...
+    assert_eq!(cpu.step(&mut bus), 4 - icache::HIT_SAVING);
+    assert_eq!(cpu.step(&mut bus), 4 - icache::HIT_SAVING);
+    // ...pero el MOVE escribió en la segunda: vuelve a fallar
+    assert_eq!(cpu.step(&mut bus), 4);
+
+    let (mut cpu, mut bus) = setup(&[0x4E71, 0x60FC]);
+    cpu.cacr = 0x01; // 68000: no hay caché
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.step(&mut bus), 4);
+}
+
+// ============================================================================
+// Regiones de 8 bits (Lanes)
+// ============================================================================
+
+/// RAM de 16 bits con un periférico de 8 bits en $F000-$F0FF.
+struct LanesBus(TestBus);
+impl MemoryBus for LanesBus {
+    fn read(&self, addr: u32) -> u8 { self.0.read(addr) }
+    fn write(&mut self, addr: u32, val: u8) { self.0.write(addr, val) }
+    fn lanes(&self, addr: u32) -> oxide_core::Lanes {
+        if (0xF000..=0xF0FF).contains(&(addr & 0xFFFF)) {
+            oxide_core::Lanes::Byte
+        } else {
+            oxide_core::Lanes::Word
+        }
+    }
+}
+
+#[test]
+fn test_word_access_to_8bit_region_duplicates_byte_lane() {
+    // MOVE.W (A0),D0 ; MOVE.L (A0),D1 ; MOVE.W D2,(A1)
+    let (mut cpu, bus) = setup(&[0x3010, 0x2210, 0x3282]);
+    let mut bus = LanesBus(bus);
+    bus.write(0xF000, 0x5A);
+    bus.write(0xF001, 0x11);
+    bus.write(0xF002, 0xC3);
+    cpu.a[0] = 0xF000;
+    cpu.a[1] = 0xF010;
+    cpu.d[2] = 0xBEEF;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[0] & 0xFFFF, 0x5A5A);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.d[1], 0x5A5A_C3C3);
+    cpu.step(&mut bus);
+    // El periférico solo recibe el byte alto
+    assert_eq!(bus.read(0xF010), 0xBE);
+    assert_eq!(bus.read(0xF011), 0x00);
+}
+
+// ============================================================================
+// Desensamblador
+// ============================================================================
+
+/// Desensambla la instrucción cargada en CODE.
+fn dis(words: &[u16]) -> (String, u32) {
+    let (_, bus) = setup(words);
+    disasm::disassemble(CODE, &bus)
+}
+
+#[test]
+fn test_disassemble_moves_and_effective_addresses() {
+    let cases: [(&[u16], &str, u32); 9] = [
+        (&[0x3218], "MOVE.W (A0)+,D1", 2),
+        (&[0x2350, 0x0010], "MOVE.L (A0),$10(A1)", 4),
+        (&[0x13BC, 0x0042, 0x2000], "MOVE.B #$42,$0(A1,D2.W)", 6),
+        (&[0x3031, 0x28FE], "MOVE.W -$2(A1,D2.L),D0", 4),
+        (&[0x2079, 0x0040, 0x0000], "MOVEA.L $00400000.L,A0", 6),
+        (&[0x203A, 0x0010], "MOVE.L $1012(PC),D0", 4),
+        (&[0x2F00], "MOVE.L D0,-(A7)", 2),
+        (&[0x70FF], "MOVEQ #-$1,D0", 2),
+        (&[0x41F8, 0x0400], "LEA $0400.W,A0", 4),
+    ];
+    for (words, text, len) in cases {
+        assert_eq!(dis(words), (text.to_string(), len), "{:04X?}", words);
+    }
+}
+
+#[test]
+fn test_disassemble_common_groups() {
+    let cases: [(&[u16], &str, u32); 14] = [
+        (&[0x0640, 0x1234], "ADDI.W #$1234,D0", 4),
+        (&[0x0C81, 0x0000, 0x0001], "CMPI.L #$00000001,D1", 6),
+        (&[0x027C, 0xF8FF], "ANDI #$F8FF,SR", 4),
+        (&[0x0839, 0x0003, 0x00EF, 0xE1FE], "BTST #3,$00EFE1FE.L", 8),
+        (&[0x01D0], "BSET D0,(A0)", 2),
+        (&[0xD241], "ADD.W D1,D1", 2),
+        (&[0x9190], "SUB.L D0,(A0)", 2),
+        (&[0x5388], "SUBQ.L #1,A0", 2),
+        (&[0x66FC], "BNE.S $FFE", 2),
+        (&[0x6100, 0x0100], "BSR.W $1102", 4),
+        (&[0x51C8, 0xFFFE], "DBF D0,$1000", 4),
+        (&[0x4EB9, 0x0040, 0x1000], "JSR $00401000.L", 6),
+        (&[0x48E7, 0xC0C0], "MOVEM.L D0-D1/A0-A1,-(A7)", 4),
+        (&[0x4CDF, 0x0303], "MOVEM.L (A7)+,D0-D1/A0-A1", 4),
+    ];
+    for (words, text, len) in cases {
+        assert_eq!(dis(words), (text.to_string(), len), "{:04X?}", words);
+    }
+    assert_eq!(dis(&[0x4E75]).0, "RTS");
+    assert_eq!(dis(&[0xE348]).0, "LSL.W #1,D0");
+}
+
+#[test]
+fn test_disassemble_unknown_words_as_dc_w() {
+    // Line A, Line F y un modo de direccionamiento inexistente
+    for word in [0xA9F0u16, 0xF000, 0x303D] {
+        assert_eq!(dis(&[word]), (format!("DC.W ${:04X}", word), 2));
+    }
+    // Sirve como desensamblador para los listados de oxide-core
+    let (_, bus) = setup(&[0x4E71, 0x4E75]);
+    let mut out = Vec::new();
+    oxide_core::write_listing(&mut out, &bus, CODE, CODE + 2, disasm::disassemble).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "1000: 4E 71        NOP\n1002: 4E 75        RTS\n");
+}
+
+#[test]
+fn test_disassemble_with_symbols_and_mac_traps() {
+    let symbols = disasm::Symbols {
+        addresses: oxide_core::SymbolTable::parse("402 InitVideo\n1102 DrawMenu\nFFFFFFF0 Scratch\n").unwrap(),
+        traps: oxide_core::SymbolTable::parse("A9F0 _LoadSeg\nA11E _NewPtr\n").unwrap(),
+    };
+    let cases: [(&[u16], &str); 9] = [
+        (&[0x4EB9, 0x0000, 0x0402], "JSR InitVideo"),
+        (&[0x6100, 0x0100], "BSR.W DrawMenu"),
+        (&[0x4EBA, 0x0100], "JSR DrawMenu(PC)"),
+        (&[0x4278, 0xFFF0], "CLR.W Scratch.W"),
+        (&[0x66FC], "BNE.S $FFE"),
+        (&[0xA9F0], "_LoadSeg"),
+        (&[0xADF0], "_LoadSeg"), // Toolbox con auto-pop
+        (&[0xA51E], "_NewPtr"),  // OS con ,SYS
+        (&[0xA000], "DC.W $A000"),
+    ];
+    for (words, text) in cases {
+        let (_, bus) = setup(words);
+        assert_eq!(disasm::disassemble_with(CODE, &bus, &symbols).0, text, "{:04X?}", words);
+    }
+}
+
+// ============================================================================
+// Trace (bit T)
+// ============================================================================
+
+#[test]
+fn test_trace_bit_takes_vector_9_after_instruction() {
+    let (mut cpu, mut bus) = setup(&[0x4E71, 0x4E71]); // NOP ; NOP
+    bus.write_u32_be(9 * 4, 0x2000);
+    cpu.set_sr(0xA700); // S + T
+    cpu.ssp = 0x8000;
+    cpu.a[7] = 0x8000;
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x2000, "el NOP se ejecuta y salta al manejador de trace");
+    assert!(!cpu.sr.trace && cpu.sr.supervisor);
+    assert_eq!(cpu.a[7], 0x8000 - 6);
+    assert_eq!(bus.read_u16_be(cpu.a[7]), 0xA700); // SR con T
+    assert_eq!(bus.read_u32_be(cpu.a[7] + 2), CODE + 2); // siguiente instrucción
+
+    // Sin T no hay trace
+    let (mut cpu, mut bus) = setup(&[0x4E71]);
+    cpu.set_sr(0x2700);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, CODE + 2);
+}
+
+#[test]
+fn test_trace_not_taken_after_illegal_or_privileged_instruction() {
+    // MOVES ($0E00, ilegal en este núcleo) con T: solo vector 4, sin trace encima
+    let (mut cpu, mut bus) = setup(&[0x0E00]);
+    bus.write_u32_be(4 * 4, 0x3000);
+    bus.write_u32_be(9 * 4, 0x2000);
+    cpu.set_sr(0xA700);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x3000);
+    assert_eq!(cpu.a[7], 0x8000 - 6);
+
+    // STOP en modo usuario con T: violación de privilegio, sin trace
+    let (mut cpu, mut bus) = setup(&[0x4E72, 0x2700]);
+    bus.write_u32_be(8 * 4, 0x3000);
+    bus.write_u32_be(9 * 4, 0x2000);
+    cpu.set_sr(0x8000);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x3000);
+
+    // Line A con T
+    let (mut cpu, mut bus) = setup(&[0xA9F0]);
+    bus.write_u32_be(10 * 4, 0x3000);
+    bus.write_u32_be(9 * 4, 0x2000);
+    cpu.set_sr(0xA700);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x3000);
+
+    // TRAP #0 sí lleva trace tras su excepción
+    let (mut cpu, mut bus) = setup(&[0x4E40]);
+    bus.write_u32_be(32 * 4, 0x3000);
+    bus.write_u32_be(9 * 4, 0x2000);
+    cpu.set_sr(0xA700);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x2000);
+}
+
+#[test]
+fn test_trace_does_not_fire_after_stop_or_rte() {
+    // STOP #$A700: queda esperando interrupción con T puesto
+    let (mut cpu, mut bus) = setup(&[0x4E72, 0xA700]);
+    bus.write_u32_be(9 * 4, 0x2000);
+    cpu.set_sr(0x2700);
+    cpu.step(&mut bus);
+    assert!(cpu.stopped && cpu.sr.trace);
+    assert_eq!(cpu.pc, CODE + 4);
+    assert_eq!(cpu.step(&mut bus), 4);
+    assert_eq!(cpu.pc, CODE + 4);
+
+    // RTE con T: vuelve al PC apilado sin trace
+    let (mut cpu, mut bus) = setup(&[0x4E73]);
+    bus.write_u32_be(9 * 4, 0x2000);
+    cpu.set_sr(0xA700);
+    cpu.a[7] = 0x7000;
+    bus.write_u16_be(0x7000, 0x2700);
+    bus.write_u32_be(0x7002, 0x3000);
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x3000);
+}
+
+// ============================================================================
+// Cola de prefetch
+// ============================================================================
+
+#[cfg(feature = "prefetch")]
+#[test]
+fn test_prefetch_queue_refill_timing() {
+    // NOP ; NOP ; BRA.S +2 ; (hueco) ; NOP
+    let (mut cpu, mut bus) = setup(&[0x4E71, 0x4E71, 0x6002, 0x0000, 0x4E71]);
+    assert_eq!(cpu.step(&mut bus), 4);
+    assert_eq!(cpu.step(&mut bus), 4);
+    // El salto rellena dentro de sus 10 ciclos: el destino no paga nada
+    assert_eq!(cpu.step(&mut bus), 10);
+    assert_eq!(cpu.pc, CODE + 8);
+    assert_eq!(cpu.step(&mut bus), 4);
+
+    // PC puesto desde fuera (depurador): la siguiente instrucción rellena la cola
+    cpu.pc = CODE;
+    assert_eq!(cpu.step(&mut bus), 4 + 2 * prefetch::REFILL_CYCLES);
+    assert_eq!(cpu.step(&mut bus), 4);
+}
+
+#[test]
+fn test_prefetch_queue_hides_write_to_next_instruction() {
+    // MOVE.W D0,(A0) pisa la instrucción siguiente (NOP) con MOVEQ #1,D1
+    let (mut cpu, mut bus) = setup(&[0x3080, 0x4E71]);
+    cpu.a[0] = CODE + 2;
+    cpu.d[0] = 0x7201;
+    cpu.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(bus.read_u16_be(CODE + 2), 0x7201);
+    // Con cola ya estaba leída la palabra vieja; sin ella se ejecuta la nueva
+    let expected = if cfg!(feature = "prefetch") { 0 } else { 1 };
+    assert_eq!(cpu.d[1], expected);
+}
+
+#[test]
+fn test_state_snapshot_round_trip() {
+    // MOVEQ #1,D0 ; MOVEQ #2,D0
+    let (mut cpu, mut bus) = setup(&[0x7001, 0x7002]);
+    cpu.d[3] = 0xDEADBEEF;
+    cpu.a[2] = 0x00FF0000;
+    cpu.usp = 0x1234;
+    cpu.sr.zero = true;
+    cpu.step(&mut bus);
+    cpu.pending_int = Some(3);
+    let snapshot = cpu.save_state();
+
+    let mut other = Oxid68k::new();
+    other.load_state(&snapshot).unwrap();
+    assert_eq!(other.save_state(), snapshot);
+    assert_eq!((other.d, other.a, other.pc), (cpu.d, cpu.a, cpu.pc));
+    assert_eq!(other.sr.to_u16(), cpu.sr.to_u16());
+    assert_eq!((other.usp, other.ssp, other.cycles), (cpu.usp, cpu.ssp, cpu.cycles));
+    assert_eq!(other.pending_int, Some(3));
+
+    // Ejecutar desde la copia da el mismo resultado (los ciclos pueden diferir con
+    // `prefetch`: la copia empieza con la cola vacía)
+    other.pending_int = None;
+    cpu.pending_int = None;
+    other.step(&mut bus);
+    cpu.step(&mut bus);
+    assert_eq!(other.d[0], 2);
+    assert_eq!((other.d, other.a, other.pc), (cpu.d, cpu.a, cpu.pc));
+}
+
+#[test]
+fn test_state_snapshot_rejects_foreign_or_corrupt_data() {
+    let cpu = Oxid68k::new();
+    let snapshot = cpu.save_state();
+    let mut target = Oxid68k::new();
+    target.d[0] = 0x55;
+
+    let mut old = snapshot.clone();
+    old[4] = 0;
+    assert_eq!(target.load_state(&old), Err(StateError::Version { found: 0, expected: 1 }));
+    assert_eq!(target.load_state(b"OZ80\x01"), Err(StateError::BadMagic("68000")));
+    assert_eq!(target.load_state(&snapshot[..snapshot.len() - 1]), Err(StateError::Truncated));
+    let mut long = snapshot.clone();
+    long.push(0);
+    assert_eq!(target.load_state(&long), Err(StateError::TrailingBytes));
+    // Un fallo no deja la CPU a medias
+    assert_eq!(target.d[0], 0x55);
+}
+
+#[test]
+fn test_state_snapshot_rejects_model_not_compiled_in() {
+    let mut snapshot = Oxid68k::new().save_state();
+    // El modelo va justo antes de VBR/SFC/DFC y CACR/CAAR (14 bytes)
+    let model = snapshot.len() - 15;
+    let mut target = Oxid68k::new();
+    for (byte, supported) in [(1, cfg!(feature = "cpu_010")), (2, cfg!(feature = "cpu_020")), (3, false)] {
+        snapshot[model] = byte;
+        let result = target.load_state(&snapshot);
+        if supported {
+            assert_eq!(result, Ok(()));
+        } else {
+            assert_eq!(result, Err(StateError::InvalidField("model")));
+            assert_eq!(target.model, Model::M68000);
+        }
+        target.model = Model::M68000;
+    }
+}
+
+#[test]
+fn test_call_return_address_for_jsr_and_bsr() {
+    // JSR $1234.L ; BSR.S +4 ; BSR.W +$100 ; JSR (A0) ; MOVEQ #0,D0
+    let (mut cpu, bus) = setup(&[0x4EB9, 0x0000, 0x1234, 0x6104, 0x6100, 0x0100, 0x4E90, 0x7000]);
+    let mut rets = Vec::new();
+    for _ in 0..5 {
+        rets.push(cpu.call_return_address(&bus));
+        let (_, len) = disasm::disassemble(cpu.pc, &bus);
+        cpu.pc += len;
+    }
+    assert_eq!(
+        rets,
+        [Some(CODE + 6), Some(CODE + 8), Some(CODE + 12), Some(CODE + 14), None]
+    );
+}
@@ -0,0 +1,41 @@
+// crates/oxid68k/src/vectors.rs
+//! Tabla de vectores de excepción (256 entradas de 32 bits desde VBR; 0 en el 68000).
+//! `dump_vector_table` la lee a través del bus y la etiqueta para depurar a qué
+//! manejadores apunta el sistema (p. ej. durante el arranque del Mac).
+
+use oxide_core::MemoryBus;
+
+/// Nombre del vector `n` según el manual del 68000.
+pub fn vector_name(n: u8) -> String {
+    let name = match n {
+        0 => "Reset SSP",
+        1 => "Reset PC",
+        2 => "Bus Error",
+        3 => "Address Error",
+        4 => "Illegal Instruction",
+        5 => "Zero Divide",
+        6 => "CHK",
+        7 => "TRAPV",
+        8 => "Privilege Violation",
+        9 => "Trace",
+        10 => "Line 1010 (A-Trap)",
+        11 => "Line 1111 (F-Trap)",
+        15 => "Uninitialized Interrupt",
+        24 => "Spurious Interrupt",
+        25..=31 => return format!("Level {} Autovector", n - 24),
+        32..=47 => return format!("TRAP #{}", n - 32),
+        12..=14 | 16..=23 | 48..=63 => "Reserved",
+        _ => "User Interrupt",
+    };
+    name.to_string()
+}
+
+/// Una línea por vector: número, dirección de la entrada, destino y nombre.
+pub fn dump_vector_table(bus: &dyn MemoryBus, base: u32) -> String {
+    (0..=255u8)
+        .map(|n| {
+            let addr = base.wrapping_add(n as u32 * 4);
+            format!("{:3} ${:06X}  {:08X}  {}\n", n, addr, bus.read_u32_be(addr), vector_name(n))
+        })
+        .collect()
+}
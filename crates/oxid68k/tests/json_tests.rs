@@ -0,0 +1,155 @@
+// Conformidad instrucción a instrucción con tests estilo SingleStepTests (680x0).
+//
+// Cada caso trae el estado inicial y final (registros, SR, pila USP/SSP, RAM).
+// Convención de esos tests: `pc` apunta DESPUÉS de la cola de prefetch (dirección
+// de la instrucción + 4) y `prefetch` lleva las dos palabras ya leídas. Aquí no
+// emulamos la cola: escribimos esas palabras en pc-4/pc-2 y ejecutamos desde pc-4.
+use oxid68k::Oxid68k;
+use oxide_core::{Cpu, MemoryBus};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+#[derive(Deserialize, Debug)]
+struct TestState {
+    d0: u32, d1: u32, d2: u32, d3: u32, d4: u32, d5: u32, d6: u32, d7: u32,
+    a0: u32, a1: u32, a2: u32, a3: u32, a4: u32, a5: u32, a6: u32,
+    usp: u32,
+    ssp: u32,
+    sr: u16,
+    pc: u32,
+    prefetch: [u16; 2],
+    ram: Vec<(u32, u8)>,
+}
+
+impl TestState {
+    fn d(&self) -> [u32; 8] {
+        [self.d0, self.d1, self.d2, self.d3, self.d4, self.d5, self.d6, self.d7]
+    }
+    fn a(&self) -> [u32; 7] {
+        [self.a0, self.a1, self.a2, self.a3, self.a4, self.a5, self.a6]
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct TestCase {
+    name: String,
+    initial: TestState,
+    #[serde(rename = "final")]
+    final_state: TestState,
+    #[serde(rename = "length")]
+    _length: u32, // Ciclos totales - no se comparan todavía
+}
+
+/// Bus de 24 bits disperso: solo existen las direcciones que toca el test (resto = 0).
+struct TestBus {
+    memory: HashMap<u32, u8>,
+}
+
+impl TestBus {
+    fn new(state: &TestState) -> Self {
+        let mut bus = Self { memory: HashMap::new() };
+        for &(addr, val) in &state.ram {
+            bus.write(addr, val);
+        }
+        let start = state.pc.wrapping_sub(4);
+        bus.write_u16_be(start, state.prefetch[0]);
+        bus.write_u16_be(start.wrapping_add(2), state.prefetch[1]);
+        bus
+    }
+}
+
+impl MemoryBus for TestBus {
+    fn read(&self, addr: u32) -> u8 {
+        self.memory.get(&(addr & 0xFFFFFF)).copied().unwrap_or(0)
+    }
+
+    fn write(&mut self, addr: u32, value: u8) {
+        self.memory.insert(addr & 0xFFFFFF, value);
+    }
+}
+
+const TESTS_DIR: &str = "../../tests/m68k_json_tests";
+/// Cuántos fallos detallar antes de resumir
+const MAX_REPORTED: usize = 20;
+
+#[test]
+#[ignore] // Start ignored until test files are present
+fn run_m68k_json_tests() {
+    let path = Path::new(TESTS_DIR);
+    if !path.exists() {
+        println!("Test directory not found: {:?}", path);
+        return;
+    }
+
+    let mut total_tests = 0;
+    let mut failures = Vec::new();
+
+    for entry in std::fs::read_dir(path).expect("Read dir failed") {
+        let path = entry.expect("Entry failed").path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        println!("Running tests from {:?}", path.file_name().unwrap());
+        let reader = BufReader::new(File::open(&path).expect("File open failed"));
+        let tests: Vec<TestCase> = serde_json::from_reader(reader).expect("JSON parse failed");
+
+        for test in tests {
+            total_tests += 1;
+            let diffs = run_single_test(&test);
+            if !diffs.is_empty() {
+                failures.push((test.name, diffs));
+            }
+        }
+    }
+
+    for (name, diffs) in failures.iter().take(MAX_REPORTED) {
+        println!("FAIL {}:\n  {}", name, diffs.join("\n  "));
+    }
+    println!("Passed {} / {} tests", total_tests - failures.len(), total_tests);
+    assert!(failures.is_empty(), "{} of {} tests failed", failures.len(), total_tests);
+}
+
+/// Ejecuta un caso y devuelve la lista de diferencias (vacía = OK).
+fn run_single_test(test: &TestCase) -> Vec<String> {
+    let init = &test.initial;
+    let mut bus = TestBus::new(init);
+
+    let mut cpu = Oxid68k::new();
+    cpu.d = init.d();
+    cpu.a[..7].copy_from_slice(&init.a());
+    cpu.sr.from_u16(init.sr);
+    cpu.usp = init.usp;
+    cpu.ssp = init.ssp;
+    cpu.a[7] = if cpu.sr.supervisor { init.ssp } else { init.usp };
+    cpu.pc = init.pc.wrapping_sub(4);
+
+    cpu.step(&mut bus);
+
+    // Mismo reparto de A7 que al cargar: el registro activo vive en a[7]
+    let fin = &test.final_state;
+    let (usp, ssp) = if cpu.sr.supervisor { (cpu.usp, cpu.a[7]) } else { (cpu.a[7], cpu.ssp) };
+
+    let mut diffs = Vec::new();
+    let mut check = |what: String, got: u32, want: u32| {
+        if got != want {
+            diffs.push(format!("{}: {:08X} != {:08X}", what, got, want));
+        }
+    };
+    for (i, (&got, want)) in cpu.d.iter().zip(fin.d()).enumerate() {
+        check(format!("D{}", i), got, want);
+    }
+    for (i, (&got, want)) in cpu.a.iter().zip(fin.a()).enumerate() {
+        check(format!("A{}", i), got, want);
+    }
+    check("USP".into(), usp, fin.usp);
+    check("SSP".into(), ssp, fin.ssp);
+    check("SR".into(), cpu.sr.to_u16() as u32, fin.sr as u32);
+    check("PC".into(), cpu.pc, fin.pc.wrapping_sub(4) & 0xFFFFFF);
+    for &(addr, val) in &fin.ram {
+        check(format!("RAM[{:06X}]", addr), bus.read(addr) as u32, val as u32);
+    }
+    diffs
+}
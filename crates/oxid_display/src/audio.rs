@@ -0,0 +1,184 @@
+// crates/oxid_display/src/audio.rs
+//
+// Salida de audio: ring buffer compartido entre el emulador (productor, una
+// ráfaga por frame) y el callback del dispositivo (consumidor, en el hilo de
+// audio). Con la feature `audio`, `OxidAudio` abre la salida por defecto con cpal.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Capacidad del ring en segundos: más es latencia, menos son cortes
+const RING_SECONDS: f64 = 0.25;
+
+/// Cola de muestras PCM i16 (canales intercalados). Los clones comparten la cola.
+#[derive(Clone)]
+pub struct AudioRing {
+    buf: Arc<Mutex<VecDeque<i16>>>,
+    capacity: usize,
+}
+
+impl AudioRing {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self { buf: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))), capacity }
+    }
+
+    /// Ring de `RING_SECONDS` para la frecuencia y canales dados.
+    pub fn for_stream(sample_rate: u32, channels: u16) -> Self {
+        Self::new((sample_rate as f64 * channels as f64 * RING_SECONDS) as usize)
+    }
+
+    /// El callback de audio no debe entrar en pánico: un lock envenenado se reutiliza tal cual
+    fn lock(&self) -> MutexGuard<'_, VecDeque<i16>> {
+        self.buf.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Encola muestras. Si no caben se descartan las más viejas: la latencia
+    /// queda acotada aunque el emulador vaya por delante del dispositivo.
+    pub fn push(&self, samples: &[i16]) {
+        let samples = &samples[samples.len().saturating_sub(self.capacity)..];
+        let mut buf = self.lock();
+        let excess = (buf.len() + samples.len()).saturating_sub(self.capacity);
+        buf.drain(..excess);
+        buf.extend(samples);
+    }
+
+    /// Rellena `out` desde la cola; lo que falte (underrun) sale en silencio.
+    /// Devuelve cuántas muestras venían de la cola.
+    pub fn fill(&self, out: &mut [i16]) -> usize {
+        let mut buf = self.lock();
+        let n = buf.len().min(out.len());
+        for (dst, src) in out.iter_mut().zip(buf.drain(..n)) {
+            *dst = src;
+        }
+        out[n..].fill(0);
+        n
+    }
+
+    /// Muestras en cola (todas las de todos los canales)
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+#[cfg(feature = "audio")]
+pub use device::{AudioError, OxidAudio};
+
+#[cfg(feature = "audio")]
+mod device {
+    use super::AudioRing;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::{SampleFormat, Stream, StreamConfig};
+    use std::fmt;
+
+    #[derive(Debug)]
+    pub enum AudioError {
+        /// El host no tiene salida de audio por defecto
+        NoDevice,
+        /// La salida no acepta i16 ni f32
+        UnsupportedFormat(String),
+        /// cpal no pudo abrir o arrancar el stream
+        Stream(String),
+    }
+
+    impl fmt::Display for AudioError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                AudioError::NoDevice => write!(f, "no default audio output device"),
+                AudioError::UnsupportedFormat(s) => write!(f, "unsupported sample format: {}", s),
+                AudioError::Stream(s) => write!(f, "audio stream error: {}", s),
+            }
+        }
+    }
+
+    impl std::error::Error for AudioError {}
+
+    /// Salida de audio por defecto del sistema. El stream vive mientras viva el struct.
+    pub struct OxidAudio {
+        ring: AudioRing,
+        sample_rate: u32,
+        channels: u16,
+        _stream: Stream,
+    }
+
+    impl OxidAudio {
+        /// Abre la salida por defecto a `sample_rate` Hz con `channels` canales intercalados.
+        pub fn new(sample_rate: u32, channels: u16) -> Result<Self, AudioError> {
+            let device = cpal::default_host().default_output_device().ok_or(AudioError::NoDevice)?;
+            let format = device
+                .default_output_config()
+                .map_err(|e| AudioError::Stream(e.to_string()))?
+                .sample_format();
+            let config = StreamConfig {
+                channels,
+                sample_rate: cpal::SampleRate(sample_rate),
+                buffer_size: cpal::BufferSize::Default,
+            };
+            let ring = AudioRing::for_stream(sample_rate, channels);
+            let feed = ring.clone();
+            let on_error = |e| eprintln!("Audio Error: {}", e);
+            let stream = match format {
+                SampleFormat::I16 => device.build_output_stream(
+                    &config,
+                    move |out: &mut [i16], _| {
+                        feed.fill(out);
+                    },
+                    on_error,
+                    None,
+                ),
+                // Muchos backends solo mezclan en f32: se convierte en el callback
+                SampleFormat::F32 => {
+                    let mut scratch = Vec::new();
+                    device.build_output_stream(
+                        &config,
+                        move |out: &mut [f32], _| {
+                            scratch.resize(out.len(), 0i16);
+                            feed.fill(&mut scratch);
+                            for (dst, &src) in out.iter_mut().zip(&scratch) {
+                                *dst = src as f32 / 32768.0;
+                            }
+                        },
+                        on_error,
+                        None,
+                    )
+                }
+                other => return Err(AudioError::UnsupportedFormat(other.to_string())),
+            }
+            .map_err(|e| AudioError::Stream(e.to_string()))?;
+            stream.play().map_err(|e| AudioError::Stream(e.to_string()))?;
+            Ok(Self { ring, sample_rate, channels, _stream: stream })
+        }
+
+        /// Encola muestras (canales intercalados) para el dispositivo.
+        pub fn push_samples(&mut self, samples: &[i16]) {
+            self.ring.push(samples);
+        }
+
+        /// Muestras aún sin reproducir: el bucle del emulador puede frenar si crece.
+        pub fn queued_samples(&self) -> usize {
+            self.ring.len()
+        }
+
+        pub fn sample_rate(&self) -> u32 {
+            self.sample_rate
+        }
+
+        pub fn channels(&self) -> u16 {
+            self.channels
+        }
+    }
+
+    impl oxide_core::AudioSink for OxidAudio {
+        fn push_samples(&mut self, samples: &[i16]) {
+            OxidAudio::push_samples(self, samples);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// crates/oxid_display/src/filter.rs
+
+/// Filtro de escalado aplicado al buffer nativo antes de presentarlo.
+/// Los filtros no-Nearest duplican la resolución (2x) y la ventana lo estira.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleFilter {
+    /// Píxel a píxel (camino rápido, sin copia)
+    #[default]
+    Nearest,
+    /// Interpolación lineal 2x (suaviza)
+    Bilinear,
+    /// Scale2x / EPX: redondea diagonales sin difuminar el pixel-art
+    Scale2x,
+}
+
+/// Scale2x (EPX). `dst` debe tener `(w*2) * (h*2)` píxeles.
+pub fn scale2x(src: &[u32], w: usize, h: usize, dst: &mut [u32]) {
+    let dw = w * 2;
+    for y in 0..h {
+        for x in 0..w {
+            let p = src[y * w + x];
+            let a = src[y.saturating_sub(1) * w + x];
+            let d = src[(y + 1).min(h - 1) * w + x];
+            let c = src[y * w + x.saturating_sub(1)];
+            let b = src[y * w + (x + 1).min(w - 1)];
+
+            let e0 = if c == a && c != d && a != b { a } else { p };
+            let e1 = if a == b && a != c && b != d { b } else { p };
+            let e2 = if d == c && d != b && c != a { c } else { p };
+            let e3 = if b == d && b != a && d != c { d } else { p };
+
+            let o = (y * 2) * dw + x * 2;
+            dst[o] = e0;
+            dst[o + 1] = e1;
+            dst[o + dw] = e2;
+            dst[o + dw + 1] = e3;
+        }
+    }
+}
+
+/// Interpolación bilineal 2x. `dst` debe tener `(w*2) * (h*2)` píxeles.
+pub fn bilinear2x(src: &[u32], w: usize, h: usize, dst: &mut [u32]) {
+    let dw = w * 2;
+    for y in 0..h {
+        for x in 0..w {
+            let p = src[y * w + x];
+            let r = src[y * w + (x + 1).min(w - 1)];
+            let d = src[(y + 1).min(h - 1) * w + x];
+            let rd = src[(y + 1).min(h - 1) * w + (x + 1).min(w - 1)];
+
+            let o = (y * 2) * dw + x * 2;
+            dst[o] = p;
+            dst[o + 1] = blend(&[p, r]);
+            dst[o + dw] = blend(&[p, d]);
+            dst[o + dw + 1] = blend(&[p, r, d, rd]);
+        }
+    }
+}
+
+/// Promedio por canal de varios píxeles 0xAARRGGBB.
+fn blend(px: &[u32]) -> u32 {
+    let n = px.len() as u32;
+    let mut acc = [0u32; 4];
+    for &p in px {
+        for (i, a) in acc.iter_mut().enumerate() {
+            *a += (p >> (i * 8)) & 0xFF;
+        }
+    }
+    acc.iter().enumerate().fold(0, |v, (i, &a)| v | ((a / n) << (i * 8)))
+}
@@ -0,0 +1,145 @@
+// crates/oxid_display/src/gif.rs
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Grabador de GIF animado (GIF89a) sin dependencias externas.
+/// Cada frame se cuantiza a una paleta fija 3-3-2 (256 colores) y se
+/// escribe en el momento, así que la memoria no crece con la duración.
+pub struct GifRecorder<W: Write> {
+    out: W,
+    width: usize,
+    height: usize,
+    /// Retardo entre frames en centésimas de segundo
+    delay_cs: u16,
+    pub frames: usize,
+}
+
+impl<W: Write> GifRecorder<W> {
+    /// Escribe la cabecera y la paleta global. `fps` = cuadros por segundo del GIF.
+    pub fn new(mut out: W, width: usize, height: usize, fps: f64) -> io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&(width as u16).to_le_bytes())?;
+        out.write_all(&(height as u16).to_le_bytes())?;
+        out.write_all(&[0xF7, 0, 0])?; // GCT presente, 256 entradas
+        for i in 0..=255u8 {
+            let r = ((i >> 5) & 7) as u16;
+            let g = ((i >> 2) & 7) as u16;
+            let b = (i & 3) as u16;
+            out.write_all(&[(r * 255 / 7) as u8, (g * 255 / 7) as u8, (b * 255 / 3) as u8])?;
+        }
+        // NETSCAPE2.0: bucle infinito
+        out.write_all(&[0x21, 0xFF, 0x0B])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+
+        let delay_cs = if fps > 0.0 { (100.0 / fps).round().max(1.0) as u16 } else { 2 };
+        Ok(Self { out, width, height, delay_cs, frames: 0 })
+    }
+
+    /// Añade un frame en formato 0x00RRGGBB (mismo tamaño que el GIF).
+    pub fn add_frame(&mut self, buffer: &[u32]) -> io::Result<()> {
+        let indices: Vec<u8> = buffer[..self.width * self.height]
+            .iter()
+            .map(|&p| quantize(p))
+            .collect();
+
+        // Graphic Control Extension (retardo)
+        self.out.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+        self.out.write_all(&self.delay_cs.to_le_bytes())?;
+        self.out.write_all(&[0x00, 0x00])?;
+
+        // Image Descriptor (frame completo, sin paleta local)
+        self.out.write_all(&[0x2C, 0, 0, 0, 0])?;
+        self.out.write_all(&(self.width as u16).to_le_bytes())?;
+        self.out.write_all(&(self.height as u16).to_le_bytes())?;
+        self.out.write_all(&[0x00])?;
+
+        self.out.write_all(&[8])?; // LZW minimum code size
+        let data = lzw_encode(&indices);
+        for chunk in data.chunks(255) {
+            self.out.write_all(&[chunk.len() as u8])?;
+            self.out.write_all(chunk)?;
+        }
+        self.out.write_all(&[0x00])?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Escribe el trailer y devuelve el destino.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3B])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// 0x00RRGGBB -> índice 3-3-2.
+fn quantize(p: u32) -> u8 {
+    let r = ((p >> 16) & 0xFF) as u8;
+    let g = ((p >> 8) & 0xFF) as u8;
+    let b = (p & 0xFF) as u8;
+    (r & 0xE0) | ((g >> 3) & 0x1C) | (b >> 6)
+}
+
+/// Compresión LZW de GIF (códigos de ancho variable, LSB primero), min code size 8.
+fn lzw_encode(data: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+
+    let mut out = Vec::new();
+    let mut acc: u32 = 0;
+    let mut nbits = 0u32;
+    let mut emit = |code: u16, width: u32, out: &mut Vec<u8>| {
+        acc |= (code as u32) << nbits;
+        nbits += width;
+        while nbits >= 8 {
+            out.push(acc as u8);
+            acc >>= 8;
+            nbits -= 8;
+        }
+    };
+
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = 258u16;
+    let mut width = 9u32;
+    emit(CLEAR, width, &mut out);
+
+    let mut iter = data.iter();
+    let mut prefix = match iter.next() {
+        Some(&b) => b as u16,
+        None => {
+            emit(END, width, &mut out);
+            if nbits > 0 { out.push(acc as u8); }
+            return out;
+        }
+    };
+
+    for &b in iter {
+        if let Some(&code) = dict.get(&(prefix, b)) {
+            prefix = code;
+            continue;
+        }
+        emit(prefix, width, &mut out);
+        if next < 4096 {
+            dict.insert((prefix, b), next);
+            // El decodificador amplía el ancho un código más tarde que el codificador
+            if next == (1 << width) && width < 12 {
+                width += 1;
+            }
+            next += 1;
+        } else {
+            emit(CLEAR, width, &mut out);
+            dict.clear();
+            next = 258;
+            width = 9;
+        }
+        prefix = b as u16;
+    }
+    emit(prefix, width, &mut out);
+    emit(END, width, &mut out);
+    if nbits > 0 {
+        out.push(acc as u8);
+    }
+    out
+}
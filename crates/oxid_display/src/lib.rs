@@ -1,8 +1,29 @@
 // crates/oxid_display/src/lib.rs
 
-use minifb::{Window, WindowOptions, Scale, Key, ScaleMode};
-use std::time::{Duration, Instant};
-use std::thread;
+use minifb::{Window, WindowOptions, Scale, Key, KeyRepeat, ScaleMode};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::time::Instant;
+
+mod audio;
+mod filter;
+mod gif;
+mod output;
+mod pacer;
+mod panel;
+pub mod text;
+#[cfg(test)]
+mod tests;
+
+pub use audio::AudioRing;
+#[cfg(feature = "audio")]
+pub use audio::{AudioError, OxidAudio};
+pub use filter::ScaleFilter;
+pub use gif::GifRecorder;
+pub use output::{FrameCallback, FrameOutput};
+pub use panel::DebugPanel;
+pub use pacer::{Clock, FramePacer, SystemClock};
 
 // ============================================================================
 //  CONFIGURACIÓN Y ERRORES
@@ -16,6 +37,9 @@ pub struct DisplayConfig {
     pub scale: WindowScale,
     pub target_fps: f64,
     pub resizable: bool,
+    /// Relación de aspecto de pantalla (ancho/alto) a la que estirar la imagen.
+    /// `None` = píxeles cuadrados (pixel-perfect). Ver el módulo `aspect`.
+    pub aspect: Option<f64>,
 }
 
 impl Default for DisplayConfig {
@@ -27,12 +51,13 @@ impl Default for DisplayConfig {
             scale: WindowScale::X2,
             target_fps: 60.0,
             resizable: false,
+            aspect: None,
         }
     }
 }
 
 /// Abstracción de escalas para no depender directamente de los enums de minifb fuera de esta lib
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WindowScale {
     X1, X2, X4, X8, FitScreen,
 }
@@ -49,12 +74,61 @@ impl From<WindowScale> for Scale {
     }
 }
 
+impl WindowScale {
+    /// Factor entero de la escala (FitScreen se trata como 1x).
+    pub fn factor(self) -> usize {
+        match self {
+            WindowScale::X1 | WindowScale::FitScreen => 1,
+            WindowScale::X2 => 2,
+            WindowScale::X4 => 4,
+            WindowScale::X8 => 8,
+        }
+    }
+
+    /// Siguiente escala del ciclo X1 -> X2 -> X4 -> X8 -> X1 (FitScreen vuelve a X1).
+    pub fn next(self) -> Self {
+        match self {
+            WindowScale::X1 => WindowScale::X2,
+            WindowScale::X2 => WindowScale::X4,
+            WindowScale::X4 => WindowScale::X8,
+            WindowScale::X8 | WindowScale::FitScreen => WindowScale::X1,
+        }
+    }
+}
+
+/// Relaciones de aspecto de pantalla típicas por sistema (ancho/alto del área activa en un TV).
+pub mod aspect {
+    /// TV estándar 4:3.
+    pub const TV_4_3: f64 = 4.0 / 3.0;
+    /// Master System NTSC: píxel 8:7 sobre 256x192.
+    pub const SMS_NTSC: f64 = (256.0 * 8.0 / 7.0) / 192.0;
+    /// ZX Spectrum: el área de 256x192 en un TV PAL, píxeles casi cuadrados (aprox. 4:3).
+    pub const SPECTRUM: f64 = 4.0 / 3.0;
+    // Macintosh: pantalla integrada de píxeles cuadrados -> usar `None`.
+}
+
+/// Tamaño de ventana (ancho, alto) para una imagen nativa con corrección de aspecto.
+/// Se conserva la altura escalada y se ajusta el ancho.
+pub fn aspect_window_size(width: usize, height: usize, scale: WindowScale, aspect: Option<f64>) -> (usize, usize) {
+    let f = scale.factor();
+    match aspect {
+        Some(a) => {
+            let h = height * f;
+            (((h as f64) * a).round() as usize, h)
+        }
+        None => (width * f, height * f),
+    }
+}
+
 // ============================================================================
 //  MOTOR DE DISPLAY (HOLY GRAIL ENGINE)
 // ============================================================================
 
 pub struct OxidDisplay {
     window: Window,
+    // Configuración con la que se abrió (para recrear la ventana)
+    config: DisplayConfig,
+    fullscreen: bool,
     
     // Dimensiones nativas del sistema emulado (ej. 320x224 para Genesis)
     width: usize,
@@ -62,28 +136,36 @@ pub struct OxidDisplay {
     
     // Control de Tiempo (Frame Limiter)
     target_micro_seconds: u128,
-    last_frame_time: Instant,
+    clock: SystemClock,
+    pacer: FramePacer,
+    // VSync del backend activo: cuando está, el limiter manual no corre
+    vsync: bool,
     
     // Performance stats
     pub fps: usize,
     frame_count: usize,
     last_fps_check: Instant,
+
+    // Filtro de escalado (Nearest = sin copia)
+    filter: ScaleFilter,
+    filter_buf: Vec<u32>,
+
+    // Grabación GIF: (grabador, capturar 1 de cada N frames, contador)
+    gif: Option<(GifRecorder<BufWriter<File>>, usize, usize)>,
+
+    // Callback de frames para integración / streaming
+    output: FrameOutput,
+
+    // Salida de audio (se abre aparte con `open_audio`)
+    #[cfg(feature = "audio")]
+    audio: Option<OxidAudio>,
 }
 
 impl OxidDisplay {
     /// Crea una nueva ventana lista para renderizar
     pub fn new(config: DisplayConfig) -> Self {
-        let mut opts = WindowOptions::default();
-        opts.scale = config.scale.into();
-        opts.resize = config.resizable;
-        opts.scale_mode = ScaleMode::AspectRatioStretch; // Mantiene aspect ratio al estirar
-
-        let window = Window::new(
-            &config.title,
-            config.width,
-            config.height,
-            opts,
-        ).expect("CRITICAL: No se pudo abrir la ventana de video (minifb failure)");
+        let window = open_window(&config, false)
+            .expect("CRITICAL: No se pudo abrir la ventana de video (minifb failure)");
 
         // Configurar timing
         let target_us = if config.target_fps > 0.0 {
@@ -92,45 +174,136 @@ impl OxidDisplay {
             0 // Sin límite
         };
 
+        let clock = SystemClock::new();
+        let pacer = FramePacer::new(target_us as u64, clock.now_us());
+        let output = FrameOutput::new(config.width, config.height);
+
         Self {
             window,
             width: config.width,
             height: config.height,
+            config,
+            fullscreen: false,
             target_micro_seconds: target_us,
-            last_frame_time: Instant::now(),
+            clock,
+            pacer,
+            vsync: false,
             fps: 0,
             frame_count: 0,
             last_fps_check: Instant::now(),
+            filter: ScaleFilter::Nearest,
+            filter_buf: Vec::new(),
+            gif: None,
+            output,
+            #[cfg(feature = "audio")]
+            audio: None,
+        }
+    }
+
+    /// Ventana auxiliar para un `DebugPanel` (minifb admite varias ventanas a la vez).
+    /// Se actualiza con `update(panel.render(&lineas))`.
+    pub fn open_panel(title: &str, panel: &DebugPanel) -> Self {
+        Self::new(panel.display_config(title))
+    }
+
+    /// Empieza a grabar un GIF animado a `fps` cuadros por segundo
+    /// (se toma 1 de cada N frames del emulador). Reemplaza una grabación en curso.
+    pub fn start_gif<P: AsRef<Path>>(&mut self, path: P, fps: f64) -> io::Result<()> {
+        self.stop_gif()?;
+        let rec = GifRecorder::new(BufWriter::new(File::create(path)?), self.width, self.height, fps)?;
+        let display_fps = if self.target_micro_seconds > 0 {
+            1_000_000.0 / self.target_micro_seconds as f64
+        } else {
+            60.0
+        };
+        let every = if fps > 0.0 { (display_fps / fps).round().max(1.0) as usize } else { 1 };
+        self.gif = Some((rec, every, 0));
+        Ok(())
+    }
+
+    /// Termina la grabación y cierra el archivo. No hace nada si no se estaba grabando.
+    pub fn stop_gif(&mut self) -> io::Result<()> {
+        if let Some((rec, _, _)) = self.gif.take() {
+            rec.finish()?;
+        }
+        Ok(())
+    }
+
+    /// ¿Hay una grabación GIF en curso?
+    pub fn is_recording_gif(&self) -> bool {
+        self.gif.is_some()
+    }
+
+    /// Abre la salida de audio por defecto; desde entonces `audio()` devuelve el sink.
+    #[cfg(feature = "audio")]
+    pub fn open_audio(&mut self, sample_rate: u32, channels: u16) -> Result<(), AudioError> {
+        self.audio = Some(OxidAudio::new(sample_rate, channels)?);
+        Ok(())
+    }
+
+    #[cfg(feature = "audio")]
+    pub fn audio(&mut self) -> Option<&mut OxidAudio> {
+        self.audio.as_mut()
+    }
+
+    /// Recibe cada frame (buffer nativo, ancho, alto) además de mostrarlo en la ventana.
+    pub fn set_frame_callback(&mut self, cb: FrameCallback) {
+        self.output.set_callback(cb);
+    }
+
+    pub fn clear_frame_callback(&mut self) {
+        self.output.clear_callback();
+    }
+
+    /// Selecciona el filtro de escalado. Los filtros 2x presentan un buffer
+    /// del doble de tamaño y minifb lo ajusta a la ventana.
+    pub fn set_filter(&mut self, f: ScaleFilter) {
+        self.filter = f;
+        if f != ScaleFilter::Nearest {
+            self.filter_buf.resize(self.width * self.height * 4, 0);
         }
     }
 
     /// El corazón del renderizado. Llama a esto una vez por frame del emulador.
     /// buffer: Slice de u32 en formato 0x00RRGGBB.
     pub fn update(&mut self, buffer: &[u32]) {
-        // 1. Renderizar buffer a la ventana
+        // 1. Renderizar buffer a la ventana (aplicando el filtro si hay uno)
         // minifb maneja el doble buffer internamente.
-        self.window
-            .update_with_buffer(buffer, self.width, self.height)
-            .unwrap_or_else(|e| eprintln!("Display Error: {}", e));
+        let (w, h) = (self.width, self.height);
+        let result = match self.filter {
+            ScaleFilter::Nearest => self.window.update_with_buffer(buffer, w, h),
+            ScaleFilter::Bilinear => {
+                filter::bilinear2x(buffer, w, h, &mut self.filter_buf);
+                self.window.update_with_buffer(&self.filter_buf, w * 2, h * 2)
+            }
+            ScaleFilter::Scale2x => {
+                filter::scale2x(buffer, w, h, &mut self.filter_buf);
+                self.window.update_with_buffer(&self.filter_buf, w * 2, h * 2)
+            }
+        };
+        result.unwrap_or_else(|e| eprintln!("Display Error: {}", e));
 
-        // 2. Frame Limiter (Sincronización)
-        // Dormir si el emulador va más rápido que 60Hz (o la tasa target)
-        if self.target_micro_seconds > 0 {
-            let elapsed = self.last_frame_time.elapsed().as_micros();
-            if elapsed < self.target_micro_seconds {
-                let sleep_time = self.target_micro_seconds - elapsed;
-                // Usamos spin-loop o sleep híbrido para precisión, 
-                // pero thread::sleep es 'bueno' para no quemar CPU.
-                if sleep_time > 1000 {
-                    thread::sleep(Duration::from_micros((sleep_time - 500) as u64));
-                }
-                // Spin-wait para el último microsegundo preciso
-                while self.last_frame_time.elapsed().as_micros() < self.target_micro_seconds {
-                    std::hint::spin_loop();
+        self.output.emit(buffer);
+
+        // Captura para el GIF (sobre el buffer nativo, sin filtro)
+        if let Some((rec, every, counter)) = self.gif.as_mut() {
+            if *counter % *every == 0 {
+                if let Err(e) = rec.add_frame(buffer) {
+                    eprintln!("GIF Error: {}", e);
+                    self.gif = None;
                 }
             }
+            if let Some((_, _, counter)) = self.gif.as_mut() {
+                *counter += 1;
+            }
+        }
+
+        // 2. Frame Limiter (Sincronización)
+        // Dormir si el emulador va más rápido que 60Hz (o la tasa target);
+        // el tramo final se hace con spin y su longitud se autocalibra
+        if !self.vsync {
+            self.pacer.wait(&mut self.clock);
         }
-        self.last_frame_time = Instant::now();
 
         // 3. Calcular FPS reales
         self.frame_count += 1;
@@ -144,6 +317,39 @@ impl OxidDisplay {
         }
     }
 
+    /// ¿El backend de ventana puede sincronizar con el refresco del monitor?
+    ///
+    /// | Backend | VSync |
+    /// |---------|-------|
+    /// | minifb  | no (se usa el frame limiter) |
+    pub const VSYNC_SUPPORTED: bool = false;
+
+    /// Pide VSync al backend. Si no lo soporta se ignora y sigue actuando el
+    /// frame limiter; `vsync_enabled` dice qué quedó activo.
+    pub fn set_vsync(&mut self, on: bool) {
+        self.vsync = on && Self::VSYNC_SUPPORTED;
+    }
+
+    pub fn vsync_enabled(&self) -> bool {
+        self.vsync
+    }
+
+    /// Multiplicador de velocidad del frame limiter: 1.0 = tiempo real, 4.0 = 4x.
+    /// `0.0` (o negativo) quita el límite. La grabación GIF sigue usando la tasa nominal.
+    pub fn set_speed(&mut self, multiplier: f64) {
+        let target = if multiplier > 0.0 {
+            (self.target_micro_seconds as f64 / multiplier) as u64
+        } else {
+            0
+        };
+        self.pacer.set_target_us(target);
+    }
+
+    /// Desviación media (µs) de la duración real de frame respecto al objetivo.
+    pub fn frame_jitter_us(&self) -> u64 {
+        self.pacer.jitter_us()
+    }
+
     /// Verifica si la ventana sigue abierta (para el loop principal)
     pub fn is_open(&self) -> bool {
         self.window.is_open() && !self.window.is_key_down(Key::Escape)
@@ -151,8 +357,101 @@ impl OxidDisplay {
 
     /// Cambiar título dinámicamente (ej. "Sonic 2 - 60 FPS")
     pub fn set_title(&mut self, title: &str) {
+        self.config.title = title.to_string();
         self.window.set_title(title);
     }
+
+    // ------------------------------------------------------------------------
+    //  Escala y pantalla completa
+    // ------------------------------------------------------------------------
+    // minifb fija escala y bordes al crear la ventana: cambiarlos en caliente
+    // significa recrearla. Si falla, se conserva la ventana anterior.
+
+    pub fn scale(&self) -> WindowScale {
+        self.config.scale
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    /// Cambia la escala de la ventana (sin efecto visible en pantalla completa
+    /// hasta salir de ella).
+    pub fn set_scale(&mut self, scale: WindowScale) {
+        let previous = self.config.scale;
+        self.config.scale = scale;
+        if !self.fullscreen && !self.reopen() {
+            self.config.scale = previous;
+        }
+    }
+
+    /// X1 -> X2 -> X4 -> X8 -> X1
+    pub fn cycle_scale(&mut self) {
+        self.set_scale(self.config.scale.next());
+    }
+
+    /// Alterna ventana sin bordes ajustada a la pantalla / ventana normal.
+    pub fn toggle_fullscreen(&mut self) {
+        self.fullscreen = !self.fullscreen;
+        if !self.reopen() {
+            self.fullscreen = !self.fullscreen;
+        }
+    }
+
+    /// Atajos estándar: F10 = siguiente escala, F11 = pantalla completa.
+    /// Llamar una vez por frame desde el bucle principal.
+    pub fn handle_window_hotkeys(&mut self) {
+        if self.window.is_key_pressed(Key::F11, KeyRepeat::No) {
+            self.toggle_fullscreen();
+        } else if self.window.is_key_pressed(Key::F10, KeyRepeat::No) {
+            self.cycle_scale();
+        }
+    }
+
+    fn reopen(&mut self) -> bool {
+        match open_window(&self.config, self.fullscreen) {
+            Ok(window) => {
+                self.window = window;
+                true
+            }
+            Err(e) => {
+                eprintln!("Display Error: no se pudo recrear la ventana: {}", e);
+                false
+            }
+        }
+    }
+}
+
+/// Abre la ventana según la configuración. Pantalla completa = ventana sin
+/// bordes a la mayor escala que cabe en el monitor (minifb no tiene modo exclusivo).
+fn open_window(config: &DisplayConfig, fullscreen: bool) -> minifb::Result<Window> {
+    // Con corrección de aspecto la ventana se abre ya al tamaño final y el
+    // buffer se estira (Stretch); sin ella, minifb escala y conserva el aspecto del buffer.
+    let (win_w, win_h, mut opts) = match config.aspect {
+        Some(_) => {
+            let (w, h) = aspect_window_size(config.width, config.height, config.scale, config.aspect);
+            (w, h, WindowOptions {
+                resize: config.resizable,
+                scale_mode: ScaleMode::Stretch,
+                ..WindowOptions::default()
+            })
+        }
+        None => (config.width, config.height, WindowOptions {
+            scale: config.scale.into(),
+            resize: config.resizable,
+            scale_mode: ScaleMode::AspectRatioStretch, // Mantiene aspect ratio al estirar
+            ..WindowOptions::default()
+        }),
+    };
+    if fullscreen {
+        opts.borderless = true;
+        opts.title = false;
+        opts.topmost = true;
+        opts.scale = Scale::FitScreen;
+        opts.scale_mode = ScaleMode::AspectRatioStretch;
+        return Window::new(&config.title, config.width, config.height, opts);
+    }
+    Window::new(&config.title, win_w, win_h, opts)
 }
 
 // ============================================================================
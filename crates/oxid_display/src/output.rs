@@ -0,0 +1,41 @@
+// crates/oxid_display/src/output.rs
+//
+// Salida de frames por callback: para embeber el emulador en otra aplicación
+// (streaming, renderer propio, modo headless) sin pasar por la ventana.
+
+/// Recibe cada frame completo: (buffer 0x00RRGGBB, ancho, alto).
+pub type FrameCallback = Box<dyn FnMut(&[u32], usize, usize)>;
+
+/// Callback opcional con las dimensiones nativas del sistema emulado.
+/// No depende de minifb: se puede usar sin `OxidDisplay`.
+pub struct FrameOutput {
+    width: usize,
+    height: usize,
+    callback: Option<FrameCallback>,
+}
+
+impl FrameOutput {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, callback: None }
+    }
+
+    /// Instala el callback (reemplaza al anterior).
+    pub fn set_callback(&mut self, cb: FrameCallback) {
+        self.callback = Some(cb);
+    }
+
+    pub fn clear_callback(&mut self) {
+        self.callback = None;
+    }
+
+    pub fn has_callback(&self) -> bool {
+        self.callback.is_some()
+    }
+
+    /// Entrega el frame al callback, si hay uno. El buffer es el nativo, sin filtro.
+    pub fn emit(&mut self, buffer: &[u32]) {
+        if let Some(cb) = self.callback.as_mut() {
+            cb(buffer, self.width, self.height);
+        }
+    }
+}
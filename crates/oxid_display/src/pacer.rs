@@ -0,0 +1,124 @@
+// crates/oxid_display/src/pacer.rs
+//
+// Frame limiter híbrido: duerme la mayor parte del tiempo restante y hace
+// spin-wait solo en el último tramo. El margen entre ambos se calibra con el
+// retraso real que mete `thread::sleep` en cada sistema (timers gruesos en
+// Windows, planificador cargado, etc.).
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Margen inicial (el valor fijo que usaba el limiter antes de calibrar)
+const INITIAL_MARGIN_US: u64 = 500;
+/// Nunca bajamos de aquí: absorbe el ruido entre frames
+const MIN_MARGIN_US: u64 = 100;
+/// Tope para que un pico aislado no nos deje haciendo spin todo el frame
+const MAX_MARGIN_US: u64 = 4000;
+
+/// Fuente de tiempo del limiter. Abstraída para poder probar la calibración.
+pub trait Clock {
+    /// Microsegundos desde un origen arbitrario (monótono)
+    fn now_us(&self) -> u64;
+    fn sleep_us(&mut self, us: u64);
+}
+
+/// Reloj real: `Instant` + `thread::sleep`.
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now_us(&self) -> u64 {
+        self.origin.elapsed().as_micros() as u64
+    }
+    fn sleep_us(&mut self, us: u64) {
+        thread::sleep(Duration::from_micros(us));
+    }
+}
+
+pub struct FramePacer {
+    target_us: u64,
+    frame_start: u64,
+    margin_us: u64,
+    jitter_us: u64,
+}
+
+impl FramePacer {
+    /// `target_us == 0` desactiva el límite (solo se mide).
+    pub fn new(target_us: u64, now: u64) -> Self {
+        Self {
+            target_us,
+            frame_start: now,
+            margin_us: INITIAL_MARGIN_US,
+            jitter_us: 0,
+        }
+    }
+
+    /// Cambia la duración objetivo del frame (0 = sin límite) sin perder la calibración.
+    pub fn set_target_us(&mut self, target_us: u64) {
+        self.target_us = target_us;
+    }
+
+    /// Tramo final (µs) que se resuelve con spin en vez de sleep.
+    pub fn margin_us(&self) -> u64 {
+        self.margin_us
+    }
+
+    /// Desviación media (µs) de la duración de frame respecto al objetivo.
+    pub fn jitter_us(&self) -> u64 {
+        self.jitter_us
+    }
+
+    /// Espera hasta completar el frame en curso y arranca el siguiente.
+    pub fn wait<C: Clock>(&mut self, clock: &mut C) {
+        if self.target_us > 0 {
+            let elapsed = clock.now_us().saturating_sub(self.frame_start);
+            if elapsed < self.target_us {
+                let remaining = self.target_us - elapsed;
+                if remaining > self.margin_us {
+                    let requested = remaining - self.margin_us;
+                    let t0 = clock.now_us();
+                    clock.sleep_us(requested);
+                    self.record_sleep(requested, clock.now_us().saturating_sub(t0));
+                }
+                while clock.now_us().saturating_sub(self.frame_start) < self.target_us {
+                    std::hint::spin_loop();
+                }
+            }
+        }
+        let now = clock.now_us();
+        self.record_frame(now.saturating_sub(self.frame_start));
+        self.frame_start = now;
+    }
+
+    /// Ajusta el margen al retraso observado: sube de golpe, baja despacio.
+    fn record_sleep(&mut self, requested: u64, actual: u64) {
+        let wanted = (actual.saturating_sub(requested) + MIN_MARGIN_US).min(MAX_MARGIN_US);
+        self.margin_us = if wanted > self.margin_us {
+            wanted
+        } else {
+            (self.margin_us * 15 + wanted) / 16
+        };
+    }
+
+    /// Media móvil (1/8) de |duración - objetivo|.
+    fn record_frame(&mut self, frame_us: u64) {
+        if self.target_us == 0 {
+            return;
+        }
+        let dev = frame_us.abs_diff(self.target_us);
+        self.jitter_us = (self.jitter_us * 7 + dev) / 8;
+    }
+}
@@ -0,0 +1,61 @@
+// crates/oxid_display/src/panel.rs
+//
+// Panel de depuración para una ventana auxiliar (registros de una CPU, estado
+// de un chip...): una rejilla fija de texto que se redibuja entera cada frame.
+
+use crate::text::{draw_text, fill_rect, CHAR_H, CHAR_W};
+use crate::{DisplayConfig, WindowScale};
+
+/// Margen alrededor del texto (px)
+const MARGIN: usize = 2;
+
+pub struct DebugPanel {
+    cols: usize,
+    rows: usize,
+    width: usize,
+    height: usize,
+    buffer: Vec<u32>,
+    pub fg: u32,
+    pub bg: u32,
+}
+
+impl DebugPanel {
+    /// Panel de `cols` x `rows` caracteres.
+    pub fn new(cols: usize, rows: usize) -> Self {
+        let width = cols * CHAR_W + 2 * MARGIN;
+        let height = rows * CHAR_H + 2 * MARGIN;
+        Self { cols, rows, width, height, buffer: vec![0; width * height], fg: 0x00E0E0E0, bg: 0x00101830 }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Configuración para abrir la ventana del panel: sin limitador de frames
+    /// (la marca la ventana principal).
+    pub fn display_config(&self, title: &str) -> DisplayConfig {
+        DisplayConfig {
+            title: title.to_string(),
+            width: self.width,
+            height: self.height,
+            scale: WindowScale::X2,
+            target_fps: 0.0,
+            resizable: false,
+            aspect: None,
+        }
+    }
+
+    /// Redibuja el panel con `lines` (lo que no cabe se recorta) y devuelve el buffer.
+    pub fn render(&mut self, lines: &[String]) -> &[u32] {
+        fill_rect(&mut self.buffer, self.width, 0, 0, self.width, self.height, self.bg);
+        for (i, line) in lines.iter().take(self.rows).enumerate() {
+            let line: String = line.chars().take(self.cols).collect();
+            draw_text(&mut self.buffer, self.width, MARGIN, MARGIN + i * CHAR_H, &line, self.fg);
+        }
+        &self.buffer
+    }
+}
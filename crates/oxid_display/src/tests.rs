@@ -0,0 +1,273 @@
+// crates/oxid_display/src/tests.rs
+use crate::filter::{bilinear2x, scale2x};
+use crate::{aspect, aspect_window_size, AudioRing, Clock, FrameOutput, FramePacer, GifRecorder, WindowScale};
+use std::cell::Cell;
+
+#[test]
+fn test_scale2x_known_pattern() {
+    let src = [1, 2, 2, 2];
+    let mut dst = [0u32; 16];
+    scale2x(&src, 2, 2, &mut dst);
+    assert_eq!(dst, [
+        1, 1, 2, 2,
+        1, 2, 2, 2,
+        2, 2, 2, 2,
+        2, 2, 2, 2,
+    ]);
+}
+
+#[test]
+fn test_bilinear2x_blends_neighbours() {
+    let src = [0x000000, 0x0000FE];
+    let mut dst = [0u32; 8];
+    bilinear2x(&src, 2, 1, &mut dst);
+    assert_eq!(&dst[..4], &[0x000000, 0x00007F, 0x0000FE, 0x0000FE]);
+}
+
+#[test]
+fn test_aspect_window_size() {
+    assert_eq!(aspect_window_size(256, 192, WindowScale::X2, None), (512, 384));
+    assert_eq!(aspect_window_size(256, 192, WindowScale::X2, Some(aspect::TV_4_3)), (512, 384));
+    assert_eq!(aspect_window_size(256, 192, WindowScale::X2, Some(aspect::SMS_NTSC)), (585, 384));
+}
+
+/// Recorre los bloques de un GIF y devuelve (ancho, alto, frames).
+fn gif_structure(data: &[u8]) -> (u16, u16, usize) {
+    assert_eq!(&data[..6], b"GIF89a");
+    let w = u16::from_le_bytes([data[6], data[7]]);
+    let h = u16::from_le_bytes([data[8], data[9]]);
+    let mut i = 13 + 3 * 256; // cabecera + paleta global
+    let mut frames = 0;
+    let skip_sub_blocks = |mut i: usize| {
+        while data[i] != 0 { i += data[i] as usize + 1; }
+        i + 1
+    };
+    loop {
+        match data[i] {
+            0x21 => i = skip_sub_blocks(i + 2),
+            0x2C => {
+                assert_eq!(u16::from_le_bytes([data[i + 5], data[i + 6]]), w);
+                assert_eq!(u16::from_le_bytes([data[i + 7], data[i + 8]]), h);
+                frames += 1;
+                i = skip_sub_blocks(i + 11);
+            }
+            0x3B => return (w, h, frames),
+            b => panic!("bloque GIF inesperado {:02X}", b),
+        }
+    }
+}
+
+#[test]
+fn test_gif_recorder_structure() {
+    let mut rec = GifRecorder::new(Vec::new(), 16, 8, 25.0).unwrap();
+    rec.add_frame(&[0xFF0000; 128]).unwrap();
+    rec.add_frame(&[0x0000FF; 128]).unwrap();
+    let data = rec.finish().unwrap();
+    assert_eq!(gif_structure(&data), (16, 8, 2));
+}
+
+
+// ============================================================================
+// Frame pacer
+// ============================================================================
+
+/// Reloj simulado: cada lectura avanza 1µs (spin) y el sleep se pasa `overshoot` µs.
+struct MockClock {
+    now: Cell<u64>,
+    overshoot: u64,
+    slept: u64,
+}
+
+impl MockClock {
+    fn new(overshoot: u64) -> Self {
+        Self { now: Cell::new(0), overshoot, slept: 0 }
+    }
+}
+
+impl Clock for MockClock {
+    fn now_us(&self) -> u64 {
+        let t = self.now.get();
+        self.now.set(t + 1);
+        t
+    }
+    fn sleep_us(&mut self, us: u64) {
+        self.slept += us;
+        self.now.set(self.now.get() + us + self.overshoot);
+    }
+}
+
+/// Simula `frames` frames en los que la emulación tarda `work` µs.
+fn run_pacer(clock: &mut MockClock, pacer: &mut FramePacer, frames: usize, work: u64) -> Vec<u64> {
+    let mut lengths = Vec::new();
+    for _ in 0..frames {
+        let start = clock.now.get();
+        clock.now.set(start + work);
+        pacer.wait(clock);
+        lengths.push(clock.now.get() - start);
+    }
+    lengths
+}
+
+#[test]
+fn test_pacer_grows_margin_on_coarse_sleep() {
+    let mut clock = MockClock::new(2000);
+    let mut pacer = FramePacer::new(16_667, 0);
+    let lengths = run_pacer(&mut clock, &mut pacer, 20, 5000);
+
+    assert!(pacer.margin_us() >= 2000);
+    // Una vez calibrado, el sleep ya no se pasa del objetivo
+    assert!(lengths[5..].iter().all(|&l| l < 16_667 + 10));
+    assert!(pacer.jitter_us() < 200);
+}
+
+#[test]
+fn test_pacer_shrinks_margin_on_precise_sleep() {
+    let mut clock = MockClock::new(20);
+    let mut pacer = FramePacer::new(16_667, 0);
+    run_pacer(&mut clock, &mut pacer, 100, 5000);
+
+    // Menos spin que el margen fijo de 500µs de antes
+    assert!(pacer.margin_us() < 200);
+    assert!(clock.slept > 100 * 11_000);
+}
+
+#[test]
+fn test_pacer_unlimited_never_sleeps() {
+    let mut clock = MockClock::new(0);
+    let mut pacer = FramePacer::new(0, 0);
+    run_pacer(&mut clock, &mut pacer, 10, 1000);
+    assert_eq!(clock.slept, 0);
+    assert_eq!(pacer.jitter_us(), 0);
+}
+
+// ============================================================================
+// Texto
+// ============================================================================
+
+#[test]
+fn test_draw_text_glyph_and_clipping() {
+    use crate::text::{draw_panel, draw_text, CHAR_W};
+
+    let mut buf = vec![0u32; 16 * 8];
+    draw_text(&mut buf, 16, 0, 0, "1", 7);
+    // '1': columna central completa, pie en la fila 6
+    assert!((0..7).all(|row| buf[row * 16 + 2] == 7));
+    assert_eq!(buf[6 * 16 + 1], 7);
+    assert_eq!(buf[0], 0);
+    assert!(buf[7 * 16..].iter().all(|&p| p == 0));
+
+    // Minúsculas = mayúsculas; desconocidos = '?'
+    let (mut a, mut b) = (vec![0u32; 16 * 8], vec![0u32; 16 * 8]);
+    draw_text(&mut a, 16, 0, 0, "a", 1);
+    draw_text(&mut b, 16, 0, 0, "A", 1);
+    assert_eq!(a, b);
+    draw_text(&mut a, 16, 0, 0, "~", 1);
+    draw_text(&mut b, 16, 0, 0, "?", 1);
+    assert_eq!(a, b);
+
+    // Fuera del buffer: se recorta sin panic
+    draw_text(&mut buf, 16, 16 - CHAR_W / 2, 4, "WW", 3);
+    draw_panel(&mut buf, 16, 10, 6, &["LONG LINE".into()], 1, 2);
+}
+
+#[test]
+fn test_window_scale_cycle() {
+    let mut scale = WindowScale::X1;
+    let mut seen = Vec::new();
+    for _ in 0..4 {
+        scale = scale.next();
+        seen.push(scale);
+    }
+    assert_eq!(seen, [WindowScale::X2, WindowScale::X4, WindowScale::X8, WindowScale::X1]);
+    assert_eq!(WindowScale::FitScreen.next(), WindowScale::X1);
+}
+
+#[test]
+fn test_pacer_target_change() {
+    let mut clock = MockClock::new(0);
+    let mut pacer = FramePacer::new(20_000, 0);
+    pacer.set_target_us(5_000); // 4x
+    clock.now.set(1_000);
+    pacer.wait(&mut clock);
+    let t = clock.now.get();
+    assert!((5_000..5_100).contains(&t), "{}", t);
+
+    pacer.set_target_us(0); // sin límite: no espera
+    let slept = clock.slept;
+    pacer.wait(&mut clock);
+    assert_eq!(clock.slept, slept);
+    assert!(clock.now.get() - t < 10);
+}
+
+#[test]
+fn test_frame_output_callback_receives_dimensions() {
+    use std::rc::Rc;
+    let seen = Rc::new(Cell::new((0usize, 0usize, 0usize, 0u32)));
+    let mut output = FrameOutput::new(4, 3);
+    output.emit(&[0; 12]); // sin callback: no hace nada
+
+    let sink = Rc::clone(&seen);
+    output.set_callback(Box::new(move |buf, w, h| sink.set((buf.len(), w, h, buf[5]))));
+    let mut frame = [0u32; 12];
+    frame[5] = 0x00FF8800;
+    output.emit(&frame);
+    assert_eq!(seen.get(), (12, 4, 3, 0x00FF8800));
+
+    output.clear_callback();
+    output.emit(&[0; 12]);
+    assert_eq!(seen.get().3, 0x00FF8800);
+    assert!(!output.has_callback());
+}
+
+#[test]
+fn test_debug_panel_renders_register_text() {
+    use crate::text::{draw_text, CHAR_H, CHAR_W};
+    use crate::DebugPanel;
+
+    let mut panel = DebugPanel::new(10, 2);
+    assert_eq!((panel.width(), panel.height()), (10 * CHAR_W + 4, 2 * CHAR_H + 4));
+    let config = panel.display_config("Z80");
+    assert_eq!((config.width, config.height, config.target_fps), (panel.width(), panel.height(), 0.0));
+
+    let (w, fg, bg) = (panel.width(), panel.fg, panel.bg);
+    let lines = ["PC=1234".to_string(), "SP=FFFF".to_string(), "NO CABE".to_string()];
+    let buf = panel.render(&lines).to_vec();
+    assert_eq!(buf.len(), w * panel.height());
+    assert_eq!(buf[0], bg);
+
+    // Mismos píxeles que dibujar el texto a mano sobre el fondo
+    let mut expected = vec![bg; buf.len()];
+    draw_text(&mut expected, w, 2, 2, "PC=1234", fg);
+    draw_text(&mut expected, w, 2, 2 + CHAR_H, "SP=FFFF", fg);
+    assert_eq!(buf, expected);
+}
+
+#[test]
+fn test_audio_ring_underrun_outputs_silence() {
+    let ring = AudioRing::new(8);
+    ring.push(&[1, 2, 3]);
+    let mut out = [9i16; 5];
+    assert_eq!(ring.fill(&mut out), 3);
+    assert_eq!(out, [1, 2, 3, 0, 0]);
+    assert!(ring.is_empty());
+    assert_eq!(ring.fill(&mut out), 0);
+    assert_eq!(out, [0; 5]);
+}
+
+#[test]
+fn test_audio_ring_overflow_drops_oldest_and_clones_share_queue() {
+    let ring = AudioRing::new(4);
+    let feed = ring.clone();
+    ring.push(&[1, 2, 3]);
+    ring.push(&[4, 5]);
+    assert_eq!(feed.len(), 4);
+    let mut out = [0i16; 4];
+    feed.fill(&mut out);
+    assert_eq!(out, [2, 3, 4, 5]);
+
+    // Una ráfaga mayor que la capacidad conserva solo el final
+    ring.push(&[10, 11, 12, 13, 14, 15]);
+    feed.fill(&mut out);
+    assert_eq!(out, [12, 13, 14, 15]);
+    assert_eq!(AudioRing::for_stream(44_100, 2).capacity(), 22_050);
+}
@@ -0,0 +1,103 @@
+// crates/oxid_display/src/text.rs
+//
+// Texto mínimo para paneles de información sobre el framebuffer: fuente 5x7
+// (mayúsculas, dígitos y algo de puntuación), celdas de 6x8 píxeles.
+// Las minúsculas se pintan como mayúsculas y lo desconocido como '?'.
+
+/// Ancho/alto de una celda de carácter (glifo 5x7 + separación).
+pub const CHAR_W: usize = 6;
+pub const CHAR_H: usize = 8;
+
+/// Glifos por columnas (bit 0 = fila superior).
+const GLYPHS: &[(u8, [u8; 5])] = &[
+    (b' ', [0x00, 0x00, 0x00, 0x00, 0x00]),
+    (b'!', [0x00, 0x00, 0x5F, 0x00, 0x00]),
+    (b'$', [0x24, 0x2A, 0x7F, 0x2A, 0x12]),
+    (b'(', [0x00, 0x1C, 0x22, 0x41, 0x00]),
+    (b')', [0x00, 0x41, 0x22, 0x1C, 0x00]),
+    (b'-', [0x08, 0x08, 0x08, 0x08, 0x08]),
+    (b'.', [0x00, 0x60, 0x60, 0x00, 0x00]),
+    (b'/', [0x20, 0x10, 0x08, 0x04, 0x02]),
+    (b'0', [0x3E, 0x51, 0x49, 0x45, 0x3E]),
+    (b'1', [0x00, 0x42, 0x7F, 0x40, 0x00]),
+    (b'2', [0x42, 0x61, 0x51, 0x49, 0x46]),
+    (b'3', [0x21, 0x41, 0x45, 0x4B, 0x31]),
+    (b'4', [0x18, 0x14, 0x12, 0x7F, 0x10]),
+    (b'5', [0x27, 0x45, 0x45, 0x45, 0x39]),
+    (b'6', [0x3C, 0x4A, 0x49, 0x49, 0x30]),
+    (b'7', [0x01, 0x71, 0x09, 0x05, 0x03]),
+    (b'8', [0x36, 0x49, 0x49, 0x49, 0x36]),
+    (b'9', [0x06, 0x49, 0x49, 0x29, 0x1E]),
+    (b':', [0x00, 0x36, 0x36, 0x00, 0x00]),
+    (b'?', [0x02, 0x01, 0x51, 0x09, 0x06]),
+    (b'A', [0x7E, 0x11, 0x11, 0x11, 0x7E]),
+    (b'B', [0x7F, 0x49, 0x49, 0x49, 0x36]),
+    (b'C', [0x3E, 0x41, 0x41, 0x41, 0x22]),
+    (b'D', [0x7F, 0x41, 0x41, 0x22, 0x1C]),
+    (b'E', [0x7F, 0x49, 0x49, 0x49, 0x41]),
+    (b'F', [0x7F, 0x09, 0x09, 0x09, 0x01]),
+    (b'G', [0x3E, 0x41, 0x49, 0x49, 0x7A]),
+    (b'H', [0x7F, 0x08, 0x08, 0x08, 0x7F]),
+    (b'I', [0x00, 0x41, 0x7F, 0x41, 0x00]),
+    (b'J', [0x20, 0x40, 0x41, 0x3F, 0x01]),
+    (b'K', [0x7F, 0x08, 0x14, 0x22, 0x41]),
+    (b'L', [0x7F, 0x40, 0x40, 0x40, 0x40]),
+    (b'M', [0x7F, 0x02, 0x0C, 0x02, 0x7F]),
+    (b'N', [0x7F, 0x04, 0x08, 0x10, 0x7F]),
+    (b'O', [0x3E, 0x41, 0x41, 0x41, 0x3E]),
+    (b'P', [0x7F, 0x09, 0x09, 0x09, 0x06]),
+    (b'Q', [0x3E, 0x41, 0x51, 0x21, 0x5E]),
+    (b'R', [0x7F, 0x09, 0x19, 0x29, 0x46]),
+    (b'S', [0x46, 0x49, 0x49, 0x49, 0x31]),
+    (b'T', [0x01, 0x01, 0x7F, 0x01, 0x01]),
+    (b'U', [0x3F, 0x40, 0x40, 0x40, 0x3F]),
+    (b'V', [0x1F, 0x20, 0x40, 0x20, 0x1F]),
+    (b'W', [0x3F, 0x40, 0x38, 0x40, 0x3F]),
+    (b'X', [0x63, 0x14, 0x08, 0x14, 0x63]),
+    (b'Y', [0x07, 0x08, 0x70, 0x08, 0x07]),
+    (b'Z', [0x61, 0x51, 0x49, 0x45, 0x43]),
+];
+
+fn glyph(ch: u8) -> [u8; 5] {
+    let ch = ch.to_ascii_uppercase();
+    GLYPHS.iter()
+        .find(|(c, _)| *c == ch)
+        .or_else(|| GLYPHS.iter().find(|(c, _)| *c == b'?'))
+        .map(|(_, g)| *g)
+        .unwrap_or_default()
+}
+
+/// Pinta `text` con la esquina superior izquierda en (x, y). Recorta en los bordes.
+pub fn draw_text(buffer: &mut [u32], width: usize, x: usize, y: usize, text: &str, color: u32) {
+    let height = buffer.len() / width.max(1);
+    for (i, ch) in text.bytes().enumerate() {
+        let cx = x + i * CHAR_W;
+        for (col, bits) in glyph(ch).iter().enumerate() {
+            for row in 0..7 {
+                let (px, py) = (cx + col, y + row);
+                if bits & (1 << row) != 0 && px < width && py < height {
+                    buffer[py * width + px] = color;
+                }
+            }
+        }
+    }
+}
+
+/// Rellena un rectángulo (fondo de paneles). Recorta en los bordes.
+pub fn fill_rect(buffer: &mut [u32], width: usize, x: usize, y: usize, w: usize, h: usize, color: u32) {
+    let height = buffer.len() / width.max(1);
+    for py in y..(y + h).min(height) {
+        for px in x..(x + w).min(width) {
+            buffer[py * width + px] = color;
+        }
+    }
+}
+
+/// Panel de texto: fondo + una línea por entrada, con 2px de margen.
+pub fn draw_panel(buffer: &mut [u32], width: usize, x: usize, y: usize, lines: &[String], fg: u32, bg: u32) {
+    let cols = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    fill_rect(buffer, width, x, y, cols * CHAR_W + 3, lines.len() * CHAR_H + 3, bg);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(buffer, width, x + 2, y + 2 + i * CHAR_H, line, fg);
+    }
+}
@@ -52,6 +52,12 @@ pub struct OxidInput {
     key_map_p2: HashMap<Key, GamepadButtons>,
 }
 
+impl Default for OxidInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl OxidInput {
     /// Crea un nuevo gestor de entrada con un mapeo por defecto inteligente
     pub fn new() -> Self {
@@ -0,0 +1,171 @@
+// crates/oxide-core/src/cheats.rs
+//! Trucos (POKEs): pares `(dirección, valor)` que el frontend vuelve a escribir
+//! en el bus después de cada frame (`MemoryBus::apply_cheats`).
+//!
+//! Formato del fichero, un truco por línea:
+//!
+//! ```text
+//! # comentario
+//! POKE 35136,0 Vidas infinitas     <- decimal, estilo Spectrum
+//! $C0A4 9 Energía                  <- hex con $ o 0x
+//! !0x8F00 1 Nivel 5                <- '!' = cargado deshabilitado
+//! ```
+//!
+//! `RamScanner` ayuda a encontrar las direcciones: compara instantáneas de la
+//! RAM y va descartando las que no cumplen la condición (p. ej. "bajó" tras
+//! perder una vida).
+
+use crate::MemoryBus;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cheat {
+    pub address: u32,
+    pub value: u8,
+    pub enabled: bool,
+    pub description: String,
+}
+
+impl Cheat {
+    pub fn new(address: u32, value: u8) -> Self {
+        Self { address, value, enabled: true, description: String::new() }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CheatError {
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: {msg}")]
+    Parse { line: usize, msg: String },
+}
+
+/// Número decimal, o hex con prefijo `$` / `0x`.
+fn parse_number(s: &str) -> Option<u32> {
+    if let Some(hex) = s.strip_prefix('$').or_else(|| s.strip_prefix("0x")).or_else(|| s.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+fn parse_line(line: &str) -> Result<Option<Cheat>, String> {
+    let line = line.split(['#', ';']).next().unwrap_or("").trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let (enabled, line) = match line.strip_prefix('!') {
+        Some(rest) => (false, rest.trim_start()),
+        None => (true, line),
+    };
+    let line = match line.get(..5) {
+        Some(kw) if kw.eq_ignore_ascii_case("poke ") => line[5..].trim_start(),
+        _ => line,
+    };
+
+    // "dir,valor" o "dir valor"; el resto es la descripción
+    let line = line.replacen(',', " ", 1);
+    let mut words = line.split_whitespace();
+    let address = words.next().unwrap_or("");
+    let address = parse_number(address).ok_or_else(|| format!("bad address '{}'", address))?;
+    let value = words.next().unwrap_or("");
+    let value = parse_number(value)
+        .and_then(|v| u8::try_from(v).ok())
+        .ok_or_else(|| format!("bad value '{}'", value))?;
+    Ok(Some(Cheat {
+        address,
+        value,
+        enabled,
+        description: words.collect::<Vec<_>>().join(" "),
+    }))
+}
+
+/// Lee una lista de POKEs (ver formato en la cabecera del módulo).
+pub fn parse_pokes(text: &str) -> Result<Vec<Cheat>, CheatError> {
+    let mut cheats = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        match parse_line(line) {
+            Ok(Some(cheat)) => cheats.push(cheat),
+            Ok(None) => {}
+            Err(msg) => return Err(CheatError::Parse { line: i + 1, msg }),
+        }
+    }
+    Ok(cheats)
+}
+
+pub fn load_pokes<P: AsRef<Path>>(path: P) -> Result<Vec<Cheat>, CheatError> {
+    parse_pokes(&fs::read_to_string(path)?)
+}
+
+// ============================================================================
+// RAM SCANNER
+// ============================================================================
+
+/// Condición de filtrado: la última instantánea frente a la anterior,
+/// salvo `Value`, que mira solo la última.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    Changed,
+    Greater,
+    Less,
+    Value(u8),
+}
+
+pub struct RamScanner {
+    range: RangeInclusive<u32>,
+    previous: Option<Vec<u8>>,
+    current: Option<Vec<u8>>,
+    candidates: Vec<u32>,
+}
+
+impl RamScanner {
+    /// Escanea `range` (p. ej. la RAM de trabajo); al empezar todas son candidatas.
+    pub fn new(range: RangeInclusive<u32>) -> Self {
+        let candidates = range.clone().collect();
+        Self { range, previous: None, current: None, candidates }
+    }
+
+    /// Vuelve a empezar con todas las direcciones y sin instantáneas.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.range.clone());
+    }
+
+    /// Guarda la RAM actual; la instantánea anterior pasa a ser la referencia.
+    pub fn snapshot(&mut self, bus: &dyn MemoryBus) {
+        let data = self.range.clone().map(|addr| bus.read(addr)).collect();
+        self.previous = self.current.replace(data);
+    }
+
+    /// Descarta las candidatas que no cumplen `cmp`. Devuelve cuántas quedan,
+    /// o `None` si faltan instantáneas para comparar.
+    pub fn filter(&mut self, cmp: Comparison) -> Option<usize> {
+        let current = self.current.as_ref()?;
+        let previous = match cmp {
+            Comparison::Value(_) => None,
+            _ => Some(self.previous.as_ref()?),
+        };
+        let start = *self.range.start();
+        self.candidates.retain(|&addr| {
+            let i = (addr - start) as usize;
+            let now = current[i];
+            match (cmp, previous.map(|p| p[i])) {
+                (Comparison::Value(v), _) => now == v,
+                (Comparison::Equal, Some(before)) => now == before,
+                (Comparison::Changed, Some(before)) => now != before,
+                (Comparison::Greater, Some(before)) => now > before,
+                (Comparison::Less, Some(before)) => now < before,
+                _ => false,
+            }
+        });
+        Some(self.candidates.len())
+    }
+
+    pub fn candidates(&self) -> Vec<u32> {
+        self.candidates.clone()
+    }
+}
+
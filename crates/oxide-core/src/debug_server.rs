@@ -0,0 +1,277 @@
+// crates/oxide-core/src/debug_server.rs
+//! Servidor de depuración remoto (feature `debug-server`).
+//!
+//! Protocolo de texto, una orden por línea y una línea de respuesta por orden
+//! (direcciones y bytes en hex):
+//!
+//! ```text
+//! regs                      -> PC=0000 SP=FFFF ...
+//! read <addr> [len]         -> 3E 01 ...            (len por defecto 16, máx 256)
+//! write <addr> <byte>...    -> OK
+//! break <addr>              -> OK
+//! delete <addr>             -> OK
+//! step [n]                  -> PC=xxxx
+//! next                      -> PC=xxxx | BREAK xxxx | RUNNING xxxx (step over)
+//! stack                     -> STACK xxxx xxxx ...  (retornos, el más interno primero)
+//! continue                  -> BREAK xxxx | RUNNING xxxx (límite de instrucciones)
+//! quit                      -> BYE (cierra el servidor)
+//! ```
+//!
+//! Los errores responden `ERR <motivo>`. El transporte es genérico (`BufRead` +
+//! `Write`); `listen` lo monta sobre TCP.
+
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+/// Máximo de bytes por `read`
+const MAX_READ: u32 = 256;
+/// Instrucciones que ejecuta `continue` sin breakpoint antes de devolver el control
+const DEFAULT_CONTINUE_LIMIT: u64 = 10_000_000;
+
+/// Máquina depurable: CPU + bus vistos desde fuera.
+pub trait DebugTarget {
+    /// Registros en el orden en que se muestran
+    fn registers(&self) -> Vec<(&'static str, u32)>;
+    fn pc(&self) -> u32;
+    fn read_mem(&self, addr: u32) -> u8;
+    fn write_mem(&mut self, addr: u32, val: u8);
+    /// Ejecuta una instrucción (con la temporización/interrupciones del sistema)
+    fn step(&mut self) -> u32;
+    /// Si la instrucción en el PC llama a una subrutina (JSR/BSR, CALL/RST), la
+    /// dirección a la que vuelve. Por defecto `None`: `next` se comporta como `step`.
+    fn call_return(&self) -> Option<u32> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Regs,
+    Read { addr: u32, len: u32 },
+    Write { addr: u32, bytes: Vec<u8> },
+    Break(u32),
+    Delete(u32),
+    Step(u32),
+    /// Step over: ejecuta la subrutina entera si la instrucción es una llamada
+    Next,
+    /// Pila de llamadas inferida
+    Stack,
+    Continue,
+    Quit,
+}
+
+fn hex(s: Option<&str>) -> Result<u32, String> {
+    let s = s.ok_or("missing argument")?;
+    let digits = s.trim_start_matches("0x").trim_start_matches('$');
+    u32::from_str_radix(digits, 16).map_err(|_| format!("bad hex value '{}'", s))
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let cmd = words.next().ok_or("empty command")?;
+        let command = match cmd.to_ascii_lowercase().as_str() {
+            "regs" | "r" => Command::Regs,
+            "read" | "m" => {
+                let addr = hex(words.next())?;
+                let len = match words.next() {
+                    Some(n) => hex(Some(n))?,
+                    None => 16,
+                };
+                if len == 0 || len > MAX_READ {
+                    return Err(format!("length must be 1..{:X}", MAX_READ));
+                }
+                Command::Read { addr, len }
+            }
+            "write" | "w" => {
+                let addr = hex(words.next())?;
+                let bytes = words
+                    .by_ref()
+                    .map(|w| hex(Some(w)).and_then(|v| u8::try_from(v).map_err(|_| format!("'{}' is not a byte", w))))
+                    .collect::<Result<Vec<u8>, String>>()?;
+                if bytes.is_empty() {
+                    return Err("no bytes to write".into());
+                }
+                Command::Write { addr, bytes }
+            }
+            "break" | "b" => Command::Break(hex(words.next())?),
+            "delete" | "d" => Command::Delete(hex(words.next())?),
+            "step" | "s" => match words.next() {
+                Some(n) => Command::Step(n.parse().map_err(|_| format!("bad count '{}'", n))?),
+                None => Command::Step(1),
+            },
+            "next" | "n" => Command::Next,
+            "stack" | "bt" => Command::Stack,
+            "continue" | "c" => Command::Continue,
+            "quit" | "q" => Command::Quit,
+            other => return Err(format!("unknown command '{}'", other)),
+        };
+        if words.next().is_some() {
+            return Err("too many arguments".into());
+        }
+        Ok(command)
+    }
+}
+
+pub struct DebugServer {
+    breakpoints: BTreeSet<u32>,
+    continue_limit: u64,
+    /// Direcciones de retorno de las llamadas en curso (la más interna al final)
+    calls: Vec<u32>,
+}
+
+impl Default for DebugServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugServer {
+    pub fn new() -> Self {
+        Self {
+            breakpoints: BTreeSet::new(),
+            continue_limit: DEFAULT_CONTINUE_LIMIT,
+            calls: Vec::new(),
+        }
+    }
+
+    /// Instrucciones máximas por `continue` (builder).
+    pub fn with_continue_limit(mut self, limit: u64) -> Self {
+        self.continue_limit = limit.max(1);
+        self
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = u32> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Pila de llamadas inferida de lo ejecutado bajo el servidor: direcciones de
+    /// retorno, la más interna primero. Solo ve llamadas hechas con `call_return`
+    /// (JSR/BSR, CALL/RST) desde que el servidor controla la máquina.
+    pub fn call_stack(&self) -> Vec<u32> {
+        self.calls.iter().rev().copied().collect()
+    }
+
+    /// Un paso de la máquina siguiendo las llamadas: si la instrucción era una
+    /// llamada y el PC no cayó en su retorno (CALL condicional no tomado), se apila;
+    /// llegar a un retorno apilado (RTS/RET, o un salto que desenrolla varios
+    /// niveles) desapila hasta él.
+    fn step_traced(&mut self, target: &mut dyn DebugTarget) {
+        let ret = target.call_return();
+        target.step();
+        let pc = target.pc();
+        if let Some(depth) = self.calls.iter().rposition(|&r| r == pc) {
+            self.calls.truncate(depth);
+        }
+        if let Some(ret) = ret.filter(|&r| r != pc) {
+            self.calls.push(ret);
+        }
+    }
+
+    /// Ejecuta una orden y devuelve la línea de respuesta (sin salto de línea).
+    pub fn execute(&mut self, target: &mut dyn DebugTarget, command: Command) -> String {
+        match command {
+            Command::Regs => target
+                .registers()
+                .iter()
+                .map(|(name, val)| format!("{}={:X}", name, val))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Command::Read { addr, len } => (0..len)
+                .map(|i| format!("{:02X}", target.read_mem(addr.wrapping_add(i))))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Command::Write { addr, bytes } => {
+                for (i, b) in bytes.iter().enumerate() {
+                    target.write_mem(addr.wrapping_add(i as u32), *b);
+                }
+                "OK".into()
+            }
+            Command::Break(addr) => {
+                self.breakpoints.insert(addr);
+                "OK".into()
+            }
+            Command::Delete(addr) => {
+                if self.breakpoints.remove(&addr) {
+                    "OK".into()
+                } else {
+                    format!("ERR no breakpoint at {:X}", addr)
+                }
+            }
+            Command::Step(n) => {
+                for _ in 0..n {
+                    self.step_traced(target);
+                }
+                format!("PC={:X}", target.pc())
+            }
+            Command::Next => {
+                let Some(ret) = target.call_return() else {
+                    self.step_traced(target);
+                    return format!("PC={:X}", target.pc());
+                };
+                // Breakpoint temporal en el retorno; los del usuario dentro de la subrutina
+                // paran antes, como en `continue`
+                for _ in 0..self.continue_limit {
+                    self.step_traced(target);
+                    if target.pc() == ret {
+                        return format!("PC={:X}", ret);
+                    }
+                    if self.breakpoints.contains(&target.pc()) {
+                        return format!("BREAK {:X}", target.pc());
+                    }
+                }
+                format!("RUNNING {:X}", target.pc())
+            }
+            Command::Stack => std::iter::once("STACK".to_string())
+                .chain(self.call_stack().iter().map(|r| format!("{:X}", r)))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Command::Continue => {
+                // Siempre avanza al menos una instrucción: continuar desde un breakpoint
+                for _ in 0..self.continue_limit {
+                    self.step_traced(target);
+                    if self.breakpoints.contains(&target.pc()) {
+                        return format!("BREAK {:X}", target.pc());
+                    }
+                }
+                format!("RUNNING {:X}", target.pc())
+            }
+            Command::Quit => "BYE".into(),
+        }
+    }
+
+    /// Atiende órdenes hasta `quit` (devuelve `true`) o fin de la entrada (`false`).
+    pub fn serve<R: BufRead, W: Write>(&mut self, target: &mut dyn DebugTarget, input: R, mut output: W) -> io::Result<bool> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (reply, quit) = match Command::parse(&line) {
+                Ok(Command::Quit) => (self.execute(target, Command::Quit), true),
+                Ok(cmd) => (self.execute(target, cmd), false),
+                Err(e) => (format!("ERR {}", e), false),
+            };
+            writeln!(output, "{}", reply)?;
+            output.flush()?;
+            if quit {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Escucha en `addr` (p. ej. `127.0.0.1:6510`), un cliente a la vez, hasta `quit`.
+    pub fn listen(&mut self, target: &mut dyn DebugTarget, addr: &str) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let reader = BufReader::new(stream.try_clone()?);
+            if self.serve(target, reader, stream)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,26 @@
 use std::fs;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
 use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+pub use cheats::Cheat;
+pub use state::StateError;
+pub use symbols::{load_symbols, SymbolTable};
+
+pub mod cheats;
+pub mod psg;
+pub mod smc;
+pub mod state;
+pub mod symbols;
+#[cfg(test)]
+mod tests;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "debug-server")]
+pub mod debug_server;
+
 // ============================================================================
 //  CONTRACTS (TRAITS)
 // ============================================================================
@@ -22,6 +41,38 @@ pub trait Cpu {
 
     /// Debugging: Obtener el Program Counter actual
     fn pc(&self) -> u32;
+
+    /// Solicita una interrupción de forma uniforme (frontends, tests, `dyn Cpu`).
+    /// Se atiende en el próximo `step`. Mapeo de `level`:
+    /// - 68000: nivel IPL 1-7 (sujeto a la máscara de SR).
+    /// - Z80: `0xFF` = NMI; cualquier otro valor distinto de 0 = INT (bus de datos 0xFF).
+    ///
+    /// `0` no hace nada. Por defecto se ignora (CPUs sin interrupciones).
+    fn request_interrupt(&mut self, _level: u8) {}
+
+    /// Instantánea de todos los registros y del estado de interrupciones (quick-save).
+    /// Formato binario versionado propio de cada núcleo (ver `state`).
+    fn save_state(&self) -> Vec<u8> {
+        panic!("save_state unsupported");
+    }
+
+    /// Restaura una instantánea de `save_state`. Si es inválida la CPU no se modifica.
+    fn load_state(&mut self, _data: &[u8]) -> Result<(), StateError> {
+        panic!("load_state unsupported");
+    }
+}
+
+/// Cómo llega una región al bus de datos de 16 bits del 68000.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lanes {
+    /// 16 bits, big endian
+    #[default]
+    Word,
+    /// 16 bits con los bytes cruzados (periféricos little endian)
+    ByteSwapped,
+    /// Periférico de 8 bits: un solo acceso por word, con su byte en ambas
+    /// mitades al leer y solo el byte alto al escribir
+    Byte,
 }
 
 /// Contrato UNIFICADO para el Bus (Memoria + I/O).
@@ -38,13 +89,51 @@ pub trait MemoryBus {
     } // Bus flotante devuelve FF
     fn port_out(&mut self, _port: u16, _val: u8) {} // Escritura al vacío
 
+    // E/S de 16 bits como dos accesos de byte little endian: `port` (bajo) y `port + 1` (alto)
+    fn port_in_u16(&mut self, port: u16) -> u16 {
+        let lo = self.port_in(port) as u16;
+        let hi = self.port_in(port.wrapping_add(1)) as u16;
+        (hi << 8) | lo
+    }
+    fn port_out_u16(&mut self, port: u16, val: u16) {
+        self.port_out(port, val as u8);
+        self.port_out(port.wrapping_add(1), (val >> 8) as u8);
+    }
+
+    // Ciclo de reconocimiento de interrupción (INTA): byte que el periférico pone en el bus
+    // de datos (vector IM2 / opcode IM0 del Z80). Bus flotante = 0xFF (RST 38h).
+    fn interrupt_ack(&mut self) -> u8 {
+        0xFF
+    }
+
+    // Línea INT mantenida por los periféricos del bus (cableado directo a la CPU).
+    // La CPU la muestrea en cada límite de instrucción junto a su propia `int_line`.
+    fn int_asserted(&self) -> bool {
+        false
+    }
+
+    // Ciclo de refresco tras cada fetch M1 del Z80: dirección (I << 8) | R en el bus
+    // de direcciones. Solo interesa a modelos finos de hardware (DRAM, decodificadores).
+    fn refresh(&mut self, _addr: u16) {}
+
     // --- Helpers Automáticos (Default Impls) ---
 
+    // Cableado del bus de datos en `addr` para accesos word/long (ver `Lanes`).
+    // Los helpers big endian de abajo lo consultan; por defecto, bus de 16 bits normal.
+    fn lanes(&self, _addr: u32) -> Lanes {
+        Lanes::Word
+    }
+
     // Lectura 16-bit Big Endian (Motorola 68k)
     fn read_u16_be(&self, addr: u32) -> u16 {
-        let hi = self.read(addr) as u16;
-        let lo = self.read(addr.wrapping_add(1)) as u16;
-        (hi << 8) | lo
+        match self.lanes(addr) {
+            Lanes::Word => ((self.read(addr) as u16) << 8) | self.read(addr.wrapping_add(1)) as u16,
+            Lanes::ByteSwapped => ((self.read(addr.wrapping_add(1)) as u16) << 8) | self.read(addr) as u16,
+            Lanes::Byte => {
+                let b = self.read(addr) as u16;
+                (b << 8) | b
+            }
+        }
     }
 
     // Lectura 16-bit Little Endian (Zilog Z80, Intel)
@@ -54,30 +143,43 @@ pub trait MemoryBus {
         (hi << 8) | lo
     }
 
-    // Lectura 32-bit Big Endian (Motorola 68k)
+    // Lectura 32-bit Big Endian (Motorola 68k): dos accesos word, como el 68000
     fn read_u32_be(&self, addr: u32) -> u32 {
-        let b0 = self.read(addr) as u32;
-        let b1 = self.read(addr.wrapping_add(1)) as u32;
-        let b2 = self.read(addr.wrapping_add(2)) as u32;
-        let b3 = self.read(addr.wrapping_add(3)) as u32;
-        (b0 << 24) | (b1 << 16) | (b2 << 8) | b3
+        ((self.read_u16_be(addr) as u32) << 16) | self.read_u16_be(addr.wrapping_add(2)) as u32
     }
 
     // Escritura 16-bit Big Endian
     fn write_u16_be(&mut self, addr: u32, val: u16) {
-        self.write(addr, (val >> 8) as u8);
-        self.write(addr.wrapping_add(1), (val & 0xFF) as u8);
+        match self.lanes(addr) {
+            Lanes::Word => {
+                self.write(addr, (val >> 8) as u8);
+                self.write(addr.wrapping_add(1), (val & 0xFF) as u8);
+            }
+            Lanes::ByteSwapped => {
+                self.write(addr, (val & 0xFF) as u8);
+                self.write(addr.wrapping_add(1), (val >> 8) as u8);
+            }
+            // El dispositivo solo ve el byte alto (D8-D15)
+            Lanes::Byte => self.write(addr, (val >> 8) as u8),
+        }
     }
 
     // Escritura 32-bit Big Endian
     fn write_u32_be(&mut self, addr: u32, val: u32) {
-        self.write(addr, (val >> 24) as u8);
-        self.write(addr.wrapping_add(1), (val >> 16) as u8);
-        self.write(addr.wrapping_add(2), (val >> 8) as u8);
-        self.write(addr.wrapping_add(3), (val & 0xFF) as u8);
+        self.write_u16_be(addr, (val >> 16) as u16);
+        self.write_u16_be(addr.wrapping_add(2), val as u16);
+    }
+
+    // Trucos: reescribe los POKEs habilitados (el frontend lo llama tras cada frame)
+    fn apply_cheats(&mut self, cheats: &[Cheat]) {
+        for cheat in cheats.iter().filter(|c| c.enabled) {
+            self.write(cheat.address, cheat.value);
+        }
     }
 
-    // Compatibilidad Legacy para oxid68k (Asume Big Endian por defecto)
+    // Compatibilidad Legacy (Asume Big Endian): ambiguo para el Z80, que es LE.
+    // Usar siempre la variante explícita.
+    #[deprecated(note = "endianness ambigua: usar read_u16_be (68k) o read_u16_le (Z80)")]
     fn read_u16(&self, addr: u32) -> u16 {
         self.read_u16_be(addr)
     }
@@ -92,6 +194,425 @@ pub trait MemoryBus {
 
 // Eliminamos el trait IoBus separado porque ahora vive dentro de MemoryBus.
 
+// ============================================================================
+//  DEVICE MAP (COMPOSICIÓN DE BUSES)
+// ============================================================================
+
+/// Bus compuesto: despacha cada acceso al sub-bus cuyo rango contiene la dirección.
+/// El sub-bus recibe la dirección relativa al inicio de su rango. Si hay solapamiento
+/// gana el primero mapeado; lo que no cae en ningún rango es bus abierto.
+pub struct DeviceMap {
+    entries: Vec<(RangeInclusive<u32>, Box<dyn MemoryBus>)>,
+    /// Cableado declarado por el mapa (tiene prioridad sobre el del dispositivo)
+    lanes: Vec<(RangeInclusive<u32>, Lanes)>,
+    open_bus: u8,
+}
+
+impl Default for DeviceMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceMap {
+    /// Mapa vacío; el bus abierto devuelve 0xFF.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            lanes: Vec::new(),
+            open_bus: 0xFF,
+        }
+    }
+
+    /// Valor devuelto al leer fuera de cualquier rango.
+    pub fn with_open_bus(mut self, val: u8) -> Self {
+        self.open_bus = val;
+        self
+    }
+
+    /// Añade un dispositivo en `range` (builder).
+    pub fn map(mut self, range: RangeInclusive<u32>, device: Box<dyn MemoryBus>) -> Self {
+        self.entries.push((range, device));
+        self
+    }
+
+    /// Declara el cableado de `range` (p. ej. un chip de 8 bits en el bus de 16).
+    pub fn with_lanes(mut self, range: RangeInclusive<u32>, lanes: Lanes) -> Self {
+        self.lanes.push((range, lanes));
+        self
+    }
+
+    /// Indica si algún dispositivo decodifica la dirección.
+    pub fn is_mapped(&self, addr: u32) -> bool {
+        self.entries.iter().any(|(r, _)| r.contains(&addr))
+    }
+
+    fn lookup(&self, addr: u32) -> Option<(u32, &dyn MemoryBus)> {
+        self.entries
+            .iter()
+            .find(|(r, _)| r.contains(&addr))
+            .map(|(r, d)| (addr - r.start(), d.as_ref()))
+    }
+}
+
+impl MemoryBus for DeviceMap {
+    fn read(&self, addr: u32) -> u8 {
+        match self.lookup(addr) {
+            Some((offset, device)) => device.read(offset),
+            None => self.open_bus,
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        if let Some((r, device)) = self.entries.iter_mut().find(|(r, _)| r.contains(&addr)) {
+            device.write(addr - r.start(), val);
+        }
+    }
+
+    fn lanes(&self, addr: u32) -> Lanes {
+        if let Some((_, lanes)) = self.lanes.iter().find(|(r, _)| r.contains(&addr)) {
+            return *lanes;
+        }
+        self.lookup(addr).map_or(Lanes::Word, |(offset, device)| device.lanes(offset))
+    }
+
+    fn bus_error(&self) -> Option<u32> {
+        self.entries.iter().find_map(|(_, d)| d.bus_error())
+    }
+
+    fn ack_bus_error(&mut self) {
+        for (_, d) in self.entries.iter_mut() {
+            d.ack_bus_error();
+        }
+    }
+}
+
+// ============================================================================
+//  RAM INIT (DETERMINISMO)
+// ============================================================================
+
+/// Patrón de llenado de la RAM al encender.
+/// Algunos juegos leen RAM sin inicializar como fuente de aleatoriedad;
+/// fijar el patrón hace que las ejecuciones sean reproducibles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RamInit {
+    /// Todo a 0x00 (comportamiento histórico de los buses)
+    #[default]
+    Zeros,
+    /// Todo a 0xFF
+    Ones,
+    /// Pseudo-aleatorio (xorshift64*) a partir de una semilla
+    Seeded(u64),
+}
+
+impl RamInit {
+    /// Interpreta el valor de la opción `-ram`: `zeros`, `ones` o `seed:<n>`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "zeros" => Some(RamInit::Zeros),
+            "ones" => Some(RamInit::Ones),
+            _ => s.strip_prefix("seed:")?.parse().ok().map(RamInit::Seeded),
+        }
+    }
+
+    /// Rellena el buffer según el patrón.
+    pub fn fill(&self, buf: &mut [u8]) {
+        match *self {
+            RamInit::Zeros => buf.fill(0x00),
+            RamInit::Ones => buf.fill(0xFF),
+            RamInit::Seeded(seed) => {
+                // xorshift no admite estado 0
+                let mut x = if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed };
+                for b in buf.iter_mut() {
+                    x ^= x >> 12;
+                    x ^= x << 25;
+                    x ^= x >> 27;
+                    *b = (x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8;
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+//  CLOCK (TIEMPO EMULADO)
+// ============================================================================
+
+/// De dónde sale la hora de pared (RTC) que ven los dispositivos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+    /// Hora del host: cómodo para uso normal, pero no reproducible
+    RealTime,
+    /// `epoch` (segundos Unix al encender) + ciclos emulados: determinista (tests, TAS)
+    Emulated { epoch: u64 },
+}
+
+/// Reloj de la máquina emulada. Avanza con los ciclos de CPU; los dispositivos
+/// con noción de tiempo (RTC, timers) lo consultan en vez de leer el host.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    cpu_hz: u64,
+    cycles: u64,
+    source: TimeSource,
+}
+
+impl Clock {
+    pub fn new(cpu_hz: u64, source: TimeSource) -> Self {
+        Self { cpu_hz: cpu_hz.max(1), cycles: 0, source }
+    }
+
+    /// Reloj determinista que arranca en `epoch` (segundos Unix).
+    pub fn emulated(cpu_hz: u64, epoch: u64) -> Self {
+        Self::new(cpu_hz, TimeSource::Emulated { epoch })
+    }
+
+    pub fn real_time(cpu_hz: u64) -> Self {
+        Self::new(cpu_hz, TimeSource::RealTime)
+    }
+
+    pub fn source(&self) -> TimeSource {
+        self.source
+    }
+
+    /// Suma los ciclos de CPU ejecutados.
+    pub fn advance(&mut self, cycles: u32) {
+        self.cycles += cycles as u64;
+    }
+
+    /// Ciclos de CPU desde el encendido.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Tiempo emulado desde el encendido (µs). Siempre determinista.
+    pub fn elapsed_us(&self) -> u64 {
+        (self.cycles as u128 * 1_000_000 / self.cpu_hz as u128) as u64
+    }
+
+    /// Hora de pared en segundos Unix, según la fuente.
+    pub fn unix_seconds(&self) -> u64 {
+        match self.source {
+            TimeSource::RealTime => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            TimeSource::Emulated { epoch } => epoch + self.cycles / self.cpu_hz,
+        }
+    }
+}
+
+// ============================================================================
+//  CO-EJECUCIÓN (MULTI-CPU)
+// ============================================================================
+
+/// Reparte el tiempo entre una CPU principal y una secundaria a distinto reloj
+/// (Genesis: 68000 a MCLK/7 y Z80 a MCLK/15). La principal corre un tramo y la
+/// secundaria se pone al día con su parte proporcional; lo que sobra o falta de
+/// la última instrucción se arrastra al tramo siguiente, así la proporción se
+/// mantiene exacta a largo plazo.
+#[derive(Debug, Clone)]
+pub struct CoExecutor {
+    main_hz: u64,
+    sub_hz: u64,
+    /// Ciclos de la secundaria pendientes, en unidades de 1/main_hz
+    debt: i64,
+    main_cycles: u64,
+    sub_cycles: u64,
+}
+
+impl CoExecutor {
+    pub fn new(main_hz: u64, sub_hz: u64) -> Self {
+        Self { main_hz: main_hz.max(1), sub_hz, debt: 0, main_cycles: 0, sub_cycles: 0 }
+    }
+
+    /// Ejecuta la principal hasta cubrir `main_cycles` y luego la secundaria
+    /// hasta alcanzarla. Ambas reciben el mismo estado (CPUs + bus) y devuelven
+    /// los ciclos de cada paso. Devuelve los ciclos de la principal en el tramo.
+    pub fn run_slice<S>(
+        &mut self,
+        state: &mut S,
+        main_cycles: u32,
+        mut main: impl FnMut(&mut S) -> u32,
+        mut sub: impl FnMut(&mut S) -> u32,
+    ) -> u32 {
+        let mut ran = 0;
+        while ran < main_cycles {
+            // Un paso de 0 ciclos (CPU detenida) no debe colgar el bucle
+            ran += main(state).max(1);
+        }
+        self.main_cycles += ran as u64;
+        self.debt += (ran as u64 * self.sub_hz) as i64;
+        while self.debt > 0 {
+            let c = sub(state).max(1);
+            self.sub_cycles += c as u64;
+            self.debt -= (c as u64 * self.main_hz) as i64;
+        }
+        ran
+    }
+
+    /// Ciclos totales de cada CPU: (principal, secundaria).
+    pub fn cycles(&self) -> (u64, u64) {
+        (self.main_cycles, self.sub_cycles)
+    }
+}
+
+/// CPU de un sistema con dos procesadores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Main,
+    Sub,
+}
+
+/// Bus compartido por dos CPUs. Cada una puede verlo con su propio mapa
+/// (`step_cpu`) y el hardware puede parar a cualquiera (`halted`: BUSREQ,
+/// RESET, HALT externo...). Una CPU parada no ejecuta, pero su tiempo corre.
+pub trait SharedBus: MemoryBus {
+    /// Un paso de `cpu` con el mapa de memoria de `side`. Por defecto, el bus tal cual.
+    fn step_cpu(&mut self, _side: Side, cpu: &mut dyn Cpu) -> u32
+    where
+        Self: Sized,
+    {
+        cpu.step(self)
+    }
+
+    fn halted(&self, _side: Side) -> bool {
+        false
+    }
+}
+
+/// Ciclos que avanza una CPU parada por cada consulta
+const HALTED_STEP_CYCLES: u32 = 4;
+
+/// Planificador de dos `Cpu` sobre un `SharedBus` a distinto reloj: la principal
+/// corre tramos de `granularity` ciclos y la secundaria se pone al día tras cada uno
+/// (ver `CoExecutor`). Granularidad menor = más precisión entre CPUs, más coste.
+#[derive(Debug, Clone)]
+pub struct CoScheduler {
+    co: CoExecutor,
+    granularity: u32,
+}
+
+impl CoScheduler {
+    pub fn new(main_hz: u64, sub_hz: u64, granularity: u32) -> Self {
+        Self { co: CoExecutor::new(main_hz, sub_hz), granularity: granularity.max(1) }
+    }
+
+    /// Ejecuta `main_cycles` ciclos de la principal (y los proporcionales de la
+    /// secundaria). Devuelve los ciclos que corrió la principal.
+    pub fn run<B: SharedBus>(&mut self, main: &mut dyn Cpu, sub: &mut dyn Cpu, bus: &mut B, main_cycles: u32) -> u32 {
+        let step = |side: Side, cpu: &mut dyn Cpu, bus: &mut B| {
+            if bus.halted(side) {
+                HALTED_STEP_CYCLES
+            } else {
+                bus.step_cpu(side, cpu)
+            }
+        };
+        let mut state = (main, sub, bus);
+        let mut ran = 0;
+        while ran < main_cycles {
+            let slice = self.granularity.min(main_cycles - ran);
+            ran += self.co.run_slice(
+                &mut state,
+                slice,
+                |(main, _, bus)| step(Side::Main, &mut **main, bus),
+                |(_, sub, bus)| step(Side::Sub, &mut **sub, bus),
+            );
+        }
+        ran
+    }
+
+    /// Ciclos totales de cada CPU: (principal, secundaria).
+    pub fn cycles(&self) -> (u64, u64) {
+        self.co.cycles()
+    }
+}
+
+// ============================================================================
+//  AUDIO
+// ============================================================================
+
+/// Destino de las muestras que genera un sistema: PCM i16 con los canales
+/// intercalados (estéreo = L, R, L, R...). `Vec<i16>` sirve de sink en tests.
+pub trait AudioSink {
+    fn push_samples(&mut self, samples: &[i16]);
+}
+
+impl AudioSink for Vec<i16> {
+    fn push_samples(&mut self, samples: &[i16]) {
+        self.extend_from_slice(samples);
+    }
+}
+
+// ============================================================================
+//  DISASSEMBLY LISTING
+// ============================================================================
+
+/// Escribe un listado estático `DIRECCIÓN: BYTES MNEMÓNICO` de `start` a `end` (inclusive).
+/// `disasm` es el desensamblador de la CPU: recibe la dirección y devuelve (texto, longitud).
+pub fn write_listing<W, F>(out: &mut W, bus: &dyn MemoryBus, start: u32, end: u32, disasm: F) -> io::Result<()>
+where
+    W: Write,
+    F: Fn(u32, &dyn MemoryBus) -> (String, u32),
+{
+    write_labeled_listing(out, bus, start, end, &SymbolTable::new(), disasm)
+}
+
+/// Como `write_listing`, con una línea `Etiqueta:` antes de cada dirección con símbolo.
+pub fn write_labeled_listing<W, F>(
+    out: &mut W,
+    bus: &dyn MemoryBus,
+    start: u32,
+    end: u32,
+    symbols: &SymbolTable,
+    disasm: F,
+) -> io::Result<()>
+where
+    W: Write,
+    F: Fn(u32, &dyn MemoryBus) -> (String, u32),
+{
+    let width = if end > 0xFFFF { 6 } else { 4 };
+    let mut pc = start;
+    while pc <= end {
+        if let Some(name) = symbols.get(pc) {
+            writeln!(out, "{}:", name)?;
+        }
+        let (mnemonic, len) = disasm(pc, bus);
+        let len = len.max(1);
+        let bytes: String = (0..len)
+            .map(|i| format!("{:02X} ", bus.read(pc.wrapping_add(i))))
+            .collect();
+        writeln!(out, "{:0width$X}: {:<12} {}", pc, bytes, mnemonic, width = width)?;
+        pc = match pc.checked_add(len) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    Ok(())
+}
+
+// ============================================================================
+//  FRAME HASH (TESTS DE REGRESIÓN)
+// ============================================================================
+
+/// CRC-32 (IEEE) de un framebuffer ARGB, píxel a píxel en little-endian.
+/// Pensado para tests "golden frame": comparar el hash en vez del buffer entero.
+pub fn hash_framebuffer(buf: &[u32]) -> u32 {
+    crc32(buf.iter().flat_map(|px| px.to_le_bytes()))
+}
+
+/// CRC-32 (IEEE) de una secuencia de bytes.
+pub fn crc32(bytes: impl IntoIterator<Item = u8>) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
 // ============================================================================
 //  ROM LOADER (UTILIDAD)
 // ============================================================================
@@ -102,6 +623,10 @@ pub enum RomError {
     Io(#[from] std::io::Error),
     #[error("ROM file is too small or empty")]
     Empty,
+    #[error("Invalid patch: {0}")]
+    InvalidPatch(&'static str),
+    #[error("Patch checksum mismatch: {0}")]
+    ChecksumMismatch(&'static str),
 }
 
 pub struct Rom {
@@ -117,6 +642,139 @@ impl Rom {
         Ok(Self { data })
     }
 
+    /// Aplica un parche IPS (traducciones, fixes) a los bytes cargados.
+    /// Llamar antes de construir el bus.
+    pub fn apply_ips(&mut self, patch_path: &str) -> Result<(), RomError> {
+        let patch = fs::read(patch_path)?;
+        self.apply_ips_bytes(&patch)
+    }
+
+    /// Formato IPS clásico: "PATCH", registros (offset de 3 bytes, longitud de 2, datos;
+    /// longitud 0 = RLE: cuenta de 2 bytes + valor) y "EOF", opcionalmente seguido de
+    /// 3 bytes con el tamaño final (truncado). Los registros pueden agrandar la ROM.
+    /// Si el parche es inválido la ROM no se modifica.
+    pub fn apply_ips_bytes(&mut self, patch: &[u8]) -> Result<(), RomError> {
+        let body = patch.strip_prefix(b"PATCH").ok_or(RomError::InvalidPatch("missing PATCH header"))?;
+        let mut data = self.data.clone();
+        let mut pos = 0;
+        let mut take = |n: usize| -> Result<&[u8], RomError> {
+            let bytes = body.get(pos..pos + n).ok_or(RomError::InvalidPatch("truncated record"))?;
+            pos += n;
+            Ok(bytes)
+        };
+        loop {
+            let offset = take(3)?;
+            if offset == b"EOF" {
+                break;
+            }
+            let offset = (offset[0] as usize) << 16 | (offset[1] as usize) << 8 | offset[2] as usize;
+            let len = take(2)?;
+            let len = (len[0] as usize) << 8 | len[1] as usize;
+            // RLE: (cuenta, valor)
+            let rle = if len == 0 {
+                let rle = take(3)?;
+                Some(((rle[0] as usize) << 8 | rle[1] as usize, rle[2]))
+            } else {
+                None
+            };
+            let end = offset + rle.map_or(len, |(count, _)| count);
+            if data.len() < end {
+                data.resize(end, 0);
+            }
+            match rle {
+                Some((_, value)) => data[offset..end].fill(value),
+                None => data[offset..end].copy_from_slice(take(len)?),
+            }
+        }
+        if let Ok(size) = take(3) {
+            data.truncate((size[0] as usize) << 16 | (size[1] as usize) << 8 | size[2] as usize);
+        }
+        self.data = data;
+        Ok(())
+    }
+
+    /// Aplica un parche BPS (delta con CRC-32 de origen, destino y parche).
+    pub fn apply_bps(&mut self, patch_path: &str) -> Result<(), RomError> {
+        let patch = fs::read(patch_path)?;
+        self.apply_bps_bytes(&patch)
+    }
+
+    /// Formato BPS1: tamaños y metadatos en varint, acciones SourceRead / TargetRead /
+    /// SourceCopy / TargetCopy y un pie con los CRC-32 (LE) de origen, destino y parche.
+    /// La ROM cargada debe ser exactamente el origen: si su CRC no coincide no se toca.
+    pub fn apply_bps_bytes(&mut self, patch: &[u8]) -> Result<(), RomError> {
+        if patch.len() < 4 + 12 || !patch.starts_with(b"BPS1") {
+            return Err(RomError::InvalidPatch("missing BPS1 header"));
+        }
+        let footer = patch.len() - 12;
+        let crc_at = |i: usize| u32::from_le_bytes([patch[i], patch[i + 1], patch[i + 2], patch[i + 3]]);
+        if crc32(patch[..footer + 8].iter().copied()) != crc_at(footer + 8) {
+            return Err(RomError::ChecksumMismatch("patch"));
+        }
+        if crc32(self.data.iter().copied()) != crc_at(footer) {
+            return Err(RomError::ChecksumMismatch("source"));
+        }
+
+        let body = &patch[..footer];
+        let source = &self.data;
+        let bad = RomError::InvalidPatch;
+        let mut pos = 4;
+        let source_size = bps_varint(body, &mut pos)?;
+        let target_size = bps_varint(body, &mut pos)?;
+        let metadata_size = bps_varint(body, &mut pos)?;
+        if source_size != source.len() {
+            return Err(bad("source size mismatch"));
+        }
+        pos = pos.saturating_add(metadata_size);
+
+        // `target_size` viene del parche (que trae su propio CRC): no se reserva a
+        // ciegas, un tamaño absurdo fallaría al asignar en vez de dar InvalidPatch
+        let mut target = Vec::with_capacity(target_size.min(source.len().saturating_mul(4)));
+        let (mut source_rel, mut target_rel) = (0usize, 0usize);
+        while pos < footer {
+            let data = bps_varint(body, &mut pos)?;
+            let len = (data >> 2) + 1;
+            let end = target.len().checked_add(len).ok_or(bad("action length overflow"))?;
+            if end > target_size {
+                return Err(bad("action writes past target size"));
+            }
+            match data & 3 {
+                // SourceRead: mismo offset en origen y destino
+                0 => {
+                    target.extend_from_slice(source.get(target.len()..end).ok_or(bad("source read out of range"))?);
+                }
+                // TargetRead: bytes literales del parche
+                1 => {
+                    let next = pos.checked_add(len).ok_or(bad("truncated data"))?;
+                    target.extend_from_slice(body.get(pos..next).ok_or(bad("truncated data"))?);
+                    pos = next;
+                }
+                // SourceCopy / TargetCopy: offset relativo con signo en el bit 0
+                kind => {
+                    let d = bps_varint(body, &mut pos)?;
+                    let rel = if kind == 2 { &mut source_rel } else { &mut target_rel };
+                    *rel = if d & 1 != 0 { rel.checked_sub(d >> 1) } else { rel.checked_add(d >> 1) }
+                        .ok_or(bad("copy offset out of range"))?;
+                    for _ in 0..len {
+                        // TargetCopy puede solaparse con lo que está escribiendo
+                        let byte = if kind == 2 { source.get(*rel) } else { target.get(*rel) };
+                        let byte = *byte.ok_or(bad("copy out of range"))?;
+                        target.push(byte);
+                        *rel += 1;
+                    }
+                }
+            }
+        }
+        if target.len() != target_size {
+            return Err(bad("target size mismatch"));
+        }
+        if crc32(target.iter().copied()) != crc_at(footer + 4) {
+            return Err(RomError::ChecksumMismatch("target"));
+        }
+        self.data = target;
+        Ok(())
+    }
+
     /// Crea una ROM vacía de tamaño fijo (útil para tests)
     pub fn new_empty(size: usize) -> Self {
         Self {
@@ -124,3 +782,19 @@ impl Rom {
         }
     }
 }
+
+/// Entero de longitud variable de BPS (7 bits por byte, el bit 7 marca el último).
+fn bps_varint(body: &[u8], pos: &mut usize) -> Result<usize, RomError> {
+    let overflow = || RomError::InvalidPatch("varint overflow");
+    let (mut value, mut shift) = (0usize, 1usize);
+    loop {
+        let x = *body.get(*pos).ok_or(RomError::InvalidPatch("truncated varint"))?;
+        *pos += 1;
+        value = ((x & 0x7F) as usize).checked_mul(shift).and_then(|v| value.checked_add(v)).ok_or_else(overflow)?;
+        if x & 0x80 != 0 {
+            return Ok(value);
+        }
+        shift = shift.checked_mul(128).ok_or_else(overflow)?;
+        value = value.checked_add(shift).ok_or_else(overflow)?;
+    }
+}
@@ -0,0 +1,148 @@
+// crates/oxide-core/src/psg.rs
+//! SN76489: 3 canales de tono cuadrado y 1 de ruido, atenuación de 4 bits en
+//! pasos de 2 dB. Variante Sega (integrada en el VDP): LFSR de ruido de 16 bits
+//! con taps en los bits 0 y 3, y periodo 0/1 = salida fija.
+//!
+//! Lo comparten la Genesis ($C00011) y la Master System (puertos $7E/$7F).
+
+/// Reloj del PSG (el del Z80 en NTSC)
+pub const PSG_CLOCK: u32 = 3_579_545;
+/// Los contadores avanzan una vez cada 16 ciclos de reloj
+const CLOCK_DIVIDER: u32 = 16;
+/// Valor del LFSR al escribir el registro de ruido
+const NOISE_SEED: u16 = 0x8000;
+
+/// Amplitud por nivel de atenuación (2 dB por paso, 15 = silencio).
+/// Máximo 2047 por canal: los cuatro sumados caben de sobra en i16.
+const VOLUME_TABLE: [i16; 16] = [2047, 1626, 1292, 1026, 815, 647, 514, 408, 324, 258, 205, 163, 129, 103, 81, 0];
+
+pub struct Psg {
+    /// Periodos de tono (10 bits) de los canales 0-2
+    pub tone: [u16; 3],
+    /// Atenuación de los 4 canales (0 = máximo, 15 = silencio)
+    pub volume: [u8; 4],
+    /// Registro de ruido: bit 2 = blanco (1) / periódico (0), bits 1-0 = frecuencia
+    pub noise: u8,
+
+    /// Registro seleccionado por el último latch: canal * 2 + (1 = volumen)
+    latched: usize,
+    counters: [u16; 4],
+    /// Nivel de salida de cada canal (alto / bajo)
+    outputs: [bool; 4],
+    noise_flip: bool,
+    lfsr: u16,
+    /// Ciclos de reloj acumulados para remuestrear a la frecuencia de salida
+    clock_acc: u32,
+}
+
+impl Default for Psg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Psg {
+    pub fn new() -> Self {
+        Self {
+            tone: [0; 3],
+            volume: [0x0F; 4],
+            noise: 0,
+            latched: 0,
+            counters: [0; 4],
+            outputs: [true; 4],
+            noise_flip: false,
+            lfsr: NOISE_SEED,
+            clock_acc: 0,
+        }
+    }
+
+    /// Escritura en el puerto: `1 cc t dddd` = latch (canal, tipo, 4 bits bajos);
+    /// `0 - dddddd` = dato (6 bits altos del tono, o 4 bits de volumen/ruido).
+    pub fn write(&mut self, val: u8) {
+        if val & 0x80 != 0 {
+            self.latched = ((val >> 4) & 0x07) as usize;
+            self.write_register(val & 0x0F, false);
+        } else {
+            self.write_register(val & 0x3F, true);
+        }
+    }
+
+    fn write_register(&mut self, data: u8, data_byte: bool) {
+        let ch = self.latched / 2;
+        match self.latched {
+            0 | 2 | 4 => {
+                self.tone[ch] = if data_byte {
+                    (self.tone[ch] & 0x000F) | ((data as u16) << 4)
+                } else {
+                    (self.tone[ch] & 0x03F0) | data as u16
+                };
+            }
+            6 => {
+                self.noise = data & 0x07;
+                self.lfsr = NOISE_SEED;
+            }
+            _ => self.volume[ch] = data & 0x0F,
+        }
+    }
+
+    fn noise_period(&self) -> u16 {
+        match self.noise & 0x03 {
+            0 => 0x10,
+            1 => 0x20,
+            2 => 0x40,
+            _ => self.tone[2],
+        }
+    }
+
+    /// Un paso de los contadores (cada 16 ciclos de reloj).
+    fn tick(&mut self) {
+        for ch in 0..3 {
+            self.counters[ch] = self.counters[ch].saturating_sub(1);
+            if self.counters[ch] == 0 {
+                self.counters[ch] = self.tone[ch];
+                self.outputs[ch] = self.tone[ch] <= 1 || !self.outputs[ch];
+            }
+        }
+
+        self.counters[3] = self.counters[3].saturating_sub(1);
+        if self.counters[3] == 0 {
+            self.counters[3] = self.noise_period();
+            self.noise_flip = !self.noise_flip;
+            // El LFSR desplaza en cada flanco de subida del contador de ruido
+            if self.noise_flip {
+                let feedback = if self.noise & 0x04 != 0 {
+                    ((self.lfsr & 0x0009).count_ones() & 1) as u16
+                } else {
+                    self.lfsr & 1
+                };
+                self.lfsr = (self.lfsr >> 1) | (feedback << 15);
+                self.outputs[3] = self.lfsr & 1 != 0;
+            }
+        }
+    }
+
+    fn level(&self) -> i32 {
+        (0..4)
+            .map(|ch| {
+                let amp = VOLUME_TABLE[self.volume[ch] as usize] as i32;
+                if self.outputs[ch] { amp } else { -amp }
+            })
+            .sum()
+    }
+
+    /// Rellena `samples` (mono) a `sample_rate`, promediando los pasos de cada muestra.
+    pub fn generate(&mut self, samples: &mut [i16], sample_rate: u32) {
+        let step = sample_rate * CLOCK_DIVIDER;
+        for sample in samples.iter_mut() {
+            self.clock_acc += PSG_CLOCK;
+            let (mut sum, mut n) = (0i32, 0i32);
+            while self.clock_acc >= step {
+                self.clock_acc -= step;
+                self.tick();
+                sum += self.level();
+                n += 1;
+            }
+            *sample = if n > 0 { sum / n } else { self.level() } as i16;
+        }
+    }
+}
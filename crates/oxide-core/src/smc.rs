@@ -0,0 +1,141 @@
+// crates/oxide-core/src/smc.rs
+//! Detector de código automodificable (SMC) para depuración.
+//!
+//! `SmcDetector::step` ejecuta una instrucción con el bus envuelto: anota las
+//! direcciones escritas en el frame y, al terminar, toma como bytes de
+//! instrucción la racha de lecturas consecutivas que empieza en el PC (opcode y
+//! operandos, en el orden en que la CPU los pide). Si alguno se escribió antes
+//! en el mismo frame, se informa con un `SmcEvent`. `new_frame` olvida lo escrito.
+//!
+//! Sirve para cualquier CPU del workspace, sin hooks dentro de los cores.
+
+use crate::{Cpu, Lanes, MemoryBus};
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+/// Instrucción que se ejecutó con bytes escritos en este mismo frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmcEvent {
+    /// PC de la instrucción afectada
+    pub pc: u32,
+    /// Byte de la instrucción que se había modificado
+    pub address: u32,
+}
+
+#[derive(Default)]
+pub struct SmcDetector {
+    written: HashSet<u32>,
+    events: Vec<SmcEvent>,
+    callback: Option<Box<dyn FnMut(SmcEvent)>>,
+}
+
+impl SmcDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Avisa de cada detección además de guardarla en `events`.
+    pub fn set_callback(&mut self, cb: Box<dyn FnMut(SmcEvent)>) {
+        self.callback = Some(cb);
+    }
+
+    /// Empieza un frame: lo escrito hasta ahora deja de contar.
+    pub fn new_frame(&mut self) {
+        self.written.clear();
+    }
+
+    /// Detecciones desde la creación o el último `take_events`.
+    pub fn events(&self) -> &[SmcEvent] {
+        &self.events
+    }
+
+    pub fn take_events(&mut self) -> Vec<SmcEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// `cpu.step` a través del bus vigilado. Devuelve los ciclos de la instrucción.
+    pub fn step(&mut self, cpu: &mut dyn Cpu, bus: &mut dyn MemoryBus) -> u32 {
+        let pc = cpu.pc();
+        let mut watch = WatchBus { inner: bus, reads: RefCell::new(Vec::new()), writes: Vec::new() };
+        let cycles = cpu.step(&mut watch);
+        let WatchBus { reads, writes, .. } = watch;
+
+        // Bytes de la instrucción: lecturas consecutivas desde el PC
+        let mut next = pc;
+        for addr in reads.into_inner() {
+            if addr != next {
+                break;
+            }
+            next = next.wrapping_add(1);
+            if self.written.contains(&addr) {
+                let event = SmcEvent { pc, address: addr };
+                self.events.push(event);
+                if let Some(cb) = self.callback.as_mut() {
+                    cb(event);
+                }
+            }
+        }
+
+        self.written.extend(writes);
+        cycles
+    }
+}
+
+/// Bus que anota lecturas y escrituras y delega todo en el bus real.
+struct WatchBus<'a> {
+    inner: &'a mut dyn MemoryBus,
+    reads: RefCell<Vec<u32>>,
+    writes: Vec<u32>,
+}
+
+impl MemoryBus for WatchBus<'_> {
+    fn read(&self, addr: u32) -> u8 {
+        self.reads.borrow_mut().push(addr);
+        self.inner.read(addr)
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        self.writes.push(addr);
+        self.inner.write(addr, val);
+    }
+
+    fn port_in(&mut self, port: u16) -> u8 {
+        self.inner.port_in(port)
+    }
+
+    fn port_out(&mut self, port: u16, val: u8) {
+        self.inner.port_out(port, val);
+    }
+
+    fn port_in_u16(&mut self, port: u16) -> u16 {
+        self.inner.port_in_u16(port)
+    }
+
+    fn port_out_u16(&mut self, port: u16, val: u16) {
+        self.inner.port_out_u16(port, val);
+    }
+
+    fn interrupt_ack(&mut self) -> u8 {
+        self.inner.interrupt_ack()
+    }
+
+    fn int_asserted(&self) -> bool {
+        self.inner.int_asserted()
+    }
+
+    fn refresh(&mut self, addr: u16) {
+        self.inner.refresh(addr);
+    }
+
+    fn lanes(&self, addr: u32) -> Lanes {
+        self.inner.lanes(addr)
+    }
+
+    fn bus_error(&self) -> Option<u32> {
+        self.inner.bus_error()
+    }
+
+    fn ack_bus_error(&mut self) {
+        self.inner.ack_bus_error();
+    }
+}
@@ -0,0 +1,114 @@
+// crates/oxide-core/src/state.rs
+//! Instantáneas binarias de CPU (`Cpu::save_state` / `Cpu::load_state`).
+//!
+//! Formato común: 4 bytes mágicos propios de cada núcleo, 1 byte de versión y
+//! los campos en little endian, en el orden que fije cada CPU. Un cambio de
+//! layout sube la versión: las instantáneas viejas se rechazan en vez de
+//! cargarse a medias.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum StateError {
+    #[error("Not a {0} state snapshot")]
+    BadMagic(&'static str),
+    #[error("Unsupported state version {found} (expected {expected})")]
+    Version { found: u8, expected: u8 },
+    #[error("State snapshot is truncated")]
+    Truncated,
+    #[error("Trailing bytes after state snapshot")]
+    TrailingBytes,
+    #[error("Invalid state field: {0}")]
+    InvalidField(&'static str),
+}
+
+/// Escribe la cabecera y los campos de una instantánea.
+pub struct StateWriter {
+    buf: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new(magic: &[u8; 4], version: u8) -> Self {
+        let mut buf = magic.to_vec();
+        buf.push(version);
+        Self { buf }
+    }
+
+    pub fn u8(&mut self, v: u8) -> &mut Self {
+        self.buf.push(v);
+        self
+    }
+
+    pub fn bool(&mut self, v: bool) -> &mut Self {
+        self.u8(v as u8)
+    }
+
+    pub fn u16(&mut self, v: u16) -> &mut Self {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    pub fn u32(&mut self, v: u32) -> &mut Self {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    pub fn finish(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+/// Lee una instantánea validando cabecera, versión y longitud.
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> StateReader<'a> {
+    /// `name` solo se usa en el mensaje de `BadMagic`.
+    pub fn new(data: &'a [u8], magic: &[u8; 4], version: u8, name: &'static str) -> Result<Self, StateError> {
+        if data.get(..4) != Some(&magic[..]) {
+            return Err(StateError::BadMagic(name));
+        }
+        let found = *data.get(4).ok_or(StateError::Truncated)?;
+        if found != version {
+            return Err(StateError::Version { found, expected: version });
+        }
+        Ok(Self { data, pos: 5 })
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], StateError> {
+        let bytes = self.data.get(self.pos..self.pos + N).ok_or(StateError::Truncated)?;
+        self.pos += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    pub fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, StateError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(StateError::InvalidField("bool")),
+        }
+    }
+
+    pub fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    /// Comprueba que no sobran bytes (layout distinto con la misma versión).
+    pub fn finish(self) -> Result<(), StateError> {
+        if self.pos == self.data.len() {
+            Ok(())
+        } else {
+            Err(StateError::TrailingBytes)
+        }
+    }
+}
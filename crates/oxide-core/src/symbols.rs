@@ -0,0 +1,90 @@
+// crates/oxide-core/src/symbols.rs
+//! Tablas de símbolos para desensamblado y trazas: `JSR InitVideo` en vez de
+//! `JSR $0402`.
+//!
+//! Formato del fichero, un símbolo por línea (dirección en hex, con o sin `$`/`0x`):
+//!
+//! ```text
+//! # comentario (también ';')
+//! 0402 InitVideo
+//! $A9F0 _LoadSeg            <- el mismo formato sirve para nombres de traps del Mac
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SymbolError {
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: {msg}")]
+    Parse { line: usize, msg: String },
+}
+
+/// Dirección -> nombre. Con varias entradas para la misma dirección gana la última.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolTable {
+    names: BTreeMap<u32, String>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SymbolError> {
+        let mut table = Self::new();
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            let err = |msg: &str| SymbolError::Parse { line: i + 1, msg: msg.into() };
+            let mut words = line.split_whitespace();
+            let addr = words.next().unwrap_or_default();
+            let digits = addr.trim_start_matches('$').trim_start_matches("0x");
+            let addr = u32::from_str_radix(digits, 16).map_err(|_| err("bad hex address"))?;
+            let name = words.next().ok_or_else(|| err("missing symbol name"))?;
+            if words.next().is_some() {
+                return Err(err("symbol names cannot contain spaces"));
+            }
+            table.insert(addr, name);
+        }
+        Ok(table)
+    }
+
+    pub fn insert(&mut self, addr: u32, name: &str) {
+        self.names.insert(addr, name.to_string());
+    }
+
+    /// Añade los símbolos de `other` (p. ej. un mapa de usuario sobre los de la ROM).
+    pub fn merge(&mut self, other: SymbolTable) {
+        self.names.extend(other.names);
+    }
+
+    pub fn get(&self, addr: u32) -> Option<&str> {
+        self.names.get(&addr).map(String::as_str)
+    }
+
+    /// Nombre de `addr`, o `$XXXX` con al menos `digits` cifras si no tiene.
+    pub fn name_or_hex(&self, addr: u32, digits: usize) -> String {
+        match self.get(addr) {
+            Some(name) => name.to_string(),
+            None => format!("${:0digits$X}", addr, digits = digits),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+pub fn load_symbols<P: AsRef<Path>>(path: P) -> Result<SymbolTable, SymbolError> {
+    SymbolTable::parse(&fs::read_to_string(path)?)
+}
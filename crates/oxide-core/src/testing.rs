@@ -0,0 +1,117 @@
+// crates/oxide-core/src/testing.rs
+//! Bus de pruebas compartido por los tests de los cores (feature `testing`).
+
+use crate::MemoryBus;
+use std::collections::{HashMap, VecDeque};
+use std::ops::RangeInclusive;
+
+type IoRead = Box<dyn Fn(u32) -> u8>;
+
+/// Bus de memoria plano para tests de integración de CPU:
+/// memoria precargable, registro de escrituras, respuestas de puerto programables
+/// y callbacks de lectura para I/O mapeado en memoria.
+pub struct MockBus {
+    /// Memoria; las direcciones se reducen módulo su tamaño
+    pub mem: Vec<u8>,
+    /// Escrituras a memoria en orden: (dirección, valor)
+    pub writes: Vec<(u32, u8)>,
+    /// Escrituras a puertos en orden: (puerto, valor)
+    pub port_writes: Vec<(u16, u8)>,
+    /// Byte devuelto en el reconocimiento de interrupción
+    pub int_ack: u8,
+    /// Direcciones de refresco (I/R) del Z80 en orden
+    pub refreshes: Vec<u16>,
+    port_mask: u16,
+    port_queue: HashMap<u16, VecDeque<u8>>,
+    port_value: HashMap<u16, u8>,
+    io_reads: Vec<(RangeInclusive<u32>, IoRead)>,
+}
+
+impl MockBus {
+    /// Bus de `size` bytes a cero.
+    pub fn new(size: usize) -> Self {
+        Self {
+            mem: vec![0; size],
+            writes: Vec::new(),
+            port_writes: Vec::new(),
+            int_ack: 0xFF,
+            refreshes: Vec::new(),
+            port_mask: 0xFFFF,
+            port_queue: HashMap::new(),
+            port_value: HashMap::new(),
+            io_reads: Vec::new(),
+        }
+    }
+
+    /// Copia `data` a partir de `addr`.
+    pub fn load(&mut self, addr: u32, data: &[u8]) -> &mut Self {
+        let start = addr as usize;
+        self.mem[start..start + data.len()].copy_from_slice(data);
+        self
+    }
+
+    /// Decodificación parcial de puertos (p.ej. 0x00FF en sistemas que solo miran A0-A7).
+    pub fn with_port_mask(mut self, mask: u16) -> Self {
+        self.port_mask = mask;
+        self
+    }
+
+    /// Valor fijo devuelto por `port` (tras agotar los encolados).
+    pub fn set_port(&mut self, port: u16, val: u8) -> &mut Self {
+        self.port_value.insert(port & self.port_mask, val);
+        self
+    }
+
+    /// Valores de un solo uso devueltos por `port` en orden.
+    pub fn queue_port(&mut self, port: u16, vals: &[u8]) -> &mut Self {
+        self.port_queue
+            .entry(port & self.port_mask)
+            .or_default()
+            .extend(vals.iter().copied());
+        self
+    }
+
+    /// Las lecturas dentro de `range` las responde `read` (recibe la dirección absoluta).
+    pub fn map_io<F>(&mut self, range: RangeInclusive<u32>, read: F) -> &mut Self
+    where
+        F: Fn(u32) -> u8 + 'static,
+    {
+        self.io_reads.push((range, Box::new(read)));
+        self
+    }
+}
+
+impl MemoryBus for MockBus {
+    fn read(&self, addr: u32) -> u8 {
+        if let Some((_, f)) = self.io_reads.iter().find(|(r, _)| r.contains(&addr)) {
+            return f(addr);
+        }
+        self.mem[addr as usize % self.mem.len()]
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        self.writes.push((addr, val));
+        let len = self.mem.len();
+        self.mem[addr as usize % len] = val;
+    }
+
+    fn port_in(&mut self, port: u16) -> u8 {
+        let port = port & self.port_mask;
+        if let Some(v) = self.port_queue.get_mut(&port).and_then(|q| q.pop_front()) {
+            return v;
+        }
+        self.port_value.get(&port).copied().unwrap_or(0xFF)
+    }
+
+    fn port_out(&mut self, port: u16, val: u8) {
+        self.port_writes.push((port, val));
+    }
+
+    fn interrupt_ack(&mut self) -> u8 {
+        self.int_ack
+    }
+
+    fn refresh(&mut self, addr: u16) {
+        self.refreshes.push(addr);
+    }
+}
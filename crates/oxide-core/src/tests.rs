@@ -0,0 +1,634 @@
+// crates/oxide-core/src/tests.rs
+use crate::*;
+
+#[test]
+fn test_ram_init_patterns() {
+    let mut buf = [0x55u8; 16];
+    RamInit::Zeros.fill(&mut buf);
+    assert!(buf.iter().all(|&b| b == 0x00));
+    RamInit::Ones.fill(&mut buf);
+    assert!(buf.iter().all(|&b| b == 0xFF));
+}
+
+#[test]
+fn test_ram_init_seeded_is_reproducible() {
+    let mut a = [0u8; 8];
+    let mut b = [0u8; 8];
+    RamInit::Seeded(1).fill(&mut a);
+    RamInit::Seeded(1).fill(&mut b);
+    assert_eq!(a, b);
+    assert_eq!(a, [0x47, 0xAB, 0xB9, 0x4D, 0x0E, 0xC8, 0xD0, 0xAC]);
+
+    RamInit::Seeded(2).fill(&mut b);
+    assert_ne!(a, b);
+}
+
+#[test]
+fn test_ram_init_parse() {
+    assert_eq!(RamInit::parse("zeros"), Some(RamInit::Zeros));
+    assert_eq!(RamInit::parse("ones"), Some(RamInit::Ones));
+    assert_eq!(RamInit::parse("seed:42"), Some(RamInit::Seeded(42)));
+    assert_eq!(RamInit::parse("seed:x"), None);
+    assert_eq!(RamInit::parse("random"), None);
+}
+
+struct ListBus([u8; 8]);
+impl MemoryBus for ListBus {
+    fn read(&self, addr: u32) -> u8 { self.0[(addr & 7) as usize] }
+    fn write(&mut self, _addr: u32, _val: u8) {}
+}
+
+#[test]
+fn test_write_listing() {
+    // Desensamblador de juguete: 0x00 = NOP (1 byte), resto = LD A,n (2 bytes)
+    let bus = ListBus([0x00, 0x3E, 0x42, 0x00, 0, 0, 0, 0]);
+    let mut out = Vec::new();
+    write_listing(&mut out, &bus, 0, 3, |pc, bus| match bus.read(pc) {
+        0x00 => ("NOP".into(), 1),
+        _ => (format!("LD A, ${:02X}", bus.read(pc + 1)), 2),
+    })
+    .unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].trim_end(), "0000: 00           NOP");
+    assert_eq!(lines[1].trim_end(), "0001: 3E 42        LD A, $42");
+    assert_eq!(lines[2].trim_end(), "0003: 00           NOP");
+}
+
+#[test]
+fn test_symbol_file_and_labeled_listing() {
+    let text = "# mapa de prueba\n0001 Start\n$0003  Done\n; otro comentario\n0x0003 End\n";
+    let symbols = SymbolTable::parse(text).unwrap();
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols.get(0x0001), Some("Start"));
+    assert_eq!(symbols.get(0x0003), Some("End"), "gana la última entrada");
+    assert_eq!(symbols.name_or_hex(0x42, 4), "$0042");
+
+    assert!(matches!(SymbolTable::parse("zz Foo"), Err(symbols::SymbolError::Parse { line: 1, .. })));
+    assert!(matches!(SymbolTable::parse("\n1000"), Err(symbols::SymbolError::Parse { line: 2, .. })));
+
+    let path = std::env::temp_dir().join(format!("oxide_core_{}.sym", std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    let loaded = load_symbols(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded, symbols);
+
+    let bus = ListBus([0x00, 0x3E, 0x42, 0x00, 0, 0, 0, 0]);
+    let mut out = Vec::new();
+    write_labeled_listing(&mut out, &bus, 0, 3, &symbols, |pc, bus| match bus.read(pc) {
+        0x00 => ("NOP".into(), 1),
+        _ => (format!("LD A, ${:02X}", bus.read(pc + 1)), 2),
+    })
+    .unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    assert_eq!(lines, ["0000: 00           NOP", "Start:", "0001: 3E 42        LD A, $42", "End:", "0003: 00           NOP"]);
+}
+
+// ============================================================================
+// DeviceMap
+// ============================================================================
+
+struct Ram(Vec<u8>);
+impl MemoryBus for Ram {
+    fn read(&self, addr: u32) -> u8 {
+        self.0[addr as usize]
+    }
+    fn write(&mut self, addr: u32, val: u8) {
+        self.0[addr as usize] = val;
+    }
+}
+
+#[test]
+fn test_device_map_dispatches_by_range() {
+    let rom = Ram((0..16).collect());
+    let mut map = DeviceMap::new()
+        .map(0x0000..=0x000F, Box::new(rom))
+        .map(0x1000..=0x13FF, Box::new(Ram(vec![0; 0x400])));
+
+    assert_eq!(map.read(0x0003), 3);
+    map.write(0x1010, 0xAB);
+    assert_eq!(map.read(0x1010), 0xAB);
+    assert_eq!(map.read(0x0010), 0xFF); // hueco -> bus abierto
+    map.write(0x2000, 0x12); // ignorada
+    assert!(map.is_mapped(0x13FF));
+    assert!(!map.is_mapped(0x1400));
+}
+
+#[test]
+fn test_device_map_relative_offsets_and_open_bus() {
+    let mut map = DeviceMap::new()
+        .with_open_bus(0x00)
+        .map(0x8000..=0x8003, Box::new(Ram(vec![0; 4])));
+    map.write(0x8002, 0x5A);
+    assert_eq!(map.read(0x8002), 0x5A);
+    assert_eq!(map.read(0x9000), 0x00);
+}
+
+#[test]
+fn test_device_map_lanes_shape_word_accesses() {
+    let mut map = DeviceMap::new()
+        .map(0x0000..=0x0003, Box::new(Ram(vec![0x12, 0x34, 0x56, 0x78])))
+        .map(0x1000..=0x1003, Box::new(Ram(vec![0x12, 0x34, 0x56, 0x78])))
+        .with_lanes(0x1000..=0x1003, Lanes::Byte)
+        .map(0x2000..=0x2003, Box::new(Ram(vec![0x12, 0x34, 0x56, 0x78])))
+        .with_lanes(0x2000..=0x2003, Lanes::ByteSwapped);
+
+    assert_eq!(map.read_u16_be(0x0000), 0x1234);
+    assert_eq!(map.read_u16_be(0x1000), 0x1212);
+    assert_eq!(map.read_u32_be(0x1000), 0x1212_5656);
+    assert_eq!(map.read_u16_be(0x2000), 0x3412);
+
+    map.write_u16_be(0x1000, 0xABCD); // solo el byte alto
+    assert_eq!((map.read(0x1000), map.read(0x1001)), (0xAB, 0x34));
+    map.write_u16_be(0x2002, 0xABCD);
+    assert_eq!((map.read(0x2002), map.read(0x2003)), (0xCD, 0xAB));
+}
+
+// ============================================================================
+// MockBus
+// ============================================================================
+
+#[test]
+fn test_mock_bus_records_and_maps_io() {
+    use crate::testing::MockBus;
+    let mut bus = MockBus::new(0x100);
+    bus.load(0x10, &[1, 2, 3]).map_io(0x80..=0x8F, |a| a as u8 ^ 0xFF);
+    assert_eq!(bus.read(0x11), 2);
+    assert_eq!(bus.read(0x81), 0x7E);
+    bus.write(0x20, 0x55);
+    assert_eq!(bus.writes, vec![(0x20, 0x55)]);
+    assert_eq!(bus.port_in(0x10), 0xFF); // sin programar -> bus flotante
+}
+
+#[test]
+fn test_port_word_access_composes_byte_ports() {
+    use crate::testing::MockBus;
+    let mut bus = MockBus::new(0x100);
+    bus.set_port(0x00BE, 0x34).set_port(0x00BF, 0x12);
+    assert_eq!(bus.port_in_u16(0x00BE), 0x1234);
+    bus.port_out_u16(0xFFFF, 0xABCD); // el byte alto va a 0x0000
+    assert_eq!(bus.port_writes, vec![(0xFFFF, 0xCD), (0x0000, 0xAB)]);
+}
+
+// ============================================================================
+// Frame hash
+// ============================================================================
+
+#[test]
+fn test_hash_framebuffer() {
+    let a = vec![0xFF00_0000u32; 256 * 192];
+    let mut b = a.clone();
+    assert_eq!(hash_framebuffer(&a), hash_framebuffer(&b));
+
+    b[1000] = 0xFF00_0001;
+    assert_ne!(hash_framebuffer(&a), hash_framebuffer(&b));
+
+    // 0x34333231 en little-endian son los bytes "1234": CRC32("1234") = 0x9BE3E0A3
+    assert_eq!(hash_framebuffer(&[0x3433_3231]), 0x9BE3_E0A3);
+}
+
+#[test]
+fn test_emulated_clock_advances_with_cycles() {
+    let mut clock = Clock::emulated(1_000_000, 1_000);
+    assert_eq!(clock.unix_seconds(), 1_000);
+
+    for _ in 0..2_500 {
+        clock.advance(1_000); // 2.5 s a 1 MHz
+    }
+    assert_eq!(clock.cycles(), 2_500_000);
+    assert_eq!(clock.elapsed_us(), 2_500_000);
+    assert_eq!(clock.unix_seconds(), 1_002);
+
+    // Mismos ciclos, misma hora: reproducible
+    let mut other = Clock::emulated(1_000_000, 1_000);
+    other.advance(2_500_000u32);
+    assert_eq!(other.unix_seconds(), clock.unix_seconds());
+}
+
+#[test]
+fn test_real_time_clock_reads_host() {
+    let clock = Clock::real_time(7_833_600);
+    assert_eq!(clock.source(), TimeSource::RealTime);
+    assert!(clock.unix_seconds() > 1_600_000_000);
+    assert_eq!(clock.elapsed_us(), 0);
+}
+
+// ============================================================================
+// Debug server (feature `debug-server`)
+// ============================================================================
+
+// ============================================================================
+// Cheats (POKEs)
+// ============================================================================
+
+#[test]
+fn test_parse_pokes_formats() {
+    let cheats = cheats::parse_pokes(
+        "# Jet Set Willy\n\
+         POKE 35899,0 Vidas infinitas\n\
+         $C0A4, 9   ; hex con $\n\
+         !0x8F00 255 Nivel secreto\n\n",
+    )
+    .unwrap();
+    assert_eq!(cheats.len(), 3);
+    assert_eq!(cheats[0], Cheat { address: 35899, value: 0, enabled: true, description: "Vidas infinitas".into() });
+    assert_eq!((cheats[1].address, cheats[1].value, cheats[1].description.as_str()), (0xC0A4, 9, ""));
+    assert_eq!((cheats[2].address, cheats[2].value, cheats[2].enabled), (0x8F00, 255, false));
+
+    let err = cheats::parse_pokes("POKE 100,1\nPOKE 200,256").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: bad value '256'");
+    assert!(cheats::parse_pokes("poke zz,1").is_err());
+}
+
+#[test]
+fn test_apply_cheats_keeps_ram_pinned() {
+    use crate::testing::MockBus;
+
+    let mut bus = MockBus::new(0x10000);
+    let mut cheats = vec![Cheat::new(0x8000, 3), Cheat::new(0x8001, 0x99)];
+    cheats[1].enabled = false;
+
+    // El "juego" resta una vida por frame; el truco la repone al final de cada frame
+    for _ in 0..5 {
+        let lives = bus.read(0x8000);
+        bus.write(0x8000, lives.wrapping_sub(1));
+        bus.apply_cheats(&cheats);
+        assert_eq!(bus.read(0x8000), 3);
+    }
+    assert_eq!(bus.read(0x8001), 0x00, "deshabilitado");
+}
+
+#[test]
+fn test_ram_scanner_finds_lives_counter() {
+    use crate::cheats::{Comparison, RamScanner};
+    use crate::testing::MockBus;
+
+    // Ruido: un contador que sube cada frame, un timer que baja, y las vidas en $C123
+    let mut bus = MockBus::new(0x10000);
+    let frame = |bus: &mut MockBus, n: u8| {
+        bus.write(0xC010, n);
+        bus.write(0xC200, 200 - n);
+    };
+    frame(&mut bus, 0);
+    bus.write(0xC123, 3);
+
+    let mut scanner = RamScanner::new(0xC000..=0xC3FF);
+    assert_eq!(scanner.filter(Comparison::Less), None, "sin instantáneas");
+    scanner.snapshot(&bus);
+    assert_eq!(scanner.filter(Comparison::Changed), None, "falta la anterior");
+    assert_eq!(scanner.filter(Comparison::Value(3)), Some(1));
+    scanner.reset();
+
+    scanner.snapshot(&bus);
+    frame(&mut bus, 1);
+    bus.write(0xC123, 2); // perdemos una vida
+    scanner.snapshot(&bus);
+    assert_eq!(scanner.filter(Comparison::Less), Some(2)); // vidas y timer
+
+    frame(&mut bus, 2); // sin perder vidas
+    scanner.snapshot(&bus);
+    assert_eq!(scanner.filter(Comparison::Equal), Some(1));
+    assert_eq!(scanner.candidates(), vec![0xC123]);
+
+    frame(&mut bus, 3);
+    bus.write(0xC123, 3); // vida extra
+    scanner.snapshot(&bus);
+    assert_eq!(scanner.filter(Comparison::Greater), Some(1));
+    assert_eq!(scanner.filter(Comparison::Changed), Some(1));
+}
+
+#[test]
+fn test_co_executor_keeps_clock_ratio() {
+    // 7:3 con instrucciones de duración irregular en ambos lados
+    let mut co = CoExecutor::new(7_000, 3_000);
+    let mut steps = (0u32, 0u32);
+    for _ in 0..100 {
+        co.run_slice(
+            &mut steps,
+            500,
+            |s| { s.0 += 1; 4 + s.0 % 7 * 2 },
+            |s| { s.1 += 1; 4 + s.1 % 3 * 3 },
+        );
+    }
+    let (main, sub) = co.cycles();
+    assert!(main >= 50_000);
+    // La secundaria nunca se queda atrás y solo se adelanta una instrucción
+    let expected = main * 3 / 7;
+    assert!(sub >= expected && sub < expected + 11, "{} vs {}", sub, expected);
+}
+
+/// CPU de prueba: cada paso cuesta `cost` ciclos. Si `halt_at` llega, escribe 1 en
+/// $0000 (la "línea BUSREQ" del bus de prueba) al dar ese paso.
+struct StubCpu {
+    cost: u32,
+    steps: u32,
+    halt_at: Option<u32>,
+}
+
+impl Cpu for StubCpu {
+    fn reset(&mut self) {}
+    fn step(&mut self, bus: &mut dyn MemoryBus) -> u32 {
+        self.steps += 1;
+        if self.halt_at == Some(self.steps) {
+            bus.write(0, 1);
+        }
+        self.cost
+    }
+    fn pc(&self) -> u32 {
+        self.steps
+    }
+}
+
+/// Bus de prueba: el byte $0000 para a la CPU secundaria.
+struct BusReqBus([u8; 1]);
+
+impl MemoryBus for BusReqBus {
+    fn read(&self, _addr: u32) -> u8 {
+        self.0[0]
+    }
+    fn write(&mut self, _addr: u32, val: u8) {
+        self.0[0] = val;
+    }
+}
+
+impl SharedBus for BusReqBus {
+    fn halted(&self, side: Side) -> bool {
+        side == Side::Sub && self.0[0] != 0
+    }
+}
+
+#[test]
+fn test_co_scheduler_ratio_and_sub_halt() {
+    let mut main = StubCpu { cost: 8, steps: 0, halt_at: None };
+    let mut sub = StubCpu { cost: 4, steps: 0, halt_at: None };
+    let mut bus = BusReqBus([0]);
+    let mut sched = CoScheduler::new(8_000_000, 4_000_000, 64);
+    assert_eq!(sched.run(&mut main, &mut sub, &mut bus, 6400), 6400);
+    assert_eq!((main.steps, sub.steps), (800, 800));
+    assert_eq!(sched.cycles(), (6400, 3200));
+
+    // La principal pide el bus de la secundaria: deja de dar pasos pero su tiempo corre
+    main.halt_at = Some(main.steps + 1);
+    sched.run(&mut main, &mut sub, &mut bus, 6400);
+    assert!(sub.steps <= 800 + 8, "{}", sub.steps); // como mucho el primer tramo
+    assert_eq!(sched.cycles(), (12800, 6400));
+
+    bus.0[0] = 0;
+    let before = sub.steps;
+    sched.run(&mut main, &mut sub, &mut bus, 640);
+    assert_eq!(sub.steps - before, 80);
+}
+
+#[cfg(feature = "debug-server")]
+mod debug_server {
+    use crate::debug_server::*;
+
+    /// CPU ficticia: avanza el PC de 2 en 2 sobre 256 bytes de memoria.
+    struct FakeMachine {
+        pc: u32,
+        mem: [u8; 256],
+    }
+
+    impl DebugTarget for FakeMachine {
+        fn registers(&self) -> Vec<(&'static str, u32)> {
+            vec![("PC", self.pc), ("A", self.mem[0] as u32)]
+        }
+        fn pc(&self) -> u32 {
+            self.pc
+        }
+        fn read_mem(&self, addr: u32) -> u8 {
+            self.mem[(addr & 0xFF) as usize]
+        }
+        fn write_mem(&mut self, addr: u32, val: u8) {
+            self.mem[(addr & 0xFF) as usize] = val;
+        }
+        fn step(&mut self) -> u32 {
+            self.pc = (self.pc + 2) & 0xFF;
+            4
+        }
+    }
+
+    fn session(script: &str) -> (Vec<String>, bool) {
+        let mut target = FakeMachine { pc: 0, mem: [0; 256] };
+        let mut server = DebugServer::new().with_continue_limit(1000);
+        let mut out = Vec::new();
+        let quit = server.serve(&mut target, script.as_bytes(), &mut out).unwrap();
+        let lines = String::from_utf8(out).unwrap().lines().map(String::from).collect();
+        (lines, quit)
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(Command::parse("read 4000 8"), Ok(Command::Read { addr: 0x4000, len: 8 }));
+        assert_eq!(Command::parse("m $10"), Ok(Command::Read { addr: 0x10, len: 16 }));
+        assert_eq!(Command::parse("w 0x20 AA bb"), Ok(Command::Write { addr: 0x20, bytes: vec![0xAA, 0xBB] }));
+        assert_eq!(Command::parse("step"), Ok(Command::Step(1)));
+        assert_eq!(Command::parse("s 10"), Ok(Command::Step(10)));
+        assert_eq!(Command::parse("BREAK 38"), Ok(Command::Break(0x38)));
+        assert_eq!(Command::parse("next"), Ok(Command::Next));
+        assert_eq!(Command::parse("n"), Ok(Command::Next));
+        assert_eq!(Command::parse("bt"), Ok(Command::Stack));
+        assert!(Command::parse("write 20 100").is_err());
+        assert!(Command::parse("read zz").is_err());
+        assert!(Command::parse("read 0 0").is_err());
+        assert!(Command::parse("regs extra").is_err());
+        assert!(Command::parse("jump 0").is_err());
+    }
+
+    #[test]
+    fn test_session_memory_and_registers() {
+        let (lines, quit) = session("write 0 12 34\nread 0 3\nregs\nbogus\n\nquit\nregs\n");
+        assert_eq!(lines, ["OK", "12 34 00", "PC=0 A=12", "ERR unknown command 'bogus'", "BYE"]);
+        assert!(quit);
+    }
+
+    #[test]
+    fn test_session_breakpoints_step_and_continue() {
+        let (lines, quit) = session(
+            "break 10\nstep 3\ncontinue\ncontinue\ndelete 10\ndelete 10\ncontinue\n",
+        );
+        assert_eq!(
+            lines,
+            [
+                "OK",
+                "PC=6",
+                "BREAK 10",
+                "BREAK 10", // da la vuelta a los 256 bytes y vuelve a parar
+                "OK",
+                "ERR no breakpoint at 10",
+                "RUNNING E0", // sin breakpoints: se corta en el límite (1000 pasos)
+            ]
+        );
+        assert!(!quit, "fin de la entrada sin quit");
+    }
+}
+
+
+// ============================================================================
+// Detector SMC
+// ============================================================================
+
+/// CPU de prueba que solo hace E/S de 16 bits: IN del puerto $10 y OUT de lo leído + 1.
+struct WordPortCpu;
+
+impl Cpu for WordPortCpu {
+    fn reset(&mut self) {}
+    fn step(&mut self, bus: &mut dyn MemoryBus) -> u32 {
+        let v = bus.port_in_u16(0x10);
+        bus.port_out_u16(0x10, v.wrapping_add(1));
+        4
+    }
+    fn pc(&self) -> u32 {
+        0
+    }
+}
+
+/// Bus con un puerto de 16 bits nativo: no se descompone en dos bytes.
+#[derive(Default)]
+struct WordPortBus {
+    word: u16,
+    byte_accesses: u32,
+}
+
+impl MemoryBus for WordPortBus {
+    fn read(&self, _addr: u32) -> u8 {
+        0
+    }
+    fn write(&mut self, _addr: u32, _val: u8) {}
+    fn port_in(&mut self, _port: u16) -> u8 {
+        self.byte_accesses += 1;
+        0xFF
+    }
+    fn port_out(&mut self, _port: u16, _val: u8) {
+        self.byte_accesses += 1;
+    }
+    fn port_in_u16(&mut self, _port: u16) -> u16 {
+        self.word
+    }
+    fn port_out_u16(&mut self, _port: u16, val: u16) {
+        self.word = val;
+    }
+}
+
+#[test]
+fn test_smc_detector_forwards_word_port_io() {
+    let mut bus = WordPortBus { word: 0x1233, ..Default::default() };
+    let mut smc = crate::smc::SmcDetector::new();
+    assert_eq!(smc.step(&mut WordPortCpu, &mut bus), 4);
+    assert_eq!(bus.word, 0x1234);
+    assert_eq!(bus.byte_accesses, 0);
+}
+
+// ============================================================================
+// Parches IPS
+// ============================================================================
+
+#[test]
+fn test_ips_patch_records_rle_and_growth() {
+    let mut rom = Rom { data: vec![0u8; 8] };
+    let mut patch = b"PATCH".to_vec();
+    patch.extend_from_slice(&[0x00, 0x00, 0x02, 0x00, 0x02, 0xAB, 0xCD]); // $0002: AB CD
+    patch.extend_from_slice(&[0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x03, 0xEE]); // RLE 3 x EE en $0005
+    patch.extend_from_slice(&[0x00, 0x00, 0x0A, 0x00, 0x01, 0x77]); // más allá del final
+    patch.extend_from_slice(b"EOF");
+    rom.apply_ips_bytes(&patch).unwrap();
+    assert_eq!(rom.data, [0, 0, 0xAB, 0xCD, 0, 0xEE, 0xEE, 0xEE, 0, 0, 0x77]);
+
+    // Extensión de truncado tras EOF
+    let mut patch = b"PATCHEOF".to_vec();
+    patch.extend_from_slice(&[0x00, 0x00, 0x04]);
+    rom.apply_ips_bytes(&patch).unwrap();
+    assert_eq!(rom.data, [0, 0, 0xAB, 0xCD]);
+}
+
+#[test]
+fn test_ips_patch_from_file_and_invalid_patches() {
+    let path = std::env::temp_dir().join(format!("oxide_core_{}.ips", std::process::id()));
+    std::fs::write(&path, b"PATCH\x00\x00\x01\x00\x01\x42EOF").unwrap();
+    let mut rom = Rom::new_empty(4);
+    rom.apply_ips(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(rom.data, [0, 0x42, 0, 0]);
+
+    // Sin cabecera o cortado: error y la ROM queda intacta
+    assert!(matches!(rom.apply_ips_bytes(b"IPS!"), Err(RomError::InvalidPatch(_))));
+    assert!(matches!(rom.apply_ips_bytes(b"PATCH\x00\x00\x00\x00\x04\xFF"), Err(RomError::InvalidPatch(_))));
+    assert_eq!(rom.data, [0, 0x42, 0, 0]);
+}
+
+// ============================================================================
+// Parches BPS
+// ============================================================================
+
+fn bps_varint(mut v: usize, out: &mut Vec<u8>) {
+    loop {
+        let x = (v & 0x7F) as u8;
+        v >>= 7;
+        if v == 0 {
+            out.push(0x80 | x);
+            return;
+        }
+        out.push(x);
+        v -= 1;
+    }
+}
+
+/// Parche de "Hello World!" a "Hello Rust World!!" con las cuatro acciones.
+fn hello_bps(source: &[u8], target: &[u8]) -> Vec<u8> {
+    hello_bps_sized(source, target, target.len())
+}
+
+/// Como `hello_bps`, declarando `target_size` en la cabecera (CRCs correctos igualmente).
+fn hello_bps_sized(source: &[u8], target: &[u8], target_size: usize) -> Vec<u8> {
+    let mut p = b"BPS1".to_vec();
+    bps_varint(source.len(), &mut p);
+    bps_varint(target_size, &mut p);
+    bps_varint(0, &mut p); // sin metadatos
+    bps_varint((6 - 1) << 2, &mut p); // SourceRead "Hello "
+    bps_varint(((5 - 1) << 2) | 1, &mut p); // TargetRead "Rust "
+    p.extend_from_slice(b"Rust ");
+    bps_varint(((6 - 1) << 2) | 2, &mut p); // SourceCopy +6 "World!"
+    bps_varint(6 << 1, &mut p);
+    bps_varint(3, &mut p); // TargetCopy +16 "!"
+    bps_varint(16 << 1, &mut p);
+    p.extend_from_slice(&crc32(source.iter().copied()).to_le_bytes());
+    p.extend_from_slice(&crc32(target.iter().copied()).to_le_bytes());
+    let crc = crc32(p.iter().copied());
+    p.extend_from_slice(&crc.to_le_bytes());
+    p
+}
+
+#[test]
+fn test_bps_patch_builds_target_from_source() {
+    let patch = hello_bps(b"Hello World!", b"Hello Rust World!!");
+    let mut rom = Rom { data: b"Hello World!".to_vec() };
+    rom.apply_bps_bytes(&patch).unwrap();
+    assert_eq!(rom.data, b"Hello Rust World!!");
+    assert_eq!(crc32(*b"123456789"), 0xCBF4_3926);
+}
+
+#[test]
+fn test_bps_patch_rejects_wrong_source_and_corruption() {
+    let patch = hello_bps(b"Hello World!", b"Hello Rust World!!");
+    let mut rom = Rom { data: b"Hello Wrold!".to_vec() };
+    assert!(matches!(rom.apply_bps_bytes(&patch), Err(RomError::ChecksumMismatch("source"))));
+    assert_eq!(rom.data, b"Hello Wrold!");
+
+    let mut corrupt = patch.clone();
+    corrupt[12] ^= 0x01;
+    let mut rom = Rom { data: b"Hello World!".to_vec() };
+    assert!(matches!(rom.apply_bps_bytes(&corrupt), Err(RomError::ChecksumMismatch("patch"))));
+    assert!(matches!(rom.apply_bps_bytes(b"IPS!"), Err(RomError::InvalidPatch(_))));
+    assert_eq!(rom.data, b"Hello World!");
+}
+
+#[test]
+fn test_bps_patch_with_bogus_target_size_is_invalid() {
+    // El CRC del parche es válido: el tamaño absurdo no debe llegar a reservarse
+    let patch = hello_bps_sized(b"Hello World!", b"Hello Rust World!!", usize::MAX / 2);
+    let mut rom = Rom { data: b"Hello World!".to_vec() };
+    assert!(matches!(rom.apply_bps_bytes(&patch), Err(RomError::InvalidPatch(_))));
+    assert_eq!(rom.data, b"Hello World!");
+}
@@ -20,22 +20,22 @@ pub const OP_NORMAL: [u32; 256] = [
 ];
 
 pub const OP_ED: [u32; 256] = [
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
     12,12,15,20,8, 14,8, 9,  12,12,15,20,8, 14,8, 9,  // 40-4F
     12,12,15,20,8, 14,8, 9,  12,12,15,20,8, 14,8, 9,  // 50-5F
     12,12,15,20,8, 14,8, 18, 12,12,15,20,8, 14,8, 18, // 60-6F
-    12,12,15,20,8, 14,8, 0,  12,12,15,20,8, 14,8, 0,  // 70-7F
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
-    16,16,16,16,0, 0, 0, 0,  16,16,16,16,0, 0, 0, 0,  // A0-AF
-    21,21,21,21,0, 0, 0, 0,  21,21,21,21,0, 0, 0, 0,  // B0-BF
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
-    0, 0, 0, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0, 0, 0,
+    12,12,15,20,8, 14,8, 8,  12,12,15,20,8, 14,8, 8,  // 70-7F
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
+    16,16,16,16,8, 8, 8, 8,  16,16,16,16,8, 8, 8, 8,  // A0-AF
+    21,21,21,21,8, 8, 8, 8,  21,21,21,21,8, 8, 8, 8,  // B0-BF
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
+    8, 8, 8, 8, 8, 8, 8, 8,  8, 8, 8, 8, 8, 8, 8, 8,
 ];
 
 pub fn get_normal_cycles(opcode: u8, taken: bool) -> u32 {
@@ -56,7 +56,7 @@ pub fn get_normal_cycles(opcode: u8, taken: bool) -> u32 {
 pub fn get_cb_cycles(opcode: u8) -> u32 {
     let r = opcode & 7;
     let b = (opcode >> 3) & 0x1F;
-    if b >= 0x08 && b <= 0x0F { // BIT
+    if (0x08..=0x0F).contains(&b) { // BIT
         if r == 6 { 12 } else { 8 }
     } else { // RLC, RRC, RL, RR, SLA, SRA, SLL, SRL, RES, SET
         if r == 6 { 15 } else { 8 }
@@ -1,6 +1,8 @@
-use oxide_core::{Cpu, MemoryBus};
+use oxide_core::state::{StateReader, StateWriter};
+use oxide_core::{Cpu, MemoryBus, StateError};
 
 mod cycles;
+#[allow(clippy::module_inception)]
 mod tests;
 
 // ============================================================================
@@ -17,6 +19,40 @@ pub mod flags {
     pub const C: u8 = 0x01; // Carry
 }
 
+// ============================================================================
+//  OPCODE STATS (feature `opcode-stats`)
+// ============================================================================
+
+/// Histograma de instrucciones ejecutadas, una tabla por prefijo.
+/// `main` también cuenta los bytes de prefijo (CB/ED/DD/FD) como tales.
+#[cfg(feature = "opcode-stats")]
+pub struct OpcodeStats {
+    pub main: [u64; 256],
+    pub cb: [u64; 256],
+    pub ed: [u64; 256],
+    pub dd: [u64; 256],
+    pub fd: [u64; 256],
+    pub ddcb: [u64; 256],
+    pub fdcb: [u64; 256],
+}
+
+#[cfg(feature = "opcode-stats")]
+impl OpcodeStats {
+    fn new() -> Self {
+        Self {
+            main: [0; 256], cb: [0; 256], ed: [0; 256],
+            dd: [0; 256], fd: [0; 256], ddcb: [0; 256], fdcb: [0; 256],
+        }
+    }
+
+    /// Total de instrucciones contadas (los prefijos no suman aparte)
+    pub fn total(&self) -> u64 {
+        self.main.iter().sum::<u64>() - self.main[0xCB] - self.main[0xED] - self.main[0xDD] - self.main[0xFD]
+            + self.cb.iter().sum::<u64>() + self.ed.iter().sum::<u64>()
+            + self.dd.iter().sum::<u64>() + self.fd.iter().sum::<u64>()
+    }
+}
+
 // ============================================================================
 //  Z80 CORE STRUCTURE
 // ============================================================================
@@ -40,6 +76,7 @@ const PARITY_TABLE: [bool; 256] = [
     true, false, false, true, false, true, true, false, false, true, true, false, true, false, false, true 
 ];
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct OxidZ80 {
     // Registros principales
     pub a: u8, pub f: u8,
@@ -66,9 +103,28 @@ pub struct OxidZ80 {
     // State
     pub halted: bool,
     pub cycles: u32,
+
+    // Interrupciones solicitadas vía `Cpu::request_interrupt` / `trigger_interrupt` (un solo uso)
+    pub pending_int: Option<u8>, // valor del bus de datos
+    pub pending_nmi: bool,
+
+    // Líneas físicas: INT por nivel (la baja el periférico), NMI por flanco
+    pub int_line: bool,
+    pub nmi_line: bool,
     
     // Internal use for prefixes
+    #[cfg_attr(feature = "serde", serde(skip))]
     _displacement: i8, 
+
+    #[cfg(feature = "opcode-stats")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stats: Box<OpcodeStats>,
+}
+
+impl Default for OxidZ80 {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl OxidZ80 {
@@ -80,9 +136,20 @@ impl OxidZ80 {
             i: 0, r: 0,
             iff1: false, iff2: false, im: 0, ei_pending: false,
             halted: false, cycles: 0, _displacement: 0,
+            pending_int: None, pending_nmi: false,
+            int_line: false, nmi_line: false,
+            #[cfg(feature = "opcode-stats")]
+            stats: Box::new(OpcodeStats::new()),
         }
     }
 
+    /// Histograma de opcodes ejecutados desde la creación o el último `reset_opcode_stats`
+    #[cfg(feature = "opcode-stats")]
+    pub fn opcode_stats(&self) -> &OpcodeStats { &self.stats }
+
+    #[cfg(feature = "opcode-stats")]
+    pub fn reset_opcode_stats(&mut self) { *self.stats = OpcodeStats::new(); }
+
     pub fn set_internals(&mut self, af_p: u16, bc_p: u16, de_p: u16, hl_p: u16, _wz: u16) {
         self.a_p = (af_p >> 8) as u8;
         self.f_p = (af_p & 0xFF) as u8;
@@ -103,6 +170,16 @@ impl OxidZ80 {
         val
     }
 
+    /// Fetch de opcode (ciclo M1): además del byte, el bus ve el refresco con
+    /// I/R tal como estaban antes de incrementar R.
+    #[inline(always)]
+    fn fetch_m1(&mut self, bus: &mut dyn MemoryBus) -> u8 {
+        let refresh = ((self.i as u16) << 8) | self.r as u16;
+        let op = self.fetch(bus);
+        bus.refresh(refresh);
+        op
+    }
+
     #[inline(always)]
     fn refresh_r(&mut self, count: u8) {
         for _ in 0..count {
@@ -138,17 +215,84 @@ impl OxidZ80 {
 //  CPU TRAIT
 // ============================================================================
 
+/// Cabecera de `save_state`; subir la versión al cambiar el layout
+const STATE_MAGIC: &[u8; 4] = b"OZ80";
+const STATE_VERSION: u8 = 1;
+
 impl Cpu for OxidZ80 {
     fn reset(&mut self) {
         self.pc = 0; self.sp = 0xFFFF;
         self.iff1 = false; self.iff2 = false; self.im = 0;
         self.halted = false; self.a = 0xFF; self.f = 0xFF;
         self.ix = 0; self.iy = 0;
+        self.pending_int = None; self.pending_nmi = false;
+        self.int_line = false; self.nmi_line = false;
     }
 
     fn pc(&self) -> u32 { self.pc as u32 }
 
+    fn save_state(&self) -> Vec<u8> {
+        let mut w = StateWriter::new(STATE_MAGIC, STATE_VERSION);
+        for v in [self.a, self.f, self.b, self.c, self.d, self.e, self.h, self.l] { w.u8(v); }
+        for v in [self.a_p, self.f_p, self.b_p, self.c_p, self.d_p, self.e_p, self.h_p, self.l_p] { w.u8(v); }
+        for v in [self.ix, self.iy, self.sp, self.pc] { w.u16(v); }
+        w.u8(self.i).u8(self.r).u8(self.im);
+        for v in [self.iff1, self.iff2, self.ei_pending, self.halted, self.pending_nmi, self.int_line, self.nmi_line] { w.bool(v); }
+        w.bool(self.pending_int.is_some()).u8(self.pending_int.unwrap_or(0));
+        w.u32(self.cycles).finish()
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        // Todo se lee antes de tocar la CPU: una instantánea inválida no la deja a medias
+        let mut r = StateReader::new(data, STATE_MAGIC, STATE_VERSION, "Z80")?;
+        let mut regs = [0u8; 16];
+        for v in &mut regs { *v = r.u8()?; }
+        let [ix, iy, sp, pc] = [r.u16()?, r.u16()?, r.u16()?, r.u16()?];
+        let [i, refresh, im] = [r.u8()?, r.u8()?, r.u8()?];
+        if im > 2 {
+            return Err(StateError::InvalidField("im"));
+        }
+        let mut lines = [false; 7];
+        for v in &mut lines { *v = r.bool()?; }
+        let pending_int = if r.bool()? { Some(r.u8()?) } else { r.u8()?; None };
+        let cycles = r.u32()?;
+        r.finish()?;
+
+        [self.a, self.f, self.b, self.c, self.d, self.e, self.h, self.l,
+         self.a_p, self.f_p, self.b_p, self.c_p, self.d_p, self.e_p, self.h_p, self.l_p] = regs;
+        (self.ix, self.iy, self.sp, self.pc) = (ix, iy, sp, pc);
+        (self.i, self.r, self.im) = (i, refresh, im);
+        [self.iff1, self.iff2, self.ei_pending, self.halted, self.pending_nmi, self.int_line, self.nmi_line] = lines;
+        self.pending_int = pending_int;
+        self.cycles = cycles;
+        Ok(())
+    }
+
+    fn request_interrupt(&mut self, level: u8) {
+        match level {
+            0 => {}
+            0xFF => self.pending_nmi = true,
+            _ => self.trigger_interrupt(0xFF),
+        }
+    }
+
     fn step(&mut self, bus: &mut dyn MemoryBus) -> u32 {
+        // Interrupciones pendientes: NMI siempre; INT solo con IFF1 (tras EI, IFF1 sigue
+        // a false hasta ejecutar la instrucción siguiente)
+        if self.pending_nmi {
+            self.pending_nmi = false;
+            return self.nmi(bus);
+        }
+        if self.iff1 {
+            if let Some(data) = self.pending_int.take() {
+                return self.irq(bus, data);
+            }
+            if self.int_line || bus.int_asserted() {
+                let data = bus.interrupt_ack();
+                return self.irq(bus, data);
+            }
+        }
+
         if self.halted {
             return 4; // CPU dormida, consume ciclos esperando IRQ
         }
@@ -161,8 +305,10 @@ impl Cpu for OxidZ80 {
         }
 
 
-        let opcode = self.fetch(bus);
+        let opcode = self.fetch_m1(bus);
         self.cycles = cycles::get_normal_cycles(opcode, true); 
+        #[cfg(feature = "opcode-stats")]
+        { self.stats.main[opcode as usize] += 1; }
 
         match opcode {
             0xCB => { self.refresh_r(1); self.exec_cb(bus); },
@@ -184,6 +330,28 @@ impl Cpu for OxidZ80 {
 // ============================================================================
 
 impl OxidZ80 {
+    /// Deja una INT pendiente con `data` en el bus de datos (vector IM2 / opcode IM0).
+    /// `step` la acepta en el siguiente límite de instrucción con IFF1 activo,
+    /// respetando el retardo de una instrucción tras EI.
+    pub fn trigger_interrupt(&mut self, data: u8) {
+        self.pending_int = Some(data);
+    }
+
+    /// Nivel de la línea INT. Mientras esté activa (o el bus la mantenga vía
+    /// `MemoryBus::int_asserted`) y IFF1=1 se acepta en cada límite de instrucción;
+    /// el byte de datos lo aporta `MemoryBus::interrupt_ack`.
+    pub fn assert_int(&mut self, active: bool) {
+        self.int_line = active;
+    }
+
+    /// Nivel de la línea NMI: dispara en el flanco de subida.
+    pub fn assert_nmi(&mut self, active: bool) {
+        if active && !self.nmi_line {
+            self.pending_nmi = true;
+        }
+        self.nmi_line = active;
+    }
+
     /// Non-Maskable Interrupt
     pub fn nmi(&mut self, bus: &mut dyn MemoryBus) -> u32 {
         self.halted = false;
@@ -394,8 +562,9 @@ impl OxidZ80 {
             0x10 => { // DJNZ
                 self.b = self.b.wrapping_sub(1);
                 let off = self.fetch(bus) as i8;
-                if self.b != 0 { self.pc = (self.pc as i32 + off as i32) as u16; self.cycles+=13; }
-                else { self.cycles+=8; }
+                let t = self.b != 0;
+                if t { self.pc = (self.pc as i32 + off as i32) as u16; }
+                self.cycles = cycles::get_normal_cycles(opcode, t);
             },
 
             // Stack
@@ -422,12 +591,13 @@ impl OxidZ80 {
             0x2F => { self.a = !self.a; self.f |= flags::H | flags::N; },
             _ => {}
         }
-        self.cycles += 4;
     }
 
     // --- PREFIX CB: BITS & SHIFTS ---
     fn exec_cb(&mut self, bus: &mut dyn MemoryBus) {
-        let op = self.fetch(bus);
+        let op = self.fetch_m1(bus);
+        #[cfg(feature = "opcode-stats")]
+        { self.stats.cb[op as usize] += 1; }
         self.cycles = cycles::get_cb_cycles(op);
         let r = op & 7;
         let val = self.read_r(bus, r);
@@ -459,10 +629,15 @@ impl OxidZ80 {
     fn exec_cb_index(&mut self, bus: &mut dyn MemoryBus, is_ix: bool) {
         let d = self.fetch(bus) as i8;
         let op = self.fetch(bus);
-        self.cycles += 23;
+        self.cycles = if (op >> 6) == 1 { 20 } else { 23 }; // BIT n,(IX+d) no escribe
+        #[cfg(feature = "opcode-stats")]
+        {
+            let t = if is_ix { &mut self.stats.ddcb } else { &mut self.stats.fdcb };
+            t[op as usize] += 1;
+        }
 
         let idx = if is_ix { self.ix } else { self.iy };
-        let addr = idx.wrapping_add(d as u16 as u16) as u32;
+        let addr = idx.wrapping_add(d as u16) as u32;
         let val = bus.read(addr);
 
         let res = match (op >> 3) & 0x1F {
@@ -499,7 +674,9 @@ impl OxidZ80 {
 
     // --- PREFIX ED: EXTENDED ---
     fn exec_ed(&mut self, bus: &mut dyn MemoryBus) {
-        let op = self.fetch(bus);
+        let op = self.fetch_m1(bus);
+        #[cfg(feature = "opcode-stats")]
+        { self.stats.ed[op as usize] += 1; }
         self.cycles = cycles::get_ed_cycles(op);
         match op {
             // Block Transfer
@@ -610,9 +787,15 @@ impl OxidZ80 {
 
     // --- PREFIX DD/FD: INDEX IX/IY ---
     fn exec_index(&mut self, bus: &mut dyn MemoryBus, is_ix: bool) {
-        let op = self.fetch(bus);
+        let op = self.fetch_m1(bus);
+        #[cfg(feature = "opcode-stats")]
+        {
+            let t = if is_ix { &mut self.stats.dd } else { &mut self.stats.fd };
+            t[op as usize] += 1;
+        }
         let idx = if is_ix { self.ix } else { self.iy };
-        self.cycles = 8; // Default for most DD/FD opcodes (4 prefix + 4 inner)
+        // T-states con el prefijo incluido (4 del DD/FD + la instrucción)
+        self.cycles = 8;
 
         // **FIX**: Split read/write lines for borrow checker (op 0x24/0x2C)
         if op == 0x24 { 
@@ -651,26 +834,27 @@ impl OxidZ80 {
              if (dst == 4 || dst == 5 || src == 4 || src == 5) && (dst != 6 && src != 6) {
                  let val = self.read_idx_8(src, is_ix);
                  self.write_idx_8(dst, val, is_ix);
-                 self.cycles += 4; return;
+                 return;
              }
         }
         
         // Standard Index Logic
         match op {
-            0xE5 => self.push(bus, idx),
-            0xE1 => { let v=self.pop(bus); if is_ix {self.ix=v} else {self.iy=v} },
-            0x21 => { let v=self.fetch_u16(bus); if is_ix {self.ix=v} else {self.iy=v} },
-            0x09 => self.add16_idx(is_ix, self.bc()),
-            0x19 => self.add16_idx(is_ix, self.de()),
-            0x29 => self.add16_idx(is_ix, idx),
-            0x39 => self.add16_idx(is_ix, self.sp),
-            0x23 => if is_ix { self.ix = self.ix.wrapping_add(1); } else { self.iy = self.iy.wrapping_add(1); },
-            0x2B => if is_ix { self.ix = self.ix.wrapping_sub(1); } else { self.iy = self.iy.wrapping_sub(1); },
-            0x22 => { let a=self.fetch_u16(bus); bus.write(a as u32, (idx & 0xFF) as u8); bus.write((a.wrapping_add(1)) as u32, (idx >> 8) as u8); },
-            0x2A => { let a=self.fetch_u16(bus); let v=bus.read_u16_le(a as u32); if is_ix { self.ix=v; } else { self.iy=v; } },
-            0xF9 => self.sp = idx, // LD SP, IX/IY
-            0xE9 => { self.pc = idx; }, // JP (IX/IY)
+            0xE5 => { self.push(bus, idx); self.cycles = 15; },
+            0xE1 => { let v=self.pop(bus); if is_ix {self.ix=v} else {self.iy=v} self.cycles = 14; },
+            0x21 => { let v=self.fetch_u16(bus); if is_ix {self.ix=v} else {self.iy=v} self.cycles = 14; },
+            0x09 => { self.add16_idx(is_ix, self.bc()); self.cycles = 15; },
+            0x19 => { self.add16_idx(is_ix, self.de()); self.cycles = 15; },
+            0x29 => { self.add16_idx(is_ix, idx); self.cycles = 15; },
+            0x39 => { self.add16_idx(is_ix, self.sp); self.cycles = 15; },
+            0x23 => { if is_ix { self.ix = self.ix.wrapping_add(1); } else { self.iy = self.iy.wrapping_add(1); } self.cycles = 10; },
+            0x2B => { if is_ix { self.ix = self.ix.wrapping_sub(1); } else { self.iy = self.iy.wrapping_sub(1); } self.cycles = 10; },
+            0x22 => { let a=self.fetch_u16(bus); bus.write(a as u32, (idx & 0xFF) as u8); bus.write((a.wrapping_add(1)) as u32, (idx >> 8) as u8); self.cycles = 20; },
+            0x2A => { let a=self.fetch_u16(bus); let v=bus.read_u16_le(a as u32); if is_ix { self.ix=v; } else { self.iy=v; } self.cycles = 20; },
+            0xF9 => { self.sp = idx; self.cycles = 10; }, // LD SP, IX/IY
+            0xE9 => { self.pc = idx; }, // JP (IX/IY): 8
             0xE3 => { // EX (SP), IX/IY
+                self.cycles = 23;
                 let lo = bus.read(self.sp as u32);
                 let hi = bus.read((self.sp.wrapping_add(1)) as u32);
                 bus.write(self.sp as u32, (idx & 0xFF) as u8);
@@ -687,7 +871,7 @@ impl OxidZ80 {
             => {
                 let d = self.fetch(bus) as i8;
                 let addr = idx.wrapping_add(d as u16) as u32;
-                self.cycles = 19;
+                self.cycles = if op == 0x34 || op == 0x35 { 23 } else { 19 };
                 match op {
                     0x34 => { let v=self.inc(bus.read(addr)); bus.write(addr, v); },
                     0x35 => { let v=self.dec(bus.read(addr)); bus.write(addr, v); },
@@ -711,7 +895,13 @@ impl OxidZ80 {
                 }
             },
             0xCB => self.exec_cb_index(bus, is_ix),
-            _ => self.exec_normal(bus, op)
+            // El prefijo no afecta a la instrucción: 4T + su coste normal
+            // (los saltos condicionales lo reescriben según se tomen o no)
+            _ => {
+                self.cycles = cycles::get_normal_cycles(op, true);
+                self.exec_normal(bus, op);
+                self.cycles += 4;
+            }
         }
     }
 
@@ -769,6 +959,16 @@ impl OxidZ80 {
     #[inline] pub fn set_hl(&mut self, v:u16) { self.h=(v>>8)as u8; self.l=v as u8; }
     #[inline] pub fn set_af(&mut self, v:u16) { self.a=(v>>8)as u8; self.f=v as u8; }
 
+    /// Dirección de retorno si la instrucción en el PC es CALL (condicional o no) o RST;
+    /// para el "step over" del depurador.
+    pub fn call_return_address(&self, bus: &dyn MemoryBus) -> Option<u16> {
+        match bus.read(self.pc as u32) {
+            0xCD | 0xC4 | 0xCC | 0xD4 | 0xDC | 0xE4 | 0xEC | 0xF4 | 0xFC => Some(self.pc.wrapping_add(3)),
+            op if op & 0xC7 == 0xC7 => Some(self.pc.wrapping_add(1)),
+            _ => None,
+        }
+    }
+
     fn exx(&mut self) {
         let (b,c,d,e,h,l) = (self.b,self.c,self.d,self.e,self.h,self.l);
         self.b=self.b_p; self.c=self.c_p; self.d=self.d_p; self.e=self.e_p; self.h=self.h_p; self.l=self.l_p;
@@ -870,7 +1070,7 @@ impl OxidZ80 {
             (1, true) => (v & 0x80) != 0, (1, false) => (v & 1) != 0, _=>false
         };
         let bit = match (mode, dir) {
-             (0, _) => if c {1} else {0}, (1, _) => if (self.f&flags::C)!=0 {1} else {0}, _=>0
+             (0, _) => c as u8, (1, _) => ((self.f&flags::C)!=0) as u8, _=>0
         };
         let r = if dir { (v << 1) | bit } else { (v >> 1) | (bit << 7) };
         self.f = logic_flags(r) | (if c {flags::C} else {0});
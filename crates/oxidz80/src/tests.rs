@@ -3,6 +3,7 @@
 mod tests {
     use crate::*;
     use oxide_core::MemoryBus;
+    use oxide_core::testing::MockBus;
 
     struct TestBus { ram: [u8; 65536] }
     impl MemoryBus for TestBus {
@@ -60,12 +61,12 @@ mod tests {
     #[test]
     fn test_bit_xy_flags() {
         let mut cpu = OxidZ80::new();
-        let mut bus = TestBus { ram: [0; 65536] };
+        let mut bus = MockBus::new(0x10000);
         
         cpu.a = 0x08;
         cpu.pc = 0x1000;
-        bus.ram[0x1000] = 0xCB;
-        bus.ram[0x1001] = 0x5F; // BIT 3, A
+        bus.mem[0x1000] = 0xCB;
+        bus.mem[0x1001] = 0x5F; // BIT 3, A
         cpu.step(&mut bus); 
         assert!((cpu.f & flags::Z) == 0);
         assert!((cpu.f & flags::X) != 0); // Bit 3 of A is 1
@@ -73,11 +74,537 @@ mod tests {
         cpu.h = 0x20; // H high byte of address
         cpu.l = 0x00;
         cpu.pc = 0x1002;
-        bus.ram[0x1002] = 0xCB;
-        bus.ram[0x1003] = 0x76; // BIT 6, (HL)
-        bus.ram[0x2000] = 0x00; // Value at (HL)
+        bus.mem[0x1002] = 0xCB;
+        bus.mem[0x1003] = 0x76; // BIT 6, (HL)
+        bus.mem[0x2000] = 0x00; // Value at (HL)
         cpu.step(&mut bus);
         assert!((cpu.f & flags::Z) != 0);
         assert!((cpu.f & flags::Y) != 0); // Y comes from H (bit 5 of 0x20)
     }
+
+    fn run_cb(cpu: &mut OxidZ80, bus: &mut TestBus, op: u8) {
+        let pc = cpu.pc as usize;
+        bus.ram[pc] = 0xCB;
+        bus.ram[pc + 1] = op;
+        cpu.step(bus);
+    }
+
+    #[test]
+    fn test_sll() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = TestBus { ram: [0; 65536] };
+
+        cpu.a = 0x80;
+        cpu.pc = 0x1000;
+        run_cb(&mut cpu, &mut bus, 0x37); // SLL A
+        assert_eq!(cpu.a, 0x01);
+        assert!((cpu.f & flags::C) != 0); // C = bit 7 anterior
+        assert!((cpu.f & (flags::S | flags::Z | flags::P | flags::H | flags::N)) == 0);
+
+        cpu.a = 0x40;
+        run_cb(&mut cpu, &mut bus, 0x37);
+        assert_eq!(cpu.a, 0x81);
+        assert!((cpu.f & flags::C) == 0);
+        assert!((cpu.f & flags::S) != 0);
+        assert!((cpu.f & flags::P) != 0); // 0x81: paridad par
+
+        // X/Y salen del resultado: 0x14 -> 0x29 (bits 3 y 5 a 1)
+        cpu.b = 0x14;
+        run_cb(&mut cpu, &mut bus, 0x30); // SLL B
+        assert_eq!(cpu.b, 0x29);
+        assert_eq!(cpu.f & (flags::X | flags::Y), flags::X | flags::Y);
+
+        // SLL (HL)
+        cpu.h = 0x20;
+        cpu.l = 0x00;
+        bus.ram[0x2000] = 0xFF;
+        run_cb(&mut cpu, &mut bus, 0x36);
+        assert_eq!(bus.ram[0x2000], 0xFF);
+        assert!((cpu.f & flags::C) != 0);
+    }
+
+    // ============================================================================
+    // I/O por puertos
+    // ============================================================================
+
+    #[test]
+    fn test_in_out_immediate_port() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000).with_port_mask(0x00FF);
+        // IN A,($FE) ; IN A,($FE) ; OUT ($BF),A
+        bus.load(0, &[0xDB, 0xFE, 0xDB, 0xFE, 0xD3, 0xBF]);
+        bus.queue_port(0xFE, &[0x1F]).set_port(0xFE, 0xBF);
+
+        cpu.step(&mut bus);
+        assert_eq!(cpu.a, 0x1F);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.a, 0xBF); // cola agotada -> valor fijo
+        cpu.step(&mut bus);
+        assert_eq!(bus.port_writes, vec![(0xBFBF, 0xBF)]);
+        assert!(bus.writes.is_empty());
+    }
+
+    #[test]
+    fn test_in_f_c_sets_flags_without_storing() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        bus.load(0, &[0xED, 0x70]); // IN (C) / IN F,(C)
+        bus.set_port(0x1234, 0x80);
+        cpu.set_bc(0x1234);
+        cpu.set_hl(0x5678);
+        cpu.a = 0x11;
+        cpu.f = flags::C | flags::H | flags::N;
+
+        assert_eq!(cpu.step(&mut bus), 12);
+        // S por bit 7, P por paridad impar=0, H=N=0, C intacto
+        assert_eq!(cpu.f, flags::S | flags::C);
+        assert_eq!((cpu.a, cpu.bc(), cpu.hl()), (0x11, 0x1234, 0x5678));
+        assert!(bus.writes.is_empty());
+    }
+
+    #[test]
+    fn test_out_c_zero() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        bus.load(0, &[0xED, 0x71]); // OUT (C),0 (NMOS)
+        cpu.set_bc(0x00BF);
+        cpu.set_hl(0xFFFF);
+        let f = cpu.f;
+        assert_eq!(cpu.step(&mut bus), 12);
+        assert_eq!(bus.port_writes, vec![(0x00BF, 0x00)]);
+        assert_eq!(cpu.f, f);
+    }
+
+    #[test]
+    fn test_ed_undefined_opcodes_are_nops() {
+        for op in [0x77u8, 0x7F, 0x00, 0xFF] {
+            let mut cpu = OxidZ80::new();
+            let mut bus = MockBus::new(0x10000);
+            bus.load(0, &[0xED, op]);
+            cpu.a = 0x42;
+            let f = cpu.f;
+            assert_eq!(cpu.step(&mut bus), 8, "ED {:02X}", op);
+            assert_eq!((cpu.pc, cpu.a, cpu.f), (2, 0x42, f));
+            assert!(bus.writes.is_empty() && bus.port_writes.is_empty());
+        }
+    }
+
+    // ============================================================================
+    // Interrupciones vía Cpu::request_interrupt
+    // ============================================================================
+
+    #[test]
+    fn test_request_interrupt_through_trait_object() {
+        let mut z80 = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        z80.sp = 0x8000;
+        z80.pc = 0x1000;
+        z80.im = 1;
+        z80.iff1 = true;
+        {
+            let cpu: &mut dyn Cpu = &mut z80;
+            cpu.request_interrupt(1);
+            cpu.step(&mut bus);
+            assert_eq!(cpu.pc(), 0x0038);
+        }
+        assert!(!z80.iff1);
+        assert_eq!(bus.read_u16_le(0x7FFE), 0x1000); // PC de retorno
+
+        // NMI (0xFF) ignora IFF1
+        let cpu: &mut dyn Cpu = &mut z80;
+        cpu.request_interrupt(0xFF);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc(), 0x0066);
+    }
+
+    #[test]
+    fn test_requested_int_waits_for_ei_delay() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        // EI ; NOP ; NOP
+        bus.load(0x1000, &[0xFB, 0x00, 0x00]);
+        cpu.sp = 0x8000;
+        cpu.pc = 0x1000;
+        cpu.im = 1;
+        cpu.request_interrupt(1);
+        cpu.step(&mut bus); // EI (INT ignorada, IFF1=0)
+        cpu.step(&mut bus); // NOP tras EI siempre se ejecuta
+        assert_eq!(cpu.pc, 0x1002);
+        cpu.step(&mut bus); // ahora se acepta
+        assert_eq!(cpu.pc, 0x0038);
+    }
+
+    #[test]
+    fn test_ei_ret_accepts_int_after_ret() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        // EI ; RET  (retorno a $2000)
+        bus.load(0x1000, &[0xFB, 0xC9]);
+        bus.load(0x7FFE, &[0x00, 0x20]);
+        cpu.sp = 0x7FFE;
+        cpu.pc = 0x1000;
+        cpu.im = 1;
+        cpu.trigger_interrupt(0xFF);
+
+        cpu.step(&mut bus); // EI
+        cpu.step(&mut bus); // RET: aún no se acepta
+        assert_eq!(cpu.pc, 0x2000);
+        assert_eq!(cpu.sp, 0x8000);
+        cpu.step(&mut bus); // INT aceptada en el límite tras RET
+        assert_eq!(cpu.pc, 0x0038);
+        assert_eq!(bus.read_u16_le(0x7FFE), 0x2000);
+        assert!(cpu.pending_int.is_none());
+    }
+
+    #[test]
+    fn test_int_stays_pending_while_disabled() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        // NOP ; NOP ; EI ; NOP
+        bus.load(0x1000, &[0x00, 0x00, 0xFB, 0x00]);
+        cpu.sp = 0x8000;
+        cpu.pc = 0x1000;
+        cpu.im = 2;
+        cpu.i = 0x30;
+        bus.load(0x3040, &[0x00, 0x50]);
+        cpu.trigger_interrupt(0x40);
+        for _ in 0..4 {
+            cpu.step(&mut bus);
+        }
+        assert_eq!(cpu.pc, 0x1004);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x5000); // IM2: vector ($3040)
+    }
+
+    // ============================================================================
+    // Líneas INT / NMI
+    // ============================================================================
+
+    #[test]
+    fn test_int_line_is_level_triggered() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        // Handler IM2 en $5000: EI ; RETI
+        bus.load(0x5000, &[0xFB, 0xED, 0x4D]);
+        bus.load(0x3040, &[0x00, 0x50]);
+        bus.int_ack = 0x40;
+        cpu.sp = 0x8000;
+        cpu.pc = 0x1000;
+        cpu.im = 2;
+        cpu.i = 0x30;
+        cpu.iff1 = true;
+
+        cpu.assert_int(true);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x5000);
+        cpu.step(&mut bus); // EI
+        cpu.step(&mut bus); // RETI
+        assert_eq!(cpu.pc, 0x1000);
+        cpu.step(&mut bus); // línea aún activa: se vuelve a aceptar
+        assert_eq!(cpu.pc, 0x5000);
+
+        // El periférico baja la línea: ya no hay más interrupciones
+        cpu.assert_int(false);
+        cpu.step(&mut bus);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x1000);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x1001);
+    }
+
+    #[test]
+    fn test_nmi_line_fires_on_rising_edge() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        cpu.sp = 0x8000;
+        cpu.pc = 0x1000;
+
+        cpu.assert_nmi(true);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x0066);
+        cpu.assert_nmi(true); // sigue alta: sin nuevo flanco
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x0067);
+        cpu.assert_nmi(false);
+        cpu.assert_nmi(true);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x0066);
+        assert_eq!(cpu.sp, 0x8000 - 4);
+    }
+
+    // ============================================================================
+    // Histograma de opcodes (feature `opcode-stats`)
+    // ============================================================================
+
+    #[test]
+    #[cfg(feature = "opcode-stats")]
+    fn test_opcode_histogram_counts_executed_instructions() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = TestBus { ram: [0; 65536] };
+        // NOP ; NOP ; SLL A ; IM 1 ; LD IX,$1234 ; SET 0,(IX+1)
+        let prog = [0x00, 0x00, 0xCB, 0x37, 0xED, 0x56, 0xDD, 0x21, 0x34, 0x12, 0xDD, 0xCB, 0x01, 0xC6];
+        bus.ram[..prog.len()].copy_from_slice(&prog);
+        for _ in 0..6 {
+            cpu.step(&mut bus);
+        }
+        let s = cpu.opcode_stats();
+        assert_eq!(s.main[0x00], 2);
+        assert_eq!(s.cb[0x37], 1);
+        assert_eq!(s.ed[0x56], 1);
+        assert_eq!(s.dd[0x21], 1);
+        assert_eq!(s.dd[0xCB], 1);
+        assert_eq!(s.ddcb[0xC6], 1);
+        assert_eq!(s.total(), 6);
+
+        cpu.reset_opcode_stats();
+        assert_eq!(cpu.opcode_stats().total(), 0);
+    }
+
+    // ============================================================================
+    // Volcado JSON del estado (feature `serde`)
+    // ============================================================================
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_state_serializes_to_json() {
+        let mut cpu = OxidZ80::new();
+        cpu.pc = 0x1234;
+        cpu.a = 0x42;
+        let json = serde_json::to_string(&cpu).unwrap();
+        assert!(json.contains("\"pc\":4660"));
+        assert!(json.contains("\"a\":66"));
+        assert!(!json.contains("_displacement"));
+    }
+
+    // ============================================================================
+    // Endianness: el Z80 es little-endian en memoria y en la pila
+    // ============================================================================
+
+    #[test]
+    fn test_z80_memory_words_are_little_endian() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        // LD BC,($2000) ; PUSH BC
+        bus.load(0, &[0xED, 0x4B, 0x00, 0x20, 0xC5]);
+        bus.load(0x2000, &[0x34, 0x12]);
+        cpu.sp = 0x8000;
+
+        cpu.step(&mut bus);
+        assert_eq!(cpu.bc(), 0x1234);
+        cpu.step(&mut bus);
+        assert_eq!(&bus.mem[0x7FFE..0x8000], &[0x34, 0x12]);
+        assert_eq!(bus.read_u16_le(0x7FFE), 0x1234);
+    }
+
+    #[test]
+    fn test_refresh_address_on_m1_cycles() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        cpu.pc = 0x8000;
+        cpu.i = 0x3F;
+        cpu.r = 0x85; // el bit 7 se conserva al incrementar
+        bus.load(0x8000, &[0x00, 0x3E, 0x12, 0xED, 0x44]); // NOP; LD A,12h; NEG
+
+        cpu.step(&mut bus);
+        assert_eq!(bus.refreshes, [0x3F85]);
+
+        // El operando de LD A,n no es un ciclo M1
+        cpu.step(&mut bus);
+        assert_eq!(bus.refreshes.len(), 2);
+        assert_eq!(bus.refreshes[1], 0x3F86);
+
+        // Prefijo ED: dos ciclos M1 (prefijo y opcode)
+        bus.refreshes.clear();
+        let r = cpu.r;
+        cpu.step(&mut bus);
+        assert_eq!(bus.refreshes.len(), 2);
+        assert_eq!(bus.refreshes[0], 0x3F00 | r as u16);
+        assert!(bus.refreshes.iter().all(|a| a >> 8 == 0x3F && a & 0x80 != 0));
+    }
+
+    #[test]
+    fn test_smc_detector_reports_patched_operand() {
+        use oxide_core::smc::{SmcDetector, SmcEvent};
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        cpu.pc = 0x8000;
+        // LD A,7Fh ; LD (8006h),A ; LD B,00h  <- el operando de LD B lo parchea la instrucción anterior
+        bus.load(0x8000, &[0x3E, 0x7F, 0x32, 0x06, 0x80, 0x06, 0x00]);
+        let mut smc = SmcDetector::new();
+        for _ in 0..3 {
+            smc.step(&mut cpu, &mut bus);
+        }
+        assert_eq!(cpu.b, 0x7F);
+        assert_eq!(smc.events(), [SmcEvent { pc: 0x8005, address: 0x8006 }]);
+
+        // En un frame nuevo lo escrito antes ya no cuenta
+        smc.new_frame();
+        cpu.pc = 0x8005;
+        smc.step(&mut cpu, &mut bus);
+        assert_eq!(smc.take_events().len(), 1); // solo la del frame anterior
+        assert!(smc.events().is_empty());
+    }
+    // ============================================================================
+    // Timing DD/FD
+    // ============================================================================
+
+    /// T-states de la primera instrucción de `prog`.
+    fn t_states(prog: &[u8]) -> u32 {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        bus.load(0, prog);
+        cpu.sp = 0x8000;
+        cpu.step(&mut bus)
+    }
+
+    #[test]
+    fn test_index_prefix_adds_four_t_states_to_plain_opcodes() {
+        assert_eq!(t_states(&[0x80]), 4); // ADD A,B
+        assert_eq!(t_states(&[0xDD, 0x80]), 8);
+        assert_eq!(t_states(&[0xFD, 0x80]), 8);
+        assert_eq!(t_states(&[0x01, 0x34, 0x12]), 10); // LD BC,nn
+        assert_eq!(t_states(&[0xDD, 0x01, 0x34, 0x12]), 14);
+        assert_eq!(t_states(&[0xDD, 0x18, 0x00]), 16); // JR e
+        // Condicionales: 4 + tomado / no tomado (F=$FF tras reset: Z=1)
+        assert_eq!(t_states(&[0xFD, 0x28, 0x00]), 16);
+        assert_eq!(t_states(&[0xFD, 0x20, 0x00]), 11);
+    }
+
+    #[test]
+    fn test_index_specific_instruction_t_states() {
+        let cases: [(&[u8], u32); 16] = [
+            (&[0xDD, 0x21, 0x00, 0x00], 14), // LD IX,nn
+            (&[0xDD, 0x09], 15),             // ADD IX,BC
+            (&[0xDD, 0x23], 10),             // INC IX
+            (&[0xDD, 0x22, 0x00, 0x90], 20), // LD (nn),IX
+            (&[0xDD, 0x2A, 0x00, 0x90], 20), // LD IX,(nn)
+            (&[0xDD, 0xE5], 15),             // PUSH IX
+            (&[0xDD, 0xE1], 14),             // POP IX
+            (&[0xDD, 0xE3], 23),             // EX (SP),IX
+            (&[0xDD, 0xE9], 8),              // JP (IX)
+            (&[0xDD, 0xF9], 10),             // LD SP,IX
+            (&[0xDD, 0x44], 8),              // LD B,IXH
+            (&[0xDD, 0x7E, 0x05], 19),       // LD A,(IX+d)
+            (&[0xDD, 0x36, 0x05, 0x42], 19), // LD (IX+d),n
+            (&[0xDD, 0x34, 0x05], 23),       // INC (IX+d)
+            (&[0xFD, 0xCB, 0x05, 0x46], 20), // BIT 0,(IY+d)
+            (&[0xFD, 0xCB, 0x05, 0x06], 23), // RLC (IY+d)
+        ];
+        for (prog, t) in cases {
+            assert_eq!(t_states(prog), t, "{:02X?}", prog);
+        }
+    }
+
+    #[test]
+    fn test_state_snapshot_round_trip() {
+        let mut cpu = OxidZ80::new();
+        let mut bus = MockBus::new(0x10000);
+        cpu.reset();
+        (cpu.b, cpu.h_p, cpu.ix, cpu.iy, cpu.sp) = (0x12, 0x34, 0x5678, 0x9ABC, 0xFF00);
+        (cpu.i, cpu.im, cpu.iff1, cpu.iff2) = (0x3F, 2, true, false);
+        cpu.step(&mut bus); // NOP
+        cpu.pending_int = Some(0xE7);
+        cpu.pending_nmi = true;
+        let snapshot = cpu.save_state();
+
+        let mut other = OxidZ80::new();
+        other.load_state(&snapshot).unwrap();
+        assert_eq!(other.save_state(), snapshot);
+        assert_eq!((other.b, other.h_p, other.ix, other.iy, other.sp), (0x12, 0x34, 0x5678, 0x9ABC, 0xFF00));
+        assert_eq!((other.i, other.im, other.iff1, other.iff2), (0x3F, 2, true, false));
+        assert_eq!((other.pc, other.r, other.cycles), (cpu.pc, cpu.r, cpu.cycles));
+        assert_eq!((other.pending_int, other.pending_nmi), (Some(0xE7), true));
+    }
+
+    #[test]
+    fn test_state_snapshot_rejects_foreign_or_corrupt_data() {
+        let snapshot = OxidZ80::new().save_state();
+        let mut target = OxidZ80::new();
+        target.a = 0x55;
+
+        let mut bad_im = snapshot.clone();
+        bad_im[5 + 16 + 8 + 2] = 3;
+        assert_eq!(target.load_state(&bad_im), Err(StateError::InvalidField("im")));
+        assert_eq!(target.load_state(b"O68K\x01"), Err(StateError::BadMagic("Z80")));
+        assert_eq!(target.load_state(&snapshot[..10]), Err(StateError::Truncated));
+        assert_eq!(target.a, 0x55);
+    }
+
+    // ============================================================================
+    // Depurador (oxide-core debug_server)
+    // ============================================================================
+
+    use oxide_core::debug_server::{Command, DebugServer, DebugTarget};
+
+    struct Machine {
+        cpu: OxidZ80,
+        bus: MockBus,
+    }
+
+    impl DebugTarget for Machine {
+        fn registers(&self) -> Vec<(&'static str, u32)> {
+            vec![("PC", self.cpu.pc as u32)]
+        }
+        fn pc(&self) -> u32 {
+            self.cpu.pc as u32
+        }
+        fn read_mem(&self, addr: u32) -> u8 {
+            self.bus.read(addr)
+        }
+        fn write_mem(&mut self, addr: u32, val: u8) {
+            self.bus.write(addr, val);
+        }
+        fn step(&mut self) -> u32 {
+            self.cpu.step(&mut self.bus)
+        }
+        fn call_return(&self) -> Option<u32> {
+            self.cpu.call_return_address(&self.bus).map(u32::from)
+        }
+    }
+
+    /// 0000: CALL 0010 ; INC A ; RST 08    0008: LD B,7 ; RET    0010: LD A,42 ; CALL 0008 ; RET
+    fn call_machine() -> Machine {
+        let mut bus = MockBus::new(0x10000);
+        bus.mem[..5].copy_from_slice(&[0xCD, 0x10, 0x00, 0x3C, 0xCF]);
+        bus.mem[0x08..0x0B].copy_from_slice(&[0x06, 0x07, 0xC9]);
+        bus.mem[0x10..0x16].copy_from_slice(&[0x3E, 0x42, 0xCD, 0x08, 0x00, 0xC9]);
+        let mut cpu = OxidZ80::new();
+        cpu.reset();
+        Machine { cpu, bus }
+    }
+
+    #[test]
+    fn test_debugger_next_steps_over_call() {
+        let mut m = call_machine();
+        let mut server = DebugServer::new().with_continue_limit(100);
+
+        // La subrutina (con su llamada anidada) se ejecuta entera
+        assert_eq!(server.execute(&mut m, Command::Next), "PC=3");
+        assert_eq!((m.cpu.a, m.cpu.b, m.cpu.sp), (0x42, 7, 0xFFFF));
+        // Sin llamada: un solo paso
+        assert_eq!(server.execute(&mut m, Command::Next), "PC=4");
+        assert_eq!(m.cpu.a, 0x43);
+        // RST también es una llamada; un breakpoint dentro para antes del retorno
+        server.execute(&mut m, Command::Break(0x0A));
+        assert_eq!(server.execute(&mut m, Command::Next), "BREAK A");
+    }
+
+    #[test]
+    fn test_debugger_reconstructs_call_stack() {
+        let mut m = call_machine();
+        let mut server = DebugServer::new();
+
+        // CALL 0010 ; LD A,42 ; CALL 0008 ; LD B,7 -> dentro de dos niveles
+        assert_eq!(server.execute(&mut m, Command::Step(4)), "PC=A");
+        assert_eq!(server.call_stack(), [0x15, 0x03]);
+        assert_eq!(server.execute(&mut m, Command::Stack), "STACK 15 3");
+        // RET, RET: de vuelta al nivel superior
+        server.execute(&mut m, Command::Step(1));
+        assert_eq!(server.call_stack(), [0x03]);
+        server.execute(&mut m, Command::Step(1));
+        assert!(server.call_stack().is_empty());
+        // INC A ; RST 08: un nivel otra vez
+        assert_eq!(server.execute(&mut m, Command::Step(2)), "PC=8");
+        assert_eq!(server.execute(&mut m, Command::Stack), "STACK 5");
+    }
 }
@@ -40,7 +40,8 @@ struct TestCase {
     initial: TestState,
     #[serde(rename = "final")]
     final_state: TestState,
-    cycles: Vec<(u16, u16, String)>, // Addr, Data, Type (read/write) - Not fully checking yet
+    #[serde(rename = "cycles")]
+    _cycles: Vec<(u16, u16, String)>, // Addr, Data, Type (read/write) - Not fully checking yet
 }
 
 struct TestBus {
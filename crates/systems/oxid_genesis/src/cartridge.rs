@@ -0,0 +1,368 @@
+// crates/systems/oxid_genesis/src/cartridge.rs
+//
+// Cartucho: ROM + memoria de guardado declarada en la cabecera ($1B0 = "RA").
+// - SRAM: rango (normalmente $200001-$203FFF) y si usa los bytes impares,
+//   los pares o ambos. $A130F1 la superpone a la ROM (ROM > 2MB) y la protege.
+// - EEPROM serie (24C02, I2C): cabecera con un rango de una sola dirección.
+//   Conexión de los juegos Sega: SDA = bit 0, SCL = bit 1 en esa dirección.
+// El contenido se persiste en un `.srm` con los bytes en crudo.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 24C02: 256 bytes, páginas de escritura de 8 bytes
+const EEPROM_SIZE: usize = 256;
+const EEPROM_PAGE: u8 = 8;
+/// Byte de dispositivo I2C: 1010 xxx R/W
+const EEPROM_DEVICE: u8 = 0xA0;
+
+/// Qué líneas del bus de datos usa la SRAM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SramLanes {
+    /// Solo direcciones impares (byte bajo): lo habitual
+    Odd,
+    /// Solo direcciones pares (byte alto)
+    Even,
+    /// SRAM de 16 bits
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveKind {
+    Sram(SramLanes),
+    Eeprom,
+}
+
+/// Memoria de guardado declarada en la cabecera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveInfo {
+    pub kind: SaveKind,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl SaveInfo {
+    /// `RA`, tipo, `$20`, inicio y fin (32 bits big-endian) en $1B0-$1BB.
+    pub fn parse(rom: &[u8]) -> Option<Self> {
+        let h = rom.get(0x1B0..0x1BC)?;
+        if &h[0..2] != b"RA" {
+            return None;
+        }
+        let start = u32::from_be_bytes([h[4], h[5], h[6], h[7]]) & 0xFFFFFF;
+        let end = u32::from_be_bytes([h[8], h[9], h[10], h[11]]) & 0xFFFFFF;
+        if end < start {
+            return None;
+        }
+        let kind = if start == end {
+            SaveKind::Eeprom
+        } else {
+            SaveKind::Sram(match h[2] & 0x18 {
+                0x10 => SramLanes::Even,
+                0x18 => SramLanes::Odd,
+                _ => SramLanes::Both,
+            })
+        };
+        Some(Self { kind, start, end })
+    }
+
+    /// Bytes de memoria real (sin los huecos de las líneas no conectadas).
+    pub fn size(&self) -> usize {
+        match self.kind {
+            SaveKind::Sram(SramLanes::Both) => (self.end - self.start + 1) as usize,
+            SaveKind::Sram(_) => ((self.end - self.start) / 2 + 1) as usize,
+            SaveKind::Eeprom => EEPROM_SIZE,
+        }
+    }
+
+    /// Índice en la SRAM de una dirección del bus, si cae en una línea conectada.
+    fn sram_index(&self, addr: u32) -> Option<usize> {
+        if !(self.start..=self.end).contains(&addr) {
+            return None;
+        }
+        match self.kind {
+            SaveKind::Sram(SramLanes::Both) => Some((addr - self.start) as usize),
+            SaveKind::Sram(SramLanes::Odd) if addr & 1 == 1 => Some(((addr - (self.start & !1)) >> 1) as usize),
+            SaveKind::Sram(SramLanes::Even) if addr & 1 == 0 => Some(((addr - (self.start & !1)) >> 1) as usize),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// EEPROM I2C
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum I2cState {
+    Idle,
+    Device,
+    Address,
+    Write,
+    Read,
+}
+
+/// 24C02 vista desde las líneas SDA/SCL que mueve el 68000.
+pub struct Eeprom {
+    pub data: [u8; EEPROM_SIZE],
+    state: I2cState,
+    sda: bool,
+    scl: bool,
+    /// SDA que pone la EEPROM (línea en colector abierto: true = suelta)
+    out: bool,
+    shift: u8,
+    /// Bit del byte en curso (8 = ciclo de ACK)
+    bit: u8,
+    /// Ya pasó el flanco de subida del ciclo de ACK
+    ack_clocked: bool,
+    /// En lectura: el primer byte sale sin incrementar la dirección
+    read_started: bool,
+    address: u8,
+    /// Se escribió algún byte desde la última consulta
+    modified: bool,
+}
+
+impl Default for Eeprom {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Eeprom {
+    pub fn new() -> Self {
+        Self {
+            data: [0xFF; EEPROM_SIZE],
+            state: I2cState::Idle,
+            sda: true,
+            scl: true,
+            out: true,
+            shift: 0,
+            bit: 0,
+            ack_clocked: false,
+            read_started: false,
+            address: 0,
+            modified: false,
+        }
+    }
+
+    /// SDA leída por el 68000.
+    pub fn sda_out(&self) -> bool {
+        self.out
+    }
+
+    /// Nuevo estado de las líneas. SDA cambiando con SCL alta = START/STOP.
+    pub fn write_lines(&mut self, sda: bool, scl: bool) {
+        let (old_sda, old_scl) = (self.sda, self.scl);
+        self.sda = sda;
+        self.scl = scl;
+        match (old_scl, scl) {
+            (true, true) if old_sda && !sda => {
+                self.state = I2cState::Device;
+                self.bit = 0;
+                self.ack_clocked = false;
+                self.out = true;
+            }
+            (true, true) if !old_sda && sda => {
+                self.state = I2cState::Idle;
+                self.out = true;
+            }
+            (false, true) => self.clock_rising(),
+            (true, false) => self.clock_falling(),
+            _ => {}
+        }
+    }
+
+    fn clock_rising(&mut self) {
+        if self.state == I2cState::Idle {
+            return;
+        }
+        if self.bit < 8 {
+            if self.state != I2cState::Read {
+                self.shift = (self.shift << 1) | self.sda as u8;
+            }
+            self.bit += 1;
+        } else {
+            // ACK del maestro en lectura: NACK termina la transferencia
+            if self.state == I2cState::Read && self.sda && self.read_started {
+                self.state = I2cState::Idle;
+                self.out = true;
+            }
+            self.ack_clocked = true;
+        }
+    }
+
+    fn clock_falling(&mut self) {
+        if self.state == I2cState::Idle {
+            return;
+        }
+        if self.bit == 8 && !self.ack_clocked {
+            // Entramos en el ciclo de ACK
+            if self.state == I2cState::Read && self.read_started {
+                self.out = true;
+            } else {
+                self.out = !self.byte_received();
+            }
+        } else if self.bit == 8 {
+            self.bit = 0;
+            self.ack_clocked = false;
+            if self.state == I2cState::Read {
+                if self.read_started {
+                    self.address = self.address.wrapping_add(1);
+                }
+                self.read_started = true;
+                self.shift = self.data[self.address as usize];
+                self.out = self.shift & 0x80 != 0;
+            } else {
+                self.out = true;
+            }
+        } else if self.state == I2cState::Read {
+            self.out = (self.shift >> (7 - self.bit)) & 1 != 0;
+        }
+    }
+
+    /// Procesa un byte recibido; devuelve si se reconoce (ACK).
+    fn byte_received(&mut self) -> bool {
+        match self.state {
+            I2cState::Device => {
+                if self.shift & 0xF0 != EEPROM_DEVICE {
+                    self.state = I2cState::Idle;
+                    return false;
+                }
+                if self.shift & 1 != 0 {
+                    self.state = I2cState::Read;
+                    self.read_started = false;
+                } else {
+                    self.state = I2cState::Address;
+                }
+            }
+            I2cState::Address => {
+                self.address = self.shift;
+                self.state = I2cState::Write;
+            }
+            I2cState::Write => {
+                self.data[self.address as usize] = self.shift;
+                self.modified = true;
+                // La dirección da la vuelta dentro de la página
+                let page = self.address & !(EEPROM_PAGE - 1);
+                self.address = page | (self.address.wrapping_add(1) & (EEPROM_PAGE - 1));
+            }
+            I2cState::Read | I2cState::Idle => {}
+        }
+        true
+    }
+}
+
+// ============================================================================
+// CARTUCHO
+// ============================================================================
+
+pub struct Cartridge {
+    pub rom: Vec<u8>,
+    pub save_info: Option<SaveInfo>,
+    pub sram: Vec<u8>,
+    pub eeprom: Eeprom,
+    /// $A130F1 bit 0: SRAM visible en lugar de la ROM
+    pub sram_mapped: bool,
+    /// $A130F1 bit 1: SRAM de solo lectura
+    pub write_protect: bool,
+    /// Hay cambios sin guardar en el `.srm`
+    pub dirty: bool,
+}
+
+impl Cartridge {
+    /// Detecta la memoria de guardado. Con ROM que no llega al rango de la SRAM,
+    /// esta queda mapeada desde el arranque (no hace falta $A130F1).
+    pub fn new(rom: Vec<u8>) -> Self {
+        let save_info = SaveInfo::parse(&rom);
+        let sram_size = match save_info {
+            Some(info @ SaveInfo { kind: SaveKind::Sram(_), .. }) => info.size(),
+            _ => 0,
+        };
+        let sram_mapped = save_info.is_some_and(|info| rom.len() as u32 <= info.start);
+        Self {
+            rom,
+            save_info,
+            sram: vec![0xFF; sram_size],
+            eeprom: Eeprom::new(),
+            sram_mapped,
+            write_protect: false,
+            dirty: false,
+        }
+    }
+
+    fn eeprom_at(&self, addr: u32) -> bool {
+        matches!(self.save_info, Some(SaveInfo { kind: SaveKind::Eeprom, start, .. }) if start == addr)
+    }
+
+    /// Lectura en $000000-$3FFFFF.
+    pub fn read(&self, addr: u32) -> u8 {
+        if self.eeprom_at(addr) {
+            return self.eeprom.sda_out() as u8;
+        }
+        if self.sram_mapped {
+            if let Some(i) = self.save_info.and_then(|info| info.sram_index(addr)) {
+                return self.sram[i];
+            }
+        }
+        self.rom.get(addr as usize).copied().unwrap_or(0xFF)
+    }
+
+    /// Escritura en $000000-$3FFFFF: solo llega a la SRAM o a la EEPROM.
+    pub fn write(&mut self, addr: u32, val: u8) {
+        if self.eeprom_at(addr) {
+            self.eeprom.write_lines(val & 0x01 != 0, val & 0x02 != 0);
+            self.dirty |= std::mem::take(&mut self.eeprom.modified);
+            return;
+        }
+        if !self.sram_mapped || self.write_protect {
+            return;
+        }
+        if let Some(i) = self.save_info.and_then(|info| info.sram_index(addr)) {
+            self.sram[i] = val;
+            self.dirty = true;
+        }
+    }
+
+    /// $A130F1: bit 0 = SRAM mapeada, bit 1 = protegida contra escritura.
+    pub fn write_control(&mut self, val: u8) {
+        if self.save_info.is_some() {
+            self.sram_mapped = val & 0x01 != 0;
+            self.write_protect = val & 0x02 != 0;
+        }
+    }
+
+    /// Contenido a persistir (SRAM o EEPROM), si el cartucho tiene.
+    pub fn save_data(&self) -> Option<&[u8]> {
+        match self.save_info?.kind {
+            SaveKind::Sram(_) => Some(&self.sram),
+            SaveKind::Eeprom => Some(&self.eeprom.data),
+        }
+    }
+
+    /// Carga un `.srm`. `Ok(false)` si no existe o el cartucho no guarda.
+    pub fn load_save(&mut self, path: &Path) -> io::Result<bool> {
+        let Some(info) = self.save_info else { return Ok(false) };
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let target: &mut [u8] = match info.kind {
+            SaveKind::Sram(_) => &mut self.sram,
+            SaveKind::Eeprom => &mut self.eeprom.data,
+        };
+        let n = data.len().min(target.len());
+        target[..n].copy_from_slice(&data[..n]);
+        self.dirty = false;
+        Ok(true)
+    }
+
+    /// Escribe el `.srm` (nada si el cartucho no guarda).
+    pub fn write_save(&mut self, path: &Path) -> io::Result<()> {
+        if let Some(data) = self.save_data() {
+            fs::write(path, data)?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,461 @@
+// crates/systems/oxid_genesis/src/genesis_vdp.rs
+//
+// VDP del Mega Drive (Modo 5, esqueleto): VRAM 64KB, CRAM 64 colores, VSRAM,
+// protocolo de los puertos de control/datos y render por scanline de los
+// planos A/B y sprites con prioridades. H32/H40, V28/V30.
+// DMA: 68K -> VRAM/CRAM/VSRAM (lo ejecuta el bus), relleno y copia de VRAM.
+// Sin implementar todavía: ventana, modo entrelazado, shadow/highlight,
+// interrupciones y temporización (HV counter).
+
+const VRAM_SIZE: usize = 0x10000;
+const CRAM_ENTRIES: usize = 64;
+const VSRAM_ENTRIES: usize = 40;
+const NUM_REGS: usize = 24;
+
+// Códigos de acceso (CD3-CD0) del puerto de control
+const CODE_VRAM_READ: u8 = 0x00;
+const CODE_VRAM_WRITE: u8 = 0x01;
+const CODE_CRAM_WRITE: u8 = 0x03;
+const CODE_VSRAM_READ: u8 = 0x04;
+const CODE_VSRAM_WRITE: u8 = 0x05;
+const CODE_CRAM_READ: u8 = 0x08;
+
+/// CD5 del comando de dirección: arranca un DMA (si el registro 1 lo permite)
+const CODE_DMA: u8 = 0x20;
+
+const STATUS_FIFO_EMPTY: u16 = 0x0200;
+const STATUS_VBLANK: u16 = 0x0008;
+const STATUS_PAL: u16 = 0x0001;
+
+/// Ancho máximo de línea (H40)
+pub const MAX_WIDTH: usize = 320;
+
+/// Convierte una entrada de CRAM (----BBB-GGG-RRR-) a ARGB 0xFFRRGGBB.
+fn cram_to_rgb(val: u16) -> u32 {
+    let r = ((val >> 1) & 7) as u32 * 36;
+    let g = ((val >> 5) & 7) as u32 * 36;
+    let b = ((val >> 9) & 7) as u32 * 36;
+    0xFF000000 | (r << 16) | (g << 8) | b
+}
+
+/// Tamaño de plano (celdas) según el campo de 2 bits del registro 16.
+fn plane_cells(bits: u8) -> usize {
+    match bits & 3 {
+        1 => 64,
+        3 => 128,
+        _ => 32, // 2 es inválido: se trata como 32
+    }
+}
+
+/// Tipo de DMA (registro 23, bits 7-6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmaMode {
+    /// Memoria del 68000 -> VRAM/CRAM/VSRAM (según el código de destino)
+    MemoryToVdp,
+    /// Relleno de VRAM con el byte alto de la siguiente escritura de datos
+    VramFill,
+    /// Copia VRAM -> VRAM byte a byte
+    VramCopy,
+}
+
+/// Píxel de una capa: índice de CRAM (0-63) y bit de prioridad.
+/// Índice % 16 == 0 = transparente.
+#[derive(Clone, Copy, Default)]
+struct LayerPixel {
+    color: u8,
+    priority: bool,
+}
+
+impl LayerPixel {
+    fn opaque(self) -> bool {
+        self.color & 0x0F != 0
+    }
+}
+
+pub struct GenesisVdp {
+    pub vram: Box<[u8; VRAM_SIZE]>,
+    pub cram: [u16; CRAM_ENTRIES],
+    pub vsram: [u16; VSRAM_ENTRIES],
+    pub regs: [u8; NUM_REGS],
+
+    // Protocolo del puerto de control
+    pub address: u16,
+    pub code: u8,
+    /// Se escribió la primera palabra de un comando de dirección
+    pub pending: bool,
+
+    // DMA en espera: del 68000 (lo ejecuta el bus) o relleno (siguiente dato)
+    pub dma_memory_pending: bool,
+    dma_fill_pending: bool,
+
+    pub vblank: bool,
+    pub pal: bool,
+}
+
+impl Default for GenesisVdp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GenesisVdp {
+    pub fn new() -> Self {
+        Self {
+            vram: Box::new([0; VRAM_SIZE]),
+            cram: [0; CRAM_ENTRIES],
+            vsram: [0; VSRAM_ENTRIES],
+            regs: [0; NUM_REGS],
+            address: 0,
+            code: 0,
+            pending: false,
+            dma_memory_pending: false,
+            dma_fill_pending: false,
+            vblank: false,
+            pal: false,
+        }
+    }
+
+    /// 320 (H40) o 256 (H32) píxeles por línea.
+    pub fn width(&self) -> usize {
+        if self.regs[12] & 0x81 != 0 { 320 } else { 256 }
+    }
+
+    /// 240 (V30) o 224 (V28) líneas visibles.
+    pub fn height(&self) -> usize {
+        if self.regs[1] & 0x08 != 0 { 240 } else { 224 }
+    }
+
+    // ========================================================================
+    //  PUERTOS
+    // ========================================================================
+
+    /// Puerto de control ($C00004), palabra de 16 bits.
+    /// `100r rrrr dddd dddd` escribe un registro; si no, es un comando de dirección
+    /// de dos palabras: `CD1 CD0 A13-A0` y luego `---- ---- CD5-CD2 -- A15 A14`.
+    pub fn write_control(&mut self, word: u16) {
+        if self.pending {
+            self.pending = false;
+            self.address = (self.address & 0x3FFF) | ((word & 0x0003) << 14);
+            self.code = (self.code & 0x03) | ((word >> 2) & 0x3C) as u8;
+            if self.code & CODE_DMA != 0 {
+                self.code &= !CODE_DMA;
+                if self.regs[1] & 0x10 != 0 {
+                    self.start_dma();
+                }
+            }
+            return;
+        }
+        if word & 0xC000 == 0x8000 {
+            let reg = ((word >> 8) & 0x1F) as usize;
+            if reg < NUM_REGS {
+                self.regs[reg] = word as u8;
+            }
+            return;
+        }
+        self.pending = true;
+        self.address = (self.address & 0xC000) | (word & 0x3FFF);
+        self.code = (self.code & 0x3C) | ((word >> 14) & 0x03) as u8;
+    }
+
+    /// Puerto de datos ($C00000): escritura de palabra según el código activo.
+    pub fn write_data(&mut self, word: u16) {
+        self.pending = false;
+        match self.code & 0x0F {
+            CODE_VRAM_WRITE => {
+                // Dirección impar: la palabra se escribe con los bytes cruzados
+                let a = (self.address & 0xFFFE) as usize;
+                let word = if self.address & 1 != 0 { word.swap_bytes() } else { word };
+                self.vram[a] = (word >> 8) as u8;
+                self.vram[a + 1] = word as u8;
+            }
+            CODE_CRAM_WRITE => {
+                self.cram[((self.address >> 1) & 0x3F) as usize] = word & 0x0EEE;
+            }
+            CODE_VSRAM_WRITE => {
+                let i = ((self.address >> 1) & 0x3F) as usize;
+                if i < VSRAM_ENTRIES {
+                    self.vsram[i] = word & 0x07FF;
+                }
+            }
+            _ => {} // Código de lectura: la escritura se ignora
+        }
+        self.advance();
+
+        if self.dma_fill_pending {
+            self.dma_fill_pending = false;
+            self.vram_fill((word >> 8) as u8);
+        }
+    }
+
+    /// Puerto de datos: lectura de palabra según el código activo.
+    #[allow(dead_code)] // El bus stub aún no modela lecturas con efectos (read es &self)
+    pub fn read_data(&mut self) -> u16 {
+        self.pending = false;
+        let value = match self.code & 0x0F {
+            CODE_VRAM_READ => self.vram_word((self.address & 0xFFFE) as usize),
+            CODE_CRAM_READ => self.cram[((self.address >> 1) & 0x3F) as usize],
+            CODE_VSRAM_READ => self.vsram.get(((self.address >> 1) & 0x3F) as usize).copied().unwrap_or(0),
+            _ => 0,
+        };
+        self.advance();
+        value
+    }
+
+    // ========================================================================
+    //  DMA
+    // ========================================================================
+
+    pub fn dma_mode(&self) -> DmaMode {
+        match self.regs[23] >> 6 {
+            2 => DmaMode::VramFill,
+            3 => DmaMode::VramCopy,
+            _ => DmaMode::MemoryToVdp,
+        }
+    }
+
+    /// Longitud (registros 19/20): palabras para 68K->VDP, bytes para relleno/copia.
+    /// 0 equivale a 0x10000.
+    fn dma_length(&self) -> u32 {
+        match u16::from_le_bytes([self.regs[19], self.regs[20]]) {
+            0 => 0x10000,
+            n => n as u32,
+        }
+    }
+
+    /// Origen (registros 21-23) en bytes para 68K->VDP.
+    pub fn dma_source(&self) -> u32 {
+        let words = u32::from_le_bytes([self.regs[21], self.regs[22], self.regs[23] & 0x7F, 0]);
+        words << 1
+    }
+
+    fn start_dma(&mut self) {
+        match self.dma_mode() {
+            DmaMode::MemoryToVdp => self.dma_memory_pending = true,
+            DmaMode::VramFill => self.dma_fill_pending = true,
+            DmaMode::VramCopy => self.vram_copy(),
+        }
+    }
+
+    /// Ejecuta el DMA 68K->VDP pendiente leyendo palabras con `read_word`.
+    /// El origen avanza dentro de su bloque de 128KB (los bits altos no cambian).
+    pub fn run_memory_dma(&mut self, read_word: &mut dyn FnMut(u32) -> u16) {
+        if !self.dma_memory_pending {
+            return;
+        }
+        self.dma_memory_pending = false;
+        let length = self.dma_length();
+        let mut source = self.dma_source();
+        for _ in 0..length {
+            let word = read_word(source);
+            self.write_data(word);
+            source = (source & 0xFE0000) | (source.wrapping_add(2) & 0x1FFFE);
+        }
+        self.finish_dma((source >> 1) as u16);
+    }
+
+    /// Relleno: el byte alto del dato escrito se repite `length` veces.
+    fn vram_fill(&mut self, value: u8) {
+        for _ in 0..self.dma_length() {
+            self.vram[(self.address ^ 1) as usize] = value;
+            self.advance();
+        }
+        let source = u16::from_le_bytes([self.regs[21], self.regs[22]]);
+        self.finish_dma(source);
+    }
+
+    /// Copia: `length` bytes desde el origen (registros 21/22, dirección de byte).
+    fn vram_copy(&mut self) {
+        let mut source = u16::from_le_bytes([self.regs[21], self.regs[22]]);
+        for _ in 0..self.dma_length() {
+            self.vram[self.address as usize] = self.vram[source as usize];
+            source = source.wrapping_add(1);
+            self.advance();
+        }
+        self.finish_dma(source);
+    }
+
+    /// Al terminar la longitud queda a 0 y el origen apunta tras lo transferido.
+    fn finish_dma(&mut self, source_low: u16) {
+        self.regs[19] = 0;
+        self.regs[20] = 0;
+        [self.regs[21], self.regs[22]] = source_low.to_le_bytes();
+    }
+
+    /// Registro de estado sin efectos laterales.
+    pub fn peek_status(&self) -> u16 {
+        let mut status = 0x3400 | STATUS_FIFO_EMPTY;
+        if self.vblank {
+            status |= STATUS_VBLANK;
+        }
+        if self.pal {
+            status |= STATUS_PAL;
+        }
+        status
+    }
+
+    /// Leer el estado cancela un comando de dirección a medias.
+    #[allow(dead_code)] // Ver read_data
+    pub fn read_status(&mut self) -> u16 {
+        self.pending = false;
+        self.peek_status()
+    }
+
+    /// Auto-incremento (registro 15) tras cada acceso al puerto de datos.
+    fn advance(&mut self) {
+        self.address = self.address.wrapping_add(self.regs[15] as u16);
+    }
+
+    fn vram_word(&self, addr: usize) -> u16 {
+        let a = addr & 0xFFFE;
+        u16::from_be_bytes([self.vram[a], self.vram[a + 1]])
+    }
+
+    /// Nibble (0-15) del píxel (col, row) de un tile de 32 bytes.
+    fn tile_pixel(&self, tile: usize, col: usize, row: usize) -> u8 {
+        let byte = self.vram[(tile * 32 + row * 4 + col / 2) & 0xFFFF];
+        if col & 1 == 0 { byte >> 4 } else { byte & 0x0F }
+    }
+
+    // ========================================================================
+    //  RENDER
+    // ========================================================================
+
+    /// Dibuja la línea `y` en `line_buffer` (al menos `width()` píxeles).
+    pub fn render_scanline(&self, y: usize, line_buffer: &mut [u32]) {
+        let width = self.width();
+        let backdrop = cram_to_rgb(self.cram[(self.regs[7] & 0x3F) as usize]);
+        if y >= self.height() || self.regs[1] & 0x40 == 0 {
+            // Fuera de pantalla o display desactivado: solo el color de fondo
+            line_buffer[..width].fill(backdrop);
+            return;
+        }
+
+        let mut plane_a = [LayerPixel::default(); MAX_WIDTH];
+        let mut plane_b = [LayerPixel::default(); MAX_WIDTH];
+        let mut sprites = [LayerPixel::default(); MAX_WIDTH];
+        let plane_a_base = ((self.regs[2] & 0x38) as usize) << 10;
+        let plane_b_base = ((self.regs[4] & 0x07) as usize) << 13;
+        self.render_plane(y, plane_a_base, 0, &mut plane_a[..width]);
+        self.render_plane(y, plane_b_base, 1, &mut plane_b[..width]);
+        self.render_sprites(y, &mut sprites[..width]);
+
+        for x in 0..width {
+            // De delante a atrás: S alta, A alta, B alta, S baja, A baja, B baja
+            let (s, a, b) = (sprites[x], plane_a[x], plane_b[x]);
+            let pixel = [s, a, b]
+                .into_iter()
+                .find(|p| p.opaque() && p.priority)
+                .or_else(|| [s, a, b].into_iter().find(|p| p.opaque()));
+            line_buffer[x] = match pixel {
+                Some(p) => cram_to_rgb(self.cram[p.color as usize]),
+                None => backdrop,
+            };
+        }
+    }
+
+    /// Frame completo en `buffer` (`width() * height()` píxeles).
+    pub fn render_frame(&self, buffer: &mut [u32]) {
+        let width = self.width();
+        for (y, line) in buffer.chunks_exact_mut(width).take(self.height()).enumerate() {
+            self.render_scanline(y, line);
+        }
+    }
+
+    /// Plano A (`plane` = 0) o B (1) con su scroll horizontal y vertical.
+    fn render_plane(&self, y: usize, base: usize, plane: usize, out: &mut [LayerPixel]) {
+        let cells_w = plane_cells(self.regs[16]);
+        let cells_h = plane_cells(self.regs[16] >> 4);
+        let (px_w, px_h) = (cells_w * 8, cells_h * 8);
+
+        // HScroll: tabla en VRAM (completo / por celda / por línea)
+        let hscroll_base = ((self.regs[13] & 0x3F) as usize) << 10;
+        let hscroll_line = match self.regs[11] & 0x03 {
+            2 => y & !7,
+            3 => y,
+            _ => 0,
+        };
+        let hscroll = (self.vram_word(hscroll_base + hscroll_line * 4 + plane * 2) & 0x3FF) as usize;
+        let per_column_vscroll = self.regs[11] & 0x04 != 0;
+
+        for (x, pixel) in out.iter_mut().enumerate() {
+            // VScroll: uno por plano, o por columnas de 16 píxeles
+            let vs_index = if per_column_vscroll { ((x / 16) * 2 + plane).min(VSRAM_ENTRIES - 1) } else { plane };
+            let vscroll = (self.vsram[vs_index] & 0x3FF) as usize;
+
+            let px = (x + px_w * 4 - hscroll % px_w) % px_w;
+            let py = (y + vscroll) % px_h;
+            let entry = self.vram_word(base + ((py / 8) * cells_w + px / 8) * 2);
+
+            let tile = (entry & 0x07FF) as usize;
+            let mut col = px % 8;
+            let mut row = py % 8;
+            if entry & 0x0800 != 0 {
+                col = 7 - col;
+            }
+            if entry & 0x1000 != 0 {
+                row = 7 - row;
+            }
+            let palette = ((entry >> 13) & 0x03) as u8;
+            *pixel = LayerPixel {
+                color: palette * 16 + self.tile_pixel(tile, col, row),
+                priority: entry & 0x8000 != 0,
+            };
+        }
+    }
+
+    /// Sprites de la línea recorriendo la lista enlazada de la SAT. El primero
+    /// de la lista queda delante.
+    fn render_sprites(&self, y: usize, out: &mut [LayerPixel]) {
+        let h40 = out.len() == 320;
+        let sat_mask = if h40 { 0x7E } else { 0x7F };
+        let sat = ((self.regs[5] & sat_mask) as usize) << 9;
+        let (max_sprites, max_per_line) = if h40 { (80, 20) } else { (64, 16) };
+
+        let line = y as i32 + 128;
+        let mut index = 0usize;
+        let mut on_line = 0;
+        for _ in 0..max_sprites {
+            let entry = sat + index * 8;
+            let sy = (self.vram_word(entry) & 0x03FF) as i32;
+            let size = self.vram[(entry + 2) & 0xFFFF];
+            let link = (self.vram[(entry + 3) & 0xFFFF] & 0x7F) as usize;
+            let attr = self.vram_word(entry + 4);
+            let sx = (self.vram_word(entry + 6) & 0x01FF) as i32 - 128;
+
+            let cells_w = ((size >> 2) & 3) as usize + 1;
+            let cells_h = (size & 3) as usize + 1;
+            let row = line - sy;
+            if row >= 0 && (row as usize) < cells_h * 8 {
+                on_line += 1;
+                if on_line > max_per_line {
+                    break;
+                }
+                let mut row = row as usize;
+                if attr & 0x1000 != 0 {
+                    row = cells_h * 8 - 1 - row;
+                }
+                let palette = ((attr >> 13) & 0x03) as u8;
+                for i in 0..cells_w * 8 {
+                    let x = sx + i as i32;
+                    if x < 0 || x as usize >= out.len() || out[x as usize].opaque() {
+                        continue;
+                    }
+                    let col = if attr & 0x0800 != 0 { cells_w * 8 - 1 - i } else { i };
+                    // Tiles de sprite en orden de columnas
+                    let tile = (attr & 0x07FF) as usize + (col / 8) * cells_h + row / 8;
+                    let nibble = self.tile_pixel(tile, col % 8, row % 8);
+                    if nibble != 0 {
+                        out[x as usize] = LayerPixel {
+                            color: palette * 16 + nibble,
+                            priority: attr & 0x8000 != 0,
+                        };
+                    }
+                }
+            }
+            if link == 0 || link >= max_sprites {
+                break;
+            }
+            index = link;
+        }
+    }
+}
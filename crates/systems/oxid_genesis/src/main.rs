@@ -1,27 +1,280 @@
-use oxide_core::{Cpu, MemoryBus};
+use oxide_core::{hash_framebuffer, AudioSink, CoScheduler, Cpu, MemoryBus, SharedBus, Side};
+use oxide_core::psg::Psg;
 use oxid68k::Oxid68k;
 use oxidz80::OxidZ80;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
 
-fn main() {
-    println!("--- Oxide-Genesis (Mega Drive) ---");
+mod cartridge;
+mod genesis_vdp;
+#[cfg(test)]
+mod tests;
+mod ym2612;
+
+use cartridge::Cartridge;
+use genesis_vdp::GenesisVdp;
+use ym2612::Ym2612;
+
+/// Frecuencia de salida del audio
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Reloj maestro NTSC: el 68000 va a MCLK/7 y el Z80 a MCLK/15
+const MASTER_CLOCK: u64 = 53_693_175;
+const M68K_CLOCK: u64 = MASTER_CLOCK / 7;
+const Z80_CLOCK: u64 = MASTER_CLOCK / 15;
+/// Ciclos del 68000 por línea (3420 de MCLK) y líneas por frame NTSC
+const M68K_CYCLES_PER_LINE: u32 = 3420 / 7;
+const LINES_PER_FRAME: u32 = 262;
+
+// 1. Hardware
+struct GenesisBus {
+    cartridge: Cartridge,
+    work_ram: [u8; 65536],
+    z80_ram: [u8; 8192],
+    /// $A11100: el 68000 pide el bus del Z80 (el Z80 se para mientras lo tiene)
+    z80_busreq: bool,
+    /// $A11200: RESET del Z80 activo (así arranca la consola)
+    z80_reset: bool,
+    /// Banco de 32KB de la memoria del 68000 visible en $8000-$FFFF del Z80
+    z80_bank: u32,
+    vdp: GenesisVdp,
+    // Byte alto de una escritura de palabra al VDP ($C00000-$C0001F)
+    vdp_latch: u8,
+    ym: Ym2612,
+    psg: Psg,
+}
+
+impl GenesisBus {
+    fn new(rom: Vec<u8>) -> Self {
+        Self {
+            cartridge: Cartridge::new(rom),
+            work_ram: [0; 65536],
+            z80_ram: [0; 8192],
+            z80_busreq: false,
+            z80_reset: true,
+            z80_bank: 0,
+            vdp: GenesisVdp::new(),
+            vdp_latch: 0,
+            ym: Ym2612::new(),
+            psg: Psg::new(),
+        }
+    }
+
+    /// ¿Puede el Z80 ejecutar? (ni en reset ni con el bus cedido al 68000)
+    fn z80_running(&self) -> bool {
+        !self.z80_reset && !self.z80_busreq
+    }
 
-    // 1. Inicializar el Hardware
-    struct GenesisBus {
-        _cartridge_rom: Vec<u8>,
-        _work_ram: [u8; 65536],
-        _z80_ram: [u8; 8192],
+    /// Genera `frames` muestras estéreo de FM + PSG y las entrega al sink.
+    fn render_audio(&mut self, frames: usize, sink: &mut dyn AudioSink) {
+        let mut out = vec![0i16; frames * 2];
+        self.ym.generate(&mut out, SAMPLE_RATE);
+        let mut psg = vec![0i16; frames];
+        self.psg.generate(&mut psg, SAMPLE_RATE);
+        for (lr, p) in out.chunks_exact_mut(2).zip(psg) {
+            lr[0] = lr[0].saturating_add(p);
+            lr[1] = lr[1].saturating_add(p);
+        }
+        sink.push_samples(&out);
     }
+}
 
-    impl MemoryBus for GenesisBus {
-        fn read(&self, _addr: u32) -> u8 { 0 }
-        fn write(&mut self, _addr: u32, _val: u8) { }
+/// Memoria del 68000 vista por la CPU y por el DMA del VDP.
+fn read_main(cartridge: &Cartridge, work_ram: &[u8; 65536], addr: u32) -> u8 {
+    match addr & 0xFFFFFF {
+        a @ 0x000000..=0x3FFFFF => cartridge.read(a),
+        a @ 0xE00000..=0xFFFFFF => work_ram[(a & 0xFFFF) as usize], // 64KB con espejo
+        _ => 0,
     }
+}
 
-    let mut bus = GenesisBus {
-        _cartridge_rom: vec![0; 1024 * 1024], // 1MB ROM ficticia
-        _work_ram: [0; 65536],
-        _z80_ram: [0; 8192],
+impl MemoryBus for GenesisBus {
+    fn read(&self, addr: u32) -> u8 {
+        match addr & 0xFFFFFF {
+            // Estado del VDP (lectura sin efectos: el bus es &self)
+            0xC00004..=0xC00007 => {
+                let status = self.vdp.peek_status();
+                if addr & 1 == 0 { (status >> 8) as u8 } else { status as u8 }
+            }
+            0xA04000..=0xA04003 => self.ym.status(),
+            // RAM del Z80: solo con el bus concedido
+            a @ 0xA00000..=0xA03FFF if !self.z80_running() => self.z80_ram[(a & 0x1FFF) as usize],
+            // Bit 0 = 0: bus concedido
+            0xA11100 => (!self.z80_busreq || self.z80_reset) as u8,
+            _ => read_main(&self.cartridge, &self.work_ram, addr),
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        let addr = addr & 0xFFFFFF;
+        if addr >= 0xE00000 {
+            self.work_ram[(addr & 0xFFFF) as usize] = val;
+            return;
+        }
+        // SRAM / EEPROM del cartucho y su registro de control
+        if addr <= 0x3FFFFF {
+            self.cartridge.write(addr, val);
+            return;
+        }
+        if addr == 0xA130F1 {
+            self.cartridge.write_control(val);
+            return;
+        }
+        match addr {
+            0xA00000..=0xA03FFF => {
+                if !self.z80_running() {
+                    self.z80_ram[(addr & 0x1FFF) as usize] = val;
+                }
+                return;
+            }
+            0xA11100 => {
+                self.z80_busreq = val & 1 != 0;
+                return;
+            }
+            0xA11200 => {
+                self.z80_reset = val & 1 == 0;
+                return;
+            }
+            _ => {}
+        }
+        if (0xA04000..=0xA04003).contains(&addr) {
+            self.ym.write((addr & 3) as u8, val);
+            return;
+        }
+        // PSG: puerto de 8 bits en las direcciones impares $C00011-$C00017
+        if (0xC00010..=0xC00017).contains(&addr) {
+            if addr & 1 != 0 {
+                self.psg.write(val);
+            }
+            return;
+        }
+        if !(0xC00000..=0xC00007).contains(&addr) {
+            return;
+        }
+        // El 68000 escribe las palabras byte a byte (alto, bajo): se entrega al completar
+        if addr & 1 == 0 {
+            self.vdp_latch = val;
+            return;
+        }
+        let word = u16::from_be_bytes([self.vdp_latch, val]);
+        if addr < 0xC00004 {
+            self.vdp.write_data(word);
+        } else {
+            self.vdp.write_control(word);
+            // DMA 68K->VDP: el VDP toma el bus y lee la memoria del 68000
+            let (rom, ram) = (&self.cartridge, &self.work_ram);
+            self.vdp.run_memory_dma(&mut |a| {
+                u16::from_be_bytes([read_main(rom, ram, a), read_main(rom, ram, a + 1)])
+            });
+        }
+    }
+}
+
+// ============================================================================
+// BUS DEL Z80
+// ============================================================================
+
+/// Mapa de memoria visto por el Z80:
+/// - $0000-$3FFF: 8KB de RAM propia (con espejo)
+/// - $4000-$5FFF: YM2612
+/// - $6000-$60FF: registro de banco (se carga bit a bit, 9 escrituras)
+/// - $7F11-$7F17: PSG
+/// - $8000-$FFFF: ventana de 32KB a la memoria del 68000
+struct Z80Bus<'a>(&'a mut GenesisBus);
+
+impl MemoryBus for Z80Bus<'_> {
+    fn read(&self, addr: u32) -> u8 {
+        let bus = &*self.0;
+        match addr & 0xFFFF {
+            a @ 0x0000..=0x3FFF => bus.z80_ram[(a & 0x1FFF) as usize],
+            0x4000..=0x5FFF => bus.ym.status(),
+            a @ 0x8000..=0xFFFF => bus.read((bus.z80_bank << 15) | (a & 0x7FFF)),
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        let bus = &mut *self.0;
+        match addr & 0xFFFF {
+            a @ 0x0000..=0x3FFF => bus.z80_ram[(a & 0x1FFF) as usize] = val,
+            a @ 0x4000..=0x5FFF => bus.ym.write((a & 3) as u8, val),
+            0x6000..=0x60FF => bus.z80_bank = ((bus.z80_bank >> 1) | ((val as u32 & 1) << 8)) & 0x1FF,
+            a @ 0x7F10..=0x7F17 if a & 1 != 0 => bus.psg.write(val),
+            a @ 0x8000..=0xFFFF => bus.write((bus.z80_bank << 15) | (a & 0x7FFF), val),
+            _ => {}
+        }
+    }
+}
+
+// ============================================================================
+// MÁQUINA
+// ============================================================================
+
+struct Genesis {
+    m68k: Oxid68k,
+    z80: OxidZ80,
+    bus: GenesisBus,
+}
+
+/// El 68000 usa el bus tal cual; el Z80 su propio mapa, y se queda en reset
+/// (o parado con el bus cedido) según $A11200 / $A11100.
+impl SharedBus for GenesisBus {
+    fn step_cpu(&mut self, side: Side, cpu: &mut dyn Cpu) -> u32 {
+        match side {
+            Side::Main => cpu.step(self),
+            Side::Sub if self.z80_reset => {
+                cpu.reset();
+                4
+            }
+            Side::Sub => cpu.step(&mut Z80Bus(self)),
+        }
+    }
+
+    fn halted(&self, side: Side) -> bool {
+        side == Side::Sub && self.z80_busreq
+    }
+}
+
+/// 68000 y Z80 intercalados línea a línea.
+fn scheduler() -> CoScheduler {
+    CoScheduler::new(M68K_CLOCK, Z80_CLOCK, M68K_CYCLES_PER_LINE)
+}
+
+/// Un frame NTSC de ambas CPUs.
+fn run_frame(g: &mut Genesis, sched: &mut CoScheduler) {
+    sched.run(&mut g.m68k, &mut g.z80, &mut g.bus, M68K_CYCLES_PER_LINE * LINES_PER_FRAME);
+}
+
+fn main() {
+    println!("--- Oxide-Genesis (Mega Drive) ---");
+
+    // oxid_genesis [rom.bin]: sin ROM se usa 1MB ficticio
+    let rom_path = env::args().nth(1).map(PathBuf::from);
+    let rom = match &rom_path {
+        Some(path) => match fs::read(path) {
+            Ok(data) => data,
+            Err(e) => {
+                println!("Error reading ROM file: {}", e);
+                return;
+            }
+        },
+        None => vec![0; 1024 * 1024],
     };
+    let mut bus = GenesisBus::new(rom);
+
+    // Guardado del cartucho: <rom>.srm junto a la ROM
+    let save_path = rom_path.map(|p| p.with_extension("srm"));
+    if let Some(info) = bus.cartridge.save_info {
+        println!("Save: {:?} ${:06X}-${:06X} ({} bytes)", info.kind, info.start, info.end, info.size());
+        if let Some(path) = &save_path {
+            match bus.cartridge.load_save(path) {
+                Ok(true) => println!("Loaded {}", path.display()),
+                Ok(false) => {}
+                Err(e) => println!("Error reading {}: {}", path.display(), e),
+            }
+        }
+    }
 
     let mut main_cpu = Oxid68k::new(); // El jefe (Juego)
     let mut sound_cpu = OxidZ80::new(); // El asistente (Audio)
@@ -34,11 +287,30 @@ fn main() {
     println!("- Main CPU: Motorola 68000");
     println!("- Sound CPU: Zilog Z80");
 
-    // 3. Ejecutar un paso en ambas (Sincronización básica)
-    // En un emulador real, el 68k corre más rápido que el Z80
-    main_cpu.step(&mut bus);
-    sound_cpu.step(&mut bus); // El Z80 lee de su región mapeada
+    // 3. Un frame con ambas CPUs sincronizadas (68000 por líneas, Z80 a su ritmo)
+    let mut genesis = Genesis { m68k: main_cpu, z80: sound_cpu, bus };
+    let mut sched = scheduler();
+    run_frame(&mut genesis, &mut sched);
+    let Genesis { m68k: main_cpu, z80: sound_cpu, mut bus } = genesis;
 
-    println!("PC 68k: 0x{:08X}", main_cpu.pc());
-    println!("PC Z80: 0x{:04X}", sound_cpu.pc() as u16);
-}
\ No newline at end of file
+    let (main_cycles, sub_cycles) = sched.cycles();
+    println!("PC 68k: 0x{:08X} ({} ciclos)", main_cpu.pc(), main_cycles);
+    println!("PC Z80: 0x{:04X} ({} ciclos)", sound_cpu.pc() as u16, sub_cycles);
+
+    // 4. Un frame del VDP
+    let (w, h) = (bus.vdp.width(), bus.vdp.height());
+    let mut frame = vec![0u32; w * h];
+    bus.vdp.render_frame(&mut frame);
+    println!("VDP: {}x{} frame CRC {:08X}", w, h, hash_framebuffer(&frame));
+
+    // 5. Un frame de audio (FM + PSG)
+    let mut audio: Vec<i16> = Vec::new();
+    bus.render_audio((SAMPLE_RATE / 60) as usize, &mut audio);
+    println!("Audio: {} muestras estéreo a {} Hz", audio.len() / 2, SAMPLE_RATE);
+
+    if let (true, Some(path)) = (bus.cartridge.dirty, &save_path) {
+        if let Err(e) = bus.cartridge.write_save(path) {
+            println!("Error writing {}: {}", path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,485 @@
+// crates/systems/oxid_genesis/src/tests.rs
+use crate::cartridge::{Cartridge, SaveInfo, SaveKind, SramLanes};
+use crate::genesis_vdp::{DmaMode, GenesisVdp};
+use crate::{run_frame, scheduler, Genesis, GenesisBus, Z80Bus, LINES_PER_FRAME, M68K_CLOCK, M68K_CYCLES_PER_LINE, Z80_CLOCK};
+use oxid68k::Oxid68k;
+use oxide_core::MemoryBus;
+use oxidz80::OxidZ80;
+
+const RED: u32 = 0xFFFC0000;
+const GREEN: u32 = 0xFF00FC00;
+const BLACK: u32 = 0xFF000000;
+
+/// Comando de dirección de dos palabras: `code` = CD5-CD0.
+fn set_address(vdp: &mut GenesisVdp, code: u8, addr: u16) {
+    vdp.write_control(((code as u16 & 0x03) << 14) | (addr & 0x3FFF));
+    vdp.write_control(((code as u16 & 0x3C) << 2) | (addr >> 14));
+}
+
+fn write_words(vdp: &mut GenesisVdp, code: u8, addr: u16, words: &[u16]) {
+    set_address(vdp, code, addr);
+    for &w in words {
+        vdp.write_data(w);
+    }
+}
+
+/// H40, display activo, plano A en $C000, B en $E000, SAT en $F800, HScroll en $FC00,
+/// planos de 32x32, tile 1 sólido (color 1). Rojo = paleta 0, verde = paleta 1.
+fn vdp_with_tile() -> GenesisVdp {
+    let mut vdp = GenesisVdp::new();
+    for reg in [0x8144, 0x8230, 0x8407, 0x857C, 0x8700, 0x8C81, 0x8D3F, 0x8F02, 0x9000] {
+        vdp.write_control(reg);
+    }
+    write_words(&mut vdp, 0x03, 0x00, &[0x0000, 0x000E]); // CRAM 0 negro, 1 rojo
+    write_words(&mut vdp, 0x03, 0x22, &[0x00E0]); // CRAM 17 verde
+    write_words(&mut vdp, 0x01, 0x0020, &[0x1111; 16]); // tile 1
+    vdp
+}
+
+// ============================================================================
+// Puertos
+// ============================================================================
+
+#[test]
+fn test_control_port_registers_and_address() {
+    let mut vdp = GenesisVdp::new();
+    vdp.write_control(0x8F02);
+    assert_eq!(vdp.regs[15], 2);
+    assert!(!vdp.pending);
+
+    set_address(&mut vdp, 0x01, 0xC002);
+    assert_eq!((vdp.address, vdp.code, vdp.pending), (0xC002, 0x01, false));
+    vdp.write_data(0xABCD);
+    vdp.write_data(0x1234);
+    assert_eq!(vdp.address, 0xC006);
+    assert_eq!(&vdp.vram[0xC002..0xC006], &[0xAB, 0xCD, 0x12, 0x34]);
+
+    // VSRAM y lectura de VRAM
+    write_words(&mut vdp, 0x05, 0x02, &[0x0123]);
+    assert_eq!(vdp.vsram[1], 0x0123);
+    set_address(&mut vdp, 0x00, 0xC002);
+    assert_eq!(vdp.read_data(), 0xABCD);
+    assert_eq!(vdp.read_data(), 0x1234);
+
+    // Leer el estado cancela la primera mitad de un comando
+    vdp.write_control(0x4000);
+    assert!(vdp.pending);
+    assert_eq!(vdp.read_status() & 0x0200, 0x0200);
+    assert!(!vdp.pending);
+}
+
+#[test]
+fn test_bus_assembles_words_for_vdp_ports() {
+    let mut bus = GenesisBus::new(Vec::new());
+    bus.write_u16_be(0xC00004, 0x8F02);
+    bus.write_u32_be(0xC00004, 0x4000_0000); // VRAM write en $0000
+    bus.write_u16_be(0xC00000, 0xBEEF);
+    assert_eq!(bus.vdp.regs[15], 2);
+    assert_eq!(&bus.vdp.vram[0..2], &[0xBE, 0xEF]);
+    assert_eq!(bus.read_u16_be(0xC00004) & 0x0200, 0x0200);
+}
+
+// ============================================================================
+// Render
+// ============================================================================
+
+#[test]
+fn test_render_plane_a_tile() {
+    let mut vdp = vdp_with_tile();
+    write_words(&mut vdp, 0x01, 0xC000, &[0x0001]); // celda (0,0) de A -> tile 1
+
+    let mut line = [0u32; 320];
+    assert_eq!(vdp.width(), 320);
+    vdp.render_scanline(0, &mut line);
+    assert!(line[..8].iter().all(|&p| p == RED));
+    assert!(line[8..256].iter().all(|&p| p == BLACK));
+    // Plano de 32 celdas (256px) en H40: se repite
+    assert!(line[256..264].iter().all(|&p| p == RED));
+
+    // Scroll horizontal de A: +4 píxeles
+    write_words(&mut vdp, 0x01, 0xFC00, &[0x0004]);
+    vdp.render_scanline(0, &mut line);
+    assert_eq!((line[3], line[4], line[11], line[12]), (BLACK, RED, RED, BLACK));
+
+    // Display desactivado: solo backdrop
+    vdp.write_control(0x8104);
+    vdp.render_scanline(0, &mut line);
+    assert!(line.iter().all(|&p| p == BLACK));
+}
+
+#[test]
+fn test_render_sprite_and_priority() {
+    let mut vdp = vdp_with_tile();
+    // Sprite 0: 8x8 en (16, 0), tile 1, paleta 1, sin enlace
+    write_words(&mut vdp, 0x01, 0xF800, &[128, 0x0000, 0x2001, 128 + 16]);
+    // Plano B con el tile 1 bajo el sprite (prioridad baja)
+    write_words(&mut vdp, 0x01, 0xE004, &[0x0001]);
+
+    let mut line = [0u32; 320];
+    vdp.render_scanline(0, &mut line);
+    assert!(line[16..24].iter().all(|&p| p == GREEN), "sprite sobre plano de prioridad baja");
+    vdp.render_scanline(8, &mut line);
+    assert!(line[16..24].iter().all(|&p| p == BLACK), "el sprite mide 8 líneas");
+
+    // Plano B con prioridad alta gana al sprite de prioridad baja
+    write_words(&mut vdp, 0x01, 0xE004, &[0x8001]);
+    vdp.render_scanline(0, &mut line);
+    assert!(line[16..24].iter().all(|&p| p == RED));
+}
+
+// ============================================================================
+// DMA
+// ============================================================================
+
+#[test]
+fn test_dma_68k_to_vram() {
+    let mut bus = GenesisBus::new(vec![0x11, 0x22, 0x33, 0x44]);
+    bus.write_u32_be(0xFF0000, 0xDEAD_BEEF);
+    bus.write_u16_be(0xFF0004, 0xCAFE);
+
+    // DMA activo, autoinc 2, 3 palabras desde $FF0000 ($7F8000 en palabras)
+    for reg in [0x8154u16, 0x8F02, 0x9303, 0x9400, 0x9500, 0x9680, 0x977F] {
+        bus.write_u16_be(0xC00004, reg);
+    }
+    assert_eq!(bus.vdp.dma_mode(), DmaMode::MemoryToVdp);
+    assert_eq!(bus.vdp.dma_source(), 0xFF0000);
+    bus.write_u32_be(0xC00004, 0x4100_0080); // VRAM write $0100 + CD5
+
+    assert_eq!(&bus.vdp.vram[0x100..0x106], &[0xDE, 0xAD, 0xBE, 0xEF, 0xCA, 0xFE]);
+    assert!(!bus.vdp.dma_memory_pending);
+    assert_eq!(bus.vdp.address, 0x0106);
+    assert_eq!((bus.vdp.regs[19], bus.vdp.regs[20]), (0, 0));
+    assert_eq!((bus.vdp.regs[21], bus.vdp.regs[22]), (0x03, 0x80)); // origen avanzado
+
+    // Desde ROM hacia CRAM
+    for reg in [0x9302u16, 0x9400, 0x9500, 0x9600, 0x9700] {
+        bus.write_u16_be(0xC00004, reg);
+    }
+    bus.write_u32_be(0xC00004, 0xC000_0080); // CRAM write $00 + CD5
+    assert_eq!(&bus.vdp.cram[..2], &[0x1122 & 0x0EEE, 0x3344 & 0x0EEE]);
+}
+
+#[test]
+fn test_dma_disabled_in_register_1_is_ignored() {
+    let mut vdp = GenesisVdp::new();
+    vdp.write_control(0x9301);
+    set_address(&mut vdp, 0x21, 0x0000); // CD5 sin el bit de DMA en reg 1
+    assert!(!vdp.dma_memory_pending);
+    assert_eq!(vdp.code, 0x01);
+}
+
+#[test]
+fn test_dma_vram_fill_and_copy() {
+    let mut vdp = GenesisVdp::new();
+    for reg in [0x8114u16, 0x8F01, 0x9304, 0x9400, 0x9780] {
+        vdp.write_control(reg);
+    }
+    assert_eq!(vdp.dma_mode(), DmaMode::VramFill);
+    set_address(&mut vdp, 0x21, 0x0200);
+    vdp.write_data(0x5500);
+    // La escritura normal y luego 4 bytes de relleno en addr ^ 1 ($201, $202, ...)
+    assert_eq!(&vdp.vram[0x200..0x207], &[0x55, 0x00, 0x55, 0x55, 0x00, 0x55, 0x00]);
+
+    // Copia de 4 bytes $0200 -> $0300
+    vdp.vram[0x203] = 0x77;
+    for reg in [0x9304u16, 0x9400, 0x9500, 0x9602, 0x97C0] {
+        vdp.write_control(reg);
+    }
+    assert_eq!(vdp.dma_mode(), DmaMode::VramCopy);
+    set_address(&mut vdp, 0x21, 0x0300);
+    assert_eq!(&vdp.vram[0x300..0x305], &[0x55, 0x00, 0x55, 0x77, 0x00]);
+    assert_eq!(vdp.address, 0x0304);
+}
+
+// ============================================================================
+// Audio (YM2612 + PSG)
+// ============================================================================
+
+/// Escribe un registro del YM2612 por el bus del 68000 (parte 0 = I, 1 = II).
+fn ym_write(bus: &mut GenesisBus, part: u32, reg: u8, val: u8) {
+    bus.write(0xA04000 + part * 2, reg);
+    bus.write(0xA04001 + part * 2, val);
+}
+
+fn peak(samples: &[i16]) -> i16 {
+    samples.iter().map(|s| s.saturating_abs()).max().unwrap_or(0)
+}
+
+#[test]
+fn test_ym2612_patch_key_on_and_release() {
+    let mut bus = GenesisBus::new(Vec::new());
+    // Canal 1: algoritmo 7, solo S4 (registros $xC) con MUL 1, TL 0, AR 31, RR 15
+    ym_write(&mut bus, 0, 0xB0, 0x07);
+    ym_write(&mut bus, 0, 0xB4, 0x80); // solo izquierda
+    for (reg, val) in [(0x3C, 0x01), (0x4C, 0x00), (0x5C, 0x1F), (0x6C, 0x00), (0x7C, 0x00), (0x8C, 0x0F)] {
+        ym_write(&mut bus, 0, reg, val);
+    }
+    ym_write(&mut bus, 0, 0xA4, 0x22); // bloque 4
+    ym_write(&mut bus, 0, 0xA0, 0x69);
+
+    let mut silent = Vec::new();
+    bus.render_audio(512, &mut silent);
+    assert_eq!(peak(&silent), 0, "sin key-on no suena");
+
+    ym_write(&mut bus, 0, 0x28, 0x80); // key-on S4, canal 1
+    let mut on = Vec::new();
+    bus.render_audio(2048, &mut on);
+    assert_eq!(on.len(), 4096);
+    let left: Vec<i16> = on.iter().step_by(2).copied().collect();
+    let right: Vec<i16> = on.iter().skip(1).step_by(2).copied().collect();
+    assert!(peak(&left) > 2000, "pico {}", peak(&left));
+    assert_eq!(peak(&right), 0);
+
+    // Key-off con RR 15: se apaga en pocos ms
+    ym_write(&mut bus, 0, 0x28, 0x00);
+    let mut off = Vec::new();
+    bus.render_audio(4410, &mut off);
+    assert_eq!(peak(&off[off.len() - 1000..]), 0);
+}
+
+#[test]
+fn test_ym2612_dac_and_timer_status() {
+    let mut bus = GenesisBus::new(Vec::new());
+    ym_write(&mut bus, 0, 0x2B, 0x80);
+    ym_write(&mut bus, 0, 0x2A, 0xFF);
+    let mut out = Vec::new();
+    bus.render_audio(64, &mut out);
+    // (0xFF - 0x80) << 6, a la mitad en la mezcla
+    assert!(out.iter().all(|&s| s == (0x7F << 6) >> 1));
+
+    // Timer A con periodo de 1 muestra: flag en el estado, se borra con reset
+    ym_write(&mut bus, 0, 0x24, 0xFF);
+    ym_write(&mut bus, 0, 0x25, 0x03);
+    ym_write(&mut bus, 0, 0x27, 0x05);
+    assert_eq!(bus.read(0xA04000) & 0x01, 0);
+    bus.render_audio(4, &mut out);
+    assert_eq!(bus.read(0xA04000) & 0x01, 0x01);
+    ym_write(&mut bus, 0, 0x27, 0x10);
+    assert_eq!(bus.read(0xA04000) & 0x01, 0);
+}
+
+#[test]
+fn test_psg_tone_mixed_into_both_channels() {
+    let mut bus = GenesisBus::new(Vec::new());
+    bus.write(0xC00011, 0x8E); // latch tono canal 0, bits bajos = E
+    bus.write(0xC00011, 0x0F); // bits altos
+    bus.write(0xC00011, 0x90); // volumen canal 0 = máximo
+    assert_eq!(bus.psg.tone[0], 0x0FE);
+    assert_eq!(bus.psg.volume[0], 0);
+
+    let mut out = Vec::new();
+    bus.render_audio(1024, &mut out);
+    assert!(peak(&out) > 1000);
+    assert!(out.chunks_exact(2).all(|lr| lr[0] == lr[1]));
+}
+
+// ============================================================================
+// Cartucho: SRAM / EEPROM
+// ============================================================================
+
+/// ROM de `size` bytes con cabecera "RA" de guardado.
+fn rom_with_save(size: usize, kind: u8, start: u32, end: u32) -> Vec<u8> {
+    let mut rom = vec![0x11; size];
+    rom[0x1B0..0x1B4].copy_from_slice(&[b'R', b'A', kind, 0x20]);
+    rom[0x1B4..0x1B8].copy_from_slice(&start.to_be_bytes());
+    rom[0x1B8..0x1BC].copy_from_slice(&end.to_be_bytes());
+    rom
+}
+
+fn temp_save_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("oxid_genesis_{}_{}.srm", name, std::process::id()))
+}
+
+#[test]
+fn test_sram_header_mapping_and_persistence() {
+    let rom = rom_with_save(0x400000, 0xF8, 0x200001, 0x203FFF);
+    let info = SaveInfo::parse(&rom).unwrap();
+    assert_eq!(info.kind, SaveKind::Sram(SramLanes::Odd));
+    assert_eq!(info.size(), 0x2000);
+
+    // ROM de 4MB: la SRAM empieza oculta tras la ROM hasta $A130F1
+    let mut bus = GenesisBus::new(rom);
+    bus.write(0x200001, 0x42);
+    assert_eq!(bus.read(0x200001), 0x11);
+    bus.write(0xA130F1, 0x01);
+    bus.write(0x200001, 0x42);
+    bus.write(0x200003, 0x43);
+    bus.write(0x200002, 0x99); // línea par: no conectada
+    assert_eq!((bus.read(0x200001), bus.read(0x200003)), (0x42, 0x43));
+    assert_eq!(bus.read(0x200002), 0x11);
+    assert!(bus.cartridge.dirty);
+
+    // Protección contra escritura
+    bus.write(0xA130F1, 0x03);
+    bus.write(0x200001, 0x00);
+    assert_eq!(bus.read(0x200001), 0x42);
+
+    let path = temp_save_path("sram");
+    bus.cartridge.write_save(&path).unwrap();
+    assert!(!bus.cartridge.dirty);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0x2000);
+
+    let mut cart = Cartridge::new(rom_with_save(0x400000, 0xF8, 0x200001, 0x203FFF));
+    assert!(cart.load_save(&path).unwrap());
+    cart.write_control(0x01);
+    assert_eq!((cart.read(0x200001), cart.read(0x200003)), (0x42, 0x43));
+    std::fs::remove_file(&path).unwrap();
+
+    // Sin fichero previo no es un error
+    assert!(!cart.load_save(&temp_save_path("missing")).unwrap());
+}
+
+/// Mueve SDA/SCL de la EEPROM en $200001 (SDA = bit 0, SCL = bit 1).
+struct I2cMaster<'a> {
+    bus: &'a mut GenesisBus,
+}
+
+impl I2cMaster<'_> {
+    fn lines(&mut self, sda: bool, scl: bool) {
+        self.bus.write(0x200001, ((scl as u8) << 1) | sda as u8);
+    }
+
+    fn sda(&self) -> bool {
+        self.bus.read(0x200001) & 1 != 0
+    }
+
+    fn start(&mut self) {
+        self.lines(true, true);
+        self.lines(false, true);
+        self.lines(false, false);
+    }
+
+    fn stop(&mut self) {
+        self.lines(false, false);
+        self.lines(false, true);
+        self.lines(true, true);
+    }
+
+    /// Envía un byte y devuelve el ACK de la EEPROM.
+    fn send(&mut self, byte: u8) -> bool {
+        for i in (0..8).rev() {
+            let bit = byte & (1 << i) != 0;
+            self.lines(bit, false);
+            self.lines(bit, true);
+            self.lines(bit, false);
+        }
+        self.lines(true, false);
+        self.lines(true, true);
+        let ack = !self.sda();
+        self.lines(true, false);
+        ack
+    }
+
+    fn receive(&mut self, ack: bool) -> u8 {
+        let mut byte = 0;
+        for _ in 0..8 {
+            self.lines(true, true);
+            byte = (byte << 1) | self.sda() as u8;
+            self.lines(true, false);
+        }
+        self.lines(!ack, false);
+        self.lines(!ack, true);
+        self.lines(!ack, false);
+        byte
+    }
+}
+
+#[test]
+fn test_eeprom_i2c_write_and_sequential_read() {
+    let rom = rom_with_save(0x80000, 0xE8, 0x200001, 0x200001);
+    assert_eq!(SaveInfo::parse(&rom).unwrap().kind, SaveKind::Eeprom);
+    let mut bus = GenesisBus::new(rom);
+    let mut i2c = I2cMaster { bus: &mut bus };
+
+    i2c.start();
+    assert!(i2c.send(0xA0)); // escritura
+    assert!(i2c.send(0x10)); // dirección
+    assert!(i2c.send(0xCA));
+    assert!(i2c.send(0xFE));
+    i2c.stop();
+
+    // Dirección actual + lectura secuencial
+    i2c.start();
+    assert!(i2c.send(0xA0));
+    assert!(i2c.send(0x10));
+    i2c.start();
+    assert!(i2c.send(0xA1));
+    assert_eq!(i2c.receive(true), 0xCA);
+    assert_eq!(i2c.receive(false), 0xFE);
+    i2c.stop();
+
+    // Otro dispositivo en el bus: sin ACK
+    i2c.start();
+    assert!(!i2c.send(0x50));
+    i2c.stop();
+
+    assert!(bus.cartridge.dirty);
+    assert_eq!(&bus.cartridge.save_data().unwrap()[0x10..0x12], &[0xCA, 0xFE]);
+}
+
+
+// ============================================================================
+// Sincronización 68000 / Z80
+// ============================================================================
+
+/// 68000 en BRA.S * desde $000000; el Z80 en JR * desde $0000 (RAM propia).
+fn genesis_idle_loops() -> Genesis {
+    let mut bus = GenesisBus::new(vec![0x60, 0xFE]);
+    bus.z80_ram[..2].copy_from_slice(&[0x18, 0xFE]);
+    Genesis { m68k: Oxid68k::new(), z80: OxidZ80::new(), bus }
+}
+
+#[test]
+fn test_frame_keeps_68k_z80_clock_ratio() {
+    let mut g = genesis_idle_loops();
+    g.bus.z80_reset = false;
+    let mut sched = scheduler();
+    run_frame(&mut g, &mut sched);
+
+    let (main, sub) = sched.cycles();
+    assert!(main >= (M68K_CYCLES_PER_LINE * LINES_PER_FRAME) as u64);
+    // Z80 = 68000 * 7/15, como mucho una instrucción (JR = 12) por delante
+    let expected = main * Z80_CLOCK / M68K_CLOCK;
+    assert!(sub >= expected && sub <= expected + 12, "{} vs {}", sub, expected);
+    assert_eq!(g.z80.pc, 0x0000);
+}
+
+#[test]
+fn test_z80_bus_request_and_reset_gate_execution() {
+    let mut g = genesis_idle_loops();
+    let mut sched = scheduler();
+    // Al encender el Z80 está en reset: no ejecuta y su RAM es del 68000
+    g.bus.write(0xA00002, 0x00);
+    assert_eq!(g.bus.read(0xA00000), 0x18);
+
+    // El 68000 suelta el reset: el Z80 corre y su RAM deja de ser accesible
+    g.bus.write(0xA11200, 0x01);
+    g.bus.z80_ram[..2].copy_from_slice(&[0x00, 0x00]); // NOPs: el PC avanza
+    run_frame(&mut g, &mut sched);
+    let pc = g.z80.pc;
+    assert_ne!(pc, 0);
+    assert_eq!(g.bus.read(0xA11100) & 1, 1);
+
+    // BUSREQ: bus concedido (bit 0 = 0) y el Z80 parado
+    g.bus.write(0xA11100, 0x01);
+    assert_eq!(g.bus.read(0xA11100) & 1, 0);
+    run_frame(&mut g, &mut sched);
+    assert_eq!(g.z80.pc, pc);
+    g.bus.write(0xA00010, 0x3C);
+    assert_eq!(g.bus.z80_ram[0x10], 0x3C);
+}
+
+#[test]
+fn test_z80_bank_window_reads_68k_memory() {
+    let mut rom = vec![0u8; 0x20000];
+    rom[0x18005] = 0xAB;
+    let mut bus = GenesisBus::new(rom);
+    // Banco 3 ($018000): bits 0, 1 = 1 y el resto a 0, de menor a mayor
+    for bit in [1, 1, 0, 0, 0, 0, 0, 0, 0] {
+        Z80Bus(&mut bus).write(0x6000, bit);
+    }
+    assert_eq!(bus.z80_bank, 3);
+    assert_eq!(Z80Bus(&mut bus).read(0x8005), 0xAB);
+    Z80Bus(&mut bus).write(0x8000 | 0x7FFF, 0x55); // cae en ROM: se ignora
+    assert_eq!(Z80Bus(&mut bus).read(0xFFFF), 0x00);
+}
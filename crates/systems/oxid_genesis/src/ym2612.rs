@@ -0,0 +1,535 @@
+// crates/systems/oxid_genesis/src/ym2612.rs
+//
+// YM2612 (OPN2): 6 canales FM de 4 operadores con generador de envolvente,
+// timers A/B y modo DAC en el canal 6. Registros en $A04000-$A04003
+// (parte I: registros globales y canales 1-3; parte II: canales 4-6).
+// Se sintetiza a la frecuencia nativa (reloj / 144 ≈ 53 kHz) y se remuestrea
+// a la de salida. Sin implementar todavía: LFO (AM/PM), SSG-EG y modo CSM.
+
+/// Reloj del YM2612 en NTSC (el mismo que el 68000)
+pub const YM_CLOCK: u32 = 7_670_453;
+/// Una muestra nativa cada 144 ciclos de reloj
+const CLOCKS_PER_SAMPLE: u32 = 144;
+
+/// Atenuación máxima del envolvente (10 bits, 1/64 de octava por unidad)
+const ENV_MAX: u16 = 0x3FF;
+/// Salida de un operador/canal: 14 bits con signo
+const OUT_MAX: i32 = 8191;
+
+/// Operador de cada grupo de registros ($30 + 4*n...): el orden es S1, S3, S2, S4
+const SLOT_TO_OP: [usize; 4] = [0, 2, 1, 3];
+
+/// Detune en unidades de incremento de fase, por DT (1-3) y keycode
+const DT_TABLE: [[u8; 32]; 4] = [
+    [0; 32],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 3, 3, 3, 4, 4, 4, 5, 5, 6, 6, 7, 8, 8, 8, 8],
+    [1, 1, 1, 1, 2, 2, 2, 2, 2, 3, 3, 3, 4, 4, 4, 5, 5, 6, 6, 7, 8, 8, 9, 10, 11, 12, 13, 14, 16, 16, 16, 16],
+    [2, 2, 2, 2, 2, 3, 3, 3, 4, 4, 4, 5, 5, 6, 6, 7, 8, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 20, 22, 22, 22, 22],
+];
+
+/// Bits bajos del keycode según F-Number bits 10-7
+const FN_NOTE: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 3, 3, 3, 3, 3, 3];
+
+/// Incrementos del envolvente por paso (fila = rate % 4, columna = ciclo % 8)
+const EG_INC: [[u16; 8]; 4] = [
+    [0, 1, 0, 1, 0, 1, 0, 1],
+    [0, 1, 0, 1, 1, 1, 0, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1],
+    [0, 1, 1, 1, 1, 1, 1, 1],
+];
+/// Rates 48-59: misma idea, multiplicada por 1, 2 o 4
+const EG_INC_FAST: [[u16; 8]; 4] = [
+    [1, 1, 1, 1, 1, 1, 1, 1],
+    [1, 1, 1, 2, 1, 1, 1, 2],
+    [1, 2, 1, 2, 1, 2, 1, 2],
+    [1, 2, 2, 2, 1, 2, 2, 2],
+];
+
+/// Incremento de atenuación para un rate efectivo (0-63) en el paso `counter`.
+fn eg_increment(rate: u8, counter: u32) -> u16 {
+    match rate {
+        0..=1 => 0,
+        2..=47 => {
+            let shift = 11 - (rate >> 2) as u32;
+            if counter & ((1 << shift) - 1) != 0 {
+                0
+            } else {
+                EG_INC[(rate & 3) as usize][((counter >> shift) & 7) as usize]
+            }
+        }
+        48..=59 => EG_INC_FAST[(rate & 3) as usize][(counter & 7) as usize] << ((rate - 48) >> 2),
+        _ => 8,
+    }
+}
+
+/// Keycode de 5 bits: bloque y los bits altos del F-Number.
+fn keycode(fnum: u16, block: u8) -> u8 {
+    (block << 2) | FN_NOTE[(fnum >> 7) as usize & 0x0F]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvPhase {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+#[derive(Clone, Copy)]
+struct Operator {
+    dt: u8,
+    mul: u8,
+    tl: u8,
+    ks: u8,
+    ar: u8,
+    d1r: u8,
+    d2r: u8,
+    sl: u8,
+    rr: u8,
+
+    /// Fase de 20 bits (los 10 altos indexan la senoide)
+    phase: u32,
+    env: u16,
+    env_phase: EnvPhase,
+    key: bool,
+}
+
+impl Operator {
+    fn new() -> Self {
+        Self {
+            dt: 0,
+            mul: 0,
+            tl: 0,
+            ks: 0,
+            ar: 0,
+            d1r: 0,
+            d2r: 0,
+            sl: 0,
+            rr: 0,
+            phase: 0,
+            env: ENV_MAX,
+            env_phase: EnvPhase::Release,
+            key: false,
+        }
+    }
+
+    /// Rate efectivo: 2 * rate + escalado por keycode (0 si el rate es 0)
+    fn rate(&self, base: u8, kc: u8) -> u8 {
+        if base == 0 {
+            0
+        } else {
+            (base * 2 + (kc >> (3 - self.ks))).min(63)
+        }
+    }
+
+    fn sustain_level(&self) -> u16 {
+        if self.sl == 15 { 0x3E0 } else { (self.sl as u16) << 5 }
+    }
+
+    fn key_on(&mut self, kc: u8) {
+        if self.key {
+            return;
+        }
+        self.key = true;
+        self.phase = 0;
+        self.env_phase = EnvPhase::Attack;
+        // Attack rate máximo: salta directamente al volumen máximo
+        if self.rate(self.ar, kc) >= 62 {
+            self.env = 0;
+            self.env_phase = EnvPhase::Decay;
+        }
+    }
+
+    fn key_off(&mut self) {
+        if self.key {
+            self.key = false;
+            self.env_phase = EnvPhase::Release;
+        }
+    }
+
+    fn step_envelope(&mut self, counter: u32, kc: u8) {
+        match self.env_phase {
+            EnvPhase::Attack => {
+                let inc = eg_increment(self.rate(self.ar, kc), counter) as i32;
+                let env = self.env as i32;
+                self.env = (env + ((!env * inc) >> 4)).max(0) as u16;
+                if self.env == 0 {
+                    self.env_phase = EnvPhase::Decay;
+                }
+            }
+            EnvPhase::Decay => {
+                self.env = (self.env + eg_increment(self.rate(self.d1r, kc), counter)).min(ENV_MAX);
+                if self.env >= self.sustain_level() {
+                    self.env_phase = EnvPhase::Sustain;
+                }
+            }
+            EnvPhase::Sustain => {
+                self.env = (self.env + eg_increment(self.rate(self.d2r, kc), counter)).min(ENV_MAX);
+            }
+            EnvPhase::Release => {
+                self.env = (self.env + eg_increment(self.rate(self.rr * 2 + 1, kc), counter)).min(ENV_MAX);
+            }
+        }
+    }
+
+    /// Incremento de fase por muestra nativa: (F-Number << bloque) / 2 + detune, por MUL.
+    fn phase_increment(&self, fnum: u16, block: u8) -> u32 {
+        let fc = ((fnum as u32) << block) >> 1;
+        let dt = DT_TABLE[(self.dt & 3) as usize][keycode(fnum, block) as usize] as u32;
+        let fc = if self.dt & 4 != 0 { fc.wrapping_sub(dt) } else { fc + dt } & 0x1FFFF;
+        if self.mul == 0 { fc >> 1 } else { fc * self.mul as u32 }
+    }
+
+    /// Salida con `modulation` sumada al índice de la senoide (1024 pasos por ciclo).
+    fn output(&self, modulation: i32) -> i32 {
+        let att = self.env as u32 + ((self.tl as u32) << 3);
+        if att >= ENV_MAX as u32 {
+            return 0;
+        }
+        let index = ((self.phase >> 10) as i32 + modulation) & 0x3FF;
+        let angle = (index as f32 + 0.5) * std::f32::consts::TAU / 1024.0;
+        let gain = (-(att as f32) / 64.0).exp2();
+        (angle.sin() * gain * OUT_MAX as f32) as i32
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Channel {
+    /// S1, S2, S3, S4 (orden del algoritmo, no el de los registros)
+    ops: [Operator; 4],
+    fnum: u16,
+    block: u8,
+    algorithm: u8,
+    feedback: u8,
+    left: bool,
+    right: bool,
+    /// Últimas dos salidas de S1 (realimentación)
+    fb_out: [i32; 2],
+}
+
+impl Channel {
+    fn new() -> Self {
+        Self {
+            ops: [Operator::new(); 4],
+            fnum: 0,
+            block: 0,
+            algorithm: 0,
+            feedback: 0,
+            left: true,
+            right: true,
+            fb_out: [0; 2],
+        }
+    }
+
+    /// Conecta los operadores según el algoritmo. La entrada de modulación
+    /// es la mitad de la salida del modulador (o de la suma de dos).
+    fn output(&mut self) -> i32 {
+        let [s1, s2, s3, s4] = &self.ops;
+        let fb = if self.feedback > 0 { (self.fb_out[0] + self.fb_out[1]) >> (10 - self.feedback) } else { 0 };
+        let o1 = s1.output(fb);
+        self.fb_out = [self.fb_out[1], o1];
+
+        let out = match self.algorithm {
+            0 => {
+                let o2 = s2.output(o1 >> 1);
+                let o3 = s3.output(o2 >> 1);
+                s4.output(o3 >> 1)
+            }
+            1 => {
+                let o2 = s2.output(0);
+                let o3 = s3.output((o1 + o2) >> 1);
+                s4.output(o3 >> 1)
+            }
+            2 => {
+                let o2 = s2.output(0);
+                let o3 = s3.output(o2 >> 1);
+                s4.output((o1 + o3) >> 1)
+            }
+            3 => {
+                let o2 = s2.output(o1 >> 1);
+                let o3 = s3.output(0);
+                s4.output((o2 + o3) >> 1)
+            }
+            4 => {
+                let o2 = s2.output(o1 >> 1);
+                let o3 = s3.output(0);
+                o2 + s4.output(o3 >> 1)
+            }
+            5 => s2.output(o1 >> 1) + s3.output(o1 >> 1) + s4.output(o1 >> 1),
+            6 => s2.output(o1 >> 1) + s3.output(0) + s4.output(0),
+            _ => o1 + s2.output(0) + s3.output(0) + s4.output(0),
+        };
+        out.clamp(-OUT_MAX, OUT_MAX)
+    }
+}
+
+pub struct Ym2612 {
+    channels: [Channel; 6],
+    /// Registro seleccionado en cada parte ($A04000 / $A04002)
+    address: [u8; 2],
+    /// Bloque + F-Number alto ($A4-$A6) pendiente de la escritura en $A0-$A2
+    freq_latch: u8,
+    /// Lo mismo para las frecuencias del canal 3 en modo especial ($AC-$AE)
+    ch3_latch: u8,
+    /// (F-Number, bloque) de S1, S2, S3 del canal 3 en modo especial
+    ch3_freq: [(u16, u8); 3],
+    /// Bits 7-6 del registro $27 (distinto de 0 = frecuencias por operador)
+    ch3_mode: u8,
+
+    pub dac_enabled: bool,
+    pub dac_data: u8,
+
+    timer_a: u16,
+    timer_b: u8,
+    timer_a_count: u32,
+    timer_b_count: u32,
+    /// Bits 3-0 del registro $27: enable flag B/A, load B/A
+    timer_ctrl: u8,
+    /// Bit 1 = overflow de B, bit 0 = overflow de A
+    status: u8,
+
+    eg_counter: u32,
+    eg_divider: u8,
+
+    clock_acc: u32,
+    last_out: (i32, i32),
+}
+
+impl Default for Ym2612 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ym2612 {
+    pub fn new() -> Self {
+        Self {
+            channels: [Channel::new(); 6],
+            address: [0; 2],
+            freq_latch: 0,
+            ch3_latch: 0,
+            ch3_freq: [(0, 0); 3],
+            ch3_mode: 0,
+            dac_enabled: false,
+            dac_data: 0x80,
+            timer_a: 0,
+            timer_b: 0,
+            timer_a_count: 0,
+            timer_b_count: 0,
+            timer_ctrl: 0,
+            status: 0,
+            eg_counter: 0,
+            eg_divider: 0,
+            clock_acc: 0,
+            last_out: (0, 0),
+        }
+    }
+
+    // ========================================================================
+    //  REGISTROS
+    // ========================================================================
+
+    /// Escritura en $A04000 + `port`: 0/2 = dirección (parte I/II), 1/3 = dato.
+    pub fn write(&mut self, port: u8, val: u8) {
+        let part = ((port >> 1) & 1) as usize;
+        if port & 1 == 0 {
+            self.address[part] = val;
+        } else {
+            self.write_register(part, self.address[part], val);
+        }
+    }
+
+    /// Estado: flags de overflow de los timers (el bit busy no se modela).
+    pub fn status(&self) -> u8 {
+        self.status
+    }
+
+    fn write_register(&mut self, part: usize, reg: u8, val: u8) {
+        if reg < 0x30 {
+            if part == 0 {
+                self.write_global(reg, val);
+            }
+            return;
+        }
+        if reg & 3 == 3 {
+            return; // No hay cuarto canal por parte
+        }
+        let ch = part * 3 + (reg & 3) as usize;
+        if reg < 0xA0 {
+            let op = &mut self.channels[ch].ops[SLOT_TO_OP[((reg >> 2) & 3) as usize]];
+            match reg & 0xF0 {
+                0x30 => {
+                    op.dt = (val >> 4) & 0x07;
+                    op.mul = val & 0x0F;
+                }
+                0x40 => op.tl = val & 0x7F,
+                0x50 => {
+                    op.ks = val >> 6;
+                    op.ar = val & 0x1F;
+                }
+                0x60 => op.d1r = val & 0x1F, // bit 7 = AM (sin LFO)
+                0x70 => op.d2r = val & 0x1F,
+                0x80 => {
+                    op.sl = val >> 4;
+                    op.rr = val & 0x0F;
+                }
+                _ => {} // $90: SSG-EG
+            }
+            return;
+        }
+        match reg & 0xFC {
+            0xA0 => {
+                let channel = &mut self.channels[ch];
+                channel.fnum = ((self.freq_latch as u16 & 0x07) << 8) | val as u16;
+                channel.block = (self.freq_latch >> 3) & 0x07;
+            }
+            0xA4 => self.freq_latch = val,
+            // $A8-$AA: S3, S1, S2 del canal 3 (solo parte I)
+            0xA8 if part == 0 => {
+                let op = [2, 0, 1][(reg & 3) as usize];
+                self.ch3_freq[op] = (((self.ch3_latch as u16 & 0x07) << 8) | val as u16, (self.ch3_latch >> 3) & 0x07);
+            }
+            0xAC if part == 0 => self.ch3_latch = val,
+            0xB0 => {
+                self.channels[ch].feedback = (val >> 3) & 0x07;
+                self.channels[ch].algorithm = val & 0x07;
+            }
+            0xB4 => {
+                // Bits 5-0: sensibilidad AMS/PMS al LFO
+                self.channels[ch].left = val & 0x80 != 0;
+                self.channels[ch].right = val & 0x40 != 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn write_global(&mut self, reg: u8, val: u8) {
+        match reg {
+            0x24 => self.timer_a = (self.timer_a & 0x03) | ((val as u16) << 2),
+            0x25 => self.timer_a = (self.timer_a & 0x3FC) | (val as u16 & 0x03),
+            0x26 => self.timer_b = val,
+            0x27 => {
+                // Load 0 -> 1 reinicia la cuenta
+                if val & 0x01 != 0 && self.timer_ctrl & 0x01 == 0 {
+                    self.timer_a_count = 0;
+                }
+                if val & 0x02 != 0 && self.timer_ctrl & 0x02 == 0 {
+                    self.timer_b_count = 0;
+                }
+                self.timer_ctrl = val & 0x0F;
+                self.status &= !((val >> 4) & 0x03);
+                self.ch3_mode = val >> 6;
+            }
+            0x28 => {
+                let ch = match val & 0x07 {
+                    c @ 0..=2 => c as usize,
+                    c @ 4..=6 => c as usize - 1,
+                    _ => return,
+                };
+                for op in 0..4 {
+                    let (fnum, block) = self.op_frequency(ch, op);
+                    let slot = &mut self.channels[ch].ops[op];
+                    if val & (0x10 << op) != 0 {
+                        slot.key_on(keycode(fnum, block));
+                    } else {
+                        slot.key_off();
+                    }
+                }
+            }
+            0x2A => self.dac_data = val,
+            0x2B => self.dac_enabled = val & 0x80 != 0,
+            _ => {} // $22: LFO
+        }
+    }
+
+    /// Frecuencia de un operador: la del canal, salvo S1-S3 del canal 3 en modo especial.
+    fn op_frequency(&self, ch: usize, op: usize) -> (u16, u8) {
+        if ch == 2 && self.ch3_mode != 0 && op < 3 {
+            self.ch3_freq[op]
+        } else {
+            (self.channels[ch].fnum, self.channels[ch].block)
+        }
+    }
+
+    // ========================================================================
+    //  SÍNTESIS
+    // ========================================================================
+
+    fn step_timers(&mut self) {
+        if self.timer_ctrl & 0x01 != 0 {
+            self.timer_a_count += 1;
+            if self.timer_a_count >= 1024 - self.timer_a as u32 {
+                self.timer_a_count = 0;
+                if self.timer_ctrl & 0x04 != 0 {
+                    self.status |= 0x01;
+                }
+            }
+        }
+        if self.timer_ctrl & 0x02 != 0 {
+            self.timer_b_count += 1;
+            if self.timer_b_count >= (256 - self.timer_b as u32) * 16 {
+                self.timer_b_count = 0;
+                if self.timer_ctrl & 0x08 != 0 {
+                    self.status |= 0x02;
+                }
+            }
+        }
+    }
+
+    /// Una muestra nativa: (L, R) con la suma de los 6 canales.
+    fn tick(&mut self) -> (i32, i32) {
+        self.step_timers();
+
+        // El envolvente avanza una vez cada 3 muestras
+        self.eg_divider += 1;
+        let eg_step = self.eg_divider == 3;
+        if eg_step {
+            self.eg_divider = 0;
+            self.eg_counter = self.eg_counter.wrapping_add(1);
+        }
+
+        let (mut left, mut right) = (0, 0);
+        for ch in 0..6 {
+            let fm = self.channels[ch].output();
+            let out = if ch == 5 && self.dac_enabled { (self.dac_data as i32 - 0x80) << 6 } else { fm };
+            for op in 0..4 {
+                let (fnum, block) = self.op_frequency(ch, op);
+                let slot = &mut self.channels[ch].ops[op];
+                slot.phase = (slot.phase + slot.phase_increment(fnum, block)) & 0xFFFFF;
+                if eg_step {
+                    slot.step_envelope(self.eg_counter, keycode(fnum, block));
+                }
+            }
+            if self.channels[ch].left {
+                left += out;
+            }
+            if self.channels[ch].right {
+                right += out;
+            }
+        }
+        (left, right)
+    }
+
+    /// Rellena `out` con muestras estéreo intercaladas (L, R) a `sample_rate`.
+    /// Los timers solo avanzan mientras se genera audio.
+    pub fn generate(&mut self, out: &mut [i16], sample_rate: u32) {
+        let step = sample_rate * CLOCKS_PER_SAMPLE;
+        for frame in out.chunks_exact_mut(2) {
+            self.clock_acc += YM_CLOCK;
+            let (mut left, mut right, mut n) = (0, 0, 0);
+            while self.clock_acc >= step {
+                self.clock_acc -= step;
+                let (l, r) = self.tick();
+                left += l;
+                right += r;
+                n += 1;
+            }
+            if n > 0 {
+                self.last_out = (left / n, right / n);
+            }
+            // 6 canales de 14 bits: a la mitad para dejar sitio al PSG
+            frame[0] = (self.last_out.0 >> 1) as i16;
+            frame[1] = (self.last_out.1 >> 1) as i16;
+        }
+    }
+}
@@ -1,7 +1,7 @@
 // crates/systems/oxid_mac/src/bus.rs
 use crate::memory::MacRam;
 use crate::via::{MacVia, ViaAction};
-use oxide_core::MemoryBus;
+use oxide_core::{DeviceMap, MemoryBus};
 use std::cell::Cell;
 
 // Macintosh Memory Map (Strict)
@@ -13,11 +13,23 @@ use std::cell::Cell;
 // $E80000 - $EFFFFF: VIA (Read/Write)
 // Everything else -> Bus Error
 
+/// Periférico aún no emulado: devuelve un valor fijo e ignora escrituras.
+struct StubDevice(u8);
+
+impl MemoryBus for StubDevice {
+    fn read(&self, _addr: u32) -> u8 {
+        self.0
+    }
+    fn write(&mut self, _addr: u32, _val: u8) {}
+}
+
 pub struct MacBus {
     pub ram: MacRam,
     pub rom: Vec<u8>,
     pub rom_overlay: bool,
     pub via: MacVia,
+    /// SCSI, SCC, IWM y Phase (stubs), decodificados por rango
+    pub io: DeviceMap,
     pub fault_addr: Cell<Option<u32>>,
 }
 
@@ -28,16 +40,14 @@ impl MacBus {
             rom: rom_data,
             rom_overlay: true,
             via: MacVia::new(),
+            io: DeviceMap::new()
+                .map(0x580000..=0x5FFFFF, Box::new(StubDevice(0x00))) // SCSI
+                .map(0x900000..=0xBFFFFF, Box::new(StubDevice(0x04))) // SCC
+                .map(0xC00000..=0xDFFFFF, Box::new(StubDevice(0x1F))) // IWM
+                .map(0xF00000..=0xFFFFFF, Box::new(StubDevice(0x00))), // Phase/Test
             fault_addr: Cell::new(None),
         }
     }
-
-    // Helper for Big Endian Word Read
-    pub fn read_u16(&self, addr: u32) -> u16 {
-        let hi = self.read(addr) as u16;
-        let lo = self.read(addr.wrapping_add(1)) as u16;
-        (hi << 8) | lo
-    }
 }
 
 impl MemoryBus for MacBus {
@@ -56,40 +66,17 @@ impl MemoryBus for MacBus {
         }
 
         let high = (address >> 20) & 0xF;
+        let io_addr = address & 0xFFFFFF;
 
         match high {
             0x0..=0x3 => self.ram.read(address),
             0x4 => {
-                let offset = (address & 0x0FFFFF) as usize % self.rom.len();
-                self.rom[offset]
-            }
-            // SCSI: 580000-5FFFFF
-            0x5 => {
-                if address >= 0x580000 {
-                    0x00 // SCSI Stub
-                } else {
-                    self.fault_addr.set(Some(address));
-                    0xFF
-                }
-            }
-            0x6..=0x8 => {
-                self.fault_addr.set(Some(address));
-                0xFF
+                let offset = (address & 0x0FFFFF) as usize % self.rom.len().max(1);
+                self.rom.get(offset).copied().unwrap_or(0xFF)
             }
-            // SCC: 900000-BFFFFF
-            0x9..=0xB => 0x04,
-            // IWM: C00000-DFFFFF
-            0xC..=0xD => 0x1F,
             // VIA: E80000-EFFFFF (E0-E7 is usually invalid/mirror?)
-            0xE => {
-                if address >= 0xE80000 {
-                    self.via.read(address & 0xFFFF)
-                } else {
-                    self.fault_addr.set(Some(address));
-                    0xFF
-                }
-            }
-            0xF => 0x00, // Phase/Test
+            0xE if address >= 0xE80000 => self.via.read(address & 0xFFFF),
+            _ if self.io.is_mapped(io_addr) => self.io.read(io_addr),
             _ => {
                 // Invalid / Unmapped -> Bus Error
                 self.fault_addr.set(Some(address));
@@ -100,6 +87,7 @@ impl MemoryBus for MacBus {
 
     fn write(&mut self, address: u32, value: u8) {
         let high = (address >> 20) & 0xF;
+        let io_addr = address & 0xFFFFFF;
 
         match high {
             0x0..=0x3 => {
@@ -109,30 +97,16 @@ impl MemoryBus for MacBus {
                     self.rom_overlay = false;
                 }
             }
-            0x4 => {
-                // Write to ROM -> STRICT BUS ERROR
-                self.fault_addr.set(Some(address));
-            }
-            0x5 => {
-                if address < 0x580000 {
-                    self.fault_addr.set(Some(address));
-                }
-            }
-            0x9..=0xB => {} // SCC
-            0xC..=0xD => {} // IWM
-            0xE => {
-                if address >= 0xE80000 {
-                    if let Some(action) = self.via.write(address & 0xFFFF, value) {
-                        match action {
-                            ViaAction::SetOverlay(enable) => self.rom_overlay = enable,
-                        }
+            0xE if address >= 0xE80000 => {
+                if let Some(action) = self.via.write(address & 0xFFFF, value) {
+                    match action {
+                        ViaAction::SetOverlay(enable) => self.rom_overlay = enable,
                     }
-                } else {
-                    self.fault_addr.set(Some(address));
                 }
             }
-            0xF => {}
+            _ if self.io.is_mapped(io_addr) => self.io.write(io_addr, value),
             _ => {
+                // Write to ROM (strict) / unmapped -> Bus Error
                 self.fault_addr.set(Some(address));
             }
         }
@@ -0,0 +1,82 @@
+// crates/systems/oxid_mac/src/fastboot.rs
+//! "Fast boot": salta el test de memoria de la ROM durante el desarrollo.
+//!
+//! HACK de depuración, opt-in (`-fastboot [<pc>:<resume>]`). Es una trampa de
+//! rutina de ROM: cuando la CPU va a ejecutar en `pc`, se la manda directamente
+//! a `resume` sin ejecutar nada en medio. No se parchea la ROM ni se tocan
+//! registros, así que lo que el test de memoria hubiera dejado calculado (tamaño
+//! de RAM, flags de error) queda como estuviera: elegir un `resume` donde la ROM
+//! no dependa de ello es responsabilidad de quien lo usa. Las direcciones
+//! cambian con cada versión de ROM; nunca activarlo para validar compatibilidad.
+//!
+//! Sin `<pc>:<resume>` se busca la ROM por su checksum en `BUILTIN`; la opción
+//! manual siempre tiene prioridad.
+
+use oxid68k::Oxid68k;
+
+/// Trampa de un solo disparo: `pc` -> `resume`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomTrap {
+    pub pc: u32,
+    pub resume: u32,
+}
+
+impl RomTrap {
+    /// Parsea `<pc>:<resume>` en hex (con o sin `$` / `0x`).
+    pub fn parse(s: &str) -> Option<Self> {
+        let hex = |s: &str| {
+            let s = s.trim_start_matches('$').trim_start_matches("0x");
+            u32::from_str_radix(s, 16).ok()
+        };
+        let (pc, resume) = s.split_once(':')?;
+        Some(Self { pc: hex(pc)?, resume: hex(resume)? })
+    }
+}
+
+/// ROM conocida, identificada por su checksum (el primer long de la imagen).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownRom {
+    pub checksum: u32,
+    pub name: &'static str,
+    pub memtest: RomTrap,
+}
+
+/// Saltos incorporados por ROM. Solo entran direcciones comprobadas contra un
+/// volcado real: un `resume` equivocado deja el arranque colgado o en Sad Mac.
+pub const BUILTIN: &[KnownRom] = &[];
+
+/// Checksum de la cabecera de la ROM (big-endian en $000000).
+pub fn rom_checksum(rom: &[u8]) -> Option<u32> {
+    Some(u32::from_be_bytes(rom.get(..4)?.try_into().ok()?))
+}
+
+/// Entrada de `table` para la ROM con ese checksum.
+pub fn lookup(table: &[KnownRom], checksum: u32) -> Option<&KnownRom> {
+    table.iter().find(|rom| rom.checksum == checksum)
+}
+
+/// Trampas armadas; cada una salta una vez (el test de memoria solo corre al arrancar).
+#[derive(Debug, Default)]
+pub struct FastBoot {
+    traps: Vec<RomTrap>,
+}
+
+impl FastBoot {
+    pub fn new(traps: Vec<RomTrap>) -> Self {
+        Self { traps }
+    }
+
+    pub fn is_armed(&self) -> bool {
+        !self.traps.is_empty()
+    }
+
+    /// Llamar antes de cada `step`: si la CPU está en una trampa, salta a su
+    /// `resume` y la desarma. Devuelve true si saltó.
+    pub fn apply(&mut self, cpu: &mut Oxid68k) -> bool {
+        let Some(i) = self.traps.iter().position(|t| t.pc == cpu.pc) else {
+            return false;
+        };
+        cpu.pc = self.traps.swap_remove(i).resume;
+        true
+    }
+}
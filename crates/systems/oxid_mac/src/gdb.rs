@@ -0,0 +1,75 @@
+// crates/systems/oxid_mac/src/gdb.rs
+//
+// -gdb <puerto> (feature `gdb-stub`): sin ventana, la máquina avanza solo bajo
+// control de m68k-elf-gdb desde el vector de reset.
+use crate::bus::MacBus;
+use oxid68k::gdb::{GdbStub, GdbTarget};
+use oxid68k::Oxid68k;
+use oxide_core::debug_server::DebugTarget;
+use oxide_core::{Cpu, MemoryBus};
+
+/// Ciclos por frame (VBLANK, nivel 1 vía CA1), como el bucle principal
+const CYCLES_PER_FRAME: u32 = 133_333;
+
+pub struct MacTarget {
+    cpu: Oxid68k,
+    bus: MacBus,
+    frame_cycles: u32,
+}
+
+impl DebugTarget for MacTarget {
+    fn registers(&self) -> Vec<(&'static str, u32)> {
+        vec![("PC", self.cpu.pc), ("SR", self.cpu.sr.to_u16() as u32)]
+    }
+
+    fn pc(&self) -> u32 {
+        self.cpu.pc
+    }
+
+    fn read_mem(&self, addr: u32) -> u8 {
+        self.bus.read(addr)
+    }
+
+    fn write_mem(&mut self, addr: u32, val: u8) {
+        self.bus.write(addr, val);
+    }
+
+    /// Una instrucción con los timers del VIA y el VBLANK del bucle principal.
+    fn step(&mut self) -> u32 {
+        let cycles = if self.cpu.stopped || self.cpu.halted { 4 } else { self.cpu.step(&mut self.bus) };
+        if self.bus.via.tick(cycles) {
+            self.cpu.trigger_interrupt(1);
+        }
+        self.frame_cycles += cycles;
+        if self.frame_cycles >= CYCLES_PER_FRAME {
+            self.frame_cycles -= CYCLES_PER_FRAME;
+            let ifr = self.bus.via.ifr.get();
+            self.bus.via.ifr.set(ifr | 0x02);
+            if self.bus.via.ier & 0x02 != 0 {
+                self.cpu.trigger_interrupt(1);
+            }
+        }
+        cycles
+    }
+
+    fn call_return(&self) -> Option<u32> {
+        self.cpu.call_return_address(&self.bus)
+    }
+}
+
+impl GdbTarget for MacTarget {
+    fn cpu(&self) -> &Oxid68k {
+        &self.cpu
+    }
+
+    fn cpu_mut(&mut self) -> &mut Oxid68k {
+        &mut self.cpu
+    }
+}
+
+pub fn run(cpu: Oxid68k, bus: MacBus, port: u16) -> std::io::Result<()> {
+    let addr = format!("127.0.0.1:{}", port);
+    println!("GDB stub listening on {} (target remote {})", addr, addr);
+    let mut target = MacTarget { cpu, bus, frame_cycles: 0 };
+    GdbStub::new().listen(&mut target, &addr)
+}
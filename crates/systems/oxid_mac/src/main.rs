@@ -1,14 +1,21 @@
 // crates/systems/oxid_mac/src/main.rs - Macintosh Emulator
 mod bus;
+mod fastboot;
+#[cfg(feature = "gdb-stub")]
+mod gdb;
 mod memory;
+mod traps;
 mod via;
 mod video;
+#[cfg(test)]
+mod tests;
 
 use crate::bus::MacBus;
+use crate::fastboot::{FastBoot, RomTrap, BUILTIN};
 use crate::video::{MacVideo, SCREEN_HEIGHT, SCREEN_WIDTH};
 use minifb::{Key, Window, WindowOptions};
-use oxid68k::Oxid68k;
-use oxide_core::Cpu;
+use oxid68k::{disasm, vectors, Oxid68k};
+use oxide_core::{cheats, load_symbols, Clock, Cpu, MemoryBus, RamInit};
 use std::env;
 use std::fs;
 use std::time::Duration;
@@ -31,6 +38,13 @@ fn get_video_base(ram_size: usize) -> usize {
     }
 }
 
+/// `-tracetraps`: una línea por cada llamada al Toolbox/OS (Line A) que se va a ejecutar.
+fn log_trap(cpu: &Oxid68k, bus: &MacBus, symbols: &disasm::Symbols) {
+    if let Some(trap) = traps::describe(bus.read_u16_be(cpu.pc()), symbols) {
+        println!("[TRAP] PC={:08X} {}", cpu.pc(), trap);
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     println!("╔══════════════════════════════════════════╗");
@@ -38,7 +52,7 @@ fn main() {
     println!("╚══════════════════════════════════════════╝");
 
     if args.len() < 2 {
-        println!("Usage: oxid_mac <path_to_mac_rom>");
+        println!("Usage: oxid_mac <path_to_mac_rom> [-ram zeros|ones|seed:<n>] [-clock host|<unix secs>] [-gdb <port>] [-cheats <pokes.txt>] [-symbols <map.txt>] [-traps <names.txt>] [-tracetraps] [-fastboot [<pc>:<resume>]] [-v]");
         return;
     }
 
@@ -67,31 +81,123 @@ fn main() {
         video_base
     );
 
+    let ram_init = match args.iter().position(|a| a == "-ram").and_then(|i| args.get(i + 1)) {
+        Some(value) => match RamInit::parse(value) {
+            Some(init) => init,
+            None => {
+                println!("Error: invalid -ram value '{}' (expected zeros, ones or seed:<n>)", value);
+                return;
+            }
+        },
+        None => RamInit::default(),
+    };
+
+    // -v: traza de arranque y diagnóstico periódico (por defecto, silencio)
+    let verbose = args.iter().any(|a| a == "-v");
+    // -tracetraps: registra cada trap de Line A con su nombre (Toolbox/OS)
+    let trace_traps = args.iter().any(|a| a == "-tracetraps");
+
+    let rom_checksum = fastboot::rom_checksum(&rom_data).unwrap_or(0);
     let mut bus = MacBus::new(rom_data, ram_size);
+    bus.ram.init(ram_init);
+    // -clock <n>: RTC determinista que arranca en el segundo Unix <n>
+    if let Some(epoch) = args.iter().position(|a| a == "-clock")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|s| s.parse().ok())
+    {
+        bus.via.clock = Clock::emulated(via::MAC_CPU_HZ, epoch);
+    }
+    let cheats = match args.iter().position(|a| a == "-cheats").and_then(|i| args.get(i + 1)) {
+        Some(path) => match cheats::load_pokes(path) {
+            Ok(cheats) => cheats,
+            Err(e) => {
+                println!("Error loading cheats: {}", e);
+                return;
+            }
+        },
+        None => Vec::new(),
+    };
+    // -symbols / -traps: etiquetas de dirección y nombres de traps de Line A para el desensamblado.
+    // Los traps parten de la tabla incorporada; `-traps` añade o redefine nombres.
+    let mut symbols = disasm::Symbols { traps: traps::builtin_traps(), ..Default::default() };
+    for (flag, table) in [("-symbols", &mut symbols.addresses), ("-traps", &mut symbols.traps)] {
+        let Some(path) = args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)) else {
+            continue;
+        };
+        match load_symbols(path) {
+            Ok(loaded) => {
+                println!("{}: {} names loaded from {}", flag, loaded.len(), path);
+                table.merge(loaded);
+            }
+            Err(e) => {
+                println!("Error loading {}: {}", path, e);
+                return;
+            }
+        }
+    }
+    // -fastboot [<pc>:<resume>] (hex): HACK de desarrollo, salta el test de memoria.
+    // Sin direcciones usa la entrada incorporada para el checksum de la ROM.
+    let mut fast_boot = FastBoot::default();
+    if let Some(i) = args.iter().position(|a| a == "-fastboot") {
+        let manual = args.get(i + 1).and_then(|s| RomTrap::parse(s));
+        match manual.or_else(|| fastboot::lookup(BUILTIN, rom_checksum).map(|rom| rom.memtest)) {
+            Some(trap) => fast_boot = FastBoot::new(vec![trap]),
+            None => println!("Fast boot: no built-in entry for ROM checksum {:08X}; use -fastboot <pc>:<resume>", rom_checksum),
+        }
+    }
+    if fast_boot.is_armed() {
+        println!("Fast boot: memory test skip armed (debug hack)");
+    }
     let mut cpu = Oxid68k::new();
     let video = MacVideo::new();
 
     cpu.reset_with_bus(&mut bus);
     println!("Reset: PC={:08X} SP={:08X}", cpu.pc(), cpu.a[7]);
 
+    // -gdb <puerto>: sin ventana, controlado por GDB desde el reset
+    if let Some(port) = args.iter().position(|a| a == "-gdb")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|s| s.parse::<u16>().ok())
+    {
+        #[cfg(feature = "gdb-stub")]
+        if let Err(e) = gdb::run(cpu, bus, port) {
+            println!("GDB stub error: {}", e);
+        }
+        #[cfg(not(feature = "gdb-stub"))]
+        println!("-gdb {}: rebuild with --features gdb-stub", port);
+        return;
+    }
+
     // TRACE: First 500 instructions to verify boot progress
-    println!("\n=== TRACE (first 500 instructions) ===");
+    if verbose {
+        println!("\n=== TRACE (first 500 instructions) ===");
+    }
     let mut last_overlay = bus.rom_overlay;
     for i in 0..500 {
+        if fast_boot.apply(&mut cpu) && verbose {
+            println!(">>> FAST BOOT at instruction {}: PC={:08X}", i, cpu.pc());
+        }
         let pc = cpu.pc();
+        if trace_traps {
+            log_trap(&cpu, &bus, &symbols);
+        }
         // Just execute, don't flood log unless overlay changes
         cpu.step(&mut bus);
 
         // Detect overlay change
         if bus.rom_overlay != last_overlay {
-            println!(
-                ">>> OVERLAY CHANGED at instruction {} PC={:08X}: {} -> {}",
-                i, pc, last_overlay, bus.rom_overlay
-            );
+            if verbose {
+                println!(
+                    ">>> OVERLAY CHANGED at instruction {} PC={:08X}: {} -> {}",
+                    i, pc, last_overlay, bus.rom_overlay
+                );
+            }
             last_overlay = bus.rom_overlay;
         }
     }
-    println!("=== END INITIAL TRACE ===\n");
+    if verbose {
+        println!("=== END INITIAL TRACE ===\n");
+    }
 
     let mut window = Window::new(
         &format!("Oxide-Mac - {}", model_name),
@@ -110,7 +216,7 @@ fn main() {
     let cycles_per_frame = 133_333u32;
     let mut frame_count = 0u64;
 
-    println!("--- Running (D=debug, V=vram, R=regs, ESC=quit) ---");
+    println!("--- Running (D=debug, V=vram, R=regs, X=vectors, ESC=quit) ---");
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let mut cycles = 0u32;
@@ -118,6 +224,10 @@ fn main() {
             let step_cycles = if cpu.stopped || cpu.halted {
                 4
             } else {
+                fast_boot.apply(&mut cpu);
+                if trace_traps {
+                    log_trap(&cpu, &bus, &symbols);
+                }
                 cpu.step(&mut bus)
             };
             cycles += step_cycles;
@@ -129,7 +239,8 @@ fn main() {
             }
         }
 
-        // VBLANK interrupt (level 1) every frame
+        bus.apply_cheats(&cheats);
+
         // VBLANK interrupt (level 1) every frame
         // Set VIA interrupt flag for CA1 (VBLANK)
         let current_ifr = bus.via.ifr.get();
@@ -138,10 +249,22 @@ fn main() {
             cpu.trigger_interrupt(1);
         }
 
+        // Teclado: cada transición va a la cola del VIA (bit 7 = soltada)
+        for key in window.get_keys_pressed(minifb::KeyRepeat::No) {
+            if let Some(code) = mac_keycode(key) {
+                bus.via.push_key((code << 1) | 1);
+            }
+        }
+        for key in window.get_keys_released() {
+            if let Some(code) = mac_keycode(key) {
+                bus.via.push_key(0x80 | (code << 1) | 1);
+            }
+        }
+
         frame_count += 1;
 
         // Diagnostic every 60 frames (1 second)
-        if frame_count % 60 == 0 {
+        if verbose && frame_count.is_multiple_of(60) {
             let slice = bus.ram.dma_slice();
             let nz = slice[video_base..video_base + 21888]
                 .iter()
@@ -157,14 +280,15 @@ fn main() {
         }
 
         if window.is_key_pressed(Key::D, minifb::KeyRepeat::No) {
-            let op = bus.read_u16(cpu.pc());
+            let op = bus.read_u16_be(cpu.pc());
             println!(
-                "[F{}] PC={:08X} SR={:04X} OP={:04X} OVL={}",
+                "[F{}] PC={:08X} SR={:04X} OP={:04X} OVL={}  {}",
                 frame_count,
                 cpu.pc(),
                 cpu.sr.to_u16(),
                 op,
-                bus.rom_overlay
+                bus.rom_overlay,
+                disasm::disassemble_with(cpu.pc(), &bus, &symbols).0
             );
         }
 
@@ -179,6 +303,10 @@ fn main() {
             );
         }
 
+        if window.is_key_pressed(Key::X, minifb::KeyRepeat::No) {
+            print!("{}", vectors::dump_vector_table(&bus, cpu.vector_base()));
+        }
+
         if window.is_key_pressed(Key::V, minifb::KeyRepeat::No) {
             let slice = bus.ram.dma_slice();
             println!(
@@ -206,3 +334,23 @@ fn main() {
     }
     println!("Done. {} frames.", frame_count);
 }
+
+/// Código de tecla del teclado Mac (M0110) para una tecla del host.
+fn mac_keycode(key: Key) -> Option<u8> {
+    Some(match key {
+        Key::A => 0x00, Key::S => 0x01, Key::D => 0x02, Key::F => 0x03,
+        Key::H => 0x04, Key::G => 0x05, Key::Z => 0x06, Key::X => 0x07,
+        Key::C => 0x08, Key::V => 0x09, Key::B => 0x0B, Key::Q => 0x0C,
+        Key::W => 0x0D, Key::E => 0x0E, Key::R => 0x0F, Key::Y => 0x10,
+        Key::T => 0x11, Key::Key1 => 0x12, Key::Key2 => 0x13, Key::Key3 => 0x14,
+        Key::Key4 => 0x15, Key::Key6 => 0x16, Key::Key5 => 0x17, Key::Equal => 0x18,
+        Key::Key9 => 0x19, Key::Key7 => 0x1A, Key::Minus => 0x1B, Key::Key8 => 0x1C,
+        Key::Key0 => 0x1D, Key::RightBracket => 0x1E, Key::O => 0x1F, Key::U => 0x20,
+        Key::LeftBracket => 0x21, Key::I => 0x22, Key::P => 0x23, Key::Enter => 0x24,
+        Key::L => 0x25, Key::J => 0x26, Key::Apostrophe => 0x27, Key::K => 0x28,
+        Key::Semicolon => 0x29, Key::Backslash => 0x2A, Key::Comma => 0x2B, Key::Slash => 0x2C,
+        Key::N => 0x2D, Key::M => 0x2E, Key::Period => 0x2F, Key::Tab => 0x30,
+        Key::Space => 0x31, Key::Backquote => 0x32, Key::Backspace => 0x33,
+        _ => return None,
+    })
+}
@@ -1,4 +1,5 @@
 // crates/systems/oxid_mac/src/memory.rs
+use oxide_core::RamInit;
 
 /// Handles Macintosh RAM, including addressing modes, mirroring, and size-dependent behavior.
 pub struct MacRam {
@@ -22,41 +23,42 @@ impl MacRam {
         }
     }
 
+    /// Fill RAM with the given power-on pattern.
+    pub fn init(&mut self, init: RamInit) {
+        init.fill(&mut self.data);
+    }
+
     /// Read a byte from RAM.
     /// Handles mirroring or open bus (0xFF) behavior.
     pub fn read(&self, addr: u32) -> u8 {
-        let addr_usize = addr as usize;
-
-        if addr_usize < self.size {
-            // Direct access within physical RAM
-            // Safe unchecked because we checked bounds
-            unsafe { *self.data.get_unchecked(addr_usize) }
-        } else if self.enable_mirroring {
-            // Mirroring for older Macs
-            // Safe because logic implies size > 0
-            self.data[addr_usize % self.size]
-        } else {
-            // Out of bounds read on Mac Plus/SE -> Open Bus
-            0xFF
+        match self.index(addr) {
+            Some(i) => self.data[i],
+            None => 0xFF, // Out of bounds read on Mac Plus/SE -> Open Bus
         }
     }
 
     /// Write a byte to RAM.
     /// Handles mirroring or ignores out of bounds writes.
     pub fn write(&mut self, addr: u32, value: u8) {
-        let addr_usize = addr as usize;
+        // Out of bounds write on Mac Plus/SE -> Ignored
+        // (In reality, writes to open bus do nothing)
+        if let Some(i) = self.index(addr) {
+            self.data[i] = value;
+        }
+    }
 
-        if addr_usize < self.size {
-            // Direct access
-            unsafe {
-                *self.data.get_unchecked_mut(addr_usize) = value;
-            }
-        } else if self.enable_mirroring {
-            // Mirroring
-            self.data[addr_usize % self.size] = value;
+    /// Physical offset for `addr`: direct, mirrored (< 1MB models) or unmapped.
+    /// Always `< data.len()`, so the indexing in read/write never panics.
+    #[inline]
+    fn index(&self, addr: u32) -> Option<usize> {
+        debug_assert_eq!(self.data.len(), self.size);
+        let addr = addr as usize;
+        if addr < self.size {
+            Some(addr)
+        } else if self.enable_mirroring && self.size > 0 {
+            Some(addr % self.size)
         } else {
-            // Out of bounds write on Mac Plus/SE -> Ignored
-            // (In reality, writes to open bus do nothing)
+            None
         }
     }
 
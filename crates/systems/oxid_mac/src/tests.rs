@@ -0,0 +1,222 @@
+// crates/systems/oxid_mac/src/tests.rs
+use crate::bus::MacBus;
+use crate::fastboot::{FastBoot, RomTrap};
+use crate::memory::MacRam;
+use crate::via::MacVia;
+use oxid68k::Oxid68k;
+use oxide_core::Cpu;
+
+// ============================================================================
+// MacRam
+// ============================================================================
+
+#[test]
+fn test_mac_ram_mirrors_below_1mb() {
+    let mut ram = MacRam::new(0x20000); // 128K
+    ram.write(0x0001_0000, 0x42);
+    assert_eq!(ram.read(0x0003_0000), 0x42); // espejo cada 128K
+    ram.write(0x00FF_FFFF, 0x99);
+    assert_eq!(ram.read(0x0001_FFFF), 0x99);
+}
+
+#[test]
+fn test_mac_ram_open_bus_above_size() {
+    let mut ram = MacRam::new(0x100000); // 1MB: sin espejo
+    ram.write(0x0010_0000, 0x42); // ignorada
+    assert_eq!(ram.read(0x0010_0000), 0xFF);
+    assert_eq!(ram.read(0), 0);
+    assert_eq!(ram.read(u32::MAX), 0xFF);
+}
+
+#[test]
+fn test_mac_ram_zero_size_is_open_bus() {
+    let mut ram = MacRam::new(0);
+    ram.write(0x1234, 0x42);
+    assert_eq!(ram.read(0x1234), 0xFF);
+}
+
+// ============================================================================
+// VIA: teclado por Shift Register
+// ============================================================================
+
+const SR: u32 = 10 << 9;
+
+#[test]
+fn test_keyboard_inquiry_waits_for_key() {
+    let mut via = MacVia::new();
+    via.ier = 0x04;
+    via.write(SR, 0x10); // Inquiry
+    assert!(!via.tick(50_000), "sin tecla no hay respuesta todavía");
+    assert_eq!(via.ifr.get() & 0x04, 0);
+
+    via.push_key(0x33 << 1 | 1); // Backspace pulsada
+    let mut irq = false;
+    for _ in 0..10 {
+        irq |= via.tick(1_000);
+    }
+    assert!(irq);
+    assert_ne!(via.ifr.get() & 0x04, 0);
+    assert_eq!(via.read(SR), 0x67);
+    assert_eq!(via.ifr.get() & 0x04, 0, "leer SR limpia el flag");
+}
+
+#[test]
+fn test_keyboard_instant_and_model_responses() {
+    let mut via = MacVia::new();
+    via.write(SR, 0x14); // Instant sin teclas: NULL
+    via.tick(10_000);
+    assert_eq!(via.read(SR), 0x7B);
+
+    via.write(SR, 0x16); // Model
+    via.tick(10_000);
+    assert_eq!(via.read(SR), 0x0B);
+
+    via.write(SR, 0x10); // Inquiry sin teclas: NULL tras el timeout
+    via.tick(1_000_000);
+    assert_eq!(via.ifr.get() & 0x04, 0);
+    via.tick(1_000_000);
+    assert_eq!(via.read(SR), 0x7B);
+}
+
+// ============================================================================
+// VIA: RTC
+// ============================================================================
+
+/// Manda un comando de 8 bits al RTC por vBufB y lee el byte de respuesta.
+fn rtc_transfer(via: &mut MacVia, cmd: u8) -> u8 {
+    via.write(0, 0x00); // rtcEnb activo, reloj bajo
+    for bit in (0..8).rev() {
+        let data = (cmd >> bit) & 1;
+        via.write(0, data);
+        via.write(0, data | 0x02);
+    }
+    let mut value = 0u8;
+    for _ in 0..8 {
+        value = (value << 1) | (via.read(0) & 1);
+        via.write(0, 0x00);
+        via.write(0, 0x02);
+    }
+    via.write(0, 0x04); // rtcEnb inactivo
+    value
+}
+
+#[test]
+fn test_rtc_reads_seconds_from_emulated_clock() {
+    use oxide_core::Clock;
+
+    let mut via = MacVia::new();
+    via.clock = Clock::emulated(crate::via::MAC_CPU_HZ, 0); // 1970-01-01
+    let read_seconds = |via: &mut MacVia| {
+        u32::from_le_bytes([0x81, 0x85, 0x89, 0x8D].map(|cmd| rtc_transfer(via, cmd)))
+    };
+    assert_eq!(read_seconds(&mut via), 2_082_844_800);
+
+    // 3 s de CPU emulada
+    for _ in 0..3 {
+        via.tick(7_833_600);
+    }
+    assert_eq!(read_seconds(&mut via), 2_082_844_803);
+}
+
+
+// ============================================================================
+// Fast boot (salto del test de memoria)
+// ============================================================================
+
+/// ROM mínima: reset a $400008, bucle de "test de memoria" de 0x8000 vueltas
+/// y luego `MEMTEST_DONE`.
+const MEMTEST_LOOP: u32 = 0x40000E;
+const MEMTEST_DONE: u32 = 0x400012;
+
+fn memtest_rom() -> Vec<u8> {
+    let words: [u16; 11] = [
+        0x0004, 0x0000, // SSP
+        0x0040, 0x0008, // PC
+        0x203C, 0x0000, 0x8000, // MOVE.L #$8000,D0
+        0x5380, // loop: SUBQ.L #1,D0
+        0x66FC, // BNE.S loop
+        0x4E71, // done: NOP
+        0x60FC, // BRA.S done
+    ];
+    let mut rom: Vec<u8> = words.iter().flat_map(|w| w.to_be_bytes()).collect();
+    rom.resize(0x10000, 0);
+    rom
+}
+
+/// Instrucciones hasta llegar a `MEMTEST_DONE`.
+fn steps_to_post_memtest(fast_boot: &mut FastBoot) -> usize {
+    let mut bus = MacBus::new(memtest_rom(), 0x20000);
+    let mut cpu = Oxid68k::new();
+    cpu.reset_with_bus(&mut bus);
+    let mut steps = 0;
+    while steps < 100_000 {
+        fast_boot.apply(&mut cpu);
+        if cpu.pc == MEMTEST_DONE {
+            break;
+        }
+        cpu.step(&mut bus);
+        steps += 1;
+    }
+    assert_eq!(cpu.pc, MEMTEST_DONE);
+    steps
+}
+
+#[test]
+fn test_fast_boot_skips_memory_test_loop() {
+    let slow = steps_to_post_memtest(&mut FastBoot::default());
+    assert_eq!(slow, 1 + 2 * 0x8000);
+
+    let trap = RomTrap::parse("$40000E:0x400012").unwrap();
+    assert_eq!(trap, RomTrap { pc: MEMTEST_LOOP, resume: MEMTEST_DONE });
+    let mut fast_boot = FastBoot::new(vec![trap]);
+    let fast = steps_to_post_memtest(&mut fast_boot);
+    assert_eq!(fast, 1);
+    assert!(!fast_boot.is_armed(), "la trampa es de un solo disparo");
+    assert!(RomTrap::parse("40000E").is_none());
+}
+
+#[test]
+fn test_fast_boot_entry_found_by_rom_checksum() {
+    use crate::fastboot::{lookup, rom_checksum, KnownRom, BUILTIN};
+
+    let rom = memtest_rom();
+    let checksum = rom_checksum(&rom).unwrap();
+    assert_eq!(checksum, 0x0004_0000);
+    assert_eq!(rom_checksum(&rom[..3]), None);
+
+    let memtest = RomTrap { pc: MEMTEST_LOOP, resume: MEMTEST_DONE };
+    let table = [KnownRom { checksum, name: "test", memtest }];
+    assert_eq!(lookup(&table, checksum).map(|r| r.memtest), Some(memtest));
+    assert_eq!(lookup(&table, 0x4D1F_8172), None);
+    // Sin entradas repetidas: cada checksum identifica una sola ROM
+    for (i, rom) in BUILTIN.iter().enumerate() {
+        assert!(BUILTIN[i + 1..].iter().all(|other| other.checksum != rom.checksum), "{}", rom.name);
+    }
+}
+
+// ============================================================================
+// Traps de Line A
+// ============================================================================
+
+#[test]
+fn test_aline_opcode_disassembles_to_builtin_trap_name() {
+    use crate::traps::{builtin_traps, describe};
+    use oxid68k::disasm::{disassemble_with, Symbols};
+
+    let mut rom: Vec<u8> = [0xA9F0u16, 0xADF4, 0xA037]
+        .iter()
+        .flat_map(|w| w.to_be_bytes())
+        .collect();
+    rom.resize(0x10000, 0);
+    let bus = MacBus::new(rom, 0x20000);
+    let symbols = Symbols { traps: builtin_traps(), ..Default::default() };
+
+    assert_eq!(disassemble_with(0x400000, &bus, &symbols), ("_LoadSeg".to_string(), 2));
+    // Auto-pop (bit 10) sobre _ExitToShell
+    assert_eq!(disassemble_with(0x400002, &bus, &symbols).0, "_ExitToShell");
+
+    // Hook de la traza: palabra + nombre, o solo la palabra si no lo hay
+    assert_eq!(describe(0xA9F0, &symbols).as_deref(), Some("A9F0 _LoadSeg"));
+    assert_eq!(describe(0xA037, &symbols).as_deref(), Some("A037"));
+    assert_eq!(describe(0x4E75, &symbols), None);
+}
@@ -2,6 +2,31 @@
 // Macintosh VIA (Versatile Interface Adapter) - 6522 emulation
 
 use std::cell::Cell;
+use oxide_core::Clock;
+use std::collections::VecDeque;
+
+/// Reloj del 68000 en el Mac 128K/512K/Plus
+pub const MAC_CPU_HZ: u64 = 7_833_600;
+/// Segundos entre 1904-01-01 (epoch del Mac) y 1970-01-01 (Unix)
+const MAC_EPOCH_OFFSET: u64 = 2_082_844_800;
+
+// IFR bit del Shift Register (transferencia completa / byte de teclado listo)
+const IFR_SR: u8 = 0x04;
+
+// Comandos del protocolo de teclado (Mac 128K/512K/Plus)
+const KBD_INQUIRY: u8 = 0x10; // Espera hasta 1/4 s a una tecla
+const KBD_INSTANT: u8 = 0x14; // Responde ya
+const KBD_MODEL: u8 = 0x16;
+const KBD_TEST: u8 = 0x36;
+// Respuestas
+const KBD_NULL: u8 = 0x7B; // Sin teclas
+const KBD_ACK: u8 = 0x7D;
+const KBD_MODEL_PLUS: u8 = 0x0B; // Teclado Mac Plus con keypad
+
+/// Tiempo que tarda el teclado en devolver un byte (~1 ms a 7.83 MHz)
+const KBD_RESPONSE_CYCLES: u32 = 8_000;
+/// Inquiry sin teclas: el teclado contesta NULL a los 0.25 s
+const KBD_INQUIRY_TIMEOUT: u32 = 1_958_400;
 
 #[derive(Clone)]
 pub struct MacVia {
@@ -24,9 +49,15 @@ pub struct MacVia {
     rtc_bit_count: u8,
     rtc_shift_reg: u32,
     rtc_data_out: u8,
+    rtc_out_byte: u8, // Respuesta de lectura, sale MSB primero
+    /// Fuente de tiempo del RTC; avanza con `tick`
+    pub clock: Clock,
 
-    // Keyboard simulation state
-    kbd_last_cmd: u8,
+    // Teclado: transiciones de tecla pendientes y respuesta en curso
+    kbd_queue: VecDeque<u8>,
+    kbd_waiting: Option<u8>,  // Comando en espera de respuesta
+    kbd_timer: u32,           // Ciclos hasta responder (o timeout de Inquiry)
+    sr_data: u8,              // Último byte recibido por el Shift Register
 
     // Video timing simulation (Cell for interior mutability)
     hblank_counter: Cell<u32>,
@@ -50,7 +81,12 @@ impl MacVia {
             rtc_bit_count: 0,
             rtc_shift_reg: 0,
             rtc_data_out: 0xFF,
-            kbd_last_cmd: 0,
+            rtc_out_byte: 0xFF,
+            clock: Clock::real_time(MAC_CPU_HZ),
+            kbd_queue: VecDeque::new(),
+            kbd_waiting: None,
+            kbd_timer: 0,
+            sr_data: 0xFF,
             hblank_counter: Cell::new(0),
         }
     }
@@ -75,7 +111,9 @@ impl MacVia {
                 self.hblank_counter.set(count);
                 let hblank = if (count % 5) < 1 { 0x00 } else { 0x40 };
 
-                self.orb | rtc_data | mouse_up | hblank
+                // Bit 0 como entrada (DDRB) = línea de datos del RTC, no lo último escrito
+                let orb = if self.ddrb & 0x01 == 0 { self.orb & !0x01 } else { self.orb };
+                orb | rtc_data | mouse_up | hblank
             }
             1 | 15 => self.ora, // ORA (vBufA)
             2 => self.ddrb,
@@ -87,18 +125,10 @@ impl MacVia {
             8 => (self.t2c & 0xFF) as u8,        // T2C-L
             9 => ((self.t2c >> 8) & 0xFF) as u8, // T2C-H
             10 => {
-                // Shift Register (Reg 10)
-                // Reading SR usually clears the interrupt flag (bit 2)
+                // Shift Register (Reg 10): leerlo limpia el flag de interrupción
                 let current_ifr = self.ifr.get();
-                self.ifr.set(current_ifr & !0x04);
-
-                // Keyboard Protocol Simulation
-                // If last command was Inquiry (0x10), return Mac Plus Keypad ID (0x0B)
-                match self.kbd_last_cmd {
-                    0x10 => 0x0B, // Mac Plus Keypad
-                    0x12 => 0x0B, // Another Inquiry?
-                    _ => 0xFF,    // Idle / No response
-                }
+                self.ifr.set(current_ifr & !IFR_SR);
+                self.sr_data
             }
             11 => self.acr,
             12 => 0, // PCR (not used)
@@ -123,10 +153,17 @@ impl MacVia {
                 if rtc_enable {
                     if !self.rtc_clock && rtc_clock {
                         // Rising edge
-                        self.rtc_shift_reg = (self.rtc_shift_reg << 1) | (rtc_data as u32);
-                        self.rtc_bit_count += 1;
-                        if self.rtc_bit_count >= 8 {
-                            self.rtc_data_out = 0xFF;
+                        if self.rtc_bit_count == 8 {
+                            // Fase de respuesta: siguiente bit de la lectura
+                            self.rtc_out_byte <<= 1;
+                            self.rtc_data_out = self.rtc_out_byte >> 7;
+                        } else {
+                            self.rtc_shift_reg = (self.rtc_shift_reg << 1) | (rtc_data as u32);
+                            self.rtc_bit_count += 1;
+                            if self.rtc_bit_count == 8 {
+                                self.rtc_out_byte = self.rtc_read(self.rtc_shift_reg as u8);
+                                self.rtc_data_out = self.rtc_out_byte >> 7;
+                            }
                         }
                     }
                     self.rtc_clock = rtc_clock;
@@ -186,12 +223,14 @@ impl MacVia {
                 None
             }
             10 => {
-                // Shift Register Write
-                self.kbd_last_cmd = val;
-
-                // Simulate "Transfer Complete" immediately by setting bit 2
-                let ifr = self.ifr.get();
-                self.ifr.set(ifr | 0x04);
+                // Shift Register Write: comando al teclado. El flag SR se levanta
+                // cuando el teclado contesta (ver `tick`), no al escribir.
+                self.kbd_waiting = Some(val);
+                self.kbd_timer = if val == KBD_INQUIRY && self.kbd_queue.is_empty() {
+                    KBD_INQUIRY_TIMEOUT
+                } else {
+                    KBD_RESPONSE_CYCLES
+                };
                 None
             }
             11 => {
@@ -218,6 +257,7 @@ impl MacVia {
 
     /// Tick the VIA timers. Returns true if an interrupt line state changes or is active.
     pub fn tick(&mut self, cycles: u32) -> bool {
+        self.clock.advance(cycles);
         let cycle_u16 = cycles as u16;
         let mut ifr = self.ifr.get();
         let mut ifr_changed = false;
@@ -238,13 +278,25 @@ impl MacVia {
 
         // Timer 2
         let (new_t2, overflow2) = self.t2c.overflowing_sub(cycle_u16);
-        if overflow2 && self.t2c > 0 {
-            if (ifr & 0x20) == 0 {
-                ifr |= 0x20;
+        if overflow2 && self.t2c > 0 && (ifr & 0x20) == 0 {
+            ifr |= 0x20;
+            ifr_changed = true;
+        }
+        self.t2c = new_t2;
+
+        // Teclado: un Inquiry en espera contesta en cuanto hay una tecla
+        if let Some(cmd) = self.kbd_waiting {
+            if cmd == KBD_INQUIRY && !self.kbd_queue.is_empty() {
+                self.kbd_timer = self.kbd_timer.min(KBD_RESPONSE_CYCLES);
+            }
+            self.kbd_timer = self.kbd_timer.saturating_sub(cycles);
+            if self.kbd_timer == 0 {
+                self.kbd_waiting = None;
+                self.sr_data = self.keyboard_response(cmd);
+                ifr |= IFR_SR;
                 ifr_changed = true;
             }
         }
-        self.t2c = new_t2;
 
         if ifr_changed {
             self.ifr.set(ifr);
@@ -253,6 +305,32 @@ impl MacVia {
         (ifr & self.ier & 0x7F) != 0
     }
 
+    /// Comando de lectura del RTC (`1aaaaa01`): los registros 0-3 (y su espejo
+    /// 4-7) son los segundos desde 1904, byte bajo primero. El resto (PRAM) aún no.
+    fn rtc_read(&self, cmd: u8) -> u8 {
+        let addr = (cmd >> 2) & 0x1F;
+        if cmd & 0x83 != 0x81 || addr >= 8 {
+            return 0xFF;
+        }
+        let seconds = (self.clock.unix_seconds() + MAC_EPOCH_OFFSET) as u32;
+        seconds.to_le_bytes()[(addr & 3) as usize]
+    }
+
+    /// Encola una transición de tecla tal como la manda el teclado
+    /// (bit 7 = soltada, bits 6-1 = código, bit 0 = 1).
+    pub fn push_key(&mut self, transition: u8) {
+        self.kbd_queue.push_back(transition);
+    }
+
+    fn keyboard_response(&mut self, cmd: u8) -> u8 {
+        match cmd {
+            KBD_INQUIRY | KBD_INSTANT => self.kbd_queue.pop_front().unwrap_or(KBD_NULL),
+            KBD_MODEL => KBD_MODEL_PLUS,
+            KBD_TEST => KBD_ACK,
+            _ => KBD_NULL,
+        }
+    }
+
     // Check pending IRQ
     #[allow(dead_code)]
     pub fn irq_pending(&self) -> bool {
@@ -1,5 +1,6 @@
 // crates/systems/oxid_master/src/bus.rs
-use oxide_core::MemoryBus;
+use oxide_core::{AudioSink, MemoryBus, RamInit};
+use oxide_core::psg::Psg;
 use crate::vdp::Vdp;
 
 /// Implementación densa del Bus del Master System.
@@ -11,6 +12,8 @@ pub struct MasterSystemBus {
     pub ram: [u8; 0x2000],
     /// Procesador de Video (VDP).
     pub vdp: Vdp,
+    /// Generador de sonido SN76489 (puertos $7E/$7F).
+    pub psg: Psg,
     /// Bancos de ROM paginados.
     /// Slot 0: $0000-$3FFF (Fijo o Banco 0)
     /// Slot 1: $4000-$7FFF (Banco seleccionable)
@@ -22,24 +25,77 @@ pub struct MasterSystemBus {
     pub joypad: u8,
     /// Joypad 2 / Misc ($DD).
     pub joypad_2: u8,
-    /// Valor del V-Counter (simulado para puerto $7E).
-    pub v_counter: u8,
     /// Valor del H-Counter (simulado para puerto $7F).
     pub h_counter: u8,
+    /// Obturador de las gafas SegaScope 3D: ojo activo en el frame en curso
+    /// (false = izquierdo, true = derecho). Alterna en cada `end_frame` y un write
+    /// a $FFF8-$FFFB (bit 0) vuelve a fijar la fase.
+    pub glasses_right_eye: bool,
+    /// Gafas en uso: el juego ha escrito en $FFF8-$FFFB desde el reset.
+    pub glasses_in_use: bool,
+    /// Modo Game Gear: habilita los puertos $00-$06 (Start/región, link serie, estéreo).
+    pub game_gear: bool,
+    /// Registros del puerto de enlace del Game Gear ($00-$06). Solo un stub sin cable conectado.
+    pub gg_regs: [u8; 7],
+    /// BIOS opcional (logo Sega / chequeo de región). Usa el mismo mapper que el cartucho.
+    pub bios: Option<Vec<u8>>,
+    /// Máscara de la BIOS (potencia de dos).
+    pub bios_mask: usize,
+    /// Registro de control de memoria (puerto $3E). Bits activos en bajo:
+    /// 6 = cartucho, 4 = RAM, 3 = BIOS, 2 = chip de I/O (0 = habilitado).
+    pub mem_control: u8,
+    /// Registro de control de I/O (puerto $3F): dirección y nivel de las líneas TR/TH.
+    pub io_control: u8,
+    /// Región que ve el software al sondear TH ($3F/$DD) o el bit 6 del puerto $00 en GG.
+    /// Forzarla equivale al switch de región: los juegos importados pasan el chequeo.
+    pub region: Region,
+}
+
+/// Región del hardware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Region {
+    /// Export (Europa/América): las líneas TH en modo salida se leen tal cual en $DD
+    #[default]
+    Export,
+    /// Japón: la lectura de TH sale invertida respecto a lo escrito en $3F
+    Japan,
+}
+
+impl Region {
+    /// Valor de la opción `-region`: `export` o `japan`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "export" => Some(Region::Export),
+            "japan" => Some(Region::Japan),
+            _ => None,
+        }
+    }
+}
+
+// Bits del puerto $3E (0 = habilitado)
+const MEM_CART_DISABLE: u8 = 0x40;
+const MEM_RAM_DISABLE: u8 = 0x10;
+const MEM_BIOS_DISABLE: u8 = 0x08;
+const MEM_IO_DISABLE: u8 = 0x04;
+
+/// Máscara potencia-de-dos para indexar una imagen de ROM.
+fn image_mask(image: &[u8]) -> usize {
+    if !image.is_empty() {
+        (1 << (image.len().next_power_of_two().trailing_zeros())) - 1
+    } else {
+        0
+    }
 }
 
 impl MasterSystemBus {
     pub fn new(rom: Vec<u8>) -> Self {
-        let mask = if rom.len() > 0 {
-            (1 << (rom.len().next_power_of_two().trailing_zeros())) - 1
-        } else {
-            0
-        };
-        
+        let mask = image_mask(&rom);
+
         Self {
             rom,
             ram: [0; 0x2000],
             vdp: Vdp::new(),
+            psg: Psg::new(),
             // Inicialización típica de mappers Sega:
             // Slot 0 -> Banco 0
             // Slot 1 -> Banco 1
@@ -48,16 +104,102 @@ impl MasterSystemBus {
             rom_mask: mask,
             joypad: 0xFF, // Pull-up resistors (1=no pulsado)
             joypad_2: 0xFF,
-            v_counter: 0,
             h_counter: 0,
+            glasses_right_eye: false,
+            glasses_in_use: false,
+            game_gear: false,
+            // Valores de reset: Start suelto + región export, link en reposo, estéreo todo activo
+            gg_regs: [0xC0, 0x7F, 0xFF, 0x00, 0xFF, 0x00, 0xFF],
+            bios: None,
+            bios_mask: 0,
+            // Sin BIOS: cartucho habilitado, BIOS deshabilitada
+            mem_control: 0xAB,
+            io_control: 0xFF, // Todas las líneas como entrada
+            region: Region::Export,
         }
     }
 
+    /// Bus con BIOS: arranca con la BIOS mapeada y el cartucho oculto.
+    /// La BIOS entrega el control escribiendo en el puerto $3E.
+    pub fn with_bios(rom: Vec<u8>, bios: Vec<u8>) -> Self {
+        let mut bus = Self::new(rom);
+        bus.bios_mask = image_mask(&bios);
+        bus.bios = Some(bios);
+        bus.mem_control = 0xE3;
+        bus
+    }
+
+    /// Fin de frame: con las gafas 3D en uso el obturador pasa al otro ojo.
+    pub fn end_frame(&mut self) {
+        if self.glasses_in_use {
+            self.glasses_right_eye = !self.glasses_right_eye;
+        }
+    }
+
+    /// Genera `samples` muestras mono del PSG a `sample_rate` y las entrega al sink.
+    pub fn render_audio(&mut self, samples: usize, sample_rate: u32, sink: &mut dyn AudioSink) {
+        let mut out = vec![0i16; samples];
+        self.psg.generate(&mut out, sample_rate);
+        sink.push_samples(&out);
+    }
+
+    /// Puerto $DD: joypad 2 + líneas TH (bit 6 = TH-A, bit 7 = TH-B).
+    /// Con TH configurada como salida en $3F, la región decide qué nivel se lee.
+    fn read_port_b(&self) -> u8 {
+        let mut v = self.joypad_2;
+        let invert = self.region == Region::Japan;
+        // (bit de dirección, bit de nivel en $3F, bit en $DD)
+        for (dir, level, bit) in [(0x02, 0x20, 0x40), (0x08, 0x80, 0x80)] {
+            if self.io_control & dir == 0 {
+                if (self.io_control & level != 0) != invert {
+                    v |= bit;
+                } else {
+                    v &= !bit;
+                }
+            }
+        }
+        v
+    }
+
+    /// Lee del área de ROM ($0000-$BFFF) según qué imagen está habilitada en $3E.
+    fn read_rom(&self, address: u32) -> u8 {
+        let (image, mask) = if self.mem_control & MEM_CART_DISABLE == 0 {
+            (&self.rom[..], self.rom_mask)
+        } else if self.mem_control & MEM_BIOS_DISABLE == 0 {
+            match self.bios {
+                Some(ref bios) => (&bios[..], self.bios_mask),
+                None => return 0xFF,
+            }
+        } else {
+            return 0xFF;
+        };
+        if image.is_empty() { return 0xFF; }
+
+        let address = address as usize;
+        let offset = match address {
+            // Los primeros 1KB son fijos (header/vectores)
+            0x0000..=0x03FF => address & mask,
+            _ => {
+                let slot = address >> 14;
+                (self.paged_rom[slot] + (address & 0x3FFF)) & mask
+            }
+        };
+        // Con imágenes que no son potencia de 2 la máscara puede caer
+        // más allá del final: ese hueco se lee como bus abierto
+        image.get(offset).copied().unwrap_or(0xFF)
+    }
+
+    /// Rellena la RAM de trabajo con el patrón de encendido indicado.
+    pub fn init_ram(&mut self, init: RamInit) {
+        init.fill(&mut self.ram);
+    }
+
     /// Escribe en los registros del Mapper (Frame Control).
     /// Los mappers de Sega usan $FFFC-$FFFF para seleccionar bancos.
     fn write_mapper(&mut self, address: u32, value: u8) {
         // Asumimos Mapper SEGA estándar por ahora.
-        let bank_addr = (value as usize * 0x4000) & self.rom_mask;
+        // La máscara se aplica al leer, según la imagen activa (cartucho o BIOS).
+        let bank_addr = value as usize * 0x4000;
         match address {
             0xFFFD => self.paged_rom[0] = bank_addr, // Control Slot 0 ($0400-$3FFF)
             0xFFFE => self.paged_rom[1] = bank_addr, // Control Slot 1 ($4000-$7FFF)
@@ -70,31 +212,13 @@ impl MasterSystemBus {
 impl MemoryBus for MasterSystemBus {
     fn read(&self, address: u32) -> u8 {
         match address & 0xFFFF {
-            // --- ROM Slots ---
-            // Slot 0: Los primeros 1KB ($0000-$03FF) son fijos al principio de la ROM (header/vectores).
-            0x0000..=0x03FF => {
-                if self.rom.is_empty() { return 0xFF; }
-                self.rom[(address as usize) & self.rom_mask]
-            }
-            0x0400..=0x3FFF => {
-                if self.rom.is_empty() { return 0xFF; }
-                let offset = (address as usize) & 0x3FFF;
-                self.rom[(self.paged_rom[0] + offset) & self.rom_mask]
-            }
-            // Slot 1
-            0x4000..=0x7FFF => {
-                if self.rom.is_empty() { return 0xFF; }
-                let offset = (address as usize) & 0x3FFF;
-                self.rom[(self.paged_rom[1] + offset) & self.rom_mask]
-            }
-            // Slot 2
-            0x8000..=0xBFFF => {
-                if self.rom.is_empty() { return 0xFF; }
-                let offset = (address as usize) & 0x3FFF;
-                self.rom[(self.paged_rom[2] + offset) & self.rom_mask]
-            }
+            // --- ROM Slots (cartucho o BIOS) ---
+            0x0000..=0xBFFF => self.read_rom(address & 0xFFFF),
 
             // --- RAM & Mirrors ---
+            // RAM deshabilitada en $3E: bus abierto
+            0xC000..=0xFFFF if self.mem_control & MEM_RAM_DISABLE != 0 => 0xFF,
+
             // RAM Principal (8KB)
             0xC000..=0xDFFF => self.ram[(address as usize) & 0x1FFF],
             
@@ -111,30 +235,51 @@ impl MemoryBus for MasterSystemBus {
             // ROM no es escribible (normalmente), pero algunos mappers raros sí.
             0x0000..=0xBFFF => {} 
 
-            // RAM Principal
-            0xC000..=0xDFFF => self.ram[(address as usize) & 0x1FFF] = value,
+            // RAM Principal (ignorada si $3E la deshabilita)
+            0xC000..=0xDFFF if self.mem_control & MEM_RAM_DISABLE == 0 => {
+                self.ram[(address as usize) & 0x1FFF] = value;
+            }
 
             // Espejo de RAM ($E000-$FFFF)
             // Aquí se solapan las escrituras de los registros de Mapper de Sega.
+            // El mapper vive en el cartucho: sigue recibiendo escrituras aunque la RAM esté deshabilitada.
             0xE000..=0xFFFF => {
-                self.ram[(address as usize) & 0x1FFF] = value; // Escribe en RAM subyacente
+                if self.mem_control & MEM_RAM_DISABLE == 0 {
+                    self.ram[(address as usize) & 0x1FFF] = value; // Escribe en RAM subyacente
+                }
                 
                 // Mapeo de Registros de Paginación (Mapper Writes)
                 if address >= 0xFFFC {
                     self.write_mapper(address, value);
+                } else if address >= 0xFFF8 {
+                    // Gafas 3D: $FFF8-$FFFB
+                    self.glasses_right_eye = value & 1 != 0;
+                    self.glasses_in_use = true;
                 }
             }
             _ => {}
         }
     }
 
+    fn int_asserted(&self) -> bool {
+        // El pin INT del VDP va directo al Z80
+        self.vdp.is_interrupting()
+    }
+
     fn port_in(&mut self, port: u16) -> u8 {
         // El puerto se decodifica usualmente con los bits bajos.
         let p = port & 0xFF;
         
         match p {
+            // Game Gear: Start/región y puerto de enlace
+            0x00 if self.game_gear => {
+                let export = if self.region == Region::Export { 0x40 } else { 0 };
+                (self.gg_regs[0] & !0x40) | export
+            }
+            0x01..=0x06 if self.game_gear => self.gg_regs[p as usize],
+
             // Specific Counters
-            0x7E => self.v_counter,
+            0x7E => self.vdp.v_counter(),
             0x7F => self.h_counter,
 
             // VDP Ports ($80-$BF). Even=Data, Odd=Status/Control
@@ -146,6 +291,9 @@ impl MemoryBus for MasterSystemBus {
                 }
             },
 
+            // Chip de I/O deshabilitado en $3E: los puertos de joypad no responden
+            0xC0..=0xDF if self.mem_control & MEM_IO_DISABLE != 0 => 0xFF,
+
             // Joypads ($C0-$DF mirrors $DC-$DD)
             // $DC (Even): Port A (Joypad 1)
             // $DD (Odd): Port B (Joypad 2)
@@ -153,7 +301,7 @@ impl MemoryBus for MasterSystemBus {
                 if p & 1 == 0 {
                     self.joypad
                 } else {
-                    self.joypad_2
+                    self.read_port_b()
                 }
             },
             
@@ -164,8 +312,16 @@ impl MemoryBus for MasterSystemBus {
     fn port_out(&mut self, port: u16, value: u8) {
         let p = port & 0xFF;
         match p {
+            // Game Gear: $00 es solo lectura; el resto del link port se guarda sin efecto
+            0x01..=0x06 if self.game_gear => self.gg_regs[p as usize] = value,
+
+            // $00-$3F pares: control de memoria ($3E)
+            0x00..=0x3F if p & 1 == 0 => self.mem_control = value,
+            // $00-$3F impares: control de I/O ($3F)
+            0x01..=0x3F => self.io_control = value,
+
             // $7E-$7F: PSG
-            0x7E | 0x7F => {}, // PSG Stub
+            0x7E | 0x7F => self.psg.write(value),
 
             // VDP Ports ($80-$BF). Even=Data, Odd=Control
             0x80..=0xBF => {
@@ -0,0 +1,122 @@
+// crates/systems/oxid_master/src/header.rs
+//
+// Cabecera "TMR SEGA" de los cartuchos SMS/Game Gear (16 bytes, normalmente en
+// $7FF0) y, si existe, la cabecera SDSC de homebrew con el título del juego.
+
+const SIGNATURE: &[u8; 8] = b"TMR SEGA";
+/// Posiciones posibles de la cabecera, de la más habitual a la menos
+const OFFSETS: [usize; 3] = [0x7FF0, 0x3FF0, 0x1FF0];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RomHeader {
+    /// Dónde se encontró "TMR SEGA"
+    pub offset: usize,
+    pub checksum: u16,
+    /// Código de producto (BCD + nibble alto del byte de versión)
+    pub product: u32,
+    pub version: u8,
+    /// Nibble alto del último byte: 3/4 = SMS, 5/6/7 = Game Gear
+    pub region: u8,
+    /// Nibble bajo del último byte: tamaño declarado (y rango del checksum)
+    pub size_code: u8,
+    /// Nombre de la cabecera SDSC (homebrew), si la hay
+    pub title: Option<String>,
+}
+
+impl RomHeader {
+    pub fn parse(rom: &[u8]) -> Option<Self> {
+        let offset = *OFFSETS.iter()
+            .find(|&&o| rom.get(o..o + 16).is_some_and(|h| h.starts_with(SIGNATURE)))?;
+        let h = &rom[offset..offset + 16];
+        let bcd = |b: u8| (b >> 4) as u32 * 10 + (b & 0x0F) as u32;
+        Some(Self {
+            offset,
+            checksum: u16::from_le_bytes([h[0x0A], h[0x0B]]),
+            product: bcd(h[0x0C]) + bcd(h[0x0D]) * 100 + (h[0x0E] >> 4) as u32 * 10_000,
+            version: h[0x0E] & 0x0F,
+            region: h[0x0F] >> 4,
+            size_code: h[0x0F] & 0x0F,
+            title: sdsc_title(rom),
+        })
+    }
+
+    pub fn is_game_gear(&self) -> bool {
+        matches!(self.region, 5..=7)
+    }
+
+    pub fn system_name(&self) -> &'static str {
+        if self.is_game_gear() { "Game Gear" } else { "Master System" }
+    }
+
+    pub fn region_name(&self) -> &'static str {
+        match self.region {
+            3 | 5 => "Japan",
+            4 | 6 => "Export",
+            7 => "International",
+            _ => "Unknown",
+        }
+    }
+
+    /// Tamaño cubierto por el checksum según el código de la cabecera.
+    pub fn declared_size(&self) -> Option<usize> {
+        Some(match self.size_code {
+            0xA => 0x2000,
+            0xB => 0x4000,
+            0xC => 0x8000,
+            0xD => 0xC000,
+            0xE => 0x10000,
+            0xF => 0x20000,
+            0x0 => 0x40000,
+            0x1 => 0x80000,
+            0x2 => 0x100000,
+            _ => return None,
+        })
+    }
+
+    /// Suma de bytes como la BIOS: se salta los 16 bytes de la cabecera en $7FF0.
+    /// `None` si el código de tamaño es inválido o la ROM es más corta que lo declarado.
+    pub fn checksum_ok(&self, rom: &[u8]) -> Option<bool> {
+        let size = self.declared_size()?;
+        if rom.len() < size {
+            return None;
+        }
+        let sum = |r: &[u8]| r.iter().fold(0u16, |acc, &b| acc.wrapping_add(b as u16));
+        let head = size.min(0x8000) - 0x10;
+        let mut total = sum(&rom[..head]);
+        if size > 0x8000 {
+            total = total.wrapping_add(sum(&rom[0x8000..size]));
+        }
+        Some(total == self.checksum)
+    }
+
+    /// Líneas para el panel de información.
+    pub fn info_lines(&self, rom: &[u8], fallback_title: &str) -> Vec<String> {
+        let title = self.title.as_deref().unwrap_or(fallback_title);
+        let checksum = match self.checksum_ok(rom) {
+            Some(true) => "OK",
+            Some(false) => "BAD",
+            None => "N/A",
+        };
+        vec![
+            format!("SYSTEM: {}", self.system_name()),
+            format!("TITLE: {}", title.chars().take(18).collect::<String>()),
+            format!("REGION: {}", self.region_name()),
+            format!("PRODUCT: {:05} V{}", self.product, self.version),
+            format!("SIZE: {}KB", rom.len() / 1024),
+            format!("CHECKSUM: ${:04X} {}", self.checksum, checksum),
+        ]
+    }
+}
+
+/// Título de la cabecera SDSC ($7FE0): puntero LE en $7FEC a un texto terminado en 0.
+fn sdsc_title(rom: &[u8]) -> Option<String> {
+    if rom.get(0x7FE0..0x7FE4)? != b"SDSC" {
+        return None;
+    }
+    let ptr = u16::from_le_bytes([rom[0x7FEC], rom[0x7FED]]) as usize;
+    if ptr == 0xFFFF {
+        return None;
+    }
+    let bytes: Vec<u8> = rom.get(ptr..)?.iter().take_while(|&&b| b != 0).copied().collect();
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
@@ -1,43 +1,175 @@
 // crates/systems/oxid_master/src/main.rs
 mod bus;
+mod header;
 mod vdp;
+#[cfg(test)]
+mod tests;
 
-use oxide_core::{Cpu, Rom};
+use oxide_core::{cheats, Cpu, MemoryBus, RamInit, Rom};
 use oxidz80::OxidZ80;
-use crate::bus::MasterSystemBus;
-use minifb::{Window, WindowOptions, Key};
+use oxid_display::text::draw_panel;
+use crate::bus::{MasterSystemBus, Region};
+use crate::header::RomHeader;
+use crate::vdp::{crop_gg_viewport, Layer, GG_HEIGHT, GG_WIDTH, NTSC_LINES, PAL_LINES};
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
 use std::env;
+use std::time::{Duration, Instant};
 
 const WIDTH: usize = 256;
 const HEIGHT: usize = 192;
+/// Frecuencia de salida del audio
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Temporización CPU/vídeo de un frame según el estándar de TV.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timing {
+    /// Ciclos de Z80 por línea (~3.58MHz / 60 / 262)
+    pub cycles_per_line: u32,
+    pub lines: usize,
+    pub fps: f64,
+}
+
+impl Timing {
+    pub const NTSC: Timing = Timing { cycles_per_line: 228, lines: NTSC_LINES, fps: 60.0 };
+    pub const PAL: Timing = Timing { cycles_per_line: 228, lines: PAL_LINES, fps: 50.0 };
+
+    /// Multiplica los ciclos de CPU por línea (el vídeo sigue al mismo ritmo).
+    pub fn overclock(self, factor: f64) -> Self {
+        let cycles = (self.cycles_per_line as f64 * factor).round().max(1.0) as u32;
+        Self { cycles_per_line: cycles, ..self }
+    }
+
+    pub fn cycles_per_frame(&self) -> u32 {
+        self.cycles_per_line * self.lines as u32
+    }
+
+    /// Muestras de audio por frame a `SAMPLE_RATE` (735 en NTSC, 882 en PAL).
+    pub fn samples_per_frame(&self) -> usize {
+        (SAMPLE_RATE as f64 / self.fps).round() as usize
+    }
+
+}
+
+/// Valor que sigue a una opción de línea de comandos (`-flag valor`).
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        println!("Usage: oxid_master <rom_path>");
+        println!("Usage: oxid_master <rom_path> [-ram zeros|ones|seed:<n>] [-bios <bios_path>] [-gg] [-fullframe] [-region export|japan] [-bench <frames>] [-pal] [-overclock <factor>] [-cheats <pokes.txt>] [-ips <patch.ips>] [-bps <patch.bps>]");
         return;
     }
 
     let rom_path = &args[1];
-    let rom = Rom::from_file(rom_path).expect("Failed to load ROM");
+    let mut rom = Rom::from_file(rom_path).expect("Failed to load ROM");
+    if let Some(path) = arg_value(&args, "-ips") {
+        rom.apply_ips(path).expect("Failed to apply IPS patch");
+    }
+    if let Some(path) = arg_value(&args, "-bps") {
+        rom.apply_bps(path).expect("Failed to apply BPS patch");
+    }
     
-    let mut bus = MasterSystemBus::new(rom.data);
+    let ram_init = match arg_value(&args, "-ram") {
+        Some(value) => match RamInit::parse(value) {
+            Some(init) => init,
+            None => {
+                println!("Invalid -ram value '{}' (expected zeros, ones or seed:<n>)", value);
+                return;
+            }
+        },
+        None => RamInit::default(),
+    };
+
+    let mut bus = match arg_value(&args, "-bios") {
+        Some(path) => {
+            let bios = Rom::from_file(path).expect("Failed to load BIOS");
+            MasterSystemBus::with_bios(rom.data, bios.data)
+        }
+        None => MasterSystemBus::new(rom.data),
+    };
+    bus.init_ram(ram_init);
+    if let Some(region) = arg_value(&args, "-region").and_then(Region::parse) {
+        bus.region = region;
+    }
+
+    // Game Gear: por flag o extensión .gg. Solo se ve la ventana central 160x144,
+    // salvo con -fullframe (depuración: frame SMS completo).
+    bus.game_gear = args.iter().any(|a| a == "-gg") || rom_path.to_lowercase().ends_with(".gg");
+    let full_frame = args.iter().any(|a| a == "-fullframe");
+    let (out_w, out_h) = if bus.game_gear && !full_frame {
+        (GG_WIDTH, GG_HEIGHT)
+    } else {
+        (WIDTH, HEIGHT)
+    };
+    let mut timing = if args.iter().any(|a| a == "-pal") { Timing::PAL } else { Timing::NTSC };
+    if let Some(f) = arg_value(&args, "-overclock").and_then(|f| f.parse::<f64>().ok()) {
+        timing = timing.overclock(f);
+    }
+    bus.vdp.pal = timing.lines == PAL_LINES;
     let mut cpu = OxidZ80::new();
     cpu.reset();
 
+    let cheats = arg_value(&args, "-cheats")
+        .map(|path| cheats::load_pokes(path).expect("Failed to load cheats"))
+        .unwrap_or_default();
+
+    // Benchmark sin throttle ni ventana: N frames a máxima velocidad
+    let bench = arg_value(&args, "-bench")
+        .or_else(|| arg_value(&args, "--bench"))
+        .and_then(|n| n.parse::<u32>().ok());
+    if let Some(frames) = bench {
+        let mut frame_buffer = vec![0u32; WIDTH * HEIGHT];
+        let mut audio: Vec<i16> = Vec::new();
+        let t0 = Instant::now();
+        for _ in 0..frames {
+            run_frame(&mut cpu, &mut bus, &timing, &mut frame_buffer);
+            audio.clear();
+            bus.render_audio(timing.samples_per_frame(), SAMPLE_RATE, &mut audio);
+        }
+        let elapsed = t0.elapsed();
+        println!("Bench: {} frames ({:.2}s emulated) in {:.3}s real -> {:.2}x",
+            frames, frames as f64 / timing.fps, elapsed.as_secs_f64(), speed_factor(frames, timing.fps, elapsed));
+        return;
+    }
+
     let mut window = Window::new(
         "Oxide-Master - Sonic The Hedgehog",
-        WIDTH * 3,
-        HEIGHT * 3,
+        out_w * 3,
+        out_h * 3,
         WindowOptions::default(),
     ).expect("Failed to create window");
 
-    window.limit_update_rate(Some(std::time::Duration::from_micros(16666))); // ~60fps
+    window.limit_update_rate(Some(Duration::from_secs_f64(1.0 / timing.fps)));
 
     let mut frame_buffer = vec![0u32; WIDTH * HEIGHT];
+    let mut gg_buffer = vec![0u32; GG_WIDTH * GG_HEIGHT];
 
     println!("SMS Emulator started with ROM: {}", rom_path);
 
+    // I: panel con los datos de la cabecera del cartucho
+    let header = RomHeader::parse(&bus.rom);
+    let rom_name = std::path::Path::new(rom_path)
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or("?");
+    let info = match header {
+        Some(ref h) => h.info_lines(&bus.rom, rom_name),
+        None => vec![format!("TITLE: {}", rom_name), "NO TMR SEGA HEADER".into()],
+    };
+    println!("{}", info.join(" | "));
+    let mut show_info = false;
+    // Audio de cada frame; con la feature `audio` se reenvía al dispositivo
+    let mut audio: Vec<i16> = Vec::new();
+    #[cfg(feature = "audio")]
+    let mut audio_out = oxid_display::OxidAudio::new(SAMPLE_RATE, 1)
+        .map_err(|e| eprintln!("Audio disabled: {}", e))
+        .ok();
+
     while window.is_open() && !window.is_key_down(Key::Escape) {
         // Actualizar input al inicio del frame (más responsivo)
         let mut pad = 0xFFu8;
@@ -49,39 +181,77 @@ fn main() {
         if window.is_key_down(Key::X)     { pad &= !0x20; } // Button 2
         bus.joypad = pad;
 
-        for y in 0..262 {
-            // Execute cycles for one scanline: ~3.58MHz / 60 / 262 = ~228 cycles
-            let mut cycles_this_line = 0;
-            while cycles_this_line < 228 { 
-                cycles_this_line += cpu.step(&mut bus);
-                
-                // Emulate H-Counter (Approximate)
-                // 228 CPU cycles ~ 342 pixels. Map to 0-255 range linear is enough for Sonic RNG/Sync.
-                bus.h_counter = ((cycles_this_line * 255) / 228) as u8;
+        run_frame(&mut cpu, &mut bus, &timing, &mut frame_buffer);
+        audio.clear();
+        bus.render_audio(timing.samples_per_frame(), SAMPLE_RATE, &mut audio);
+        #[cfg(feature = "audio")]
+        if let Some(out) = audio_out.as_mut() {
+            out.push_samples(&audio);
+        }
+        bus.apply_cheats(&cheats);
+        if window.is_key_pressed(Key::I, KeyRepeat::No) {
+            show_info = !show_info;
+        }
+        // F1/F2: ocultar fondo/sprites (depuración de gráficos)
+        for (key, layer) in [(Key::F1, Layer::Background), (Key::F2, Layer::Sprites)] {
+            if window.is_key_pressed(key, KeyRepeat::No) {
+                let on = !bus.vdp.layer_enabled(layer);
+                bus.vdp.set_layer_enabled(layer, on);
+                println!("{:?}: {}", layer, if on { "on" } else { "off" });
             }
+        }
+        if show_info {
+            // Dentro de la ventana visible también en Game Gear
+            let (x, y) = if out_w == GG_WIDTH { ((WIDTH - GG_WIDTH) / 2 + 2, (HEIGHT - GG_HEIGHT) / 2 + 2) } else { (8, 8) };
+            draw_panel(&mut frame_buffer, WIDTH, x, y, &info, 0xFFFFFFFF, 0xFF000000);
+        }
 
-            if y < 192 {
-                let mut line_buf = [0u32; WIDTH];
-                bus.vdp.render_scanline(y, &mut line_buf);
-                for x in 0..WIDTH {
-                    frame_buffer[y * WIDTH + x] = line_buf[x];
-                }
-            }
+        if out_w == GG_WIDTH {
+            crop_gg_viewport(&frame_buffer, &mut gg_buffer);
+            window.update_with_buffer(&gg_buffer, GG_WIDTH, GG_HEIGHT).unwrap();
+        } else {
+            window.update_with_buffer(&frame_buffer, WIDTH, HEIGHT).unwrap();
+        }
+    }
+}
+
+// ============================================================================
+//  FRAME LOOP
+// ============================================================================
 
-            // V-Counter mapping for NTSC: 00-DA, then jumps to D5-FF
-            let v_cnt = if y <= 218 {
-                y as u8
-            } else {
-                (y as i32 - 6) as u8
-            };
-            bus.v_counter = v_cnt;
-
-            bus.vdp.tick_scanline(y);
-            if bus.vdp.is_interrupting() {
-                cpu.irq(&mut bus, 0xFF);
+/// Ejecuta un frame completo según `timing` y devuelve los ciclos de CPU consumidos.
+fn run_frame(cpu: &mut OxidZ80, bus: &mut MasterSystemBus, timing: &Timing, frame_buffer: &mut [u32]) -> u32 {
+    let mut total = 0;
+    for y in 0..timing.lines {
+        let mut cycles_this_line = 0;
+        while cycles_this_line < timing.cycles_per_line {
+            cycles_this_line += cpu.step(bus);
+            
+            // Emulate H-Counter (Approximate)
+            // Una línea ~ 342 pixels. Map to 0-255 range linear is enough for Sonic RNG/Sync.
+            bus.h_counter = ((cycles_this_line * 255) / timing.cycles_per_line) as u8;
+        }
+        total += cycles_this_line;
+
+        if y < 192 {
+            let mut line_buf = [0u32; WIDTH];
+            bus.vdp.render_scanline(y, &mut line_buf);
+            for x in 0..WIDTH {
+                frame_buffer[y * WIDTH + x] = line_buf[x];
             }
         }
 
-        window.update_with_buffer(&frame_buffer, WIDTH, HEIGHT).unwrap();
+        bus.vdp.tick_scanline(y);
+    }
+    bus.end_frame();
+    total
+}
+
+/// Segundos emulados por segundo real (1.0 = tiempo real justo).
+fn speed_factor(frames: u32, fps: f64, elapsed: Duration) -> f64 {
+    let real = elapsed.as_secs_f64();
+    if real <= 0.0 {
+        return f64::INFINITY;
     }
-}
\ No newline at end of file
+    frames as f64 / fps / real
+}
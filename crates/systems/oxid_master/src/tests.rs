@@ -0,0 +1,523 @@
+// crates/systems/oxid_master/src/tests.rs
+use crate::bus::{MasterSystemBus, Region};
+use crate::vdp::{crop_gg_viewport, Layer, Vdp, GG_HEIGHT, GG_WIDTH};
+use oxide_core::MemoryBus;
+
+/// Escribe un byte en CRAM a través de los puertos de control/datos.
+fn write_cram(vdp: &mut Vdp, index: u8, val: u8) {
+    vdp.write_control(index);
+    vdp.write_control(0xC0); // Code 3 = CRAM
+    vdp.write_data(val);
+}
+
+// ============================================================================
+// VDP - Paleta por línea
+// ============================================================================
+
+#[test]
+fn test_cram_write_between_lines_affects_next_line_only() {
+    let mut vdp = Vdp::new();
+    vdp.regs[7] = 0x00; // Backdrop = color 16
+    write_cram(&mut vdp, 16, 0x03); // Rojo
+
+    let mut line0 = [0u32; 256];
+    let mut line1 = [0u32; 256];
+    vdp.render_scanline(0, &mut line0);
+
+    write_cram(&mut vdp, 16, 0x30); // Azul
+    vdp.render_scanline(1, &mut line1);
+
+    assert!(line0.iter().all(|&p| p == 0xFFFF0000));
+    assert!(line1.iter().all(|&p| p == 0xFF0000FF));
+}
+
+// ============================================================================
+// Gafas 3D / Game Gear link port
+// ============================================================================
+
+#[test]
+fn test_3d_glasses_alternate_per_frame() {
+    use crate::{run_frame, Timing};
+    use oxide_core::Cpu;
+    use oxidz80::OxidZ80;
+
+    // LD A,1 ; LD ($FFF8),A ; JR $ (enciende las gafas una sola vez)
+    let mut rom = vec![0; 0x8000];
+    rom[..7].copy_from_slice(&[0x3E, 0x01, 0x32, 0xF8, 0xFF, 0x18, 0xFE]);
+    let mut bus = MasterSystemBus::new(rom);
+    let mut cpu = OxidZ80::new();
+    cpu.reset();
+    let mut frame = vec![0u32; 256 * 192];
+
+    assert!(!bus.glasses_in_use);
+    let mut eyes = Vec::new();
+    for _ in 0..4 {
+        run_frame(&mut cpu, &mut bus, &Timing::NTSC, &mut frame);
+        eyes.push(bus.glasses_right_eye);
+    }
+    assert_eq!(eyes, [false, true, false, true]);
+    assert_eq!(bus.paged_rom, [0, 0x4000, 0x8000]); // no toca el mapper
+
+    // Un write vuelve a fijar la fase
+    bus.write(0xFFF8, 1);
+    assert!(bus.glasses_right_eye);
+    bus.end_frame();
+    assert!(!bus.glasses_right_eye);
+}
+
+#[test]
+fn test_3d_glasses_idle_without_writes() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    bus.end_frame();
+    bus.end_frame();
+    assert!(!bus.glasses_right_eye && !bus.glasses_in_use);
+}
+
+#[test]
+fn test_gg_link_port_stub() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    assert_eq!(bus.port_in(0x01), 0xFF); // SMS: sin link port
+
+    bus.game_gear = true;
+    assert_eq!(bus.port_in(0x00) & 0x80, 0x80); // Start suelto
+    bus.port_out(0x01, 0x12);
+    assert_eq!(bus.port_in(0x01), 0x12);
+    bus.port_out(0x00, 0x00); // solo lectura
+    assert_eq!(bus.port_in(0x00), 0xC0);
+}
+
+#[test]
+fn test_setup_api_renders_known_display() {
+    let mut vdp = Vdp::new();
+    vdp.set_register(2, 0xFF); // Name table en $3800
+    vdp.set_register(7, 0x00); // Backdrop = color 16
+    vdp.write_cram_at(0, &[0x00, 0x03]); // 0 negro, 1 rojo
+    vdp.write_vram_at(32, &[0xFF, 0x00, 0x00, 0x00]); // tile 1, fila 0: color 1
+    vdp.write_vram_at(0x3800, &[0x01, 0x00]); // celda (0,0) -> tile 1
+
+    let mut line = [0u32; 256];
+    vdp.render_scanline(0, &mut line);
+    assert!(line[..8].iter().all(|&p| p == 0xFFFF0000));
+    assert!(line[8..16].iter().all(|&p| p == 0xFF000000));
+
+    // No altera el estado del protocolo de puertos
+    assert!(!vdp.address_latch);
+    assert_eq!(vdp.address, 0);
+
+    // Wrap de VRAM
+    vdp.write_vram_at(0x3FFF, &[0xAA, 0xBB]);
+    assert_eq!((vdp.vram[0x3FFF], vdp.vram[0]), (0xAA, 0xBB));
+}
+
+#[test]
+fn test_layer_toggles_hide_background_or_sprites() {
+    let mut vdp = Vdp::new();
+    vdp.set_register(2, 0xFF); // Name table en $3800
+    vdp.set_register(5, 0x7F); // SAT en $3F00
+    vdp.write_cram_at(0, &[0x00, 0x03]); // fondo: 1 rojo
+    vdp.write_cram_at(16, &[0x00, 0x0C]); // sprites: backdrop negro, 1 verde
+    vdp.write_vram_at(32, &[0xFF, 0x00, 0x00, 0x00]); // tile 1, fila 0: color 1
+    vdp.write_vram_at(0x3800, &[0x01, 0x00]); // celda (0,0) -> tile 1
+    vdp.write_vram_at(0x3F00, &[0xFF, 0xD0]); // sprite 0 en la línea 0
+    vdp.write_vram_at(0x3F80, &[16, 0x01]); // x=16, tile 1
+
+    let (red, green, black) = (0xFFFF0000, 0xFF00FF00, 0xFF000000);
+    let render = |vdp: &mut Vdp| {
+        let mut line = [0u32; 256];
+        vdp.render_scanline(0, &mut line);
+        (line[0], line[16])
+    };
+    assert_eq!(render(&mut vdp), (red, green));
+
+    vdp.set_layer_enabled(Layer::Sprites, false);
+    assert!(!vdp.layer_enabled(Layer::Sprites));
+    assert_eq!(render(&mut vdp), (red, black), "solo se compone el fondo");
+
+    vdp.set_layer_enabled(Layer::Sprites, true);
+    vdp.set_layer_enabled(Layer::Background, false);
+    assert_eq!(render(&mut vdp), (black, green));
+}
+
+// ============================================================================
+// VDP - Sprite overflow
+// ============================================================================
+
+#[test]
+fn test_sprite_overflow_reports_ninth_sprite_index() {
+    let mut vdp = Vdp::new();
+    vdp.regs[5] = 0x7F; // SAT en $3F00
+    let sat = 0x3F00;
+    // Sprites 0-1 fuera de la línea, 2-10 (nueve) en la línea 52
+    vdp.vram[sat] = 100;
+    vdp.vram[sat + 1] = 100;
+    for i in 2..=10 {
+        vdp.vram[sat + i] = 50;
+    }
+    vdp.vram[sat + 11] = 0xD0;
+
+    let mut line = [0u32; 256];
+    vdp.render_scanline(40, &mut line);
+    assert_eq!(vdp.read_status() & 0x40, 0);
+
+    vdp.render_scanline(52, &mut line);
+    let status = vdp.read_status();
+    assert_eq!(status & 0x40, 0x40);
+    assert_eq!(status & 0x1F, 10);
+}
+
+// ============================================================================
+// VDP - Viewer API
+// ============================================================================
+
+#[test]
+fn test_render_tile_decodes_planes() {
+    let mut vdp = Vdp::new();
+    // Tile 1: fila 0 usa el plano 0 en el píxel izquierdo (color 1),
+    // el resto de la fila con los planos 0+1 (color 3).
+    vdp.vram[32] = 0xFF;
+    vdp.vram[33] = 0x7F;
+    vdp.cram[0] = 0x00; // negro
+    vdp.cram[1] = 0x03; // rojo
+    vdp.cram[3] = 0x0C; // verde
+    vdp.cram[16 + 1] = 0x30; // azul (paleta de sprites)
+
+    let mut out = [0u32; 64];
+    vdp.render_tile(1, 0, &mut out);
+    assert_eq!(out[0], 0xFFFF0000);
+    assert!(out[1..8].iter().all(|&p| p == 0xFF00FF00));
+    assert!(out[8..].iter().all(|&p| p == 0xFF000000));
+
+    vdp.render_tile(1, 1, &mut out);
+    assert_eq!(out[0], 0xFF0000FF);
+
+    assert_eq!(vdp.dump_vram().len(), 0x4000);
+    assert_eq!(vdp.dump_cram()[1], 0x03);
+}
+
+// ============================================================================
+// BIOS + cartucho (puerto $3E)
+// ============================================================================
+
+#[test]
+fn test_bios_to_cartridge_handoff() {
+    let mut bios = vec![0u8; 0x2000];
+    bios[0] = 0xB1;
+    let mut cart = vec![0u8; 0x8000];
+    cart[0] = 0xCA;
+
+    let mut bus = MasterSystemBus::with_bios(cart, bios);
+    assert_eq!(bus.read(0x0000), 0xB1); // arranca en la BIOS
+
+    bus.port_out(0x3E, 0xA8); // cartucho on, BIOS off
+    assert_eq!(bus.read(0x0000), 0xCA);
+
+    bus.port_out(0x3E, 0xE8); // ambos off
+    assert_eq!(bus.read(0x0000), 0xFF);
+
+    bus.port_out(0x3E, 0xE0); // de vuelta a la BIOS
+    assert_eq!(bus.read(0x0000), 0xB1);
+}
+
+#[test]
+fn test_non_power_of_two_rom_reads_open_bus_past_end() {
+    // 48KB: la máscara cubre 64KB, el último banco no existe
+    let mut rom = vec![0u8; 0xC000];
+    rom[0xBFFF] = 0x5A;
+    let mut bus = MasterSystemBus::new(rom);
+
+    bus.write(0xFFFF, 0x02); // slot 2 -> banco 2 ($8000-$BFFF)
+    assert_eq!(bus.read(0xBFFF), 0x5A);
+
+    bus.write(0xFFFF, 0x03); // banco 3: fuera de la imagen
+    assert_eq!(bus.read(0x8000), 0xFF);
+    assert_eq!(bus.read(0xBFFF), 0xFF);
+
+    // Imagen vacía/diminuta tampoco revienta
+    let tiny = MasterSystemBus::new(vec![0x11; 3]);
+    assert_eq!(tiny.read(0x0002), 0x11);
+    assert_eq!(tiny.read(0x0003), 0xFF);
+    assert_eq!(tiny.read(0x7FFF), 0xFF);
+}
+
+#[test]
+fn test_mem_control_disables_io_and_ram() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    bus.joypad = 0xFE; // Arriba pulsado
+    bus.write(0xC000, 0x42);
+    assert_eq!(bus.port_in(0xDC), 0xFE);
+
+    bus.port_out(0x3E, 0xAB | 0x04); // I/O off
+    assert_eq!(bus.port_in(0xDC), 0xFF);
+    assert_eq!(bus.read(0xC000), 0x42);
+
+    bus.port_out(0x3E, 0xAB | 0x10); // RAM off
+    assert_eq!(bus.read(0xC000), 0xFF);
+    bus.write(0xC000, 0x99); // ignorado
+    bus.write(0xFFFF, 0x01); // el mapper sigue respondiendo
+    assert_eq!(bus.paged_rom[2], 0x4000);
+
+    bus.port_out(0x3E, 0xAB);
+    assert_eq!(bus.read(0xC000), 0x42);
+    assert_eq!(bus.port_in(0xDC), 0xFE);
+}
+
+// ============================================================================
+// VDP -> Z80: línea INT
+// ============================================================================
+
+#[test]
+fn test_vdp_int_line_held_until_status_read() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    bus.vdp.regs[1] = 0x20; // Frame interrupt habilitada
+    assert!(!bus.int_asserted());
+
+    bus.vdp.tick_scanline(192);
+    assert!(bus.int_asserted());
+    bus.vdp.tick_scanline(193);
+    assert!(bus.int_asserted()); // sigue activa sin leer estado
+
+    let status = bus.port_in(0xBF);
+    assert_eq!(status & 0x80, 0x80);
+    assert!(!bus.int_asserted());
+}
+
+#[test]
+fn test_vdp_int_reaches_z80_without_frontend_polling() {
+    use oxide_core::Cpu;
+    use oxidz80::OxidZ80;
+
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    let mut cpu = OxidZ80::new();
+    cpu.sp = 0xDFF0;
+    cpu.im = 1;
+    cpu.iff1 = true;
+    bus.vdp.regs[1] = 0x20;
+    bus.vdp.tick_scanline(192);
+
+    cpu.step(&mut bus);
+    assert_eq!(cpu.pc, 0x0038);
+    assert!(!cpu.iff1);
+}
+
+// ============================================================================
+// Game Gear: ventana visible
+// ============================================================================
+
+#[test]
+fn test_gg_viewport_crops_centered_160x144() {
+    // Cada píxel codifica su posición: (y << 16) | x
+    let frame: Vec<u32> = (0..192u32)
+        .flat_map(|y| (0..256u32).map(move |x| (y << 16) | x))
+        .collect();
+    let mut gg = vec![0u32; GG_WIDTH * GG_HEIGHT];
+    crop_gg_viewport(&frame, &mut gg);
+
+    assert_eq!(gg[0], (24 << 16) | 48);
+    assert_eq!(gg[GG_WIDTH - 1], (24 << 16) | 207);
+    assert_eq!(gg[(GG_HEIGHT - 1) * GG_WIDTH], (167 << 16) | 48);
+    assert_eq!(*gg.last().unwrap(), (167 << 16) | 207);
+}
+
+// ============================================================================
+// Región forzada (TH en $3F/$DD)
+// ============================================================================
+
+#[test]
+fn test_region_detection_reads_forced_value() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+
+    // Chequeo clásico: TH como salida a 1 ($F5) y a 0 ($55)
+    bus.port_out(0x3F, 0xF5);
+    assert_eq!(bus.port_in(0xDD) & 0xC0, 0xC0);
+    bus.port_out(0x3F, 0x55);
+    assert_eq!(bus.port_in(0xDD) & 0xC0, 0x00);
+
+    bus.region = Region::Japan;
+    bus.port_out(0x3F, 0xF5);
+    assert_eq!(bus.port_in(0xDD) & 0xC0, 0x00);
+    bus.port_out(0x3F, 0x55);
+    assert_eq!(bus.port_in(0xDD) & 0xC0, 0xC0);
+
+    // TH como entrada: se lee el pull-up del joypad
+    bus.port_out(0x3F, 0xFF);
+    assert_eq!(bus.port_in(0xDD) & 0xC0, 0xC0);
+}
+
+#[test]
+fn test_gg_region_bit_follows_forced_region() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    bus.game_gear = true;
+    assert_eq!(bus.port_in(0x00) & 0x40, 0x40);
+    bus.region = Region::Japan;
+    assert_eq!(bus.port_in(0x00) & 0x40, 0x00);
+}
+
+// ============================================================================
+// Frame loop / benchmark
+// ============================================================================
+
+#[test]
+fn test_run_frame_consumes_a_full_ntsc_frame() {
+    use crate::run_frame;
+    use oxide_core::Cpu;
+    use oxidz80::OxidZ80;
+
+    // Cada línea corre hasta 228 ciclos y se pasa como mucho una instrucción (≤23)
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    let mut cpu = OxidZ80::new();
+    cpu.reset();
+    let mut frame = vec![0u32; 256 * 192];
+    let cycles = run_frame(&mut cpu, &mut bus, &crate::Timing::NTSC, &mut frame);
+    assert!(cycles >= 262 * 228);
+    assert!(cycles < 262 * (228 + 23));
+}
+
+#[test]
+fn test_speed_factor() {
+    use crate::speed_factor;
+    use std::time::Duration;
+
+    assert_eq!(speed_factor(600, 60.0, Duration::from_secs(5)), 2.0);
+    assert_eq!(speed_factor(60, 60.0, Duration::from_secs(1)), 1.0);
+    assert!(speed_factor(1, 60.0, Duration::ZERO).is_infinite());
+}
+
+#[test]
+fn test_timing_cycles_per_frame() {
+    use crate::Timing;
+
+    // Los valores que estaban fijos en el bucle NTSC: 228 x 262
+    assert_eq!(Timing::NTSC.cycles_per_frame(), 228 * 262);
+    assert_eq!(Timing::PAL.cycles_per_frame(), 228 * 313);
+    assert_eq!(Timing::NTSC.overclock(2.0).cycles_per_frame(), 456 * 262);
+    assert_eq!(Timing::NTSC.overclock(2.0).lines, 262);
+}
+
+// ============================================================================
+// VDP - V-Counter ($7E)
+// ============================================================================
+
+#[test]
+fn test_v_counter_ntsc_across_frame() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    let mut seen = Vec::new();
+    for y in 0..262 {
+        seen.push(bus.port_in(0x7E));
+        bus.vdp.tick_scanline(y);
+    }
+    assert_eq!(seen[0], 0x00);
+    assert_eq!(seen[192], 0xC0);
+    assert_eq!(seen[218], 0xDA);
+    assert_eq!(seen[219], 0xD5); // salto: D5-DA se repiten
+    assert_eq!(seen[261], 0xFF);
+    assert_eq!(seen.iter().filter(|&&v| v == 0xD5).count(), 2);
+    // Vuelve a 0 al empezar el frame siguiente
+    assert_eq!(bus.port_in(0x7E), 0x00);
+}
+
+#[test]
+fn test_v_counter_pal_table() {
+    use crate::vdp::{VCOUNT_PAL, PAL_LINES};
+
+    assert_eq!(VCOUNT_PAL[0xF2], 0xF2);
+    assert_eq!(VCOUNT_PAL[0xF3], 0xBA);
+    assert_eq!(VCOUNT_PAL[PAL_LINES - 1], 0xFF);
+
+    let mut vdp = Vdp::new();
+    vdp.pal = true;
+    for y in 0..0xF3 {
+        vdp.tick_scanline(y);
+    }
+    assert_eq!(vdp.v_counter(), 0xBA);
+}
+
+// ============================================================================
+// Cabecera TMR SEGA
+// ============================================================================
+
+/// ROM de 32KB con cabecera en $7FF0 y checksum correcto.
+fn rom_with_header(region_size: u8) -> Vec<u8> {
+    let mut rom: Vec<u8> = (0..0x8000).map(|i| (i * 7) as u8).collect();
+    rom[0x7FF0..0x7FF8].copy_from_slice(b"TMR SEGA");
+    let sum = rom[..0x7FF0].iter().fold(0u16, |a, &b| a.wrapping_add(b as u16));
+    rom[0x7FFA..0x7FFC].copy_from_slice(&sum.to_le_bytes());
+    rom[0x7FFC] = 0x26; // producto 7026
+    rom[0x7FFD] = 0x70;
+    rom[0x7FFE] = 0x01; // versión 1
+    rom[0x7FFF] = region_size;
+    rom
+}
+
+#[test]
+fn test_header_parse_and_checksum() {
+    use crate::header::RomHeader;
+
+    let mut rom = rom_with_header(0x4C); // SMS Export, 32KB
+    let h = RomHeader::parse(&rom).unwrap();
+    assert_eq!(h.offset, 0x7FF0);
+    assert_eq!((h.product, h.version), (7026, 1));
+    assert_eq!((h.system_name(), h.region_name()), ("Master System", "Export"));
+    assert_eq!(h.checksum_ok(&rom), Some(true));
+    assert!(h.info_lines(&rom, "sonic")[1].ends_with("sonic"));
+
+    rom[0x1234] ^= 0xFF;
+    assert_eq!(h.checksum_ok(&rom), Some(false));
+    // Declara 64KB pero solo hay 32KB
+    let big = RomHeader::parse(&rom_with_header(0x4E)).unwrap();
+    assert_eq!(big.checksum_ok(&rom), None);
+
+    assert!(RomHeader::parse(&[0u8; 0x8000]).is_none());
+}
+
+#[test]
+fn test_header_game_gear_and_sdsc_title() {
+    use crate::header::RomHeader;
+
+    let mut rom = rom_with_header(0x6C);
+    rom[0x7FE0..0x7FE4].copy_from_slice(b"SDSC");
+    rom[0x7FEC..0x7FEE].copy_from_slice(&0x7F00u16.to_le_bytes());
+    rom[0x7F00..0x7F06].copy_from_slice(b"Demo\0x");
+    let h = RomHeader::parse(&rom).unwrap();
+    assert!(h.is_game_gear());
+    assert_eq!(h.title.as_deref(), Some("Demo"));
+}
+
+// ============================================================================
+// PSG (SN76489)
+// ============================================================================
+
+#[test]
+fn test_psg_latch_and_data_decode_tone_period() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    bus.port_out(0x7F, 0x8E); // latch tono canal 0, 4 bits bajos = E
+    bus.port_out(0x7F, 0x0F); // dato: 6 bits altos
+    bus.port_out(0x7E, 0xA5); // latch tono canal 1 (puerto espejo), bits bajos = 5
+    bus.port_out(0x7F, 0x3F); // dato: 6 bits altos
+    bus.port_out(0x7F, 0xCF); // latch tono canal 2 sin dato: solo cambian los bits bajos
+    bus.port_out(0x7F, 0x90); // volumen canal 0 = máximo
+    bus.port_out(0x7F, 0xE5); // ruido: blanco, periodo 0x20
+    assert_eq!(bus.psg.tone, [0x0FE, 0x3F5, 0x00F]);
+    assert_eq!(bus.psg.volume, [0, 0x0F, 0x0F, 0x0F]);
+    assert_eq!(bus.psg.noise, 0x05);
+
+    // Un dato sin latch nuevo sigue yendo al último registro latcheado (ruido)
+    bus.port_out(0x7F, 0x03);
+    assert_eq!(bus.psg.noise, 0x03);
+    assert_eq!(bus.psg.tone[2], 0x00F);
+}
+
+#[test]
+fn test_psg_renders_square_wave_to_sink() {
+    let mut bus = MasterSystemBus::new(vec![0; 0x8000]);
+    let mut silent = Vec::new();
+    bus.render_audio(256, 44_100, &mut silent);
+    assert!(silent.iter().all(|&s| s == 0), "atenuación 15 en todos los canales");
+
+    bus.port_out(0x7F, 0x8E);
+    bus.port_out(0x7F, 0x0F);
+    bus.port_out(0x7F, 0x90);
+    let mut out = Vec::new();
+    bus.render_audio(735, 44_100, &mut out);
+    assert_eq!(out.len(), 735);
+    assert!(out.iter().any(|&s| s > 1000) && out.iter().any(|&s| s < -1000));
+}
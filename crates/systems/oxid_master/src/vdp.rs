@@ -11,6 +11,62 @@ const STATUS_VBLANK: u8    = 0x80; // Frame Interrupt Pending
 const STATUS_OVERFLOW: u8  = 0x40; // Sprite Overflow (> 8 sprites per line)
 const STATUS_COLLISION: u8 = 0x20; // Sprite Collision
 
+// Ventana visible de la Game Gear, centrada dentro del frame SMS de 256x192
+pub const GG_WIDTH: usize = 160;
+pub const GG_HEIGHT: usize = 144;
+const GG_X: usize = (FRAME_WIDTH - GG_WIDTH) / 2;
+const GG_Y: usize = (192 - GG_HEIGHT) / 2;
+
+/// Recorta la ventana de la Game Gear (160x144) de un frame SMS completo (256x192).
+pub fn crop_gg_viewport(frame: &[u32], out: &mut [u32]) {
+    for (y, row) in out.chunks_exact_mut(GG_WIDTH).take(GG_HEIGHT).enumerate() {
+        let src = (y + GG_Y) * FRAME_WIDTH + GG_X;
+        row.copy_from_slice(&frame[src..src + GG_WIDTH]);
+    }
+}
+
+// V-Counter (puerto $7E) en modo 192 líneas. No es lineal: tras la última línea
+// "normal" salta hacia atrás y repite valores hasta llegar a $FF.
+pub const NTSC_LINES: usize = 262;
+pub const PAL_LINES: usize = 313;
+/// NTSC: $00-$DA, $D5-$FF
+pub const VCOUNT_NTSC: [u8; NTSC_LINES] = vcount_table(0xDA, 0xD5);
+/// PAL: $00-$F2, $BA-$FF
+pub const VCOUNT_PAL: [u8; PAL_LINES] = vcount_table(0xF2, 0xBA);
+
+const fn vcount_table<const N: usize>(last: u8, jump_to: u8) -> [u8; N] {
+    let mut t = [0u8; N];
+    let mut v = 0u8;
+    let mut jumped = false;
+    let mut i = 0;
+    while i < N {
+        t[i] = v;
+        if v == last && !jumped {
+            v = jump_to;
+            jumped = true;
+        } else {
+            v = v.wrapping_add(1);
+        }
+        i += 1;
+    }
+    t
+}
+
+/// Convierte una entrada de CRAM (--BBGGRR) a ARGB 0xFFRRGGBB.
+fn cram_to_rgb(val: u8) -> u32 {
+    let r = (val & 0x03) as u32 * 85;
+    let g = ((val >> 2) & 0x03) as u32 * 85;
+    let b = ((val >> 4) & 0x03) as u32 * 85;
+    0xFF000000 | (r << 16) | (g << 8) | b
+}
+
+/// Capas que se pueden ocultar para depurar (`set_layer_enabled`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Background,
+    Sprites,
+}
+
 /// Implementación del SMS VDP (Video Display Processor).
 /// Basado en el TMS9918a pero con extensiones de Sega (Modo 4).
 pub struct Vdp {
@@ -29,6 +85,14 @@ pub struct Vdp {
     // Contadores de Interrupción
     pub line_counter: u8,       // Reg 10 Down Counter
     pub interrupt_pending: bool,// Line Interrupt Request
+
+    // Temporización
+    pub pal: bool,   // 313 líneas en vez de 262
+    pub line: usize, // Scanline en curso (la que ve el V-Counter)
+
+    // Depuración: capas visibles (no afecta a colisión/overflow)
+    show_background: bool,
+    show_sprites: bool,
 }
 
 impl Vdp {
@@ -44,9 +108,40 @@ impl Vdp {
             address_latch: false,
             line_counter: 0,
             interrupt_pending: false,
+            pal: false,
+            line: 0,
+            show_background: true,
+            show_sprites: true,
         }
     }
 
+    /// Oculta o muestra una capa en la composición. Los sprites ocultos se siguen
+    /// evaluando, así que los flags de colisión y overflow no cambian.
+    pub fn set_layer_enabled(&mut self, layer: Layer, on: bool) {
+        match layer {
+            Layer::Background => self.show_background = on,
+            Layer::Sprites => self.show_sprites = on,
+        }
+    }
+
+    pub fn layer_enabled(&self, layer: Layer) -> bool {
+        match layer {
+            Layer::Background => self.show_background,
+            Layer::Sprites => self.show_sprites,
+        }
+    }
+
+    /// Líneas por frame según el estándar de TV.
+    pub fn lines(&self) -> usize {
+        if self.pal { PAL_LINES } else { NTSC_LINES }
+    }
+
+    /// Valor del V-Counter para la línea en curso.
+    pub fn v_counter(&self) -> u8 {
+        let table: &[u8] = if self.pal { &VCOUNT_PAL } else { &VCOUNT_NTSC };
+        table[self.line % table.len()]
+    }
+
     /// Ejecuta la lógica al final de una scanline.
     /// Maneja el Line Counter y la bandera de VBlank.
     pub fn tick_scanline(&mut self, y: usize) {
@@ -70,9 +165,12 @@ impl Vdp {
         if y == 192 {
             self.status |= STATUS_VBLANK;
         }
+
+        self.line = if y + 1 >= self.lines() { 0 } else { y + 1 };
     }
 
-    /// Verifica si hay alguna interrupción pendiente (IRQ) hacia el Z80.
+    /// Nivel de la línea INT del VDP hacia el Z80 (VBlank y/o interrupción de línea).
+    /// Se mantiene activa hasta que se lee el registro de estado ($BF).
     pub fn is_interrupting(&self) -> bool {
         // Frame Interrupt (VBlank): Habilitado si Reg 1 Bit 5 está activo
         let vblank_irq = (self.status & STATUS_VBLANK) != 0 && (self.regs[1] & 0x20) != 0;
@@ -173,9 +271,13 @@ impl Vdp {
     }
 
     /// Renderiza una línea de scanline (0-191).
+    /// La CRAM se captura una sola vez al inicio: toda la línea usa la misma paleta,
+    /// y una escritura a CRAM entre dos llamadas solo afecta a la línea siguiente.
     pub fn render_scanline(&mut self, y: usize, line_buffer: &mut [u32]) {
         if y >= 192 { return; }
 
+        let palette = self.cram;
+
         let mut bg_buffer = [(0u8, false); FRAME_WIDTH]; // (color_idx, priority_bit)
         let mut spr_buffer = [(0u8, 0u8); FRAME_WIDTH];   // (color_idx, sprite_index) - index not strictly needed for color, but debugging
 
@@ -185,6 +287,14 @@ impl Vdp {
         // 2. Render Sprites
         self.render_sprites(y, &mut spr_buffer);
 
+        // Capas ocultas (depuración): transparentes en la composición
+        if !self.show_background {
+            bg_buffer.fill((0, false));
+        }
+        if !self.show_sprites {
+            spr_buffer.fill((0, 0));
+        }
+
         // 3. Composition
         let backdrop_color_idx = (self.regs[7] & 0x0F) + 16; // Backdrop uses Sprite Palette? No, Reg 7 lower nibble. 
         // Docs: "Background color register... bits 0-3 select color from sub-palette 2 (sprite palette)" -> +16.
@@ -226,12 +336,7 @@ impl Vdp {
             };
 
             // Palette Lookup
-            let val = self.cram[(final_idx & 0x1F) as usize];
-            let r = (val & 0x03) * 85;
-            let g = ((val >> 2) & 0x03) * 85;
-            let b = ((val >> 4) & 0x03) * 85;
-            
-            line_buffer[x] = 0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
+            line_buffer[x] = cram_to_rgb(palette[(final_idx & 0x1F) as usize]);
         }
     }
 
@@ -246,7 +351,7 @@ impl Vdp {
 
         // Mask Column 0 is handled in composition, but we render fully here.
 
-        for x in 0..FRAME_WIDTH {
+        for (x, cell) in buffer.iter_mut().enumerate().take(FRAME_WIDTH) {
             let cur_scroll_x = if h_scroll_inh { 0 } else { scroll_x };
             let cur_scroll_y = if v_scroll_inh && x >= 192 { 0 } else { scroll_y };
 
@@ -274,7 +379,7 @@ impl Vdp {
             let py = if v_flip { 7 - (bg_y % 8) } else { bg_y % 8 };
             let px = if h_flip { 7 - (bg_x % 8) } else { bg_x % 8 };
 
-            let tile_addr = (tile_idx as usize * 32) + (py as usize * 4);
+            let tile_addr = (tile_idx as usize * 32) + (py * 4);
             // Optimization: read 4 bytes at once? No, vram is u8 array.
             
             let b0 = self.vram[tile_addr];
@@ -284,14 +389,14 @@ impl Vdp {
 
             let shift = 7 - px;
             let color_val = 
-                (((b0 >> shift) & 1) << 0) |
+                ((b0 >> shift) & 1) |
                 (((b1 >> shift) & 1) << 1) |
                 (((b2 >> shift) & 1) << 2) |
                 (((b3 >> shift) & 1) << 3);
 
             let final_idx = if palette_sel { 16 + color_val } else { color_val };
             
-            buffer[x] = (final_idx, priority);
+            *cell = (final_idx, priority);
         }
     }
 
@@ -319,7 +424,10 @@ impl Vdp {
             let line_y = y as i32;
             if line_y >= sy && line_y < (sy + sprite_height) {
                 if sprites_drawn >= 8 {
-                    self.status |= STATUS_OVERFLOW;
+                    // Bits 0-4: índice del sprite que desbordó (solo el primero hasta leer el estado)
+                    if self.status & STATUS_OVERFLOW == 0 {
+                        self.status = (self.status & !0x1F) | STATUS_OVERFLOW | (i as u8 & 0x1F);
+                    }
                     break; 
                 }
                 
@@ -342,7 +450,7 @@ impl Vdp {
 
                 for px in 0..8 {
                     let screen_x = sx + px;
-                    if screen_x < 0 || screen_x >= 256 { continue; }
+                    if !(0..256).contains(&screen_x) { continue; }
                     let screen_x_u = screen_x as usize;
 
                     // Already drawn a sprite here? SMS shows first sprite in list.
@@ -354,7 +462,7 @@ impl Vdp {
                         // Collision flag is set when two non-transparent sprite pixels overlap.
                         let shift = 7 - px;
                         let color_val = 
-                             (((b0 >> shift) & 1) << 0) |
+                             ((b0 >> shift) & 1) |
                              (((b1 >> shift) & 1) << 1) |
                              (((b2 >> shift) & 1) << 2) |
                              (((b3 >> shift) & 1) << 3);
@@ -367,7 +475,7 @@ impl Vdp {
 
                     let shift = 7 - px;
                     let color_val = 
-                        (((b0 >> shift) & 1) << 0) |
+                        ((b0 >> shift) & 1) |
                         (((b1 >> shift) & 1) << 1) |
                         (((b2 >> shift) & 1) << 2) |
                         (((b3 >> shift) & 1) << 3);
@@ -381,3 +489,65 @@ impl Vdp {
         }
     }
 }
+
+// ============================================================================
+//  VIEWER API (solo lectura, para herramientas de depuración)
+// ============================================================================
+
+#[allow(dead_code)] // Consumida por herramientas externas y tests
+impl Vdp {
+    /// Contenido completo de la VRAM (16KB).
+    pub fn dump_vram(&self) -> &[u8] {
+        &self.vram
+    }
+
+    /// Contenido completo de la CRAM (32 entradas).
+    pub fn dump_cram(&self) -> &[u8] {
+        &self.cram
+    }
+
+    /// Decodifica el tile `index` (0-511) a 8x8 píxeles RGB en `out` (64 entradas, fila a fila).
+    /// `palette` 0 = paleta de fondo, 1 = paleta de sprites.
+    pub fn render_tile(&self, index: usize, palette: usize, out: &mut [u32]) {
+        let base = (index & 0x1FF) * 32;
+        let pal_off = if palette != 0 { 16 } else { 0 };
+        for py in 0..8 {
+            let row = base + py * 4;
+            let (b0, b1, b2, b3) = (self.vram[row], self.vram[row + 1], self.vram[row + 2], self.vram[row + 3]);
+            for px in 0..8 {
+                let shift = 7 - px;
+                let color_val = ((b0 >> shift) & 1)
+                    | (((b1 >> shift) & 1) << 1)
+                    | (((b2 >> shift) & 1) << 2)
+                    | (((b3 >> shift) & 1) << 3);
+                out[py * 8 + px] = cram_to_rgb(self.cram[pal_off + color_val as usize]);
+            }
+        }
+    }
+}
+
+// ============================================================================
+//  SETUP API (tests y herramientas; en ejecución manda el protocolo de puertos)
+// ============================================================================
+
+#[allow(dead_code)] // Consumida por herramientas externas y tests
+impl Vdp {
+    /// Escribe un registro sin pasar por el latch del puerto de control.
+    pub fn set_register(&mut self, index: u8, value: u8) {
+        self.regs[(index & 0x0F) as usize] = value;
+    }
+
+    /// Copia `data` a la VRAM desde `addr` (con wrap en 16KB). No toca dirección ni latch.
+    pub fn write_vram_at(&mut self, addr: u16, data: &[u8]) {
+        for (i, &b) in data.iter().enumerate() {
+            self.vram[(addr as usize + i) & 0x3FFF] = b;
+        }
+    }
+
+    /// Copia `data` a la CRAM desde la entrada `index` (con wrap en 32).
+    pub fn write_cram_at(&mut self, index: u8, data: &[u8]) {
+        for (i, &b) in data.iter().enumerate() {
+            self.cram[(index as usize + i) & 0x1F] = b;
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// crates/systems/oxid_spec/src/debug.rs
+//
+// Modo headless con servidor de depuración (feature `debug-server`): la máquina
+// solo avanza cuando el cliente manda `step`/`continue`.
+use crate::{SpectrumBus, CYCLES_PER_FRAME, ULA_INT_LENGTH};
+use oxide_core::debug_server::{DebugServer, DebugTarget};
+use oxide_core::{Cpu, MemoryBus};
+use oxidz80::OxidZ80;
+
+pub struct SpectrumTarget {
+    pub cpu: OxidZ80,
+    pub bus: SpectrumBus,
+    /// T-states dentro del frame actual (pulso INT de la ULA)
+    frame_cycles: u32,
+}
+
+impl SpectrumTarget {
+    pub fn new(cpu: OxidZ80, bus: SpectrumBus) -> Self {
+        Self { cpu, bus, frame_cycles: 0 }
+    }
+}
+
+impl DebugTarget for SpectrumTarget {
+    fn registers(&self) -> Vec<(&'static str, u32)> {
+        let c = &self.cpu;
+        vec![
+            ("PC", c.pc as u32), ("SP", c.sp as u32),
+            ("AF", c.af() as u32), ("BC", c.bc() as u32),
+            ("DE", c.de() as u32), ("HL", c.hl() as u32),
+            ("IX", c.ix as u32), ("IY", c.iy as u32),
+            ("I", c.i as u32), ("R", c.r as u32),
+        ]
+    }
+
+    fn pc(&self) -> u32 {
+        self.cpu.pc as u32
+    }
+
+    fn read_mem(&self, addr: u32) -> u8 {
+        self.bus.read(addr)
+    }
+
+    fn write_mem(&mut self, addr: u32, val: u8) {
+        self.bus.write(addr, val);
+    }
+
+    /// Mismo reparto de INT que `run_frame`, instrucción a instrucción.
+    fn step(&mut self) -> u32 {
+        if self.frame_cycles == 0 {
+            self.bus.flash_frame = self.bus.flash_frame.wrapping_add(1);
+        }
+        self.cpu.assert_int(self.frame_cycles < ULA_INT_LENGTH);
+        self.bus.frame_cycles = self.frame_cycles;
+        crate::tape_trap(&mut self.cpu, &mut self.bus);
+        let cycles = self.cpu.step(&mut self.bus);
+        self.frame_cycles += cycles;
+        if self.frame_cycles >= CYCLES_PER_FRAME {
+            self.frame_cycles = 0;
+            // Headless: no hay salida de audio que consuma el beeper
+            self.bus.beeper_edges.clear();
+        }
+        cycles
+    }
+
+    fn call_return(&self) -> Option<u32> {
+        self.cpu.call_return_address(&self.bus).map(u32::from)
+    }
+}
+
+/// Bloquea atendiendo clientes en `127.0.0.1:<port>` hasta `quit`.
+pub fn run(cpu: OxidZ80, bus: SpectrumBus, port: u16) -> std::io::Result<()> {
+    let addr = format!("127.0.0.1:{}", port);
+    println!("Debug server listening on {}", addr);
+    let mut target = SpectrumTarget::new(cpu, bus);
+    DebugServer::new().listen(&mut target, &addr)
+}
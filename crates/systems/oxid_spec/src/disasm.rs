@@ -1,6 +1,8 @@
-use oxide_core::MemoryBus;
+use oxide_core::{MemoryBus, SymbolTable};
 
-pub fn disassemble(pc: u16, bus: &dyn MemoryBus) -> (String, u16) {
+/// (mnemónico, longitud). Saltos, llamadas y accesos `(nn)` salen con su nombre si
+/// está en `symbols`.
+pub fn disassemble(pc: u16, bus: &dyn MemoryBus, symbols: &SymbolTable) -> (String, u16) {
     let b0 = bus.read(pc as u32);
     let mut len = 1;
 
@@ -22,35 +24,35 @@ pub fn disassemble(pc: u16, bus: &dyn MemoryBus) -> (String, u16) {
         0x0E => { len=2; format!("LD C, ${:02X}", bus.read((pc+1) as u32)) }
         0x0F => "RRCA".into(),
         
-        0x10 => { len=2; format!("DJNZ ${:04X}", rel(pc, bus)) }
+        0x10 => { len=2; format!("DJNZ {}", label(rel(pc, bus), symbols)) }
         0x11 => { len=3; format!("LD DE, ${:04X}", read16(pc+1, bus)) }
         0x12 => "LD (DE), A".into(),
         0x13 => "INC DE".into(),
         0x17 => "RLA".into(),
-        0x18 => { len=2; format!("JR ${:04X}", rel(pc, bus)) }
+        0x18 => { len=2; format!("JR {}", label(rel(pc, bus), symbols)) }
         0x19 => "ADD HL, DE".into(),
         0x1A => "LD A, (DE)".into(),
         0x1B => "DEC DE".into(),
 
-        0x20 => { len=2; format!("JR NZ, ${:04X}", rel(pc, bus)) }
+        0x20 => { len=2; format!("JR NZ, {}", label(rel(pc, bus), symbols)) }
         0x21 => { len=3; format!("LD HL, ${:04X}", read16(pc+1, bus)) }
-        0x22 => { len=3; format!("LD (${:04X}), HL", read16(pc+1, bus)) }
+        0x22 => { len=3; format!("LD ({}), HL", label(read16(pc+1, bus), symbols)) }
         0x23 => "INC HL".into(),
         0x27 => "DAA".into(),
-        0x28 => { len=2; format!("JR Z, ${:04X}", rel(pc, bus)) }
+        0x28 => { len=2; format!("JR Z, {}", label(rel(pc, bus), symbols)) }
         0x29 => "ADD HL, HL".into(),
-        0x2A => { len=3; format!("LD HL, (${:04X})", read16(pc+1, bus)) }
+        0x2A => { len=3; format!("LD HL, ({})", label(read16(pc+1, bus), symbols)) }
         0x2F => "CPL".into(),
 
-        0x30 => { len=2; format!("JR NC, ${:04X}", rel(pc, bus)) }
+        0x30 => { len=2; format!("JR NC, {}", label(rel(pc, bus), symbols)) }
         0x31 => { len=3; format!("LD SP, ${:04X}", read16(pc+1, bus)) }
-        0x32 => { len=3; format!("LD (${:04X}), A", read16(pc+1, bus)) }
+        0x32 => { len=3; format!("LD ({}), A", label(read16(pc+1, bus), symbols)) }
         0x33 => "INC SP".into(),
         0x36 => { len=2; format!("LD (HL), ${:02X}", bus.read((pc+1) as u32)) }
         0x37 => "SCF".into(),
-        0x38 => { len=2; format!("JR C, ${:04X}", rel(pc, bus)) }
+        0x38 => { len=2; format!("JR C, {}", label(rel(pc, bus), symbols)) }
         0x39 => "ADD HL, SP".into(),
-        0x3A => { len=3; format!("LD A, (${:04X})", read16(pc+1, bus)) }
+        0x3A => { len=3; format!("LD A, ({})", label(read16(pc+1, bus), symbols)) }
         0x3B => "DEC SP".into(),
         0x3C => "INC A".into(),
         0x3D => "DEC A".into(),
@@ -75,15 +77,15 @@ pub fn disassemble(pc: u16, bus: &dyn MemoryBus) -> (String, u16) {
 
         0xC0 => "RET NZ".into(),
         0xC1 => "POP BC".into(),
-        0xC2 => { len=3; format!("JP NZ, ${:04X}", read16(pc+1, bus)) }
-        0xC3 => { len=3; format!("JP ${:04X}", read16(pc+1, bus)) }
-        0xC4 => { len=3; format!("CALL NZ, ${:04X}", read16(pc+1, bus)) }
+        0xC2 => { len=3; format!("JP NZ, {}", label(read16(pc+1, bus), symbols)) }
+        0xC3 => { len=3; format!("JP {}", label(read16(pc+1, bus), symbols)) }
+        0xC4 => { len=3; format!("CALL NZ, {}", label(read16(pc+1, bus), symbols)) }
         0xC5 => "PUSH BC".into(),
         0xC8 => "RET Z".into(),
         0xC9 => "RET".into(),
-        0xCA => { len=3; format!("JP Z, ${:04X}", read16(pc+1, bus)) }
-        0xCC => { len=3; format!("CALL Z, ${:04X}", read16(pc+1, bus)) }
-        0xCD => { len=3; format!("CALL ${:04X}", read16(pc+1, bus)) }
+        0xCA => { len=3; format!("JP Z, {}", label(read16(pc+1, bus), symbols)) }
+        0xCC => { len=3; format!("CALL Z, {}", label(read16(pc+1, bus), symbols)) }
+        0xCD => { len=3; format!("CALL {}", label(read16(pc+1, bus), symbols)) }
 
         0xD0 => "RET NC".into(),
         0xD1 => "POP DE".into(),
@@ -118,12 +120,12 @@ pub fn disassemble(pc: u16, bus: &dyn MemoryBus) -> (String, u16) {
             let b1 = bus.read((pc+1) as u32);
             len = 2;
             match b1 {
-                0x4B => { len=4; format!("LD BC, (${:04X})", read16(pc+2, bus)) }
-                0x5B => { len=4; format!("LD DE, (${:04X})", read16(pc+2, bus)) }
-                0x7B => { len=4; format!("LD SP, (${:04X})", read16(pc+2, bus)) }
-                0x43 => { len=4; format!("LD (${:04X}), BC", read16(pc+2, bus)) }
-                0x53 => { len=4; format!("LD (${:04X}), DE", read16(pc+2, bus)) }
-                0x73 => { len=4; format!("LD (${:04X}), SP", read16(pc+2, bus)) }
+                0x4B => { len=4; format!("LD BC, ({})", label(read16(pc+2, bus), symbols)) }
+                0x5B => { len=4; format!("LD DE, ({})", label(read16(pc+2, bus), symbols)) }
+                0x7B => { len=4; format!("LD SP, ({})", label(read16(pc+2, bus), symbols)) }
+                0x43 => { len=4; format!("LD ({}), BC", label(read16(pc+2, bus), symbols)) }
+                0x53 => { len=4; format!("LD ({}), DE", label(read16(pc+2, bus), symbols)) }
+                0x73 => { len=4; format!("LD ({}), SP", label(read16(pc+2, bus), symbols)) }
                 0xB0 => "LDIR".into(),
                 0xB1 => "CPIR".into(),
                 0xB8 => "LDDR".into(),
@@ -159,6 +161,10 @@ fn reg(r: u8) -> &'static str {
     match r { 0=>"B", 1=>"C", 2=>"D", 3=>"E", 4=>"H", 5=>"L", 6=>"(HL)", 7=>"A", _=>"?" }
 }
 
+fn label(addr: u16, symbols: &SymbolTable) -> String {
+    symbols.name_or_hex(addr as u32, 4)
+}
+
 fn read16(pc: u16, bus: &dyn MemoryBus) -> u16 {
     let l = bus.read(pc as u32) as u16;
     let h = bus.read((pc+1) as u32) as u16;
@@ -1,7 +1,10 @@
 use oxidz80::OxidZ80;
-use oxide_core::{Cpu, MemoryBus, Rom};
-use oxid_display::{OxidDisplay, DisplayConfig, WindowScale};
+use oxide_core::{cheats, load_symbols, write_labeled_listing, Cpu, MemoryBus, RamInit, Rom, SymbolTable};
+use oxid_display::{aspect, DebugPanel, OxidDisplay, DisplayConfig, ScaleFilter, WindowScale};
 use minifb::Key;
+use tape::TapeBlock;
+use uspeech::MicroSpeech;
+use std::collections::VecDeque;
 
 // ============================================================================
 //  CONSTANTS
@@ -9,6 +12,14 @@ use minifb::Key;
 const SCREEN_WIDTH: usize = 256;
 const SCREEN_HEIGHT: usize = 192;
 const CYCLES_PER_FRAME: u32 = 69888; // 3.5MHz / 50.08 Hz
+const ULA_INT_LENGTH: u32 = 32; // T-states que la ULA mantiene INT activa
+const CPU_CLOCK: u32 = 3_500_000;
+/// Frecuencia de salida del audio (beeper y µSpeech)
+const SAMPLE_RATE: u32 = 44_100;
+/// Amplitud del beeper con EAR a 1 (EAR a 0 es silencio, sin offset DC)
+const BEEPER_VOLUME: f64 = 8000.0;
+const SPEECH_SAMPLE_RATE: u32 = SAMPLE_RATE;
+const SPEECH_SAMPLES_PER_FRAME: usize = 882; // 44100 / 50
 
 // Paleta Oficial (0-7 Normal, 8-15 Bright)
 const PALETTE: [u32; 16] = [
@@ -19,16 +30,139 @@ const PALETTE: [u32; 16] = [
 // ============================================================================
 //  BUS implementation
 // ============================================================================
+/// Modelo de Spectrum: solo cambia la RAM instalada.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Model {
+    /// 16KB ($4000-$7FFF); $8000-$FFFF sin poblar (bus abierto)
+    Spectrum16K,
+    Spectrum48K,
+}
+
+impl Model {
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "16k" | "16" => Some(Self::Spectrum16K),
+            "48k" | "48" => Some(Self::Spectrum48K),
+            _ => None,
+        }
+    }
+
+    fn ram_size(self) -> usize {
+        match self {
+            Self::Spectrum16K => 16 * 1024,
+            Self::Spectrum48K => 48 * 1024,
+        }
+    }
+}
+
+// ============================================================================
+//  KEYBOARD
+// ============================================================================
+/// Posición en la matriz: (fila = bit de A8-A15 que la selecciona, bit del dato)
+type MatrixKey = (usize, u8);
+
+const CAPS_SHIFT: MatrixKey = (0, 0);
+const SYMBOL_SHIFT: MatrixKey = (7, 1);
+
+/// Las 40 teclas del Spectrum y la tecla del host en la misma posición.
+/// Shift izquierdo = CAPS SHIFT, shift derecho = SYMBOL SHIFT.
+const MATRIX: [[Key; 5]; 8] = [
+    [Key::LeftShift, Key::Z, Key::X, Key::C, Key::V],
+    [Key::A, Key::S, Key::D, Key::F, Key::G],
+    [Key::Q, Key::W, Key::E, Key::R, Key::T],
+    [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5],
+    [Key::Key0, Key::Key9, Key::Key8, Key::Key7, Key::Key6],
+    [Key::P, Key::O, Key::I, Key::U, Key::Y],
+    [Key::Enter, Key::L, Key::K, Key::J, Key::H],
+    [Key::Space, Key::RightShift, Key::M, Key::N, Key::B],
+];
+
+fn matrix_position(key: Key) -> Option<MatrixKey> {
+    MATRIX.iter().enumerate().find_map(|(row, keys)| {
+        keys.iter().position(|&k| k == key).map(|bit| (row, bit as u8))
+    })
+}
+
+/// Qué hacen las teclas del host que no están en la matriz.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyLayout {
+    /// Cursores = 5/6/7/8 sin CAPS SHIFT (joystick "cursor"); Backspace = DELETE
+    Gaming,
+    /// Solo las 40 teclas de la matriz
+    Matrix,
+    /// Para escribir BASIC: cursores y Backspace con CAPS SHIFT (editor),
+    /// puntuación del host con SYMBOL SHIFT, Esc = BREAK
+    Typing,
+}
+
+impl KeyLayout {
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "gaming" => Some(Self::Gaming),
+            "matrix" => Some(Self::Matrix),
+            "typing" => Some(Self::Typing),
+            _ => None,
+        }
+    }
+
+    /// Siguiente distribución (F8)
+    fn next(self) -> Self {
+        match self {
+            Self::Gaming => Self::Typing,
+            Self::Typing => Self::Matrix,
+            Self::Matrix => Self::Gaming,
+        }
+    }
+
+    /// Posiciones de la matriz que pulsa una tecla extra del host.
+    fn extra(self, key: Key) -> &'static [MatrixKey] {
+        match (self, key) {
+            (Self::Gaming, Key::Left) => &[(3, 4)],
+            (Self::Gaming, Key::Down) => &[(4, 4)],
+            (Self::Gaming, Key::Up) => &[(4, 3)],
+            (Self::Gaming, Key::Right) => &[(4, 2)],
+            (Self::Gaming, Key::Backspace) => &[CAPS_SHIFT, (4, 0)],
+            (Self::Typing, Key::Left) => &[CAPS_SHIFT, (3, 4)],
+            (Self::Typing, Key::Down) => &[CAPS_SHIFT, (4, 4)],
+            (Self::Typing, Key::Up) => &[CAPS_SHIFT, (4, 3)],
+            (Self::Typing, Key::Right) => &[CAPS_SHIFT, (4, 2)],
+            (Self::Typing, Key::Backspace) => &[CAPS_SHIFT, (4, 0)],
+            (Self::Typing, Key::Escape) => &[CAPS_SHIFT, (7, 0)],
+            (Self::Typing, Key::Comma) => &[SYMBOL_SHIFT, (7, 3)],
+            (Self::Typing, Key::Period) => &[SYMBOL_SHIFT, (7, 2)],
+            (Self::Typing, Key::Semicolon) => &[SYMBOL_SHIFT, (5, 1)],
+            (Self::Typing, Key::Apostrophe) => &[SYMBOL_SHIFT, (4, 3)],
+            (Self::Typing, Key::Minus) => &[SYMBOL_SHIFT, (6, 3)],
+            (Self::Typing, Key::Equal) => &[SYMBOL_SHIFT, (6, 1)],
+            (Self::Typing, Key::Slash) => &[SYMBOL_SHIFT, (0, 4)],
+            _ => &[],
+        }
+    }
+}
+
 struct SpectrumBus {
     rom: Vec<u8>,
     ram: Vec<u8>,
     border_color: u8,
     keys: Vec<Key>,
+    layout: KeyLayout,
     flash_frame: u32,
+    /// Currah µSpeech (opcional, -uspeech)
+    speech: Option<MicroSpeech>,
+    /// T-state dentro del frame de la instrucción en curso (lo mantiene `run_frame`)
+    frame_cycles: u32,
+    /// Nivel actual de EAR (bit 4 del puerto $FE)
+    beeper: bool,
+    /// Cambios de EAR en este frame: (T-state, nuevo nivel)
+    beeper_edges: Vec<(u32, bool)>,
+    /// Resto fraccionario de muestras entre frames (en unidades de 1/CPU_CLOCK)
+    beeper_carry: u64,
+    /// Bloques de la cinta insertada (-tape) aún sin leer
+    tape: VecDeque<TapeBlock>,
 }
 
 impl SpectrumBus {
-    fn new(rom: Rom) -> Self {
+    fn with_model(rom: Rom, model: Model) -> Self {
         // Ensure ROM is exactly 16KB
         let mut rom_data = rom.data;
         if rom_data.len() > 16384 { rom_data.truncate(16384); }
@@ -36,125 +170,96 @@ impl SpectrumBus {
 
         Self {
             rom: rom_data,
-            ram: vec![0; 49152], // 48KB RAM
+            ram: vec![0; model.ram_size()], // Lo que no está poblado lee bus abierto
             border_color: 7,
             keys: Vec::new(),
+            layout: KeyLayout::Gaming,
             flash_frame: 0,
+            speech: None,
+            frame_cycles: 0,
+            beeper: false,
+            beeper_edges: Vec::new(),
+            beeper_carry: 0,
+            tape: VecDeque::new(),
         }
     }
 
-    fn read_keyboard(&self, row_mask: u8) -> u8 {
-        let mut data = 0xFF; // All keys released (1)
-
-        // Debug: Print mask
-        // if row_mask == 0 { println!("Keyboard Scan: Mask 0"); }
-
-        // Check for convenience keys
-        let backspace = self.keys.contains(&Key::Backspace);
-        let left = self.keys.contains(&Key::Left);
-        let down = self.keys.contains(&Key::Down);
-        let up = self.keys.contains(&Key::Up);
-        let right = self.keys.contains(&Key::Right);
-        
-        let force_caps = backspace || left || down || up || right;
+    /// Reconstruye el audio del beeper del frame que acaba de terminar y vacía
+    /// los cambios acumulados. Cada muestra es el nivel medio de EAR en su
+    /// ventana de T-states, así que los flancos dentro de una muestra no se
+    /// pierden. El número de muestras arrastra el resto fraccionario entre
+    /// frames (69888 T-states a 44.1kHz son ~880.6 muestras); un frame sin
+    /// cambios produce las mismas muestras con el último nivel.
+    fn drain_audio(&mut self, cycles_per_frame: u32, sample_rate: u32) -> Vec<i16> {
+        let total = cycles_per_frame as u64 * sample_rate as u64 + self.beeper_carry;
+        let samples = (total / CPU_CLOCK as u64) as usize;
+        self.beeper_carry = total % CPU_CLOCK as u64;
+
+        // Nivel al empezar el frame: el contrario del primer flanco
+        let mut level = self.beeper_edges.first().map_or(self.beeper, |&(_, l)| !l);
+        let mut edges = self.beeper_edges.drain(..).peekable();
+        let step = cycles_per_frame as f64 / samples.max(1) as f64;
+        let mut out = Vec::with_capacity(samples);
+        for i in 0..samples {
+            let end = (i + 1) as f64 * step;
+            let mut t = i as f64 * step;
+            let mut high = 0.0;
+            while let Some(&(at, l)) = edges.peek() {
+                let at = at as f64;
+                if at >= end {
+                    break;
+                }
+                if level {
+                    high += at.max(t) - t;
+                }
+                t = at.max(t);
+                level = l;
+                edges.next();
+            }
+            if level {
+                high += end - t;
+            }
+            out.push((high / step * BEEPER_VOLUME).round() as i16);
+        }
+        out
+    }
 
-        // Helper to check key and pull bit low (0)
-        let check = |key: Key, bit: u8, current: u8| -> u8 {
-            if self.keys.contains(&key) { current & !(1 << bit) } else { current }
-        };
+    /// Rellena la RAM con el patrón de encendido indicado.
+    fn init_ram(&mut self, init: RamInit) {
+        init.fill(&mut self.ram);
+    }
 
-        // Row 0 (Bit 0 low): SHIFT, Z, X, C, V
-        if (row_mask & 0x01) == 0 {
-            // CAPS SHIFT (Bit 0)
-            if self.keys.contains(&Key::LeftShift) || force_caps {
-                data &= !0x01;
-            }
-            data = check(Key::Z, 1, data);
-            data = check(Key::X, 2, data);
-            data = check(Key::C, 3, data);
-            data = check(Key::V, 4, data);
-        }
-        // Row 1 (Bit 1 low): A, S, D, F, G
-        if (row_mask & 0x02) == 0 {
-            data = check(Key::A, 0, data);
-            data = check(Key::S, 1, data);
-            data = check(Key::D, 2, data);
-            data = check(Key::F, 3, data);
-            data = check(Key::G, 4, data);
-        }
-        // Row 2: Q, W, E, R, T
-        if (row_mask & 0x04) == 0 {
-            data = check(Key::Q, 0, data);
-            data = check(Key::W, 1, data);
-            data = check(Key::E, 2, data);
-            data = check(Key::R, 3, data);
-            data = check(Key::T, 4, data);
-        }
-        // Row 3: 1, 2, 3, 4, 5
-        if (row_mask & 0x08) == 0 {
-            data = check(Key::Key1, 0, data);
-            data = check(Key::Key2, 1, data);
-            data = check(Key::Key3, 2, data);
-            data = check(Key::Key4, 3, data);
-            // 5 (Bit 4) -> Left
-            if self.keys.contains(&Key::Key5) || left { data &= !0x10; }
-        }
-        // Row 4: 0, 9, 8, 7, 6
-        if (row_mask & 0x10) == 0 {
-            // 0 (Bit 0) -> Backspace
-            if self.keys.contains(&Key::Key0) || backspace { data &= !0x01; }
-            data = check(Key::Key9, 1, data);
-            // 8 (Bit 2) -> Right
-            if self.keys.contains(&Key::Key8) || right { data &= !0x04; }
-            // 7 (Bit 3) -> Up
-            if self.keys.contains(&Key::Key7) || up { data &= !0x08; }
-            // 6 (Bit 4) -> Down
-            if self.keys.contains(&Key::Key6) || down { data &= !0x10; }
-        }
-        // Row 5: P, O, I, U, Y
-        if (row_mask & 0x20) == 0 {
-            data = check(Key::P, 0, data);
-            data = check(Key::O, 1, data);
-            data = check(Key::I, 2, data);
-            data = check(Key::U, 3, data);
-            data = check(Key::Y, 4, data);
-        }
-        // Row 6: ENTER, L, K, J, H
-        if (row_mask & 0x40) == 0 {
-            data = check(Key::Enter, 0, data);
-            data = check(Key::L, 1, data);
-            data = check(Key::K, 2, data);
-            data = check(Key::J, 3, data);
-            data = check(Key::H, 4, data);
-        }
-        // Row 7: SPACE, SYM, M, N, B
-        if (row_mask & 0x80) == 0 {
-            data = check(Key::Space, 0, data);
-            data = check(Key::RightShift, 1, data); // Sym Shift
-            data = check(Key::M, 2, data);
-            data = check(Key::N, 3, data);
-            data = check(Key::B, 4, data);
-        }
-        
-        if data != 0xFF {
-             // println!("SCAN RowMask:{:02X} Data:{:02X}", row_mask, data);
+    /// Lee las filas del teclado seleccionadas por `row_mask` (A8-A15, activo bajo).
+    fn read_keyboard(&self, row_mask: u8) -> u8 {
+        let mut pressed = [0u8; 8];
+        for &key in &self.keys {
+            let direct = matrix_position(key);
+            for &(row, bit) in direct.iter().chain(self.layout.extra(key)) {
+                pressed[row] |= 1 << bit;
+            }
         }
-        data
+        (0..8)
+            .filter(|row| row_mask & (1 << row) == 0)
+            .fold(0xFF, |data, row| data & !pressed[row])
     }
 
-
     // I/O methods moved to Trait Implementation
 }
 
 impl MemoryBus for SpectrumBus {
     fn read(&self, addr: u32) -> u8 {
         let a = addr & 0xFFFF;
+        // Acceso con comprobación: lo que quede fuera de ROM/RAM es bus abierto,
+        // así un cambio en el mapa de memoria no puede leer fuera de los buffers
         if a < 0x4000 {
+            if let Some(status) = self.speech.as_ref().and_then(|sp| sp.read(a as u16)) {
+                return status;
+            }
             // ROM (0x0000 - 0x3FFF)
-            unsafe { *self.rom.get_unchecked(a as usize) }
+            self.rom.get(a as usize).copied().unwrap_or(0xFF)
         } else {
             // RAM (0x4000 - 0xFFFF) -> Offset 0
-            unsafe { *self.ram.get_unchecked((a - 0x4000) as usize) }
+            self.ram.get((a - 0x4000) as usize).copied().unwrap_or(0xFF)
         }
     }
 
@@ -162,9 +267,13 @@ impl MemoryBus for SpectrumBus {
         let a = addr & 0xFFFF;
         if a >= 0x4000 {
             // RAM
-            unsafe { *self.ram.get_unchecked_mut((a - 0x4000) as usize) = val; }
+            if let Some(cell) = self.ram.get_mut((a - 0x4000) as usize) {
+                *cell = val;
+            }
+        } else if let Some(speech) = self.speech.as_mut() {
+            // ROM: las escrituras se ignoran, salvo los registros del µSpeech
+            speech.write(a as u16, val);
         }
-        // ROM Writes ignored
     }
 
     fn port_in(&mut self, port: u16) -> u8 {
@@ -181,21 +290,67 @@ impl MemoryBus for SpectrumBus {
         // ULA Port 0xFE: Border + MIC/EAR
         if (port & 1) == 0 {
             self.border_color = val & 0x07;
-            // TODO: Audio (Bit 3 MIC, Bit 4 EAR)
+            // Bit 4 EAR mueve el altavoz; bit 3 MIC solo va a la salida de cinta
+            let ear = val & 0x10 != 0;
+            if ear != self.beeper {
+                self.beeper = ear;
+                self.beeper_edges.push((self.frame_cycles, ear));
+            }
         }
     }
 }
 
 mod disasm;
+mod rom_state;
+mod sna;
+mod tape;
+mod uspeech;
+#[cfg(feature = "debug-server")]
+mod debug;
+#[cfg(test)]
+mod tests;
 
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
 
 struct Config {
     rom_path: String,
+    /// Parche IPS aplicado a la ROM antes de construir el bus
+    ips_path: Option<String>,
+    /// Parche BPS (valida el CRC de la ROM original)
+    bps_path: Option<String>,
     log_path: Option<String>,
     verbosity: u32,
+    ram_init: RamInit,
+    /// Listado estático: (inicio, fin, archivo)
+    disasm: Option<(u32, u32, String)>,
+    filter: ScaleFilter,
+    aspect_correct: bool,
+    /// Frames a ejecutar sin display antes de abrir la ventana
+    warp_frames: u32,
+    model: Model,
+    /// Mostrar las teclas pulsadas en la última fila de la pantalla
+    show_keys: bool,
+    /// Ventana auxiliar con los registros del Z80
+    show_regs: bool,
+    /// Log de referencia para comparar la traza instrucción a instrucción
+    compare_path: Option<String>,
+    /// Fast-forward automático durante la carga desde cinta
+    auto_turbo: bool,
+    layout: KeyLayout,
+    /// Currah µSpeech conectado
+    uspeech: bool,
+    /// Fichero de POKEs aplicados tras cada frame
+    cheats_path: Option<String>,
+    /// Mapa de símbolos (`dirección nombre`) para trazas y listados
+    symbols_path: Option<String>,
+    /// Modo headless: servidor de depuración en este puerto TCP
+    debug_port: Option<u16>,
+    /// Cinta .TAP servida por la trampa de LD-BYTES
+    tape_path: Option<String>,
+    /// Instantánea .SNA de 48K cargada tras el reset
+    sna_path: Option<String>,
 }
 
 struct LogManager {
@@ -248,12 +403,77 @@ impl LogManager {
     }
 }
 
-fn parse_args() -> Config {
+/// Compara la traza propia contra un log de referencia (otro emulador), línea a línea.
+/// Los espacios se normalizan para tolerar alineaciones de columna distintas.
+struct TraceCompare<R: BufRead> {
+    reference: R,
+    line_no: usize,
+    /// Primera divergencia: (línea, esperado, obtenido)
+    divergence: Option<(usize, String, String)>,
+    exhausted: bool,
+}
+
+impl<R: BufRead> TraceCompare<R> {
+    fn new(reference: R) -> Self {
+        Self { reference, line_no: 0, divergence: None, exhausted: false }
+    }
+
+    /// Compara una línea; devuelve `false` cuando ya no hay nada más que comparar.
+    fn check(&mut self, line: &str) -> bool {
+        if self.divergence.is_some() || self.exhausted {
+            return false;
+        }
+        let mut expected = String::new();
+        if self.reference.read_line(&mut expected).unwrap_or(0) == 0 {
+            self.exhausted = true;
+            return false;
+        }
+        self.line_no += 1;
+        let norm = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+        let (expected, actual) = (norm(&expected), norm(line));
+        if expected != actual {
+            self.divergence = Some((self.line_no, expected, actual));
+            return false;
+        }
+        true
+    }
+
+    /// Diff de la divergencia con un marcador en la primera columna distinta.
+    fn report(&self) -> Option<String> {
+        let (line_no, expected, actual) = self.divergence.as_ref()?;
+        let col = expected.chars().zip(actual.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        Some(format!("Trace divergence at line {}:\n  - {}\n  + {}\n    {}^",
+            line_no, expected, actual, " ".repeat(col)))
+    }
+}
+
+fn parse_args() -> Result<Config, Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
     let mut config = Config {
         rom_path: "roms/48.rom".into(),
+        ips_path: None,
+        bps_path: None,
         log_path: None,
         verbosity: 0,
+        ram_init: RamInit::default(),
+        disasm: None,
+        filter: ScaleFilter::Nearest,
+        aspect_correct: false,
+        warp_frames: 0,
+        compare_path: None,
+        show_keys: false,
+        show_regs: false,
+        model: Model::Spectrum48K,
+        debug_port: None,
+        auto_turbo: false,
+        layout: KeyLayout::Gaming,
+        uspeech: false,
+        cheats_path: None,
+        symbols_path: None,
+        tape_path: None,
+        sna_path: None,
     };
 
     let mut i = 1;
@@ -263,32 +483,171 @@ fn parse_args() -> Config {
                 config.rom_path = args[i + 1].clone();
                 i += 2;
             }
+            "-ips" if i + 1 < args.len() => {
+                config.ips_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-bps" if i + 1 < args.len() => {
+                config.bps_path = Some(args[i + 1].clone());
+                i += 2;
+            }
             "-log" if i + 1 < args.len() => {
                 config.log_path = Some(args[i + 1].clone());
                 i += 2;
             }
+            "-ram" if i + 1 < args.len() => {
+                config.ram_init = RamInit::parse(&args[i + 1])
+                    .ok_or_else(|| format!("Invalid -ram value '{}' (expected zeros, ones or seed:<n>)", args[i + 1]))?;
+                i += 2;
+            }
+            "-disasm" if i + 2 < args.len() => {
+                // -disasm <inicio>:<fin> <archivo>  (hex)
+                if let Some((a, b)) = args[i + 1].split_once(':') {
+                    if let (Ok(a), Ok(b)) = (u32::from_str_radix(a, 16), u32::from_str_radix(b, 16)) {
+                        config.disasm = Some((a, b, args[i + 2].clone()));
+                    }
+                }
+                i += 3;
+            }
+            "-filter" if i + 1 < args.len() => {
+                config.filter = match args[i + 1].as_str() {
+                    "bilinear" => ScaleFilter::Bilinear,
+                    "scale2x" => ScaleFilter::Scale2x,
+                    _ => ScaleFilter::Nearest,
+                };
+                i += 2;
+            }
+            "-aspect" => { config.aspect_correct = true; i += 1; }
+            "-keys" => { config.show_keys = true; i += 1; }
+            "-regs" => { config.show_regs = true; i += 1; }
+            "-autoturbo" => { config.auto_turbo = true; i += 1; }
+            "-uspeech" => { config.uspeech = true; i += 1; }
+            "-cheats" if i + 1 < args.len() => {
+                config.cheats_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-tape" if i + 1 < args.len() => {
+                config.tape_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-sna" if i + 1 < args.len() => {
+                config.sna_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-symbols" if i + 1 < args.len() => {
+                config.symbols_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "-layout" if i + 1 < args.len() => {
+                config.layout = KeyLayout::parse(&args[i + 1]).unwrap_or(KeyLayout::Gaming);
+                i += 2;
+            }
+            "-model" if i + 1 < args.len() => {
+                config.model = Model::parse(&args[i + 1])
+                    .ok_or_else(|| format!("Invalid -model value '{}' (expected 16k or 48k)", args[i + 1]))?;
+                i += 2;
+            }
+            "-debug-server" if i + 1 < args.len() => {
+                config.debug_port = args[i + 1].parse().ok();
+                i += 2;
+            }
+            "-compare" if i + 1 < args.len() => {
+                config.compare_path = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--warp" | "-warp" if i + 1 < args.len() => {
+                config.warp_frames = args[i + 1].parse().unwrap_or(0);
+                i += 2;
+            }
             "-v" => { config.verbosity = 1; i += 1; }
             "-vv" => { config.verbosity = 2; i += 1; }
             "-vvv" => { config.verbosity = 3; i += 1; }
             _ => i += 1,
         }
     }
-    config
+    Ok(config)
 }
 
 // ============================================================================
 //  MAIN
 // ============================================================================
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = parse_args();
-    println!("--- Oxide Spectrum (ZX Spectrum 48K) ---");
+    let config = parse_args()?;
+    println!("--- Oxide Spectrum ({:?}) ---", config.model);
     println!("ROM: {}", config.rom_path);
     if let Some(ref p) = config.log_path { println!("Logging execution base: {}", p); }
     println!("Verbosity level: {}", config.verbosity);
 
-    let rom = Rom::from_file(&config.rom_path)?;
-    let mut bus = SpectrumBus::new(rom);
+    let mut rom = Rom::from_file(&config.rom_path)?;
+    if let Some(ref path) = config.ips_path {
+        rom.apply_ips(path)?;
+        println!("IPS patch applied: {}", path);
+    }
+    if let Some(ref path) = config.bps_path {
+        rom.apply_bps(path)?;
+        println!("BPS patch applied: {}", path);
+    }
+    let mut bus = SpectrumBus::with_model(rom, config.model);
+    bus.init_ram(config.ram_init);
+    bus.layout = config.layout;
+    if config.uspeech {
+        bus.speech = Some(MicroSpeech::new());
+    }
+    if let Some(ref path) = config.tape_path {
+        let blocks = tape::parse_tap(&std::fs::read(path)?)?;
+        println!("Tape: {} blocks in {}", blocks.len(), path);
+        bus.tape = blocks.into();
+    }
+    let cheats = match config.cheats_path {
+        Some(ref path) => {
+            let cheats = cheats::load_pokes(path)?;
+            println!("Cheats: {} loaded from {}", cheats.len(), path);
+            cheats
+        }
+        None => Vec::new(),
+    };
+    let symbols = match config.symbols_path {
+        Some(ref path) => {
+            let symbols = load_symbols(path)?;
+            println!("Symbols: {} loaded from {}", symbols.len(), path);
+            symbols
+        }
+        None => SymbolTable::new(),
+    };
+
+    if let Some((start, end, ref path)) = config.disasm {
+        // disasm lee hasta pc+3 con aritmética u16 sin wrap (la instrucción prefijada
+        // más larga ocupa 4 bytes): con el último inicio en $FFFB cabe antes de $FFFF
+        let end = end.min(0xFFFB);
+        let mut out = std::io::BufWriter::new(File::create(path)?);
+        write_labeled_listing(&mut out, &bus, start, end, &symbols, |pc, bus| {
+            let (text, len) = disasm::disassemble(pc as u16, bus, &symbols);
+            (text, len as u32)
+        })?;
+        println!("Disassembly {:04X}-{:04X} written to {}", start, end, path);
+        return Ok(());
+    }
+
     let mut cpu = OxidZ80::new();
+    cpu.reset();
+    if let Some(ref path) = config.sna_path {
+        sna::load_sna(&std::fs::read(path)?, &mut cpu, &mut bus)?;
+        println!("Snapshot: {} (PC={:04X})", path, cpu.pc);
+    }
+    if config.warp_frames > 0 {
+        let t0 = std::time::Instant::now();
+        warp(&mut cpu, &mut bus, config.warp_frames);
+        println!("Warped {} frames in {:?} (PC={:04X})", config.warp_frames, t0.elapsed(), cpu.pc);
+    }
+
+    if let Some(port) = config.debug_port {
+        #[cfg(feature = "debug-server")]
+        debug::run(cpu, bus, port)?;
+        #[cfg(not(feature = "debug-server"))]
+        eprintln!("-debug-server {}: rebuild with --features debug-server", port);
+        return Ok(());
+    }
+
     let mut display = OxidDisplay::new(DisplayConfig {
         title: format!("Oxide Spectrum - {}", config.rom_path),
         width: SCREEN_WIDTH,
@@ -296,25 +655,60 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         scale: WindowScale::X2,
         target_fps: 50.0,
         resizable: false,
+        aspect: config.aspect_correct.then_some(aspect::SPECTRUM),
     });
+    display.set_filter(config.filter);
+    let mut regs_panel = DebugPanel::new(18, 6);
+    let mut regs_window = config.show_regs.then(|| OxidDisplay::open_panel("Z80", &regs_panel));
     
     let mut frame_buffer = vec![0u32; SCREEN_WIDTH * SCREEN_HEIGHT];
     let mut log_mgr = config.log_path.as_ref().map(|p| LogManager::new(p));
+    let mut compare = match config.compare_path {
+        Some(ref p) => Some(TraceCompare::new(BufReader::new(File::open(p)?))),
+        None => None,
+    };
 
-    cpu.reset();
+    // F9: iniciar/detener grabación GIF
+    let mut gif_key_prev = false;
+    // F8: cambiar la distribución del teclado
+    let mut layout_key_prev = false;
+    let mut speech_audio: Vec<i16> = Vec::new();
+    #[cfg(feature = "audio")]
+    if let Err(e) = display.open_audio(SAMPLE_RATE, 1) {
+        eprintln!("Audio disabled: {}", e);
+    }
+    let mut auto_turbo = config.auto_turbo.then(AutoTurbo::default);
+    let mut turbo_on = false;
+    let mut rom_mode = rom_state::detect(cpu.pc, &bus);
     
     while display.is_open() {
+        display.handle_window_hotkeys(); // F10 escala, F11 pantalla completa
         bus.keys = display.get_keys();
-        if !bus.keys.is_empty() {
+        let gif_key = bus.keys.contains(&Key::F9);
+        if gif_key && !gif_key_prev {
+            if display.is_recording_gif() {
+                display.stop_gif()?;
+                println!("GIF saved: oxid_spec.gif");
+            } else {
+                display.start_gif("oxid_spec.gif", 25.0)?;
+                println!("Recording GIF (F9 to stop)...");
+            }
+        }
+        gif_key_prev = gif_key;
+        let layout_key = bus.keys.contains(&Key::F8);
+        if layout_key && !layout_key_prev {
+            bus.layout = bus.layout.next();
+            println!("Keyboard layout: {:?}", bus.layout);
+        }
+        layout_key_prev = layout_key;
+        if config.verbosity > 0 && !bus.keys.is_empty() {
             println!("KEYS PRESSED: {:?}", bus.keys);
         }
-        bus.flash_frame = bus.flash_frame.wrapping_add(1);
-
-        if bus.flash_frame % 50 == 0 {
+        if bus.flash_frame.is_multiple_of(50) {
              // Logic removed
         }
 
-        if bus.flash_frame % 60 == 0 {
+        if config.verbosity > 0 && bus.flash_frame.is_multiple_of(60) {
             // [DIAGNOSTIC] Check if FRAMES system variable is incrementing
             // FRAMES is at 0x5C78 (Low) and 0x5C79 (High)
             // 0x5C78 - 0x4000 = 0x1C78 offset in RAM
@@ -333,62 +727,202 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         // Run Frame
-        let mut cycles = 0;
-        while cycles < CYCLES_PER_FRAME {
-            // Tracing / Logging logic
-            if config.verbosity > 0 || log_mgr.is_some() {
-                let pc = cpu.pc;
-                // Filter out Screen Clear Loop to avoid massive logs
-                if pc >= 0x0E4D && pc <= 0x0E66 { 
-                    cycles += cpu.step(&mut bus); 
-                    continue; 
-                }
+        let verbosity = config.verbosity;
+        let tracing = verbosity > 0 || log_mgr.is_some();
+        run_frame(&mut cpu, &mut bus, |cpu, bus| {
+            if let Some(ref mut cmp) = compare {
+                // Sin filtros ni símbolos: la referencia trae todas las instrucciones en hex
+                cmp.check(&format_trace(cpu, bus, verbosity.max(1), &SymbolTable::new()));
+            }
+            if !tracing {
+                return;
+            }
+            // Filter out Screen Clear Loop to avoid massive logs
+            if (0x0E4D..=0x0E66).contains(&cpu.pc) {
+                return;
+            }
+            let line = format_trace(cpu, bus, verbosity, &symbols);
+            if let Some(ref mut mgr) = log_mgr {
+                mgr.write_line(&line).ok();
+            } else {
+                println!("{}", line);
+            }
+        });
+        bus.apply_cheats(&cheats);
 
-                let (mnemonic, len) = disasm::disassemble(pc, &bus);
-                let mut bytes_str = String::new();
-                for i in 0..len {
-                    bytes_str.push_str(&format!("{:02X} ", bus.read((pc + i) as u32)));
-                }
+        if let Some(ref cmp) = compare {
+            if let Some(diff) = cmp.report() {
+                println!("{}", diff);
+                break;
+            }
+            if cmp.exhausted {
+                println!("Trace matches reference ({} lines)", cmp.line_no);
+                compare = None;
+            }
+        }
 
-                let mut line = format!("{:04X}: {:<12} {:<20}", pc, bytes_str, mnemonic);
-                
-                if config.verbosity >= 2 {
-                    line.push_str(&format!(" AF:{:04X} BC:{:04X} DE:{:04X} HL:{:04X}", 
-                        cpu.af(), cpu.bc(), cpu.de(), cpu.hl()));
-                }
-                if config.verbosity >= 3 {
-                    line.push_str(&format!(" IX:{:04X} IY:{:04X} SP:{:04X} I:{:02X} R:{:02X}", 
-                        cpu.ix, cpu.iy, cpu.sp, cpu.i, cpu.r));
+        if let Some(ref mut turbo) = auto_turbo {
+            let active = turbo.update(cpu.pc);
+            if active != turbo_on {
+                turbo_on = active;
+                display.set_speed(if active { TURBO_SPEED } else { 1.0 });
+                if config.verbosity > 0 {
+                    println!("Auto-turbo {}", if active { "ON" } else { "OFF" });
                 }
+            }
+        }
 
-                if let Some(ref mut mgr) = log_mgr {
-                    mgr.write_line(&line).ok();
-                } else if config.verbosity > 0 {
-                    println!("{}", line);
-                }
-                
-                cycles += cpu.step(&mut bus);
-            } else {
-                if cpu.halted {
-                    println!("CPU HALTED at frame, IFF1={}", cpu.iff1);
-                }
-                cycles += cpu.step(&mut bus);
+        let mode = rom_state::detect(cpu.pc, &bus);
+        if mode != rom_mode {
+            rom_mode = mode;
+            if config.verbosity > 0 {
+                println!("ROM mode: {:?}", mode);
             }
         }
 
-        // VBLANK Interrupt
-        if cpu.iff1 {
-            cpu.irq(&mut bus, 0xFF);
+        // Audio: beeper del frame, con la voz del µSpeech mezclada encima
+        let mut audio = bus.drain_audio(CYCLES_PER_FRAME, SAMPLE_RATE);
+        if let Some(ref mut speech) = bus.speech {
+            if config.verbosity > 0 {
+                if let Some(allophone) = speech.speaking() {
+                    println!("uSpeech: {}", allophone.name);
+                }
+            }
+            speech_audio.clear();
+            speech.render(SPEECH_SAMPLES_PER_FRAME, SPEECH_SAMPLE_RATE, &mut speech_audio);
+            for (dst, &src) in audio.iter_mut().zip(&speech_audio) {
+                *dst = dst.saturating_add(src);
+            }
         }
-        
+        #[cfg(feature = "audio")]
+        if let Some(out) = display.audio() {
+            out.push_samples(&audio);
+        }
+
         // Render
         render_screen(&bus, &mut frame_buffer);
+        if config.show_keys && !bus.keys.is_empty() {
+            let names: Vec<String> = bus.keys.iter().map(|k| format!("{:?}", k)).collect();
+            draw_text(&mut frame_buffer, &bus.rom, 0, SCREEN_HEIGHT / 8 - 1, &names.join(" "));
+        }
         display.update(&frame_buffer);
+        if let Some(ref mut window) = regs_window {
+            window.update(regs_panel.render(&register_lines(&cpu)));
+            if !window.is_open() {
+                regs_window = None;
+            }
+        }
     }
 
+    display.stop_gif()?;
     Ok(())
 }
 
+/// Contenido del panel de registros (`-regs`).
+fn register_lines(cpu: &OxidZ80) -> Vec<String> {
+    vec![
+        format!("PC {:04X}  SP {:04X}", cpu.pc, cpu.sp),
+        format!("AF {:02X}{:02X}  BC {:04X}", cpu.a, cpu.f, cpu.bc()),
+        format!("DE {:04X}  HL {:04X}", cpu.de(), cpu.hl()),
+        format!("IX {:04X}  IY {:04X}", cpu.ix, cpu.iy),
+        format!("I {:02X} R {:02X} IM {}", cpu.i, cpu.r, cpu.im),
+        format!("IFF {}{} {}", cpu.iff1 as u8, cpu.iff2 as u8, if cpu.halted { "HALT" } else { "" }),
+    ]
+}
+
+// ============================================================================
+//  FRAME LOOP (HEADLESS)
+// ============================================================================
+
+/// Ejecuta un frame completo (69888 T-states) sin display, input ni limitador.
+/// `trace` se llama antes de cada instrucción con el estado previo.
+fn run_frame<F: FnMut(&OxidZ80, &SpectrumBus)>(cpu: &mut OxidZ80, bus: &mut SpectrumBus, mut trace: F) {
+    bus.flash_frame = bus.flash_frame.wrapping_add(1);
+    let mut cycles = 0;
+    while cycles < CYCLES_PER_FRAME {
+        // VBLANK: la ULA mantiene INT activa solo los primeros T-states del frame;
+        // si IFF1=0 durante ese pulso, la interrupción se pierde
+        cpu.assert_int(cycles < ULA_INT_LENGTH);
+        tape_trap(cpu, bus);
+        trace(cpu, bus);
+        bus.frame_cycles = cycles;
+        cycles += cpu.step(bus);
+    }
+}
+
+/// Carga instantánea: si el PC está en LD-BYTES y hay cinta, el siguiente
+/// bloque se resuelve sin ejecutar el cargador (ver `tape`).
+fn tape_trap(cpu: &mut OxidZ80, bus: &mut SpectrumBus) {
+    if cpu.pc != tape::LD_BYTES {
+        return;
+    }
+    if let Some(block) = bus.tape.pop_front() {
+        tape::load_block(cpu, bus, &block);
+    }
+}
+
+/// Avanza `frames` frames a máxima velocidad (reproducir bugs tardíos, tests).
+fn warp(cpu: &mut OxidZ80, bus: &mut SpectrumBus, frames: u32) {
+    for _ in 0..frames {
+        run_frame(cpu, bus, |_, _| {});
+        // Sin salida de audio: los flancos del beeper se descartan
+        bus.beeper_edges.clear();
+    }
+}
+
+/// Rutinas de carga de la ROM 48K: LD-BYTES ($0556) hasta el final de LD-8-BITS.
+/// Mientras se espera/lee la cinta el PC pasa casi todo el tiempo en LD-EDGE.
+const ROM_LOADER: std::ops::RangeInclusive<u16> = 0x0556..=0x0604;
+/// Frames seguidos fuera del cargador antes de volver a velocidad normal
+/// (entre bloques la ROM sale un momento del cargador)
+const TURBO_RELEASE_FRAMES: u32 = 25;
+/// Velocidad del limiter durante la carga (0 = sin límite)
+const TURBO_SPEED: f64 = 0.0;
+
+/// Fast-forward automático mientras se ejecuta el cargador de cinta de la ROM.
+#[derive(Default)]
+struct AutoTurbo {
+    active: bool,
+    frames_outside: u32,
+}
+
+impl AutoTurbo {
+    /// Muestrea el PC al final del frame; devuelve si el turbo debe estar activo.
+    fn update(&mut self, pc: u16) -> bool {
+        if ROM_LOADER.contains(&pc) {
+            self.active = true;
+            self.frames_outside = 0;
+        } else if self.active {
+            self.frames_outside += 1;
+            if self.frames_outside >= TURBO_RELEASE_FRAMES {
+                self.active = false;
+            }
+        }
+        self.active
+    }
+}
+
+/// Línea de traza `PC: BYTES MNEMÓNICO [registros]` según el nivel de verbosidad.
+fn format_trace(cpu: &OxidZ80, bus: &SpectrumBus, verbosity: u32, symbols: &SymbolTable) -> String {
+    let pc = cpu.pc;
+    let (mnemonic, len) = disasm::disassemble(pc, bus, symbols);
+    let mut bytes_str = String::new();
+    for i in 0..len {
+        bytes_str.push_str(&format!("{:02X} ", bus.read((pc + i) as u32)));
+    }
+
+    let mut line = format!("{:04X}: {:<12} {:<20}", pc, bytes_str, mnemonic);
+    if verbosity >= 2 {
+        line.push_str(&format!(" AF:{:04X} BC:{:04X} DE:{:04X} HL:{:04X}",
+            cpu.af(), cpu.bc(), cpu.de(), cpu.hl()));
+    }
+    if verbosity >= 3 {
+        line.push_str(&format!(" IX:{:04X} IY:{:04X} SP:{:04X} I:{:02X} R:{:02X}",
+            cpu.ix, cpu.iy, cpu.sp, cpu.i, cpu.r));
+    }
+    line
+}
+
 fn render_screen(bus: &SpectrumBus, buffer: &mut [u32]) {
     // VRAM is at 0x4000 in System Map.
     // In our new bus.ram, 0x4000 maps to index 0.
@@ -411,8 +945,8 @@ fn render_screen(bus: &SpectrumBus, buffer: &mut [u32]) {
         let attr_idx = ATTR_OFFSET | (sector << 8) | (row << 5);
 
         for x_byte in 0..32 {
-            let pixels = unsafe { *bus.ram.get_unchecked(pixel_idx + x_byte) };
-            let attr = unsafe { *bus.ram.get_unchecked(attr_idx + x_byte) };
+            let pixels = bus.ram[pixel_idx + x_byte];
+            let attr = bus.ram[attr_idx + x_byte];
 
             let mut ink = PALETTE[(attr & 0x07) as usize + if (attr & 0x40)!=0 {8} else {0}];
             let mut paper = PALETTE[((attr >> 3) & 0x07) as usize + if (attr & 0x40)!=0 {8} else {0}];
@@ -428,3 +962,22 @@ fn render_screen(bus: &SpectrumBus, buffer: &mut [u32]) {
         }
     }
 }
+
+/// Pinta texto con el juego de caracteres de la ROM ($3D00, 8x8, ASCII $20-$7F)
+/// en la celda (`col`, `row`), blanco sobre negro. Lo que no cabe en la fila se corta.
+fn draw_text(buffer: &mut [u32], rom: &[u8], col: usize, row: usize, text: &str) {
+    const CHARSET: usize = 0x3D00;
+    let cols = SCREEN_WIDTH / 8;
+    for (i, ch) in text.bytes().enumerate().take(cols.saturating_sub(col)) {
+        let ch = if (0x20..0x80).contains(&ch) { ch } else { b'?' };
+        let glyph = CHARSET + (ch - 0x20) as usize * 8;
+        for line in 0..8 {
+            let bits = rom.get(glyph + line).copied().unwrap_or(0);
+            let y = row * 8 + line;
+            for bit in 0..8 {
+                let color = if bits & (0x80 >> bit) != 0 { PALETTE[15] } else { PALETTE[0] };
+                buffer[y * SCREEN_WIDTH + (col + i) * 8 + bit] = color;
+            }
+        }
+    }
+}
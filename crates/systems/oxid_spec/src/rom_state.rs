@@ -0,0 +1,49 @@
+// crates/systems/oxid_spec/src/rom_state.rs
+//! Qué está haciendo la ROM 48K: arrancando, esperando en el prompt de BASIC,
+//! cargando de cinta o ejecutando un programa. Se deduce del PC y de las
+//! variables del sistema; sirve para sincronizar auto-tecleo, turbo de carga
+//! e inyección de snapshots.
+
+use crate::ROM_LOADER;
+use oxide_core::MemoryBus;
+
+/// CHARS: la ROM la pone a $3C00 al inicializar las variables del sistema
+const CHARS: u32 = 0x5C36;
+/// ERR_NR: código de informe - 1 ($FF = sin error)
+const ERR_NR: u32 = 0x5C3A;
+/// FLAGS: bit 7 = ejecutando (0 = comprobando sintaxis en el editor)
+const FLAGS: u32 = 0x5C3B;
+const CHARS_INITIALIZED: u16 = 0x3C00;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RomMode {
+    /// Test de RAM e inicialización: las variables del sistema aún no valen
+    Booting,
+    /// Editor de BASIC (prompt, o un informe de error en pantalla)
+    Idle,
+    /// Rutinas de carga de cinta (LD-BYTES)
+    Loading,
+    /// Programa BASIC o código máquina
+    Running,
+}
+
+/// Modo actual a partir del PC y las variables del sistema.
+pub fn detect(pc: u16, bus: &dyn MemoryBus) -> RomMode {
+    if ROM_LOADER.contains(&pc) {
+        return RomMode::Loading;
+    }
+    if bus.read_u16_le(CHARS) != CHARS_INITIALIZED {
+        return RomMode::Booting;
+    }
+    // Fuera de la ROM solo puede estar corriendo código máquina
+    if pc >= 0x4000 {
+        return RomMode::Running;
+    }
+    // En la ROM: ejecutando BASIC salvo que haya un error pendiente de informar
+    let executing = bus.read(FLAGS) & 0x80 != 0;
+    if executing && bus.read(ERR_NR) == 0xFF {
+        RomMode::Running
+    } else {
+        RomMode::Idle
+    }
+}
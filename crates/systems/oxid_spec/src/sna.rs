@@ -0,0 +1,69 @@
+// crates/systems/oxid_spec/src/sna.rs
+//
+// Instantáneas .SNA de 48K: 27 bytes de cabecera + 48KB de RAM ($4000-$FFFF).
+//   0 I | 1 HL' | 3 DE' | 5 BC' | 7 AF' | 9 HL | 11 DE | 13 BC | 15 IY | 17 IX
+//  19 IFF2 (bit 2) | 20 R | 21 AF | 23 SP | 25 IM | 26 borde   (pares en LE)
+// El PC no está en la cabecera: el snapshot se tomó dentro de una NMI y el PC
+// quedó en la pila, así que se recupera como un RETN: POP PC e IFF1 = IFF2.
+
+use crate::SpectrumBus;
+use oxide_core::MemoryBus;
+use oxidz80::OxidZ80;
+use std::fmt;
+
+const HEADER_LEN: usize = 27;
+pub const SNA_48K_LEN: usize = HEADER_LEN + 48 * 1024;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SnaError {
+    /// Tamaño distinto de 49179 bytes (los .SNA de 128K no se soportan)
+    WrongSize(usize),
+}
+
+impl fmt::Display for SnaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnaError::WrongSize(len) => {
+                write!(f, "SNA must be {} bytes (48K), got {}", SNA_48K_LEN, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnaError {}
+
+/// Restaura CPU, RAM y borde desde un .SNA de 48K.
+pub fn load_sna(bytes: &[u8], cpu: &mut OxidZ80, bus: &mut SpectrumBus) -> Result<(), SnaError> {
+    if bytes.len() != SNA_48K_LEN {
+        return Err(SnaError::WrongSize(bytes.len()));
+    }
+    let word = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+
+    cpu.i = bytes[0];
+    cpu.set_internals(word(7), word(5), word(3), word(1), 0);
+    cpu.set_hl(word(9));
+    cpu.set_de(word(11));
+    cpu.set_bc(word(13));
+    cpu.iy = word(15);
+    cpu.ix = word(17);
+    cpu.iff2 = bytes[19] & 0x04 != 0;
+    cpu.r = bytes[20];
+    cpu.set_af(word(21));
+    cpu.sp = word(23);
+    cpu.im = bytes[25] & 0x03;
+    cpu.halted = false;
+    cpu.ei_pending = false;
+    bus.border_color = bytes[26] & 0x07;
+
+    // En un 16K lo que cae fuera de la RAM instalada se descarta
+    for (addr, &byte) in (0x4000u32..).zip(&bytes[HEADER_LEN..]) {
+        bus.write(addr, byte);
+    }
+
+    // RETN: el PC guardado está en la cima de la pila
+    let sp = cpu.sp as u32;
+    cpu.pc = u16::from_le_bytes([bus.read(sp), bus.read((sp + 1) & 0xFFFF)]);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.iff1 = cpu.iff2;
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// crates/systems/oxid_spec/src/tape.rs
+//
+// Cintas .TAP y trampa del cargador de la ROM.
+// - Formato: bloques seguidos de [longitud u16 LE][flag][datos...][checksum];
+//   la longitud cuenta flag y checksum, y el checksum es el XOR de flag y datos
+// - No hay señal de cinta: cuando el PC llega a LD-BYTES ($0556) el bloque
+//   siguiente se copia de golpe a memoria y se sale por SA/LD-RET ($053F), que
+//   restaura el borde, hace EI y vuelve al llamante como la rutina original
+// - Entrada de LD-BYTES: A = flag esperado, carry = LOAD (sin carry = VERIFY),
+//   IX = destino, DE = longitud. Salida: carry = carga correcta
+// - Bloque con flag distinto: se consume sin cargar y sale sin carry (la ROM
+//   sigue buscando, p. ej. LOAD "" salta bloques de datos hasta una cabecera)
+// - Longitud pedida (DE) distinta de la del bloque: error de carga, carry a 0.
+//   Se copian los bytes que quepan en ambos, como una carga real que se queda
+//   sin datos o lee un byte de datos como checksum ("R Tape loading error")
+
+use oxide_core::MemoryBus;
+use oxidz80::{flags, OxidZ80};
+use std::fmt;
+
+/// Entrada de LD-BYTES en la ROM 48K
+pub const LD_BYTES: u16 = 0x0556;
+/// SA/LD-RET: epílogo común de carga y grabación
+pub const SA_LD_RET: u16 = 0x053F;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TapeError {
+    /// El fichero acaba a mitad de un bloque (offset del bloque)
+    Truncated(usize),
+    /// Bloque de menos de 2 bytes: no caben flag y checksum (offset del bloque)
+    ShortBlock(usize),
+}
+
+impl fmt::Display for TapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TapeError::Truncated(at) => write!(f, "TAP truncated in block at offset {}", at),
+            TapeError::ShortBlock(at) => write!(f, "TAP block at offset {} has no flag/checksum", at),
+        }
+    }
+}
+
+impl std::error::Error for TapeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapeBlock {
+    /// $00 cabecera, $FF datos (los cargadores propios usan otros)
+    pub flag: u8,
+    pub data: Vec<u8>,
+    pub checksum: u8,
+}
+
+impl TapeBlock {
+    /// Paridad de la ROM: flag XOR datos XOR checksum debe dar 0.
+    pub fn checksum_ok(&self) -> bool {
+        self.data.iter().fold(self.flag, |acc, &b| acc ^ b) == self.checksum
+    }
+}
+
+pub fn parse_tap(bytes: &[u8]) -> Result<Vec<TapeBlock>, TapeError> {
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let header = bytes.get(pos..pos + 2).ok_or(TapeError::Truncated(pos))?;
+        let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+        let body = bytes.get(pos + 2..pos + 2 + len).ok_or(TapeError::Truncated(pos))?;
+        if len < 2 {
+            return Err(TapeError::ShortBlock(pos));
+        }
+        blocks.push(TapeBlock {
+            flag: body[0],
+            data: body[1..len - 1].to_vec(),
+            checksum: body[len - 1],
+        });
+        pos += 2 + len;
+    }
+    Ok(blocks)
+}
+
+/// Resuelve una llamada a LD-BYTES con `block` y deja el PC en SA/LD-RET.
+pub fn load_block(cpu: &mut OxidZ80, mem: &mut dyn MemoryBus, block: &TapeBlock) {
+    let verify = cpu.f & flags::C == 0;
+    let mut ok = block.flag == cpu.a;
+    if ok {
+        let wanted = cpu.de() as usize;
+        let n = wanted.min(block.data.len());
+        for &byte in &block.data[..n] {
+            let addr = cpu.ix as u32;
+            if verify {
+                ok &= mem.read(addr) == byte;
+            } else {
+                mem.write(addr, byte);
+            }
+            cpu.ix = cpu.ix.wrapping_add(1);
+        }
+        cpu.set_de((wanted - n) as u16);
+        ok &= n == block.data.len() && n == wanted && block.checksum_ok();
+    }
+    cpu.f = if ok { cpu.f | flags::C } else { cpu.f & !flags::C };
+    cpu.pc = SA_LD_RET;
+}
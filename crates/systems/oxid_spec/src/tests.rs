@@ -0,0 +1,522 @@
+// crates/systems/oxid_spec/src/tests.rs
+use crate::{format_trace, warp, AutoTurbo, KeyLayout, Model, SpectrumBus, TraceCompare, TURBO_RELEASE_FRAMES};
+use crate::rom_state::{self, RomMode};
+use crate::uspeech::{MicroSpeech, ALLOPHONES};
+use minifb::Key;
+use oxide_core::{Cpu, Rom, SymbolTable};
+use oxidz80::OxidZ80;
+
+/// ROM mínima: IM 1 + EI + bucle; la ISR en $0038 incrementa ($8000).
+fn counter_machine() -> (OxidZ80, SpectrumBus) {
+    let mut data = vec![0u8; 0x4000];
+    data[0x0000..0x0008].copy_from_slice(&[
+        0x21, 0x00, 0x80, // LD HL,$8000
+        0xED, 0x56,       // IM 1
+        0xFB,             // EI
+        0x18, 0xFE,       // JR $
+    ]);
+    data[0x0038..0x003B].copy_from_slice(&[
+        0x34, // INC (HL)
+        0xFB, // EI
+        0xC9, // RET
+    ]);
+    let bus = SpectrumBus::with_model(Rom { data }, Model::Spectrum48K);
+    let mut cpu = OxidZ80::new();
+    cpu.reset();
+    (cpu, bus)
+}
+
+// ============================================================================
+// Warp
+// ============================================================================
+
+#[test]
+fn test_warp_counts_one_interrupt_per_frame() {
+    let (mut cpu, mut bus) = counter_machine();
+    warp(&mut cpu, &mut bus, 10);
+
+    // El pulso INT del primer frame cae antes del EI: se pierde
+    assert_eq!(bus.ram[0x4000], 9);
+    assert_eq!(bus.flash_frame, 10);
+}
+
+#[test]
+fn test_warp_is_deterministic() {
+    let (mut cpu_a, mut bus_a) = counter_machine();
+    let (mut cpu_b, mut bus_b) = counter_machine();
+    warp(&mut cpu_a, &mut bus_a, 7);
+    warp(&mut cpu_b, &mut bus_b, 3);
+    warp(&mut cpu_b, &mut bus_b, 4);
+
+    assert_eq!(cpu_a.pc, cpu_b.pc);
+    assert_eq!(cpu_a.sp, cpu_b.sp);
+    assert_eq!(cpu_a.r, cpu_b.r);
+    assert_eq!(bus_a.ram, bus_b.ram);
+}
+
+// ============================================================================
+// Trace compare
+// ============================================================================
+
+/// Ejecuta instrucciones comparando hasta agotar la referencia o divergir.
+fn run_compare(reference: &str) -> TraceCompare<&[u8]> {
+    let (mut cpu, mut bus) = counter_machine();
+    let mut cmp = TraceCompare::new(reference.as_bytes());
+    while cmp.check(&format_trace(&cpu, &bus, 1, &SymbolTable::new())) {
+        cpu.step(&mut bus);
+    }
+    cmp
+}
+
+#[test]
+fn test_trace_uses_symbol_names() {
+    let (mut cpu, mut bus) = counter_machine();
+    let symbols = SymbolTable::parse("8000 Counter\n0006 MainLoop\n").unwrap();
+    assert!(format_trace(&cpu, &bus, 1, &symbols).trim_end().ends_with("LD HL, $8000"), "inmediato: sin símbolo");
+    while cpu.pc != 0x0006 {
+        cpu.step(&mut bus);
+    }
+    assert_eq!(format_trace(&cpu, &bus, 1, &symbols).trim_end(), "0006: 18 FE        JR MainLoop");
+}
+
+#[test]
+fn test_trace_compare_matching_reference() {
+    // Alineación distinta a la nuestra: solo cuentan los tokens
+    let cmp = run_compare(
+        "0000: 21 00 80  LD HL, $8000\n\
+         0003: ED 56     ED $56\n\
+         0005: FB        EI\n\
+         0006: 18 FE     JR $0006\n",
+    );
+    assert!(cmp.exhausted);
+    assert_eq!(cmp.line_no, 4);
+    assert!(cmp.report().is_none());
+}
+
+#[test]
+fn test_trace_compare_stops_at_first_divergence() {
+    let cmp = run_compare(
+        "0000: 21 00 80  LD HL, $8000\n\
+         0003: ED 56     ED $56\n\
+         0006: 18 FE     JR $0006\n\
+         0006: 18 FE     JR $0006\n",
+    );
+    let (line_no, expected, actual) = cmp.divergence.clone().unwrap();
+    assert_eq!(line_no, 3);
+    assert_eq!(expected, "0006: 18 FE JR $0006");
+    assert_eq!(actual, "0005: FB EI");
+    assert!(cmp.report().unwrap().contains("line 3"));
+    assert!(!cmp.exhausted);
+}
+
+// ============================================================================
+// Texto en pantalla (indicador de teclas)
+// ============================================================================
+
+#[test]
+fn test_draw_text_uses_rom_charset() {
+    use crate::{draw_text, PALETTE, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+    let mut rom = vec![0u8; 0x4000];
+    let glyph_a = 0x3D00 + (b'A' - 0x20) as usize * 8;
+    rom[glyph_a] = 0x81; // primera línea: bordes
+    let mut buf = vec![0x123456u32; SCREEN_WIDTH * SCREEN_HEIGHT];
+    draw_text(&mut buf, &rom, 1, 23, "A");
+
+    let y = 23 * 8;
+    assert_eq!(buf[y * SCREEN_WIDTH + 8], PALETTE[15]);
+    assert_eq!(buf[y * SCREEN_WIDTH + 9], PALETTE[0]);
+    assert_eq!(buf[y * SCREEN_WIDTH + 15], PALETTE[15]);
+    assert_eq!(buf[(y + 1) * SCREEN_WIDTH + 8], PALETTE[0]);
+    // Fuera de la celda no se toca
+    assert_eq!(buf[y * SCREEN_WIDTH + 7], 0x123456);
+    assert_eq!(buf[y * SCREEN_WIDTH + 16], 0x123456);
+
+    // Texto más largo que la fila: se corta sin desbordar
+    draw_text(&mut buf, &rom, 30, 23, "ABCDEF");
+}
+
+// ============================================================================
+// Mapa de memoria
+// ============================================================================
+
+#[test]
+fn test_full_address_space_is_accessible() {
+    use oxide_core::MemoryBus;
+
+    let (_, mut bus) = counter_machine();
+    for addr in 0..=0xFFFFu32 {
+        bus.write(addr, addr as u8);
+    }
+    for addr in 0..=0xFFFFu32 {
+        let expected = if addr < 0x4000 { bus.rom[addr as usize] } else { addr as u8 };
+        assert_eq!(bus.read(addr), expected, "{:04X}", addr);
+    }
+    // Bits altos del bus ignorados (A16+)
+    assert_eq!(bus.read(0x1_8000), 0x00);
+    assert_eq!(bus.read(u32::MAX), 0xFF);
+}
+
+#[test]
+fn test_16k_model_upper_ram_is_open_bus() {
+    use oxide_core::MemoryBus;
+
+    let mut bus = SpectrumBus::with_model(Rom { data: vec![0; 0x4000] }, Model::Spectrum16K);
+    assert_eq!(bus.ram.len(), 0x4000);
+    bus.write(0x7FFF, 0x42);
+    bus.write(0x8000, 0x42);
+    assert_eq!(bus.read(0x7FFF), 0x42);
+    assert_eq!(bus.read(0x8000), 0xFF);
+    assert_eq!(bus.read(0xFFFF), 0xFF);
+
+    assert_eq!(Model::parse("16K"), Some(Model::Spectrum16K));
+    assert_eq!(Model::parse("128k"), None);
+}
+
+// ============================================================================
+// Auto-turbo
+// ============================================================================
+
+#[test]
+fn test_auto_turbo_follows_rom_loader() {
+    let mut turbo = AutoTurbo::default();
+    assert!(!turbo.update(0x12A9)); // bucle principal del editor
+    assert!(turbo.update(0x05E7)); // LD-EDGE-2
+
+    // Salidas breves (entre bloques) no cortan el turbo
+    for _ in 1..TURBO_RELEASE_FRAMES {
+        assert!(turbo.update(0x8000));
+    }
+    assert!(turbo.update(0x0556));
+    for _ in 1..TURBO_RELEASE_FRAMES {
+        turbo.update(0x8000);
+    }
+    assert!(!turbo.update(0x8000), "el juego corre a velocidad normal");
+}
+
+// ============================================================================
+// Modo de la ROM
+// ============================================================================
+
+#[test]
+fn test_rom_mode_from_system_variables() {
+    use oxide_core::MemoryBus;
+    let mut bus = SpectrumBus::with_model(Rom { data: vec![0; 0x4000] }, Model::Spectrum48K);
+    // RAM a cero: la ROM aún no inicializó CHARS
+    assert_eq!(rom_state::detect(0x11E2, &bus), RomMode::Booting);
+
+    bus.write(0x5C36, 0x00); // CHARS = $3C00
+    bus.write(0x5C37, 0x3C);
+    bus.write(0x5C3A, 0xFF); // ERR_NR: sin error
+    bus.write(0x5C3B, 0x00); // FLAGS: editor comprobando sintaxis
+    assert_eq!(rom_state::detect(0x10A8, &bus), RomMode::Idle);
+
+    bus.write(0x5C3B, 0x80); // ejecutando una línea de BASIC
+    assert_eq!(rom_state::detect(0x1B76, &bus), RomMode::Running);
+    bus.write(0x5C3A, 0x01); // "2 Variable not found": vuelta al editor
+    assert_eq!(rom_state::detect(0x1303, &bus), RomMode::Idle);
+
+    assert_eq!(rom_state::detect(0x05E7, &bus), RomMode::Loading); // LD-EDGE-2
+    assert_eq!(rom_state::detect(0x8000, &bus), RomMode::Running); // código máquina
+}
+
+// ============================================================================
+// Teclado
+// ============================================================================
+
+/// Lee la fila `row` (0-7) del puerto $FE con `keys` pulsadas.
+fn read_row(bus: &mut SpectrumBus, keys: &[Key], row: u8) -> u8 {
+    use oxide_core::MemoryBus;
+
+    bus.keys = keys.to_vec();
+    bus.port_in((!(1u16 << row) << 8) | 0xFE) & 0x1F
+}
+
+#[test]
+fn test_keyboard_layouts() {
+    let mut bus = SpectrumBus::with_model(Rom { data: vec![0; 0x4000] }, Model::Spectrum48K);
+
+    // Gaming: cursores = 5/6/7/8 sin CAPS SHIFT
+    assert_eq!(bus.layout, KeyLayout::Gaming);
+    assert_eq!(read_row(&mut bus, &[Key::Left], 3), 0x0F);
+    assert_eq!(read_row(&mut bus, &[Key::Left], 0), 0x1F);
+    assert_eq!(read_row(&mut bus, &[Key::Up, Key::Right], 4), 0x13);
+    assert_eq!(read_row(&mut bus, &[Key::Backspace], 0), 0x1E);
+    assert_eq!(read_row(&mut bus, &[Key::Backspace], 4), 0x1E);
+
+    // Matrix: solo las 40 teclas
+    bus.layout = KeyLayout::parse("matrix").unwrap();
+    assert_eq!(read_row(&mut bus, &[Key::Left, Key::Backspace], 3), 0x1F);
+    assert_eq!(read_row(&mut bus, &[Key::Left, Key::Backspace], 4), 0x1F);
+    assert_eq!(read_row(&mut bus, &[Key::Key5, Key::LeftShift], 3), 0x0F);
+    assert_eq!(read_row(&mut bus, &[Key::Key5, Key::LeftShift], 0), 0x1E);
+
+    // Varias filas a la vez: AND de todas
+    use oxide_core::MemoryBus;
+    bus.keys = vec![Key::Q, Key::A];
+    assert_eq!(bus.port_in(0xF9FE) & 0x1F, 0x1E);
+}
+
+#[test]
+fn test_typing_layout_maps_keys_positionally() {
+    let mut bus = SpectrumBus::with_model(Rom { data: vec![0; 0x4000] }, Model::Spectrum48K);
+    bus.layout = KeyLayout::Gaming.next();
+    assert_eq!(bus.layout, KeyLayout::Typing);
+
+    // '5' es solo la tecla 5: sin CAPS SHIFT espurio
+    assert_eq!(read_row(&mut bus, &[Key::Key5], 3), 0x0F);
+    assert_eq!(read_row(&mut bus, &[Key::Key5], 0), 0x1F);
+    assert_eq!(read_row(&mut bus, &[Key::L], 6), 0x1D);
+
+    // Cursor del editor: CAPS SHIFT + 5
+    assert_eq!(read_row(&mut bus, &[Key::Left], 3), 0x0F);
+    assert_eq!(read_row(&mut bus, &[Key::Left], 0), 0x1E);
+
+    // Coma = SYMBOL SHIFT + N
+    assert_eq!(read_row(&mut bus, &[Key::Comma], 7), 0x15);
+    assert_eq!(KeyLayout::Typing.next().next(), KeyLayout::Gaming);
+}
+
+// ============================================================================
+// Currah µSpeech
+// ============================================================================
+
+#[test]
+fn test_uspeech_queues_and_speaks_allophones() {
+    use oxide_core::MemoryBus;
+
+    let mut bus = SpectrumBus::with_model(Rom { data: vec![0xAA; 0x4000] }, Model::Spectrum48K);
+    // Sin el dispositivo, $1000 es ROM normal
+    bus.write(0x1000, 0x13);
+    assert_eq!(bus.read(0x1000), 0xAA);
+
+    bus.speech = Some(MicroSpeech::new());
+    assert_eq!(bus.read(0x1000), 0x00);
+    bus.write(0x3001, 0);
+    bus.write(0x1000, 0x13); // IY
+    bus.write(0x1000, 0x40 | 0x02); // PA3 (solo 6 bits)
+    let speech = bus.speech.as_mut().unwrap();
+    assert_eq!(speech.queue, [0x13, 0x02]);
+    assert!(speech.high_pitch);
+    assert_eq!(bus.read(0x1000), 0x01, "ocupado");
+
+    // IY dura 250 ms: suena durante ese tiempo, luego la pausa y silencio
+    let speech = bus.speech.as_mut().unwrap();
+    let mut audio = Vec::new();
+    speech.render(100, 10_000, &mut audio);
+    assert_eq!(speech.speaking(), Some(&ALLOPHONES[0x13]));
+    assert_eq!(speech.speaking().unwrap().name, "IY");
+    assert!(audio.iter().any(|&s| s != 0));
+
+    audio.clear();
+    speech.render(2400 + 500, 10_000, &mut audio);
+    assert_eq!(audio.len(), 2900);
+    assert!(audio[2400..].iter().all(|&s| s == 0), "PA3 y cola vacía = silencio");
+    assert!(!speech.is_busy());
+    assert_eq!(bus.read(0x1000), 0x00);
+}
+
+
+// ============================================================================
+// Beeper
+// ============================================================================
+
+#[test]
+fn test_beeper_square_wave_from_ear_toggles() {
+    use crate::{BEEPER_VOLUME, CYCLES_PER_FRAME};
+    use oxide_core::MemoryBus;
+    let (_, mut bus) = counter_machine();
+
+    // EAR alto durante la primera mitad del frame; MIC (bit 3) no cuenta como flanco
+    bus.port_out(0x00FE, 0x10);
+    bus.frame_cycles = 1000;
+    bus.port_out(0x00FE, 0x18);
+    bus.frame_cycles = CYCLES_PER_FRAME / 2 + 40;
+    bus.port_out(0x00FE, 0x08);
+    assert_eq!(bus.beeper_edges, [(0, true), (CYCLES_PER_FRAME / 2 + 40, false)]);
+
+    // 69888 T-states a 3.5MHz y 44.1kHz: 880.59 muestras
+    let audio = bus.drain_audio(CYCLES_PER_FRAME, 44_100);
+    assert_eq!(audio.len(), 880);
+    assert!(bus.beeper_edges.is_empty());
+    let high = BEEPER_VOLUME as i16;
+    assert!(audio[..440].iter().all(|&s| s == high));
+    assert!(audio[441..].iter().all(|&s| s == 0));
+    // El flanco cae a mitad de la muestra 440: nivel medio
+    assert!(audio[440] > 0 && audio[440] < high);
+}
+
+#[test]
+fn test_beeper_idle_frame_holds_last_level() {
+    use crate::{BEEPER_VOLUME, CYCLES_PER_FRAME};
+    use oxide_core::MemoryBus;
+    let (_, mut bus) = counter_machine();
+
+    bus.frame_cycles = 60_000;
+    bus.port_out(0x00FE, 0x10);
+    let first = bus.drain_audio(CYCLES_PER_FRAME, 44_100);
+    assert_eq!(*first.last().unwrap(), BEEPER_VOLUME as i16);
+
+    // Sin flancos: frame completo al último nivel, y el resto fraccionario
+    // se arrastra (segundo frame: 0.59 + 0.59 -> 881 muestras)
+    let idle = bus.drain_audio(CYCLES_PER_FRAME, 44_100);
+    assert_eq!(idle.len(), 881);
+    assert!(idle.iter().all(|&s| s == BEEPER_VOLUME as i16));
+
+    let total: usize = (0..48).map(|_| bus.drain_audio(CYCLES_PER_FRAME, 44_100).len()).sum();
+    assert_eq!(total + first.len() + idle.len(), 44_029); // 50 frames = 50 * 880.59
+}
+
+// ============================================================================
+// Cinta .TAP (trampa de LD-BYTES)
+// ============================================================================
+
+/// Bloque .TAP con flag, datos y checksum correcto.
+fn tap_block(flag: u8, data: &[u8]) -> Vec<u8> {
+    let mut out = ((data.len() + 2) as u16).to_le_bytes().to_vec();
+    out.push(flag);
+    out.extend_from_slice(data);
+    out.push(data.iter().fold(flag, |acc, &b| acc ^ b));
+    out
+}
+
+/// CPU parada en LD-BYTES con A = flag, carry = LOAD, IX = destino, DE = longitud.
+fn at_ld_bytes(cpu: &mut OxidZ80, flag: u8, dest: u16, len: u16) {
+    use oxidz80::flags;
+    cpu.pc = crate::tape::LD_BYTES;
+    cpu.a = flag;
+    cpu.f |= flags::C;
+    cpu.ix = dest;
+    cpu.set_de(len);
+}
+
+#[test]
+fn test_tap_parse_blocks() {
+    use crate::tape::{parse_tap, TapeError};
+    let mut tap = tap_block(0x00, &[1, 2, 3]);
+    tap.extend(tap_block(0xFF, &[0xAA; 5]));
+    let blocks = parse_tap(&tap).unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!((blocks[0].flag, blocks[0].data.as_slice()), (0x00, &[1u8, 2, 3][..]));
+    assert_eq!((blocks[1].flag, blocks[1].data.len()), (0xFF, 5));
+    assert!(blocks.iter().all(|b| b.checksum_ok()));
+
+    assert_eq!(parse_tap(&tap[..tap.len() - 1]), Err(TapeError::Truncated(7)));
+    assert_eq!(parse_tap(&[1, 0, 0xFF]), Err(TapeError::ShortBlock(0)));
+}
+
+#[test]
+fn test_tape_trap_loads_next_block() {
+    use crate::tape::{parse_tap, SA_LD_RET};
+    use crate::tape_trap;
+    use oxide_core::MemoryBus;
+    use oxidz80::flags;
+    let (mut cpu, mut bus) = counter_machine();
+    let mut tap = tap_block(0x00, &[0x11; 17]);
+    tap.extend(tap_block(0xFF, &[0xDE, 0xAD, 0xBE, 0xEF]));
+    bus.tape = parse_tap(&tap).unwrap().into();
+
+    // Se pide una cabecera: el primer bloque la cumple
+    at_ld_bytes(&mut cpu, 0x00, 0x9000, 17);
+    tape_trap(&mut cpu, &mut bus);
+    assert_eq!(cpu.pc, SA_LD_RET);
+    assert!(cpu.f & flags::C != 0);
+    assert_eq!((cpu.ix, cpu.de()), (0x9011, 0));
+    assert_eq!(bus.read(0x9010), 0x11);
+
+    // Fuera de LD-BYTES la trampa no hace nada
+    tape_trap(&mut cpu, &mut bus);
+    assert_eq!(bus.tape.len(), 1);
+
+    at_ld_bytes(&mut cpu, 0xFF, 0xC000, 4);
+    tape_trap(&mut cpu, &mut bus);
+    assert!(cpu.f & flags::C != 0);
+    assert_eq!((0..4).map(|i| bus.read(0xC000 + i)).collect::<Vec<_>>(), [0xDE, 0xAD, 0xBE, 0xEF]);
+    assert!(bus.tape.is_empty());
+
+    // Sin cinta se ejecuta el cargador de la ROM
+    at_ld_bytes(&mut cpu, 0xFF, 0xC000, 4);
+    tape_trap(&mut cpu, &mut bus);
+    assert_eq!(cpu.pc, crate::tape::LD_BYTES);
+}
+
+#[test]
+fn test_tape_trap_length_or_flag_mismatch_clears_carry() {
+    use crate::tape::parse_tap;
+    use crate::tape_trap;
+    use oxide_core::MemoryBus;
+    use oxidz80::flags;
+    let (mut cpu, mut bus) = counter_machine();
+    let mut tap = tap_block(0xFF, &[1, 2, 3, 4]);
+    tap.extend(tap_block(0xFF, &[5, 6]));
+    tap.extend(tap_block(0x00, &[7]));
+    bus.tape = parse_tap(&tap).unwrap().into();
+
+    // Bloque más largo que lo pedido: se copian 2 bytes y es error
+    at_ld_bytes(&mut cpu, 0xFF, 0x9000, 2);
+    tape_trap(&mut cpu, &mut bus);
+    assert!(cpu.f & flags::C == 0);
+    assert_eq!((bus.read(0x9001), bus.read(0x9002)), (2, 0));
+
+    // Bloque más corto: se copia lo que hay, DE queda con lo que faltó
+    at_ld_bytes(&mut cpu, 0xFF, 0xA000, 4);
+    tape_trap(&mut cpu, &mut bus);
+    assert!(cpu.f & flags::C == 0);
+    assert_eq!((cpu.ix, cpu.de()), (0xA002, 2));
+    assert_eq!(bus.read(0xA001), 6);
+
+    // Flag distinto: el bloque se salta sin cargar nada
+    at_ld_bytes(&mut cpu, 0xFF, 0xB000, 1);
+    tape_trap(&mut cpu, &mut bus);
+    assert!(cpu.f & flags::C == 0);
+    assert_eq!(bus.read(0xB000), 0);
+    assert!(bus.tape.is_empty());
+}
+
+// ============================================================================
+// Instantáneas .SNA
+// ============================================================================
+
+#[test]
+fn test_sna_restores_registers_ram_and_pops_pc() {
+    use crate::sna::{load_sna, SnaError, SNA_48K_LEN};
+    use oxide_core::MemoryBus;
+    let (mut cpu, mut bus) = counter_machine();
+
+    let mut sna = vec![0u8; SNA_48K_LEN];
+    sna[..27].copy_from_slice(&[
+        0x3F,       // I
+        0x11, 0x22, // HL'
+        0x33, 0x44, // DE'
+        0x55, 0x66, // BC'
+        0x77, 0x88, // AF'
+        0x01, 0x02, // HL
+        0x03, 0x04, // DE
+        0x05, 0x06, // BC
+        0x3A, 0x5C, // IY
+        0x07, 0x08, // IX
+        0x04,       // IFF2
+        0x42,       // R
+        0xA5, 0x5A, // AF (F=$A5, A=$5A)
+        0xFE, 0xFF, // SP = $FFFE
+        0x01,       // IM 1
+        0x02,       // borde rojo
+    ]);
+    sna[27] = 0x99; // $4000
+    sna[27 + 0xBFFE..].copy_from_slice(&[0x34, 0x12]); // PC en la pila ($FFFE)
+    load_sna(&sna, &mut cpu, &mut bus).unwrap();
+
+    assert_eq!((cpu.i, cpu.r, cpu.im), (0x3F, 0x42, 1));
+    assert_eq!((cpu.a_p, cpu.f_p, cpu.h_p, cpu.l_p), (0x88, 0x77, 0x22, 0x11));
+    assert_eq!((cpu.b_p, cpu.c_p, cpu.d_p, cpu.e_p), (0x66, 0x55, 0x44, 0x33));
+    assert_eq!((cpu.hl(), cpu.de(), cpu.bc()), (0x0201, 0x0403, 0x0605));
+    assert_eq!((cpu.iy, cpu.ix), (0x5C3A, 0x0807));
+    assert_eq!((cpu.a, cpu.f), (0x5A, 0xA5));
+    assert!(cpu.iff1 && cpu.iff2);
+    assert_eq!(bus.border_color, 2);
+    assert_eq!(bus.read(0x4000), 0x99);
+    // RETN: PC desde la pila y SP+2 (con wrap)
+    assert_eq!((cpu.pc, cpu.sp), (0x1234, 0x0000));
+
+    assert_eq!(load_sna(&sna[..1000], &mut cpu, &mut bus), Err(SnaError::WrongSize(1000)));
+}
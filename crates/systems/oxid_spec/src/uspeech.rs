@@ -0,0 +1,152 @@
+// crates/systems/oxid_spec/src/uspeech.rs
+//
+// Currah µSpeech: sintetizador SP0256-AL2 por alófonos, mapeado en memoria.
+// - Escritura en $1000: código de alófono (6 bits) a la cola del chip
+// - Escritura en $3000 / $3001: entonación normal / alta
+// - Lectura de $1000: bit 0 = ocupado (todavía hablando)
+// No se modela la ROM del interfaz ni su paginación (lectura de $0038): las
+// direcciones anteriores caen en ROM, donde las escrituras se ignoran igual.
+// El sonido es una aproximación: tren de pulsos para los alófonos sonoros,
+// ruido para los sordos y silencio para las pausas, con la duración del chip.
+
+use oxide_core::AudioSink;
+use std::collections::VecDeque;
+
+pub const SPEECH_DATA: u16 = 0x1000;
+pub const INTONATION_LOW: u16 = 0x3000;
+pub const INTONATION_HIGH: u16 = 0x3001;
+
+/// Tono de los alófonos sonoros (Hz): entonación normal / alta
+const PITCH_LOW: u32 = 110;
+const PITCH_HIGH: u32 = 130;
+const AMPLITUDE: i16 = 6000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Voicing {
+    Pause,
+    Voiced,
+    Unvoiced,
+}
+
+/// Alófono del SP0256-AL2: nombre, duración (ms) y tipo de excitación.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Allophone {
+    pub name: &'static str,
+    pub duration_ms: u32,
+    pub voicing: Voicing,
+}
+
+const fn a(name: &'static str, duration_ms: u32, voicing: Voicing) -> Allophone {
+    Allophone { name, duration_ms, voicing }
+}
+
+use Voicing::{Pause as P, Unvoiced as U, Voiced as V};
+
+pub const ALLOPHONES: [Allophone; 64] = [
+    a("PA1", 10, P), a("PA2", 30, P), a("PA3", 50, P), a("PA4", 100, P),
+    a("PA5", 200, P), a("OY", 420, V), a("AY", 260, V), a("EH", 70, V),
+    a("KK3", 120, U), a("PP", 210, U), a("JH", 140, V), a("NN1", 140, V),
+    a("IH", 70, V), a("TT2", 140, U), a("RR1", 170, V), a("AX", 70, V),
+    a("MM", 180, V), a("TT1", 100, U), a("DH1", 290, V), a("IY", 250, V),
+    a("EY", 280, V), a("DD1", 70, V), a("UW1", 100, V), a("AO", 100, V),
+    a("AA", 100, V), a("YY2", 180, V), a("AE", 120, V), a("HH1", 130, U),
+    a("BB1", 80, V), a("TH", 180, U), a("UH", 100, V), a("UW2", 260, V),
+    a("AW", 370, V), a("DD2", 160, V), a("GG3", 140, V), a("VV", 190, V),
+    a("GG1", 80, V), a("SH", 160, U), a("ZH", 190, V), a("RR2", 120, V),
+    a("FF", 150, U), a("KK2", 190, U), a("KK1", 160, U), a("ZZ", 210, V),
+    a("NG", 220, V), a("LL", 110, V), a("WW", 180, V), a("XR", 360, V),
+    a("WH", 200, U), a("YY1", 130, V), a("CH", 190, U), a("ER1", 160, V),
+    a("ER2", 300, V), a("OW", 240, V), a("DH2", 240, V), a("SS", 90, U),
+    a("NN2", 190, V), a("HH2", 180, U), a("OR", 330, V), a("AR", 290, V),
+    a("YR", 350, V), a("GG2", 40, V), a("EL", 190, V), a("BB2", 50, V),
+];
+
+pub struct MicroSpeech {
+    /// Alófonos pendientes (códigos 0-63)
+    pub queue: VecDeque<u8>,
+    /// Alófono sonando y muestras que le quedan
+    current: Option<(u8, u32)>,
+    pub high_pitch: bool,
+    /// Fase del tren de pulsos (muestras dentro del periodo)
+    phase: u32,
+    lfsr: u16,
+}
+
+impl Default for MicroSpeech {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MicroSpeech {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            current: None,
+            high_pitch: false,
+            phase: 0,
+            lfsr: 1,
+        }
+    }
+
+    /// Escritura del Z80. Devuelve `true` si la dirección es del µSpeech.
+    pub fn write(&mut self, addr: u16, val: u8) -> bool {
+        match addr {
+            SPEECH_DATA => self.queue.push_back(val & 0x3F),
+            INTONATION_LOW => self.high_pitch = false,
+            INTONATION_HIGH => self.high_pitch = true,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Lectura del Z80: estado en $1000 (bit 0 = ocupado).
+    pub fn read(&self, addr: u16) -> Option<u8> {
+        (addr == SPEECH_DATA).then_some(self.is_busy() as u8)
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.current.is_some() || !self.queue.is_empty()
+    }
+
+    /// Alófono que está sonando.
+    pub fn speaking(&self) -> Option<&'static Allophone> {
+        self.current.map(|(code, _)| &ALLOPHONES[code as usize])
+    }
+
+    fn next_sample(&mut self, sample_rate: u32) -> i16 {
+        if self.current.is_none() {
+            let code = match self.queue.pop_front() {
+                Some(code) => code,
+                None => return 0,
+            };
+            let samples = ALLOPHONES[code as usize].duration_ms * sample_rate / 1000;
+            self.current = Some((code, samples));
+            self.phase = 0;
+        }
+        let Some((code, left)) = self.current else { return 0 };
+        let sample = match ALLOPHONES[code as usize].voicing {
+            Voicing::Pause => 0,
+            Voicing::Voiced => {
+                // Pulso estrecho (1/8 del periodo): timbre "zumbido" del SP0256
+                let pitch = if self.high_pitch { PITCH_HIGH } else { PITCH_LOW };
+                let period = (sample_rate / pitch).max(1);
+                self.phase = (self.phase + 1) % period;
+                if self.phase < period / 8 { AMPLITUDE } else { -AMPLITUDE / 8 }
+            }
+            Voicing::Unvoiced => {
+                let bit = (self.lfsr ^ (self.lfsr >> 2) ^ (self.lfsr >> 3) ^ (self.lfsr >> 5)) & 1;
+                self.lfsr = (self.lfsr >> 1) | (bit << 15);
+                if self.lfsr & 1 != 0 { AMPLITUDE / 2 } else { -AMPLITUDE / 2 }
+            }
+        };
+        self.current = (left > 1).then_some((code, left - 1));
+        sample
+    }
+
+    /// Genera `samples` muestras mono y las entrega al sink.
+    pub fn render(&mut self, samples: usize, sample_rate: u32, sink: &mut dyn AudioSink) {
+        let out: Vec<i16> = (0..samples).map(|_| self.next_sample(sample_rate)).collect();
+        sink.push_samples(&out);
+    }
+}